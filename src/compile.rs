@@ -0,0 +1,65 @@
+use super::*;
+
+impl ShaderModuleSource
+{
+    // compiles GLSL and WGSL with naga, SPIR-V is passed through. the entry point has to be called main. naga's
+    // GLSL frontend only handles vertex, fragment and compute shaders
+    pub fn to_spirv(&self, stage: ShaderStage) -> Result<Vec<u32>, ShaderModuleError>
+    {
+        let (module, source) = match self
+        {
+            ShaderModuleSource::Spirv(code) => { return Ok(code.clone()); },
+            ShaderModuleSource::Glsl(source) =>
+            {
+                let stage = match stage
+                {
+                    ShaderStage::Vertex => naga::ShaderStage::Vertex,
+                    ShaderStage::Fragment => naga::ShaderStage::Fragment,
+                    ShaderStage::Compute => naga::ShaderStage::Compute,
+                    _ => { return Err(ShaderModuleError::UnsupportedSource(format!("GLSL {:?} shaders have to be compiled to SPIR-V first", stage))); }
+                };
+
+                let module = naga::front::glsl::Frontend::default().parse(&naga::front::glsl::Options::from(stage), source)
+                    .map_err(|errors| ShaderModuleError::CompilationFailed(errors.iter()
+                        .map(|error| { let location = error.meta.location(source); format!("{}:{}: {}", location.line_number, location.line_position, error) })
+                        .collect::<Vec<String>>()
+                        .join("\n")))?;
+                (module, source)
+            },
+            ShaderModuleSource::Wgsl(source) =>
+            {
+                let module = naga::front::wgsl::parse_str(source).map_err(|error| ShaderModuleError::CompilationFailed(error.emit_to_string(source)))?;
+                (module, source)
+            },
+            ShaderModuleSource::Software(_) => { return Err(ShaderModuleError::UnsupportedSource("software shaders only run on the software backend".to_string())); }
+        };
+
+        let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+            .validate(&module)
+            .map_err(|error| ShaderModuleError::CompilationFailed(error.emit_to_string(source)))?;
+
+        naga::back::spv::write_vec(&module, &info, &naga::back::spv::Options::default(), None)
+            .map_err(|error| ShaderModuleError::CompilationFailed(error.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn glsl_and_wgsl_compile_to_spirv()
+    {
+        let glsl = ShaderModuleSource::Glsl("#version 450\nlayout(location = 0) out vec4 color;\nvoid main() { color = vec4(1.0); }\n".to_string());
+        assert!(matches!(glsl.to_spirv(ShaderStage::Fragment), Ok(code) if code.first() == Some(&SPIRV_MAGIC)));
+
+        let wgsl = ShaderModuleSource::Wgsl("@compute @workgroup_size(1) fn main() {}\n".to_string());
+        assert!(matches!(wgsl.to_spirv(ShaderStage::Compute), Ok(code) if code.first() == Some(&SPIRV_MAGIC)));
+
+        let broken = ShaderModuleSource::Wgsl("fn main( {}\n".to_string());
+        assert!(matches!(broken.to_spirv(ShaderStage::Compute), Err(ShaderModuleError::CompilationFailed(_))));
+
+        assert!(matches!(glsl.to_spirv(ShaderStage::Geometry), Err(ShaderModuleError::UnsupportedSource(_))));
+    }
+}
@@ -0,0 +1,225 @@
+use super::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct FrameRingCreateInfo
+{
+    pub frames_in_flight: u32,
+    pub wait_stage_mask: u32
+}
+
+impl Default for FrameRingCreateInfo
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            frames_in_flight: 2,
+            wait_stage_mask: PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+        }
+    }
+}
+
+pub struct Frame
+{
+    pub command_pool: CommandPool,
+    pub image_available: Semaphore,
+    pub in_flight: Fence
+}
+
+pub struct FrameContext
+{
+    pub frame_index: usize,
+    pub image_index: u32,
+    pub encoder: CommandEncoder
+}
+
+// owns the per frame objects of the usual acquire -> record -> submit -> present loop. the cpu runs
+// at most frames_in_flight frames ahead of the gpu
+pub struct FrameRing
+{
+    queue: Queue,
+    swapchain: Swapchain,
+    wait_stage_mask: u32,
+    frames: Vec<Frame>,
+    // presentation can hold on to its wait semaphore past the frame's fence, so these are per
+    // swapchain image rather than per frame
+    render_finished: Vec<Semaphore>,
+    // the image a frame acquired while no submission has waited on its image_available yet
+    acquired: Vec<Option<u32>>,
+    current: usize
+}
+
+impl FrameRing
+{
+    pub fn new(device: &Device, queue: &Queue, swapchain: &Swapchain, create_info: &FrameRingCreateInfo) -> Result<Self, ()>
+    {
+        if create_info.frames_in_flight == 0
+        {
+            return Err(());
+        }
+
+        let frames = (0..create_info.frames_in_flight)
+            .map(|_| -> Result<Frame, ()>
+            {
+                Ok(Frame
+                {
+                    command_pool: device.create_command_pool(queue)?,
+                    image_available: device.create_semaphore()?,
+                    in_flight: device.create_fence(true)?
+                })
+            })
+            .collect::<Result<Vec<Frame>, ()>>()?;
+
+        let mut ring = Self
+        {
+            queue: queue.clone(),
+            swapchain: swapchain.clone(),
+            wait_stage_mask: create_info.wait_stage_mask,
+            acquired: vec![ None; frames.len() ],
+            frames,
+            render_finished: Vec::new(),
+            current: 0
+        };
+        ring.create_render_finished_semaphores(device)?;
+
+        Ok(ring)
+    }
+
+    fn create_render_finished_semaphores(&mut self, device: &Device) -> Result<(), ()>
+    {
+        self.render_finished = (0..self.swapchain.image_views().len())
+            .map(|_| device.create_semaphore())
+            .collect::<Result<Vec<Semaphore>, ()>>()?;
+
+        Ok(())
+    }
+
+    pub fn frames(&self) -> &[Frame]
+    {
+        &self.frames
+    }
+
+    pub fn swapchain(&self) -> &Swapchain
+    {
+        &self.swapchain
+    }
+
+    // waits until the frame's previous submission has finished, then acquires the next swapchain image
+    // and hands back an encoder from the frame's freshly reset command pool. every begin_frame should be followed by
+    // end_frame, a frame that was dropped or failed to submit is cleaned up by the next begin_frame on its slot
+    pub fn begin_frame(&mut self) -> Result<FrameContext, SwapchainError>
+    {
+        if let Some(image_index) = self.acquired[self.current]
+        {
+            self.release_image(self.current, image_index)?;
+        }
+
+        let frame = &self.frames[self.current];
+        frame.in_flight.wait(u64::MAX).map_err(|_| SwapchainError::Failed)?;
+
+        let image_index = self.swapchain.acquire_next_image(Some(&frame.image_available), None)?;
+        self.acquired[self.current] = Some(image_index);
+
+        frame.command_pool.reset().map_err(|_| SwapchainError::Failed)?;
+        let encoder = frame.command_pool.create_command_encoder().map_err(|_| SwapchainError::Failed)?;
+
+        Ok(FrameContext { frame_index: self.current, image_index, encoder })
+    }
+
+    pub fn end_frame(&mut self, context: FrameContext) -> Result<(), SwapchainError>
+    {
+        let frame = &self.frames[context.frame_index];
+        let render_finished = &self.render_finished[context.image_index as usize];
+        let command_buffer = context.encoder.finish().map_err(|_| SwapchainError::Failed)?;
+
+        self.current = (context.frame_index + 1) % self.frames.len();
+
+        self.submit_frame(context.frame_index, &SubmitInfo
+        {
+            wait_semaphores: &[ &frame.image_available ],
+            wait_stage_masks: &[ self.wait_stage_mask ],
+            command_buffers: &[ &command_buffer ],
+            signal_semaphores: &[ render_finished ],
+            fence: None
+        }).map_err(|_| SwapchainError::Failed)?;
+        self.acquired[context.frame_index] = None;
+
+        self.queue.present(&self.swapchain, context.image_index, &[ render_finished ])
+    }
+
+    // submits with the frame's fence. a rejected submission leaves the fence reset, so it is signaled again by an
+    // empty one and waiting on the frame can't block forever
+    fn submit_frame(&self, frame_index: usize, submit_info: &SubmitInfo) -> Result<(), ()>
+    {
+        let fence = &self.frames[frame_index].in_flight;
+        fence.reset()?;
+
+        let result = self.queue.submit(&SubmitInfo { fence: Some(fence), ..*submit_info });
+        if result.is_err()
+        {
+            self.queue.submit(&SubmitInfo { fence: Some(fence), ..Default::default() })?;
+        }
+
+        result
+    }
+
+    // an abandoned frame holds a swapchain image and leaves image_available signaled. presenting the image
+    // unchanged consumes the semaphore and hands the image back to the swapchain
+    fn release_image(&mut self, frame_index: usize, image_index: u32) -> Result<(), SwapchainError>
+    {
+        let frame = &self.frames[frame_index];
+        frame.in_flight.wait(u64::MAX).map_err(|_| SwapchainError::Failed)?;
+        frame.command_pool.reset().map_err(|_| SwapchainError::Failed)?;
+
+        let image = self.swapchain.image_views().get(image_index as usize).ok_or(SwapchainError::Failed)?.image();
+        let mut encoder = frame.command_pool.create_command_encoder().map_err(|_| SwapchainError::Failed)?;
+        encoder.use_image(&image, ImageAccess::Present);
+        let command_buffer = encoder.finish().map_err(|_| SwapchainError::Failed)?;
+
+        let render_finished = &self.render_finished[image_index as usize];
+        self.submit_frame(frame_index, &SubmitInfo
+        {
+            wait_semaphores: &[ &frame.image_available ],
+            wait_stage_masks: &[ PipelineStageFlags::ALL_COMMANDS ],
+            command_buffers: &[ &command_buffer ],
+            signal_semaphores: &[ render_finished ],
+            fence: None
+        }).map_err(|_| SwapchainError::Failed)?;
+        self.acquired[frame_index] = None;
+
+        // an out of date swapchain still takes the image back
+        match self.queue.present(&self.swapchain, image_index, &[ render_finished ])
+        {
+            Err(SwapchainError::Failed) => Err(SwapchainError::Failed),
+            _ => Ok(())
+        }
+    }
+
+    pub fn wait_idle(&self) -> Result<(), ()>
+    {
+        for frame in self.frames.iter()
+        {
+            frame.in_flight.wait(u64::MAX)?;
+        }
+
+        Ok(())
+    }
+
+    // call after SwapchainError::OutOfDate with the recreated swapchain. images acquired from the old
+    // swapchain by abandoned frames are handed back to it first
+    pub fn set_swapchain(&mut self, device: &Device, swapchain: &Swapchain) -> Result<(), ()>
+    {
+        for frame_index in 0..self.frames.len()
+        {
+            if let Some(image_index) = self.acquired[frame_index]
+            {
+                self.release_image(frame_index, image_index).map_err(|_| ())?;
+            }
+        }
+
+        self.wait_idle()?;
+        self.queue.wait_idle()?;
+        self.swapchain = swapchain.clone();
+        self.create_render_finished_semaphores(device)
+    }
+}
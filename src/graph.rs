@@ -0,0 +1,1079 @@
+use super::*;
+use crate::state::{ResourceState, Transition};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GraphImage(usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GraphBuffer(usize);
+
+struct GraphImageNode<'a>
+{
+    imported: Option<(Image, ImageView)>,
+    create_info: ImageCreateInfo<'a>,
+    initial: Option<ImageAccess>,
+    export: Option<ImageAccess>
+}
+
+struct GraphBufferNode<'a>
+{
+    imported: Option<Buffer>,
+    create_info: BufferCreateInfo<'a>,
+    initial: Option<BufferAccess>,
+    export: Option<BufferAccess>
+}
+
+type PassCallback<'a> = Box<dyn FnOnce(&mut CommandEncoder, &GraphResources) + 'a>;
+
+pub struct GraphPass<'a>
+{
+    name: String,
+    color_attachments: Vec<(GraphImage, Operations)>,
+    depth_attachment: Option<(GraphImage, Operations)>,
+    images: Vec<(GraphImage, ImageAccess)>,
+    buffers: Vec<(GraphBuffer, BufferAccess)>,
+    side_effects: bool,
+    callback: Option<PassCallback<'a>>
+}
+
+impl<'a> GraphPass<'a>
+{
+    pub fn name(&self) -> &str
+    {
+        &self.name
+    }
+
+    pub fn color_attachment(&mut self, image: GraphImage, operations: Operations) -> &mut Self
+    {
+        self.color_attachments.push((image, operations));
+        self.images.push((image, ImageAccess::ColorAttachment));
+        self
+    }
+
+    pub fn depth_attachment(&mut self, image: GraphImage, operations: Operations) -> &mut Self
+    {
+        self.depth_attachment = Some((image, operations));
+        self.images.push((image, ImageAccess::DepthStencilAttachment));
+        self
+    }
+
+    // read_* takes accesses that only read and write_* ones that write, anything else is reported and left out
+    pub fn read_image(&mut self, image: GraphImage, access: ImageAccess) -> &mut Self
+    {
+        if check_access(&self.name, "read_image", access.access().write, false)
+        {
+            self.images.push((image, access));
+        }
+        self
+    }
+
+    pub fn write_image(&mut self, image: GraphImage, access: ImageAccess) -> &mut Self
+    {
+        if check_access(&self.name, "write_image", access.access().write, true)
+        {
+            self.images.push((image, access));
+        }
+        self
+    }
+
+    pub fn read_buffer(&mut self, buffer: GraphBuffer, access: BufferAccess) -> &mut Self
+    {
+        if check_access(&self.name, "read_buffer", access.access().write, false)
+        {
+            self.buffers.push((buffer, access));
+        }
+        self
+    }
+
+    pub fn write_buffer(&mut self, buffer: GraphBuffer, access: BufferAccess) -> &mut Self
+    {
+        if check_access(&self.name, "write_buffer", access.access().write, true)
+        {
+            self.buffers.push((buffer, access));
+        }
+        self
+    }
+
+    // keeps the pass alive even if nothing reads what it writes, e.g. queries or host readback
+    pub fn side_effects(&mut self) -> &mut Self
+    {
+        self.side_effects = true;
+        self
+    }
+
+    pub fn execute<F>(&mut self, callback: F) -> &mut Self where F: FnOnce(&mut CommandEncoder, &GraphResources) + 'a
+    {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    // an attachment that loads its previous contents reads them as well
+    fn reads_image(&self, image: GraphImage) -> bool
+    {
+        let loads = self.color_attachments.iter().chain(self.depth_attachment.iter())
+            .any(|(attachment, operations)| *attachment == image && operations.load_op == LoadOp::Load);
+
+        loads || self.images.iter().any(|(i, access)| *i == image && !access.access().write)
+    }
+
+    fn discards_image(&self, image: GraphImage) -> bool
+    {
+        self.color_attachments.iter().chain(self.depth_attachment.iter())
+            .any(|(attachment, operations)| *attachment == image && operations.load_op != LoadOp::Load)
+            && !self.images.iter().any(|(i, access)| *i == image && !access.access().write)
+    }
+}
+
+fn check_access(pass: &str, what: &str, writes: bool, expected: bool) -> bool
+{
+    if writes != expected
+    {
+        let kind = if writes { "writes" } else { "only reads" };
+        state::validation_error(&format!("{} in pass \"{}\" was given an access that {}", what, pass, kind));
+    }
+
+    writes == expected
+}
+
+// a frame's worth of passes. passes declare the images and buffers they touch and run in declaration
+// order, which is always a valid order since a pass can only use resources declared before it. execute culls passes
+// whose results nobody observes, places transients with disjoint lifetimes over the same memory whatever their shape,
+// and records the barriers and layout transitions between passes. the placement only depends on the graph, so a graph
+// that looks like the previous frame's reuses its resources instead of creating new ones
+pub struct RenderGraph<'a>
+{
+    images: Vec<GraphImageNode<'a>>,
+    buffers: Vec<GraphBufferNode<'a>>,
+    passes: Vec<GraphPass<'a>>
+}
+
+impl<'a> Default for RenderGraph<'a>
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl<'a> RenderGraph<'a>
+{
+    pub fn new() -> Self
+    {
+        Self { images: Vec::new(), buffers: Vec::new(), passes: Vec::new() }
+    }
+
+    // initial is how the image was last used before the graph, None if its contents don't matter
+    pub fn import_image(&mut self, image: &Image, view: &ImageView, initial: Option<ImageAccess>) -> GraphImage
+    {
+        self.images.push(GraphImageNode
+        {
+            imported: Some((image.clone(), view.clone())),
+            create_info: image.create_info(),
+            initial,
+            export: None
+        });
+        GraphImage(self.images.len() - 1)
+    }
+
+    pub fn import_buffer(&mut self, buffer: &Buffer, initial: Option<BufferAccess>) -> GraphBuffer
+    {
+        self.buffers.push(GraphBufferNode
+        {
+            imported: Some(buffer.clone()),
+            create_info: BufferCreateInfo { size: buffer.size(), usage: 0, location: MemoryLocation::GpuOnly, label: None },
+            initial,
+            export: None
+        });
+        GraphBuffer(self.buffers.len() - 1)
+    }
+
+    // transient resources only live for the graph. usage flags for the declared accesses are added on
+    // top of the create info
+    pub fn create_image(&mut self, create_info: &ImageCreateInfo<'a>) -> GraphImage
+    {
+        self.images.push(GraphImageNode { imported: None, create_info: *create_info, initial: None, export: None });
+        GraphImage(self.images.len() - 1)
+    }
+
+    pub fn create_buffer(&mut self, create_info: &BufferCreateInfo<'a>) -> GraphBuffer
+    {
+        self.buffers.push(GraphBufferNode { imported: None, create_info: *create_info, initial: None, export: None });
+        GraphBuffer(self.buffers.len() - 1)
+    }
+
+    // transitions an imported resource once all passes ran, e.g. ImageAccess::Present for the
+    // swapchain image
+    pub fn export_image(&mut self, image: GraphImage, access: ImageAccess)
+    {
+        self.images[image.0].export = Some(access);
+    }
+
+    pub fn export_buffer(&mut self, buffer: GraphBuffer, access: BufferAccess)
+    {
+        self.buffers[buffer.0].export = Some(access);
+    }
+
+    pub fn add_pass(&mut self, name: &str) -> &mut GraphPass<'a>
+    {
+        self.passes.push(GraphPass
+        {
+            name: name.to_string(),
+            color_attachments: Vec::new(),
+            depth_attachment: None,
+            images: Vec::new(),
+            buffers: Vec::new(),
+            side_effects: false,
+            callback: None
+        });
+        self.passes.last_mut().unwrap()
+    }
+
+    fn cull(&self) -> Vec<bool>
+    {
+        let mut alive = vec![ false; self.passes.len() ];
+        let mut needed_images: Vec<bool> = self.images.iter().map(|image| image.imported.is_some()).collect();
+        let mut needed_buffers: Vec<bool> = self.buffers.iter().map(|buffer| buffer.imported.is_some()).collect();
+
+        for (index, pass) in self.passes.iter().enumerate().rev()
+        {
+            let writes_needed = pass.images.iter().any(|(image, access)| access.access().write && needed_images[image.0])
+                || pass.buffers.iter().any(|(buffer, access)| access.access().write && needed_buffers[buffer.0]);
+            if !pass.side_effects && !writes_needed
+            {
+                continue;
+            }
+
+            alive[index] = true;
+
+            // a transient that gets overwritten without being read doesn't need its earlier writers
+            for (image, access) in pass.images.iter()
+            {
+                if access.access().write && self.images[image.0].imported.is_none() && !pass.reads_image(*image)
+                {
+                    needed_images[image.0] = false;
+                }
+            }
+            for (buffer, access) in pass.buffers.iter()
+            {
+                let reads = pass.buffers.iter().any(|(b, a)| b == buffer && !a.access().write);
+                if access.access().write && self.buffers[buffer.0].imported.is_none() && !reads
+                {
+                    needed_buffers[buffer.0] = false;
+                }
+            }
+
+            for (image, _) in pass.images.iter()
+            {
+                if pass.reads_image(*image)
+                {
+                    needed_images[image.0] = true;
+                }
+            }
+            for (buffer, access) in pass.buffers.iter()
+            {
+                if !access.access().write
+                {
+                    needed_buffers[buffer.0] = true;
+                }
+            }
+        }
+
+        alive
+    }
+
+    pub fn execute(mut self, device: &Device, cache: &mut RenderGraphCache, encoder: &mut CommandEncoder) -> Result<(), ()>
+    {
+        let alive = self.cull();
+        let mut passes = std::mem::take(&mut self.passes);
+
+        // first and last live pass touching each resource, transients nobody uses aren't allocated
+        let mut image_lifetimes: Vec<Option<(usize, usize)>> = vec![ None; self.images.len() ];
+        let mut buffer_lifetimes: Vec<Option<(usize, usize)>> = vec![ None; self.buffers.len() ];
+        for (index, pass) in passes.iter().enumerate().filter(|(index, _)| alive[*index])
+        {
+            for (image, access) in pass.images.iter()
+            {
+                let lifetime = &mut image_lifetimes[image.0];
+                *lifetime = Some(lifetime.map_or((index, index), |(first, _)| (first, index)));
+                self.images[image.0].create_info.usage |= access.usage();
+            }
+            for (buffer, access) in pass.buffers.iter()
+            {
+                let lifetime = &mut buffer_lifetimes[buffer.0];
+                *lifetime = Some(lifetime.map_or((index, index), |(first, _)| (first, index)));
+                self.buffers[buffer.0].create_info.usage |= access.usage();
+            }
+        }
+
+        let image_slots = cache.allocate_images(device, &self.images, &image_lifetimes)?;
+        let buffer_slots = cache.allocate_buffers(device, &self.buffers, &buffer_lifetimes)?;
+
+        let mut resources = GraphResources
+        {
+            images: self.images.iter().zip(image_slots.iter())
+                .map(|(image, slot)| match (&image.imported, slot)
+                {
+                    (Some(imported), _) => Some(imported.clone()),
+                    (None, Some(slot)) => Some((cache.images[*slot].image.clone(), cache.images[*slot].view.clone())),
+                    (None, None) => None
+                })
+                .collect(),
+            buffers: self.buffers.iter().zip(buffer_slots.iter())
+                .map(|(buffer, slot)| match (&buffer.imported, slot)
+                {
+                    (Some(imported), _) => Some(imported.clone()),
+                    (None, Some(slot)) => Some(cache.buffers[*slot].buffer.clone()),
+                    (None, None) => None
+                })
+                .collect(),
+            render_pass: None
+        };
+
+        // creating render passes and framebuffers can fail, so do it before the encoder sees a single barrier
+        let mut attachments = Vec::with_capacity(passes.len());
+        for (index, pass) in passes.iter().enumerate()
+        {
+            attachments.push(if alive[index] { self.render_pass_attachments(pass, &resources, device, cache)? } else { None });
+        }
+
+        // transients track their state in the cache slot, and take on the state of everything placed over the same
+        // memory at their first use. aliases within this graph and later graphs wait on whatever the previous occupants
+        // left the memory doing. submissions on the same queue are ordered, which also covers the previous frame still
+        // being in flight
+        let mut image_states: Vec<ResourceState> = self.images.iter()
+            .map(|image| ResourceState::from_access(image.initial.map(|access| access.access())))
+            .collect();
+        let mut buffer_states: Vec<ResourceState> = self.buffers.iter()
+            .map(|buffer| ResourceState::from_access(buffer.initial.map(|access| access.access())))
+            .collect();
+
+        for (index, pass) in passes.iter_mut().enumerate()
+        {
+            if !alive[index]
+            {
+                continue;
+            }
+
+            for (image, access) in pass.images.iter()
+            {
+                let (state, discard) = match image_slots[image.0]
+                {
+                    Some(slot) =>
+                    {
+                        let first_use = image_lifetimes[image.0].unwrap().0 == index;
+                        if first_use
+                        {
+                            cache.alias_image(slot);
+                        }
+                        (&mut cache.images[slot].state, first_use)
+                    },
+                    None => (&mut image_states[image.0], false)
+                };
+
+                if let Some(transition) = state.transition(&access.access(), discard || pass.discards_image(*image))
+                {
+                    record_image_transition(encoder, resources.image(*image), &transition);
+                }
+            }
+
+            for (buffer, access) in pass.buffers.iter()
+            {
+                let state = match buffer_slots[buffer.0]
+                {
+                    Some(slot) =>
+                    {
+                        if buffer_lifetimes[buffer.0].unwrap().0 == index
+                        {
+                            cache.alias_buffer(slot);
+                        }
+                        &mut cache.buffers[slot].state
+                    },
+                    None => &mut buffer_states[buffer.0]
+                };
+
+                if let Some(transition) = state.transition(&access.access(), false)
+                {
+                    record_buffer_transition(encoder, resources.buffer(*buffer), &transition);
+                }
+            }
+
+            resources.render_pass = attachments[index].as_ref().map(|(render_pass, _)| render_pass.clone());
+            if let Some((render_pass, framebuffer)) = attachments[index].as_ref()
+            {
+                encoder.begin_render_pass(render_pass, framebuffer);
+            }
+
+            if let Some(callback) = pass.callback.take()
+            {
+                callback(encoder, &resources);
+            }
+
+            if attachments[index].is_some()
+            {
+                encoder.end_render_pass();
+            }
+            resources.render_pass = None;
+        }
+
+        for (index, image) in self.images.iter().enumerate()
+        {
+            let Some(export) = image.export else { continue; };
+            if image.imported.is_none()
+            {
+                continue;
+            }
+
+            if let Some(transition) = image_states[index].transition(&export.access(), false)
+            {
+                record_image_transition(encoder, resources.image(GraphImage(index)), &transition);
+            }
+        }
+
+        for (index, buffer) in self.buffers.iter().enumerate()
+        {
+            let Some(export) = buffer.export else { continue; };
+            if buffer.imported.is_none()
+            {
+                continue;
+            }
+
+            if let Some(transition) = buffer_states[index].transition(&export.access(), false)
+            {
+                record_buffer_transition(encoder, resources.buffer(GraphBuffer(index)), &transition);
+            }
+        }
+
+        Ok(())
+    }
+
+    // the graph already moved every attachment into its layout, so the render pass keeps them there
+    // and needs no subpass dependencies
+    fn render_pass_attachments(&self, pass: &GraphPass, resources: &GraphResources, device: &Device, cache: &mut RenderGraphCache) -> Result<Option<(RenderPass, Framebuffer)>, ()>
+    {
+        if pass.color_attachments.is_empty() && pass.depth_attachment.is_none()
+        {
+            return Ok(None);
+        }
+
+        let mut create_info = RenderPassCreateInfo
+        {
+            attachments: Vec::new(),
+            subpasses: vec![ SubpassDescription
+            {
+                color_attachments: Vec::new(),
+                resolve_attachments: Vec::new(),
+                depth_stencil_attachment: None
+            } ],
+            dependencies: Vec::new(),
+            label: None
+        };
+        let mut views = Vec::new();
+        let mut extent = None;
+
+        for (image, operations, layout) in pass.color_attachments.iter()
+            .map(|(image, operations)| (image, operations, ImageLayout::ColorAttachmentOptimal))
+            .chain(pass.depth_attachment.iter().map(|(image, operations)| (image, operations, ImageLayout::DepthStencilAttachmentOptimal)))
+        {
+            let image_info = &self.images[image.0].create_info;
+            if *extent.get_or_insert((image_info.width, image_info.height)) != (image_info.width, image_info.height)
+            {
+                return Err(());
+            }
+
+            let reference = AttachmentReference { attachment: create_info.attachments.len() as u32, layout };
+            if layout == ImageLayout::DepthStencilAttachmentOptimal
+            {
+                create_info.subpasses[0].depth_stencil_attachment = Some(reference);
+            }
+            else
+            {
+                create_info.subpasses[0].color_attachments.push(reference);
+            }
+
+            create_info.attachments.push(AttachmentDescription
+            {
+                format: image_info.format,
+                samples: image_info.samples,
+                operations: *operations,
+                stencil_operations: if image_info.format.has_stencil() { *operations } else { Operations { load_op: LoadOp::DontCare, store_op: StoreOp::DontCare } },
+                initial_layout: Some(layout),
+                final_layout: layout
+            });
+            views.push(resources.image_view(*image).clone());
+        }
+
+        let render_pass = match cache.render_passes.iter().find(|(info, _)| *info == create_info)
+        {
+            Some((_, render_pass)) => render_pass.clone(),
+            None =>
+            {
+                let render_pass = device.create_render_pass(&create_info)?;
+                cache.render_passes.push((create_info, render_pass.clone()));
+                render_pass
+            }
+        };
+
+        let (width, height) = extent.unwrap();
+        let cached = cache.framebuffers.iter().find(|cached|
+        {
+            Arc::ptr_eq(&cached.render_pass.internal, &render_pass.internal)
+                && (cached.width, cached.height) == (width, height)
+                && cached.views.len() == views.len()
+                && cached.views.iter().zip(views.iter()).all(|(a, b)| Arc::ptr_eq(&a.internal, &b.internal))
+        });
+
+        let framebuffer = match cached
+        {
+            Some(cached) => cached.framebuffer.clone(),
+            None =>
+            {
+                let view_refs: Vec<&ImageView> = views.iter().collect();
+                let framebuffer = device.create_framebuffer(&FramebufferCreateInfo
+                {
+                    render_pass: &render_pass,
+                    attachments: &view_refs,
+                    width,
+                    height,
+                    layers: 1,
+                    label: None
+                })?;
+                cache.framebuffers.push(CachedFramebuffer { render_pass: render_pass.clone(), views, width, height, framebuffer: framebuffer.clone() });
+                framebuffer
+            }
+        };
+
+        Ok(Some((render_pass, framebuffer)))
+    }
+}
+
+fn record_image_transition(encoder: &mut CommandEncoder, image: &Image, transition: &Transition)
+{
+    encoder.image_barrier(&ImageBarrier
+    {
+        image,
+        subresource_range: None,
+        old_layout: transition.old_layout,
+        new_layout: transition.new_layout.unwrap(),
+        src_stage_mask: transition.src_stage_mask,
+        src_access_mask: transition.src_access_mask,
+        dst_stage_mask: transition.dst_stage_mask,
+        dst_access_mask: transition.dst_access_mask,
+        queue_transfer: None
+    });
+}
+
+fn record_buffer_transition(encoder: &mut CommandEncoder, buffer: &Buffer, transition: &Transition)
+{
+    encoder.buffer_barrier(&BufferBarrier
+    {
+        buffer,
+        offset: 0,
+        size: buffer.size(),
+        src_stage_mask: transition.src_stage_mask,
+        src_access_mask: transition.src_access_mask,
+        dst_stage_mask: transition.dst_stage_mask,
+        dst_access_mask: transition.dst_access_mask,
+        queue_transfer: None
+    });
+}
+
+// what a pass callback sees. the render pass is set while recording an attachment pass so pipelines
+// can be created against it
+pub struct GraphResources
+{
+    images: Vec<Option<(Image, ImageView)>>,
+    buffers: Vec<Option<Buffer>>,
+    render_pass: Option<RenderPass>
+}
+
+impl GraphResources
+{
+    pub fn image(&self, image: GraphImage) -> &Image
+    {
+        &self.images[image.0].as_ref().unwrap().0
+    }
+
+    pub fn image_view(&self, image: GraphImage) -> &ImageView
+    {
+        &self.images[image.0].as_ref().unwrap().1
+    }
+
+    pub fn buffer(&self, buffer: GraphBuffer) -> &Buffer
+    {
+        self.buffers[buffer.0].as_ref().unwrap()
+    }
+
+    pub fn render_pass(&self) -> Option<&RenderPass>
+    {
+        self.render_pass.as_ref()
+    }
+}
+
+struct CachedImage
+{
+    create_info: ImageCreateInfo<'static>,
+    memory: usize,
+    offset: u64,
+    size: u64,
+    image: Image,
+    view: ImageView,
+    state: ResourceState
+}
+
+struct CachedBuffer
+{
+    create_info: BufferCreateInfo<'static>,
+    memory: usize,
+    offset: u64,
+    size: u64,
+    buffer: Buffer,
+    state: ResourceState
+}
+
+// images and buffers never share an allocation, so optimal and linear resources don't need to be kept a
+// buffer-image granularity apart
+struct CachedMemory
+{
+    images: bool,
+    location: MemoryLocation,
+    memory_type_bits: u32,
+    memory: Memory
+}
+
+struct CachedFramebuffer
+{
+    render_pass: RenderPass,
+    views: Vec<ImageView>,
+    width: u32,
+    height: u32,
+    framebuffer: Framebuffer
+}
+
+// keeps transient resources, the memory they are placed in, render passes and framebuffers alive between graphs.
+// reuse one cache per queue, every frame, and clear it after recreating the swapchain
+#[derive(Default)]
+pub struct RenderGraphCache
+{
+    images: Vec<CachedImage>,
+    buffers: Vec<CachedBuffer>,
+    memories: Vec<CachedMemory>,
+    image_requirements: Vec<(ImageCreateInfo<'static>, MemoryRequirements)>,
+    buffer_requirements: Vec<(BufferCreateInfo<'static>, MemoryRequirements)>,
+    render_passes: Vec<(RenderPassCreateInfo<'static>, RenderPass)>,
+    framebuffers: Vec<CachedFramebuffer>
+}
+
+impl RenderGraphCache
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    // waits for the device to go idle and destroys everything the cache created. imported views and
+    // resources stay untouched, only the framebuffers built around them are destroyed
+    pub fn clear(&mut self, device: &Device) -> Result<(), ()>
+    {
+        device.wait_idle()?;
+
+        for cached in self.framebuffers.drain(..)
+        {
+            device.destroy_framebuffer(&cached.framebuffer);
+        }
+        for (_, render_pass) in self.render_passes.drain(..)
+        {
+            device.destroy_render_pass(&render_pass);
+        }
+        for cached in self.images.drain(..)
+        {
+            device.destroy_image_view(&cached.view);
+            device.destroy_image(&cached.image);
+        }
+        for cached in self.buffers.drain(..)
+        {
+            device.destroy_buffer(&cached.buffer);
+        }
+        for cached in self.memories.drain(..)
+        {
+            device.free_memory(&cached.memory);
+        }
+        self.image_requirements.clear();
+        self.buffer_requirements.clear();
+
+        Ok(())
+    }
+
+    // places every live transient in memory shared with transients of the same memory type, at an offset no
+    // other transient alive at the same time occupies, and hands it the cached resource at that spot. labels
+    // don't take part in the match, a reused resource keeps the label it was created with
+    fn allocate_images(&mut self, device: &Device, images: &[GraphImageNode], lifetimes: &[Option<(usize, usize)>]) -> Result<Vec<Option<usize>>, ()>
+    {
+        let mut slots: Vec<Option<usize>> = vec![ None; images.len() ];
+
+        let mut order: Vec<usize> = (0..images.len()).filter(|index| images[*index].imported.is_none() && lifetimes[*index].is_some()).collect();
+        order.sort_by_key(|index| lifetimes[*index].unwrap().0);
+
+        let mut groups: Vec<(u32, Placements)> = Vec::new();
+        for index in order
+        {
+            let requirements = self.image_requirements(device, &images[index].create_info)?;
+            match groups.iter_mut().find(|(memory_type_bits, _)| *memory_type_bits == requirements.memory_type_bits)
+            {
+                Some((_, members)) => members.push((index, requirements)),
+                None => groups.push((requirements.memory_type_bits, vec![ (index, requirements) ]))
+            }
+        }
+
+        for (memory_type_bits, members) in groups
+        {
+            let (offsets, size) = place(&members, lifetimes);
+            let memory = self.memory(device, true, MemoryLocation::GpuOnly, memory_type_bits, size)?;
+
+            for ((index, requirements), offset) in members.into_iter().zip(offsets)
+            {
+                let create_info = &images[index].create_info;
+                let slot = match self.images.iter().position(|cached| cached.create_info == ImageCreateInfo { label: None, ..*create_info } && cached.memory == memory && cached.offset == offset)
+                {
+                    Some(slot) => slot,
+                    None =>
+                    {
+                        let image = device.create_placed_image(create_info, &self.memories[memory].memory, offset)?;
+                        let view = device.create_image_view(&ImageViewCreateInfo
+                        {
+                            image: &image,
+                            base_mip_level: 0,
+                            mip_level_count: create_info.mip_levels,
+                            base_array_layer: 0,
+                            array_layer_count: create_info.array_layers,
+                            label: create_info.label
+                        })?;
+                        self.images.push(CachedImage
+                        {
+                            create_info: image_create_info(create_info),
+                            memory,
+                            offset,
+                            size: requirements.size,
+                            image,
+                            view,
+                            state: ResourceState::default()
+                        });
+                        self.images.len() - 1
+                    }
+                };
+
+                slots[index] = Some(slot);
+            }
+        }
+
+        Ok(slots)
+    }
+
+    fn allocate_buffers(&mut self, device: &Device, buffers: &[GraphBufferNode], lifetimes: &[Option<(usize, usize)>]) -> Result<Vec<Option<usize>>, ()>
+    {
+        let mut slots: Vec<Option<usize>> = vec![ None; buffers.len() ];
+
+        let mut order: Vec<usize> = (0..buffers.len()).filter(|index| buffers[*index].imported.is_none() && lifetimes[*index].is_some()).collect();
+        order.sort_by_key(|index| lifetimes[*index].unwrap().0);
+
+        let mut groups: Vec<(MemoryLocation, u32, Placements)> = Vec::new();
+        for index in order
+        {
+            let location = buffers[index].create_info.location;
+            let requirements = self.buffer_requirements(device, &buffers[index].create_info)?;
+            match groups.iter_mut().find(|(other, memory_type_bits, _)| *other == location && *memory_type_bits == requirements.memory_type_bits)
+            {
+                Some((_, _, members)) => members.push((index, requirements)),
+                None => groups.push((location, requirements.memory_type_bits, vec![ (index, requirements) ]))
+            }
+        }
+
+        for (location, memory_type_bits, members) in groups
+        {
+            let (offsets, size) = place(&members, lifetimes);
+            let memory = self.memory(device, false, location, memory_type_bits, size)?;
+
+            for ((index, requirements), offset) in members.into_iter().zip(offsets)
+            {
+                let create_info = &buffers[index].create_info;
+                let slot = match self.buffers.iter().position(|cached| cached.create_info == BufferCreateInfo { label: None, ..*create_info } && cached.memory == memory && cached.offset == offset)
+                {
+                    Some(slot) => slot,
+                    None =>
+                    {
+                        let buffer = device.create_placed_buffer(create_info, &self.memories[memory].memory, offset)?;
+                        self.buffers.push(CachedBuffer
+                        {
+                            create_info: BufferCreateInfo { size: create_info.size, usage: create_info.usage, location: create_info.location, label: None },
+                            memory,
+                            offset,
+                            size: requirements.size,
+                            buffer,
+                            state: ResourceState::default()
+                        });
+                        self.buffers.len() - 1
+                    }
+                };
+
+                slots[index] = Some(slot);
+            }
+        }
+
+        Ok(slots)
+    }
+
+    // the newest allocation of a kind is the one resources get placed in. an allocation that grew too small stays
+    // alive together with everything placed in it, since a frame still in flight may be using them
+    fn memory(&mut self, device: &Device, images: bool, location: MemoryLocation, memory_type_bits: u32, size: u64) -> Result<usize, ()>
+    {
+        let newest = self.memories.iter()
+            .rposition(|cached| cached.images == images && cached.location == location && cached.memory_type_bits == memory_type_bits);
+        if let Some(slot) = newest.filter(|slot| self.memories[*slot].memory.size() >= size)
+        {
+            return Ok(slot);
+        }
+
+        let memory = device.allocate_memory(&MemoryAllocateInfo { size, memory_type_bits, location, label: Some("render graph transients") })?;
+        self.memories.push(CachedMemory { images, location, memory_type_bits, memory });
+        Ok(self.memories.len() - 1)
+    }
+
+    // vulkan 1.0 creates a throwaway object for every query, so the answers are kept
+    fn image_requirements(&mut self, device: &Device, create_info: &ImageCreateInfo) -> Result<MemoryRequirements, ()>
+    {
+        let create_info = image_create_info(create_info);
+        if let Some((_, requirements)) = self.image_requirements.iter().find(|(cached, _)| *cached == create_info)
+        {
+            return Ok(*requirements);
+        }
+
+        let requirements = device.image_memory_requirements(&create_info)?;
+        self.image_requirements.push((create_info, requirements));
+        Ok(requirements)
+    }
+
+    fn buffer_requirements(&mut self, device: &Device, create_info: &BufferCreateInfo) -> Result<MemoryRequirements, ()>
+    {
+        let create_info = BufferCreateInfo { size: create_info.size, usage: create_info.usage, location: create_info.location, label: None };
+        if let Some((_, requirements)) = self.buffer_requirements.iter().find(|(cached, _)| *cached == create_info)
+        {
+            return Ok(*requirements);
+        }
+
+        let requirements = device.buffer_memory_requirements(&create_info)?;
+        self.buffer_requirements.push((create_info, requirements));
+        Ok(requirements)
+    }
+
+    // a transient's first use in a graph may follow other resources placed over the same memory, in this graph or
+    // an earlier one, and has to wait on everything they did
+    fn alias_image(&mut self, slot: usize)
+    {
+        let (memory, start, end) = (self.images[slot].memory, self.images[slot].offset, self.images[slot].offset + self.images[slot].size);
+        let aliased = self.images.iter().enumerate()
+            .filter(|(other, cached)| *other != slot && cached.memory == memory && cached.offset < end && start < cached.offset + cached.size)
+            .fold(ResourceState::default(), |mut state, (_, cached)| { state.alias(&cached.state); state });
+        self.images[slot].state.alias(&aliased);
+    }
+
+    fn alias_buffer(&mut self, slot: usize)
+    {
+        let (memory, start, end) = (self.buffers[slot].memory, self.buffers[slot].offset, self.buffers[slot].offset + self.buffers[slot].size);
+        let aliased = self.buffers.iter().enumerate()
+            .filter(|(other, cached)| *other != slot && cached.memory == memory && cached.offset < end && start < cached.offset + cached.size)
+            .fold(ResourceState::default(), |mut state, (_, cached)| { state.alias(&cached.state); state });
+        self.buffers[slot].state.alias(&aliased);
+    }
+}
+
+fn image_create_info(create_info: &ImageCreateInfo) -> ImageCreateInfo<'static>
+{
+    ImageCreateInfo
+    {
+        format: create_info.format,
+        width: create_info.width,
+        height: create_info.height,
+        depth: create_info.depth,
+        mip_levels: create_info.mip_levels,
+        array_layers: create_info.array_layers,
+        samples: create_info.samples,
+        usage: create_info.usage,
+        label: None
+    }
+}
+
+// transients sharing an allocation, by index into the graph's images or buffers
+type Placements = Vec<(usize, MemoryRequirements)>;
+
+// first fit over the members in order of their first use: each one takes the lowest aligned offset that doesn't
+// overlap a member placed before it whose lifetime overlaps its own. returns the offsets and the size the memory needs
+fn place(members: &[(usize, MemoryRequirements)], lifetimes: &[Option<(usize, usize)>]) -> (Vec<u64>, u64)
+{
+    let mut offsets: Vec<u64> = Vec::with_capacity(members.len());
+    let mut size = 0;
+
+    for (index, requirements) in members.iter()
+    {
+        let (first, last) = lifetimes[*index].unwrap();
+        let alignment = requirements.alignment.max(1);
+
+        // every collision moves the offset past the member it hit, so this settles after one pass per member at most
+        let mut offset = 0;
+        while let Some(end) = members.iter().zip(offsets.iter())
+            .filter(|((other, _), _)| lifetimes[*other].is_some_and(|(other_first, other_last)| other_first <= last && first <= other_last))
+            .map(|((_, other), other_offset)| (*other_offset, *other_offset + other.size))
+            .find(|(other_start, other_end)| *other_start < offset + requirements.size && offset < *other_end)
+            .map(|(_, other_end)| other_end)
+        {
+            offset = (end + alignment - 1) / alignment * alignment;
+        }
+
+        offsets.push(offset);
+        size = size.max(offset + requirements.size);
+    }
+
+    (offsets, size)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use std::cell::Cell;
+
+    fn device() -> Device
+    {
+        Device { internal: Box::new(software::SwDevice { }) }
+    }
+
+    fn encoder(device: &Device) -> CommandEncoder
+    {
+        let queue = device.get_device_queue().unwrap();
+        device.create_command_pool(&queue).unwrap().create_command_encoder().unwrap()
+    }
+
+    const BUFFER: BufferCreateInfo<'static> = BufferCreateInfo { label: None, size: 16, usage: 0, location: MemoryLocation::GpuOnly };
+
+    #[test]
+    fn culls_passes_nobody_observes()
+    {
+        let device = device();
+        let mut encoder = encoder(&device);
+        let mut cache = RenderGraphCache::new();
+        let output = device.create_buffer(&BUFFER).unwrap();
+        let ran = Cell::new(0);
+
+        let mut graph = RenderGraph::new();
+        let a = graph.create_buffer(&BUFFER);
+        let b = graph.create_buffer(&BUFFER);
+        let unused = graph.create_buffer(&BUFFER);
+        let o = graph.import_buffer(&output, None);
+        graph.add_pass("a").write_buffer(a, BufferAccess::TransferWrite).execute(|_, _| ran.set(ran.get() | 1));
+        graph.add_pass("b").read_buffer(a, BufferAccess::TransferRead).write_buffer(b, BufferAccess::TransferWrite).execute(|_, _| ran.set(ran.get() | 2));
+        graph.add_pass("unused").write_buffer(unused, BufferAccess::TransferWrite).execute(|_, _| ran.set(ran.get() | 4));
+        graph.add_pass("c").read_buffer(b, BufferAccess::TransferRead).write_buffer(o, BufferAccess::TransferWrite).execute(|_, _| ran.set(ran.get() | 8));
+        graph.export_buffer(o, BufferAccess::HostRead);
+        graph.execute(&device, &mut cache, &mut encoder).unwrap();
+
+        assert_eq!(ran.get(), 1 | 2 | 8);
+        // a and b overlap in pass b, the culled transient isn't allocated
+        assert_eq!(cache.buffers.len(), 2);
+    }
+
+    #[test]
+    fn transients_with_disjoint_lifetimes_share_resources()
+    {
+        let device = device();
+        let mut encoder = encoder(&device);
+        let mut cache = RenderGraphCache::new();
+        let output = device.create_buffer(&BUFFER).unwrap();
+
+        for _ in 0..2
+        {
+            let mut graph = RenderGraph::new();
+            let a = graph.create_buffer(&BUFFER);
+            let b = graph.create_buffer(&BUFFER);
+            let c = graph.create_buffer(&BUFFER);
+            let o = graph.import_buffer(&output, None);
+            graph.add_pass("a").write_buffer(a, BufferAccess::TransferWrite);
+            graph.add_pass("b").read_buffer(a, BufferAccess::TransferRead).write_buffer(b, BufferAccess::TransferWrite);
+            graph.add_pass("c").read_buffer(b, BufferAccess::TransferRead).write_buffer(c, BufferAccess::TransferWrite);
+            graph.add_pass("d").read_buffer(c, BufferAccess::TransferRead).write_buffer(o, BufferAccess::TransferWrite);
+            graph.execute(&device, &mut cache, &mut encoder).unwrap();
+
+            // c starts after a's last use, so it is placed where a was and takes over a's buffer, and the next
+            // graph reuses both
+            assert_eq!(cache.buffers.len(), 2);
+            assert_eq!((cache.memories.len(), cache.memories[0].memory.size()), (1, 32));
+        }
+
+        cache.clear(&device).unwrap();
+        assert!(cache.buffers.is_empty() && cache.memories.is_empty());
+    }
+
+    #[test]
+    fn transients_of_different_shapes_share_memory()
+    {
+        let device = device();
+        let mut encoder = encoder(&device);
+        let mut cache = RenderGraphCache::new();
+        let output = device.create_buffer(&BUFFER).unwrap();
+
+        let mut graph = RenderGraph::new();
+        let a = graph.create_buffer(&BufferCreateInfo { size: 64, ..BUFFER });
+        let b = graph.create_buffer(&BUFFER);
+        let c = graph.create_buffer(&BufferCreateInfo { size: 32, ..BUFFER });
+        let o = graph.import_buffer(&output, None);
+        graph.add_pass("a").write_buffer(a, BufferAccess::TransferWrite);
+        graph.add_pass("b").read_buffer(a, BufferAccess::TransferRead).write_buffer(b, BufferAccess::TransferWrite);
+        graph.add_pass("c").read_buffer(b, BufferAccess::TransferRead).write_buffer(c, BufferAccess::TransferWrite);
+        graph.add_pass("d").read_buffer(c, BufferAccess::TransferRead).write_buffer(o, BufferAccess::TransferWrite);
+        graph.execute(&device, &mut cache, &mut encoder).unwrap();
+
+        // b goes behind a, c fits into the first half of a's range
+        let placements: Vec<(u64, u64)> = cache.buffers.iter().map(|cached| (cached.offset, cached.size)).collect();
+        assert_eq!(placements, [ (0, 64), (64, 16), (0, 32) ]);
+        assert_eq!(cache.memories[0].memory.size(), 80);
+    }
+
+    #[test]
+    fn uses_have_to_match_their_direction()
+    {
+        let mut graph = RenderGraph::new();
+        let buffer = graph.create_buffer(&BUFFER);
+        let image = graph.create_image(&ImageCreateInfo { width: 4, height: 4, ..Default::default() });
+        graph.add_pass("pass")
+            .read_buffer(buffer, BufferAccess::TransferWrite)
+            .write_buffer(buffer, BufferAccess::ShaderRead(ShaderStageFlags::COMPUTE))
+            .read_image(image, ImageAccess::ShaderWrite(ShaderStageFlags::COMPUTE))
+            .write_image(image, ImageAccess::TransferRead);
+        assert!(graph.passes[0].buffers.is_empty() && graph.passes[0].images.is_empty());
+
+        graph.add_pass("matching")
+            .read_buffer(buffer, BufferAccess::ShaderRead(ShaderStageFlags::COMPUTE))
+            .write_buffer(buffer, BufferAccess::ShaderWrite(ShaderStageFlags::COMPUTE))
+            .read_image(image, ImageAccess::TransferRead)
+            .write_image(image, ImageAccess::TransferWrite);
+        assert_eq!((graph.passes[1].buffers.len(), graph.passes[1].images.len()), (2, 2));
+    }
+
+    #[test]
+    fn failed_attachments_record_nothing()
+    {
+        let device = device();
+        let mut encoder = encoder(&device);
+        let mut cache = RenderGraphCache::new();
+        let output = device.create_buffer(&BUFFER).unwrap();
+        let ran = Cell::new(false);
+
+        let mut graph = RenderGraph::new();
+        let o = graph.import_buffer(&output, None);
+        let small = graph.create_image(&ImageCreateInfo { width: 4, height: 4, ..Default::default() });
+        let large = graph.create_image(&ImageCreateInfo { width: 8, height: 8, ..Default::default() });
+        let operations = Operations { load_op: LoadOp::Clear(0.0, 0.0, 0.0, 1.0), store_op: StoreOp::Store };
+        graph.add_pass("first").write_buffer(o, BufferAccess::TransferWrite).execute(|_, _| ran.set(true));
+        graph.add_pass("mismatched").color_attachment(small, operations).color_attachment(large, operations).side_effects();
+
+        assert!(graph.execute(&device, &mut cache, &mut encoder).is_err());
+        assert!(!ran.get());
+    }
+}
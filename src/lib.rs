@@ -1,8 +1,30 @@
 mod software;
 mod vulkan;
 mod opengl;
+mod png;
+mod frame;
+mod graph;
+mod state;
+mod reload;
+mod compile;
 
-use std::{any::Any, fmt, io::Read, rc::Rc};
+pub use software::{ComputeInvocation, FragmentInvocation, FragmentOutput, ShaderResources, VertexInvocation, VertexOutput};
+pub use frame::{Frame, FrameContext, FrameRing, FrameRingCreateInfo};
+pub use graph::{GraphBuffer, GraphImage, GraphPass, GraphResources, RenderGraph, RenderGraphCache};
+pub use state::{BufferAccess, ImageAccess};
+pub use reload::{ShaderWatcher, WatchablePipeline, WatchedPipeline, WatchedShader};
+
+use std::{any::Any, fmt, io::Read, sync::Arc};
+
+// threading. every handle is Send + Sync and cheap to clone, and most of them are immutable after
+// creation so they can be used from any thread. the exceptions:
+// - Queue: submissions, presents and wait_idle are serialized internally per vulkan queue, including the device's
+//   own upload and readback submissions, so sharing one queue between threads is safe but contended
+// - CommandPool: create_command_encoder and reset are locked, but recording into encoders from the same pool must
+//   not happen on two threads at once, and reset must not race with recording. use one pool per thread
+// - CommandEncoder: Send but not Sync, record on one thread at a time
+// - Fence: wait and is_signaled are free threaded, reset must not race with a submission using the fence
+// - Swapchain: acquire_next_image and presents of the swapchain are serialized internally
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum API
@@ -41,7 +63,10 @@ pub enum ShaderStage
 {
     Vertex,
     Fragment,
-    Geometry
+    Geometry,
+    Compute,
+    TessellationControl,
+    TessellationEvaluation
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
@@ -51,7 +76,8 @@ pub enum Primitive
 	LineList,
 	LineStrip,
 	TriangleList,
-	TriangleStrip
+	TriangleStrip,
+	PatchList(u32)
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
@@ -84,6 +110,27 @@ pub enum PolygonMode
     Point
 }
 
+// how the window system combines the swapchain's alpha with what is behind the window
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum CompositeAlpha
+{
+    Opaque,
+    PreMultiplied,
+    PostMultiplied,
+    Inherit
+}
+
+// how the presentation engine interprets swapchain values. everything but SrgbNonlinear needs
+// VK_EXT_swapchain_colorspace and a display that supports it
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum ColorSpace
+{
+    SrgbNonlinear,
+    Bt709Linear,
+    ExtendedSrgbLinear,
+    Hdr10St2084
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum PresentMode
 {
@@ -93,6 +140,14 @@ pub enum PresentMode
     Mailbox
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum QueueKind
+{
+    Graphics,
+    Compute,
+    Transfer
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum IndexFormat
 {
@@ -100,6 +155,93 @@ pub enum IndexFormat
     Uint32
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum VertexFormat
+{
+    Float32,
+    Float32x2,
+    Float32x3,
+    Float32x4,
+    Uint32,
+    Uint32x2,
+    Uint32x3,
+    Uint32x4,
+    Sint32,
+    Sint32x2,
+    Sint32x3,
+    Sint32x4,
+    Float16x2,
+    Float16x4,
+    Unorm16x2,
+    Unorm16x4,
+    Unorm8x4,
+    Snorm8x4,
+    Uint8x4
+}
+
+impl VertexFormat
+{
+    pub fn size(&self) -> u32
+    {
+        match self
+        {
+            VertexFormat::Float32 | VertexFormat::Uint32 | VertexFormat::Sint32 | VertexFormat::Float16x2 | VertexFormat::Unorm16x2 |
+            VertexFormat::Unorm8x4 | VertexFormat::Snorm8x4 | VertexFormat::Uint8x4 => 4,
+            VertexFormat::Float32x2 | VertexFormat::Uint32x2 | VertexFormat::Sint32x2 | VertexFormat::Float16x4 | VertexFormat::Unorm16x4 => 8,
+            VertexFormat::Float32x3 | VertexFormat::Uint32x3 | VertexFormat::Sint32x3 => 12,
+            VertexFormat::Float32x4 | VertexFormat::Uint32x4 | VertexFormat::Sint32x4 => 16
+        }
+    }
+}
+
+// Instance(n) advances once every n instances, Instance(1) is plain per instance data
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum VertexStepMode
+{
+    Vertex,
+    Instance(u32)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct VertexAttribute
+{
+    pub location: u32,
+    pub offset: u32,
+    pub format: VertexFormat
+}
+
+// layouts are bound by their index in RenderPipelineCreateInfo::vertex_buffers
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct VertexBufferLayout<'a>
+{
+    pub stride: u32,
+    pub step_mode: VertexStepMode,
+    pub attributes: &'a [VertexAttribute]
+}
+
+// laid out like VkDrawIndirectCommand and GL's DrawArraysIndirectCommand
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, PartialOrd)]
+pub struct DrawIndirectCommand
+{
+    pub vertex_count: u32,
+    pub instance_count: u32,
+    pub first_vertex: u32,
+    pub first_instance: u32
+}
+
+// laid out like VkDrawIndexedIndirectCommand and GL's DrawElementsIndirectCommand
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, PartialOrd)]
+pub struct DrawIndexedIndirectCommand
+{
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub vertex_offset: i32,
+    pub first_instance: u32
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum BlendFactor
 {
@@ -124,36 +266,183 @@ pub enum BlendFactor
     OneMinusSrc1Alpha
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum BlendOp
+{
+    Add,
+    Subtract,
+    ReverseSubtract,
+    Min,
+    Max
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum CompareOp
+{
+    Never,
+    Less,
+    Equal,
+    LessOrEqual,
+    Greater,
+    NotEqual,
+    GreaterOrEqual,
+    Always
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum StencilOp
+{
+    Keep,
+    Zero,
+    Replace,
+    IncrementAndClamp,
+    DecrementAndClamp,
+    Invert,
+    IncrementAndWrap,
+    DecrementAndWrap
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum MemoryLocation
+{
+    GpuOnly,
+    CpuToGpu,
+    GpuToCpu
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum DescriptorType
+{
+    UniformBuffer,
+    StorageBuffer,
+    StorageImage
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum InstanceError
 {
     ApiNotSupported
 }
 
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum DeviceError
+{
+    NoSuitableDevice,
+    // the FeatureFlags out of DeviceCreateInfo::required_features the device doesn't support
+    MissingFeatures(u32),
+    MissingExtensions(Vec<DeviceExtension>),
+    // a DeviceExtension::Named that can't be passed on to the backend, e.g. because it contains a nul byte
+    InvalidExtensionName(String),
+    CreationFailed
+}
+
+// extensions the backends know how to enable, together with whatever they depend on. Named takes the
+// backend's own name, e.g. "VK_KHR_ray_query", and is enabled as is: its dependencies are neither added
+// nor checked unless it names one of the extensions above, so list them alongside it, e.g.
+// "VK_KHR_acceleration_structure" and "VK_KHR_spirv_1_4" for ray queries on a 1.0 device
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum DeviceExtension
+{
+    DynamicRendering,
+    TimelineSemaphore,
+    Synchronization2,
+    DescriptorIndexing,
+    MemoryBudget,
+    PushDescriptor,
+    DrawIndirectCount,
+    VertexAttributeDivisor,
+    Named(String)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum SwapchainError
+{
+    OutOfDate,
+    Failed
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum PipelineError
+{
+    InvalidShaderStage,
+    InvalidPrimitive,
+    InvalidSampleCount,
+    InvalidVertexLayout,
+    InvalidColorTargets,
+    UnsupportedShaderSource,
+    FeatureNotSupported,
+    CreationFailed
+}
+
 #[derive(Debug, Clone)]
 pub struct PhysicalDeviceProperties
 {
     pub vendor_id: u32,
     pub device_id: u32,
     pub device_type: DeviceType,
-    pub device_name: String
+    pub device_name: String,
+    // FeatureFlags the device supports, Device::enabled_features has the ones that were turned on
+    pub features: u32,
+    pub limits: Limits
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Limits
+{
+    pub max_image_dimension_2d: u32,
+    pub max_image_dimension_3d: u32,
+    pub max_image_array_layers: u32,
+    pub max_uniform_buffer_range: u32,
+    pub max_storage_buffer_range: u32,
+    pub max_bound_descriptor_sets: u32,
+    pub max_vertex_input_attributes: u32,
+    pub max_vertex_input_bindings: u32,
+    pub max_vertex_input_attribute_offset: u32,
+    pub max_vertex_input_binding_stride: u32,
+    pub max_color_attachments: u32,
+    pub max_framebuffer_width: u32,
+    pub max_framebuffer_height: u32,
+    pub max_viewport_dimensions: [u32; 2],
+    pub max_compute_workgroup_count: [u32; 3],
+    pub max_compute_workgroup_size: [u32; 3],
+    pub max_compute_workgroup_invocations: u32,
+    // 1 without FeatureFlags::MULTI_DRAW_INDIRECT
+    pub max_draw_indirect_count: u32,
+    pub min_uniform_buffer_offset_alignment: u64,
+    pub min_storage_buffer_offset_alignment: u64,
+    // nanoseconds per timestamp tick, query results are already converted
+    pub timestamp_period: f32,
+    pub point_size_range: [f32; 2],
+    pub line_width_range: [f32; 2]
+}
+
+// no PartialEq, software shaders are function pointers and comparing those says nothing about the shader
+#[derive(Debug, Clone)]
 pub enum ShaderModuleSource
 {
     Glsl(String),
-    Spirv(Vec<u32>)
+    Wgsl(String),
+    Spirv(Vec<u32>),
+    Software(SoftwareShader)
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct ShaderModuleCreateInfo
+#[derive(Debug, Copy, Clone)]
+pub enum SoftwareShader
+{
+    Compute { workgroup_size: [u32; 3], main: fn(&ComputeInvocation) },
+    Vertex { main: fn(&VertexInvocation) -> VertexOutput },
+    Fragment { main: fn(&FragmentInvocation) -> FragmentOutput }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShaderModuleCreateInfo<'a>
 {
     pub stage: ShaderStage,
-    pub source: ShaderModuleSource
+    pub source: ShaderModuleSource,
+    pub label: Option<&'a str>
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum ImageLayout
 {
     General,
@@ -167,7 +456,7 @@ pub enum ImageLayout
     PresentSrc
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum Format
 {
     Undefined,
@@ -178,13 +467,60 @@ pub enum Format
     R16Uint,
     R16Sint,
     R16Unorm,
-    R16Snorm
+    R16Snorm,
+    R16Float,
+    R32Uint,
+    R32Sint,
+    R32Float,
+    R8G8Unorm,
+    R16G16Float,
+    R32G32Float,
+    R8G8B8A8Unorm,
+    R8G8B8A8Srgb,
+    R8G8B8A8Uint,
+    B8G8R8A8Unorm,
+    B8G8R8A8Srgb,
+    A2B10G10R10Unorm,
+    R16G16B16A16Float,
+    R32G32B32A32Uint,
+    R32G32B32A32Float,
+    D16Unorm,
+    D32Float,
+    D24UnormS8Uint,
+    D32FloatS8Uint
+}
+
+impl Format
+{
+    pub fn has_depth(&self) -> bool
+    {
+        matches!(self, Format::D16Unorm | Format::D32Float | Format::D24UnormS8Uint | Format::D32FloatS8Uint)
+    }
+
+    pub fn has_stencil(&self) -> bool
+    {
+        matches!(self, Format::D24UnormS8Uint | Format::D32FloatS8Uint)
+    }
+
+    pub fn texel_size(&self) -> u32
+    {
+        match self
+        {
+            Format::Undefined => 0,
+            Format::R8Unorm | Format::R8Snorm | Format::R8Uint | Format::R8Sint => 1,
+            Format::R16Uint | Format::R16Sint | Format::R16Unorm | Format::R16Snorm | Format::R16Float | Format::R8G8Unorm | Format::D16Unorm => 2,
+            Format::R32Uint | Format::R32Sint | Format::R32Float | Format::R16G16Float | Format::R8G8B8A8Unorm | Format::R8G8B8A8Srgb |
+            Format::R8G8B8A8Uint | Format::B8G8R8A8Unorm | Format::B8G8R8A8Srgb | Format::A2B10G10R10Unorm | Format::D32Float | Format::D24UnormS8Uint => 4,
+            Format::R32G32Float | Format::R16G16B16A16Float | Format::D32FloatS8Uint => 8,
+            Format::R32G32B32A32Uint | Format::R32G32B32A32Float => 16
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct AttachmentDescription
 {
-    pub format: u32, // TODO(quigly): change this to enum!
+    pub format: Format,
     pub samples: u32,
     pub operations: Operations,
     pub stencil_operations: Operations,
@@ -192,268 +528,2176 @@ pub struct AttachmentDescription
     pub final_layout: ImageLayout
 }
 
-mod PipelineStageFlags
+#[allow(non_snake_case)]
+pub mod PipelineStageFlags
 {
-    const TOP_OF_PIPE:                    u32 = 0b00000001;
-    const DRAW_INDIRECT:                  u32 = 0b00000010;
-    const VERTEX_INPUT:                   u32 = 0b00000011;
-    const VERTEX_SHADER:                  u32 = 0b00000100;
-    const TESSELLATION_CONTROL_SHADER:    u32 = 0b00000101;
-    const TESSELLATION_EVALUATION_SHADER: u32 = 0b00000110;
-    const GEOMETRY_SHADER:                u32 = 0b00000111;
-    const FRAGMENT_SHADER:                u32 = 0b00001000;
-    const EARLY_FRAGMENT_TESTS:           u32 = 0b00001001;
-    const LATE_FRAGMENT_TESTS:            u32 = 0b00001010;
-    const COLOR_ATTACHMENT_OUTPUT:        u32 = 0b00001011;
-    const COMPUTE_SHADER:                 u32 = 0b00001100;
-    const TRANSFER_BIT:                   u32 = 0b00001101;
-    const BOTTOM_OF_PIPE:                 u32 = 0b00001110;
-    const HOST:                           u32 = 0b00001111;
-    const ALL_GRAPHICS:                   u32 = 0b00010000;
-    const ALL_COMMANDS:                   u32 = 0b00010001;
+    pub const TOP_OF_PIPE:                    u32 = 1 << 0;
+    pub const DRAW_INDIRECT:                  u32 = 1 << 1;
+    pub const VERTEX_INPUT:                   u32 = 1 << 2;
+    pub const VERTEX_SHADER:                  u32 = 1 << 3;
+    pub const TESSELLATION_CONTROL_SHADER:    u32 = 1 << 4;
+    pub const TESSELLATION_EVALUATION_SHADER: u32 = 1 << 5;
+    pub const GEOMETRY_SHADER:                u32 = 1 << 6;
+    pub const FRAGMENT_SHADER:                u32 = 1 << 7;
+    pub const EARLY_FRAGMENT_TESTS:           u32 = 1 << 8;
+    pub const LATE_FRAGMENT_TESTS:            u32 = 1 << 9;
+    pub const COLOR_ATTACHMENT_OUTPUT:        u32 = 1 << 10;
+    pub const COMPUTE_SHADER:                 u32 = 1 << 11;
+    pub const TRANSFER_BIT:                   u32 = 1 << 12;
+    pub const BOTTOM_OF_PIPE:                 u32 = 1 << 13;
+    pub const HOST:                           u32 = 1 << 14;
+    pub const ALL_GRAPHICS:                   u32 = 1 << 15;
+    pub const ALL_COMMANDS:                   u32 = 1 << 16;
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct SubpassDependency
+#[allow(non_snake_case)]
+pub mod AccessFlags
 {
-    pub src_subpass: u32,
-    pub dst_subpass: u32,
-    pub src_stage_mask: u32,
-    pub dst_stage_mask: u32,
-    pub src_access_mask: u32,
-    pub dst_access_mask: u32
+    pub const INDIRECT_COMMAND_READ:          u32 = 1 << 0;
+    pub const INDEX_READ:                     u32 = 1 << 1;
+    pub const VERTEX_ATTRIBUTE_READ:          u32 = 1 << 2;
+    pub const UNIFORM_READ:                   u32 = 1 << 3;
+    pub const INPUT_ATTACHMENT_READ:          u32 = 1 << 4;
+    pub const SHADER_READ:                    u32 = 1 << 5;
+    pub const SHADER_WRITE:                   u32 = 1 << 6;
+    pub const COLOR_ATTACHMENT_READ:          u32 = 1 << 7;
+    pub const COLOR_ATTACHMENT_WRITE:         u32 = 1 << 8;
+    pub const DEPTH_STENCIL_ATTACHMENT_READ:  u32 = 1 << 9;
+    pub const DEPTH_STENCIL_ATTACHMENT_WRITE: u32 = 1 << 10;
+    pub const TRANSFER_READ:                  u32 = 1 << 11;
+    pub const TRANSFER_WRITE:                 u32 = 1 << 12;
+    pub const HOST_READ:                      u32 = 1 << 13;
+    pub const HOST_WRITE:                     u32 = 1 << 14;
+    pub const MEMORY_READ:                    u32 = 1 << 15;
+    pub const MEMORY_WRITE:                   u32 = 1 << 16;
 }
 
-pub struct RenderPassCreateInfo
+#[allow(non_snake_case)]
+pub mod ShaderStageFlags
 {
-    //pub attachments: Vec<AttachmentDescription>,
-    //pub subpasses: Vec<TODO>
+    pub const VERTEX:                  u32 = 1 << 0;
+    pub const TESSELLATION_CONTROL:    u32 = 1 << 1;
+    pub const TESSELLATION_EVALUATION: u32 = 1 << 2;
+    pub const GEOMETRY:                u32 = 1 << 3;
+    pub const FRAGMENT:                u32 = 1 << 4;
+    pub const COMPUTE:                 u32 = 1 << 5;
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub enum ShaderModuleError
+#[allow(non_snake_case)]
+pub mod BufferUsageFlags
 {
-    CompilationFailed,
+    pub const TRANSFER_SRC: u32 = 1 << 0;
+    pub const TRANSFER_DST: u32 = 1 << 1;
+    pub const UNIFORM:      u32 = 1 << 2;
+    pub const STORAGE:      u32 = 1 << 3;
+    pub const INDEX:        u32 = 1 << 4;
+    pub const VERTEX:       u32 = 1 << 5;
+    pub const INDIRECT:     u32 = 1 << 6;
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct SwapchainCreateInfo
+#[allow(non_snake_case)]
+pub mod ColorWriteFlags
 {
-    pub present_mode: PresentMode
+    pub const R:   u32 = 1 << 0;
+    pub const G:   u32 = 1 << 1;
+    pub const B:   u32 = 1 << 2;
+    pub const A:   u32 = 1 << 3;
+    pub const ALL: u32 = R | G | B | A;
 }
 
-impl Default for SwapchainCreateInfo
+#[allow(non_snake_case)]
+pub mod FeatureFlags
 {
-    fn default() -> Self
-    {
-		Self
-		{
-			present_mode: PresentMode::Fifo
-		}
-    }
+    pub const GEOMETRY_SHADER:                    u32 = 1 << 0;
+    pub const TESSELLATION_SHADER:                u32 = 1 << 1;
+    pub const DEPTH_CLAMP:                        u32 = 1 << 2;
+    pub const DEPTH_BIAS_CLAMP:                   u32 = 1 << 3;
+    pub const SAMPLE_RATE_SHADING:                u32 = 1 << 4;
+    pub const PIPELINE_STATISTICS_QUERY:          u32 = 1 << 5;
+    pub const MULTI_DRAW_INDIRECT:                u32 = 1 << 6;
+    pub const DRAW_INDIRECT_FIRST_INSTANCE:       u32 = 1 << 7;
+    pub const DRAW_INDIRECT_COUNT:                u32 = 1 << 8;
+    pub const VERTEX_ATTRIBUTE_DIVISOR:           u32 = 1 << 9;
+    pub const FILL_MODE_NON_SOLID:                u32 = 1 << 10;
+    pub const WIDE_LINES:                         u32 = 1 << 11;
+    pub const LARGE_POINTS:                       u32 = 1 << 12;
+    pub const INDEPENDENT_BLEND:                  u32 = 1 << 13;
+    pub const DUAL_SOURCE_BLEND:                  u32 = 1 << 14;
+    pub const FRAGMENT_STORES_AND_ATOMICS:        u32 = 1 << 15;
+    pub const VERTEX_PIPELINE_STORES_AND_ATOMICS: u32 = 1 << 16;
+    pub const SHADER_FLOAT64:                     u32 = 1 << 17;
+    pub const SHADER_INT64:                       u32 = 1 << 18;
+    pub const SHADER_INT16:                       u32 = 1 << 19;
+    pub const DYNAMIC_RENDERING:                  u32 = 1 << 20;
+    pub const ALL:                                u32 = (1 << 21) - 1;
 }
 
-pub trait AbstractInstance
+// pipeline state that is taken from the command encoder's set_* calls instead of the create info
+#[allow(non_snake_case)]
+pub mod DynamicStateFlags
 {
-    fn as_any(&self) -> &dyn Any;
-    fn create_surface(&self, window: &qpl::Window) -> Result<Surface, ()>;
-    fn create_device(&self, surface: &Surface) -> Result<Device, ()>;
-    fn create_swapchain(&self, device: &Device, surface: &Surface, create_info: &SwapchainCreateInfo) -> Result<Swapchain, ()>;
+    pub const VIEWPORT:          u32 = 1 << 0;
+    pub const SCISSOR:           u32 = 1 << 1;
+    pub const LINE_WIDTH:        u32 = 1 << 2;
+    pub const DEPTH_BIAS:        u32 = 1 << 3;
+    pub const BLEND_CONSTANTS:   u32 = 1 << 4;
+    pub const STENCIL_REFERENCE: u32 = 1 << 5;
 }
 
-pub trait AbstractDevice
+#[allow(non_snake_case)]
+pub mod ImageUsageFlags
 {
-    fn as_any(&self) -> &dyn Any;
-    fn get_device_queue(&self) -> Result<Queue, ()>;
-    fn get_physical_device_properties(&self) -> Result<PhysicalDeviceProperties, ()>;
-    fn create_shader_module(&self, create_info: &ShaderModuleCreateInfo) -> Result<ShaderModule, ShaderModuleError>;
+    pub const TRANSFER_SRC:             u32 = 1 << 0;
+    pub const TRANSFER_DST:             u32 = 1 << 1;
+    pub const SAMPLED:                  u32 = 1 << 2;
+    pub const STORAGE:                  u32 = 1 << 3;
+    pub const COLOR_ATTACHMENT:         u32 = 1 << 4;
+    pub const DEPTH_STENCIL_ATTACHMENT: u32 = 1 << 5;
 }
 
-pub trait AbstractQueue
-{
-    fn as_any(&self) -> &dyn Any;
-}
+pub const SUBPASS_EXTERNAL: u32 = !0;
+
+const SPIRV_MAGIC: u32 = 0x07230203;
 
-pub trait AbstractSurface
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct SubpassDependency
 {
-    fn as_any(&self) -> &dyn Any;
+    pub src_subpass: u32,
+    pub dst_subpass: u32,
+    pub src_stage_mask: u32,
+    pub dst_stage_mask: u32,
+    pub src_access_mask: u32,
+    pub dst_access_mask: u32
 }
 
-pub trait AbstractSwapchain
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct AttachmentReference
 {
-    fn as_any(&self) -> &dyn Any;
+    pub attachment: u32,
+    pub layout: ImageLayout
 }
 
-pub trait AbstractImage
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct SubpassDescription
 {
-    fn as_any(&self) -> &dyn Any;
+    pub color_attachments: Vec<AttachmentReference>,
+    pub resolve_attachments: Vec<AttachmentReference>,
+    pub depth_stencil_attachment: Option<AttachmentReference>
 }
 
-pub trait AbstractImageView
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct RenderPassCreateInfo<'a>
 {
-    fn as_any(&self) -> &dyn Any;
+    pub attachments: Vec<AttachmentDescription>,
+    pub subpasses: Vec<SubpassDescription>,
+    pub dependencies: Vec<SubpassDependency>,
+    pub label: Option<&'a str>
 }
 
-pub trait AbstractShaderModule
+// attachments of begin_rendering, the render area covers the views' extent at their base mip level.
+// there are no resolve attachments yet, multisampled attachments are resolved with
+// CommandEncoder::resolve_image after end_rendering or rendered through a subpass' resolve_attachments
+pub struct RenderingAttachment<'a>
 {
-    fn as_any(&self) -> &dyn Any;
+    pub view: &'a ImageView,
+    pub operations: Operations
 }
 
-pub struct Instance
+pub struct RenderingInfo<'a>
 {
-    api: API,
-    internal: Box<dyn AbstractInstance>
+    pub color_attachments: &'a [RenderingAttachment<'a>],
+    pub depth_stencil_attachment: Option<RenderingAttachment<'a>>,
+    pub stencil_operations: Operations
 }
 
-impl Instance
+impl RenderingInfo<'_>
 {
-    pub fn new(api: API, window: &qpl::Window) -> Result<Self, InstanceError>
+    pub(crate) fn extent(&self) -> (u32, u32)
     {
-        match api
-        {
-            API::Vulkan =>
-            {
-                match vulkan::VkInstance::new()
-                {
-                    Ok(instance) =>
-                    {
-                        Ok(Self
-                        {
-                            api,
-                            internal: Box::new(instance)
-                        })
-                    },
-                    Err(err) =>
-                    {
-                        Err(err)
-                    }
-                }
-                
-            },
-            /*API::OpenGL =>
+        self.color_attachments.iter().chain(self.depth_stencil_attachment.iter())
+            .map(|attachment|
             {
-                match opengl::GlInstance::new(api, window)
-                {
-                    Ok(instance) =>
-                    {
-                        Ok(Self
-                        {
-                            api,
-                            internal: Box::new(instance)
-                        })
-                    },
-                    Err(err) =>
-                    {
-                        Err(err)
-                    }
-                }
-            },*/
-            _ => { todo!() }
-        }
-    }
-
-    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
-
-    pub fn create_surface(&self, window: &qpl::Window) -> Result<Surface, ()>
-    {
-        self.internal.create_surface(window)
-    }
-
-    pub fn create_device(&self, surface: &Surface) -> Result<Device, ()>
-    {
-        self.internal.create_device(surface)
+                let create_info = attachment.view.image().create_info();
+                let mip_level = attachment.view.subresource_range().base_mip_level;
+                ((create_info.width >> mip_level).max(1), (create_info.height >> mip_level).max(1))
+            })
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1)))
+            .unwrap_or((0, 0))
     }
 
-    pub fn create_swapchain(&self, device: &Device, surface: &Surface, create_info: &SwapchainCreateInfo) -> Result<Swapchain, ()>
+    pub(crate) fn layers(&self) -> u32
     {
-        self.internal.create_swapchain(device, surface, create_info)
+        self.color_attachments.iter().chain(self.depth_stencil_attachment.iter())
+            .map(|attachment| attachment.view.subresource_range().array_layer_count)
+            .min()
+            .unwrap_or(1)
     }
 }
 
-pub struct Device
+pub struct FramebufferCreateInfo<'a>
 {
-    internal: Box<dyn AbstractDevice>
+    pub render_pass: &'a RenderPass,
+    pub attachments: &'a [&'a ImageView],
+    pub width: u32,
+    pub height: u32,
+    pub layers: u32,
+    pub label: Option<&'a str>
 }
 
-impl Device
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Viewport
 {
-    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
-
-    pub fn get_device_queue(&self) -> Result<Queue, ()>
-    {
-        self.internal.get_device_queue()
-    }
-
-    pub fn get_physical_device_properties(&self) -> Result<PhysicalDeviceProperties, ()>
-    {
-        self.internal.get_physical_device_properties()
-    }
-
-    pub fn create_shader_module(&self, create_info: &ShaderModuleCreateInfo) -> Result<ShaderModule, ShaderModuleError>
-    {
-        self.internal.create_shader_module(create_info)
-    }
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub min_depth: f32,
+    pub max_depth: f32
 }
 
-#[derive(Clone)]
-pub struct Queue
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Scissor
 {
-    internal: Rc<dyn AbstractQueue>
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32
 }
 
-impl Queue
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct StencilFaceState
 {
-    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+    pub fail_op: StencilOp,
+    pub pass_op: StencilOp,
+    pub depth_fail_op: StencilOp,
+    pub compare_op: CompareOp,
+    pub compare_mask: u32,
+    pub write_mask: u32,
+    pub reference: u32
 }
 
-#[derive(Clone)]
-pub struct Surface
+impl Default for StencilFaceState
 {
-    internal: Rc<dyn AbstractSurface>
+    fn default() -> Self
+    {
+        Self
+        {
+            fail_op: StencilOp::Keep,
+            pass_op: StencilOp::Keep,
+            depth_fail_op: StencilOp::Keep,
+            compare_op: CompareOp::Always,
+            compare_mask: 0xFF,
+            write_mask: 0xFF,
+            reference: 0
+        }
+    }
 }
 
-impl Surface
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct DepthStencilState
 {
-    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+    pub depth_test_enable: bool,
+    pub depth_write_enable: bool,
+    pub depth_compare_op: CompareOp,
+    pub stencil_test_enable: bool,
+    pub front: StencilFaceState,
+    pub back: StencilFaceState
 }
 
-#[derive(Clone)]
-pub struct Swapchain
+impl Default for DepthStencilState
 {
-    internal: Rc<dyn AbstractSwapchain>
+    fn default() -> Self
+    {
+        Self
+        {
+            depth_test_enable: false,
+            depth_write_enable: false,
+            depth_compare_op: CompareOp::Less,
+            stencil_test_enable: false,
+            front: StencilFaceState::default(),
+            back: StencilFaceState::default()
+        }
+    }
 }
 
-impl Swapchain
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct DepthBias
 {
-    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+    pub constant_factor: f32,
+    pub slope_factor: f32,
+    pub clamp: f32
 }
 
-pub struct Image
+// Src1 factors read the fragment's second output and need the dualSrcBlend feature
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct ColorTargetState
 {
-    internal: Rc<dyn AbstractImage>
+    pub blend_enable: bool,
+    pub src_color_factor: BlendFactor,
+    pub dst_color_factor: BlendFactor,
+    pub color_op: BlendOp,
+    pub src_alpha_factor: BlendFactor,
+    pub dst_alpha_factor: BlendFactor,
+    pub alpha_op: BlendOp,
+    pub write_mask: u32
 }
 
-impl Image
+impl Default for ColorTargetState
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            blend_enable: false,
+            src_color_factor: BlendFactor::One,
+            dst_color_factor: BlendFactor::Zero,
+            color_op: BlendOp::Add,
+            src_alpha_factor: BlendFactor::One,
+            dst_alpha_factor: BlendFactor::Zero,
+            alpha_op: BlendOp::Add,
+            write_mask: ColorWriteFlags::ALL
+        }
+    }
+}
+
+impl ColorTargetState
+{
+    pub fn uses_dual_source(&self) -> bool
+    {
+        let dual_source = |factor: BlendFactor| matches!(factor,
+            BlendFactor::Src1Color | BlendFactor::OneMinusSrc1Color | BlendFactor::Src1Alpha | BlendFactor::OneMinusSrc1Alpha);
+
+        self.blend_enable && [ self.src_color_factor, self.dst_color_factor, self.src_alpha_factor, self.dst_alpha_factor ].into_iter().any(dual_source)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct MultisampleState
+{
+    pub samples: u32,
+    pub alpha_to_coverage_enable: bool,
+    pub min_sample_shading: Option<f32>
+}
+
+impl Default for MultisampleState
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            samples: 1,
+            alpha_to_coverage_enable: false,
+            min_sample_shading: None
+        }
+    }
+}
+
+// pipelines for begin_rendering only declare the formats of their attachments
+#[derive(Copy, Clone)]
+pub enum PipelineRendering<'a>
+{
+    RenderPass { render_pass: &'a RenderPass, subpass: u32 },
+    Dynamic { color_formats: &'a [Format], depth_stencil_format: Option<Format> }
+}
+
+pub struct RenderPipelineCreateInfo<'a>
+{
+    pub stages: &'a [&'a ShaderModule],
+    pub vertex_buffers: &'a [VertexBufferLayout<'a>],
+    pub primitive: Primitive,
+    pub polygon_mode: PolygonMode,
+    pub cull_mode: Option<CullMode>,
+    pub front_face: FrontFace,
+    pub viewport: Viewport,
+    pub depth_stencil: DepthStencilState,
+    pub depth_bias: Option<DepthBias>,
+    pub depth_clamp: bool,
+    pub multisample: MultisampleState,
+    // one per color attachment of the subpass or color format, or empty for no blending and full write masks
+    pub color_targets: &'a [ColorTargetState],
+    pub dynamic_state: u32,
+    pub set_layouts: &'a [&'a DescriptorSetLayout],
+    pub rendering: PipelineRendering<'a>,
+    pub pipeline_cache: Option<&'a PipelineCache>,
+    pub label: Option<&'a str>
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum ShaderModuleError
+{
+    // diagnostics from the compiler or driver
+    CompilationFailed(String),
+    // what the backend or file loader expected instead. the vulkan backend compiles GLSL and WGSL, the opengl backend takes GLSL as is
+    UnsupportedSource(String),
+    Io(String)
+}
+
+// what a buffer or image needs from the memory it is placed in. bit i of memory_type_bits is set when
+// the resource can live in the device's memory type i
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct MemoryRequirements
+{
+    pub size: u64,
+    pub alignment: u64,
+    pub memory_type_bits: u32
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct MemoryAllocateInfo<'a>
+{
+    pub size: u64,
+    // the memory_type_bits of every resource that is going to be placed in the allocation, and-ed together
+    pub memory_type_bits: u32,
+    pub location: MemoryLocation,
+    pub label: Option<&'a str>
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct BufferCreateInfo<'a>
+{
+    pub size: u64,
+    pub usage: u32,
+    pub location: MemoryLocation,
+    pub label: Option<&'a str>
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct ImageCreateInfo<'a>
+{
+    pub format: Format,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub mip_levels: u32,
+    pub array_layers: u32,
+    pub samples: u32,
+    pub usage: u32,
+    pub label: Option<&'a str>
+}
+
+impl Default for ImageCreateInfo<'_>
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            format: Format::R8G8B8A8Unorm,
+            width: 1,
+            height: 1,
+            depth: 1,
+            mip_levels: 1,
+            array_layers: 1,
+            samples: 1,
+            usage: ImageUsageFlags::SAMPLED,
+            label: None
+        }
+    }
+}
+
+pub struct ImageViewCreateInfo<'a>
+{
+    pub image: &'a Image,
+    pub base_mip_level: u32,
+    pub mip_level_count: u32,
+    pub base_array_layer: u32,
+    pub array_layer_count: u32,
+    pub label: Option<&'a str>
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageSubresourceRange
+{
+    pub base_mip_level: u32,
+    pub mip_level_count: u32,
+    pub base_array_layer: u32,
+    pub array_layer_count: u32
+}
+
+impl Default for ImageSubresourceRange
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            base_mip_level: 0,
+            mip_level_count: 1,
+            base_array_layer: 0,
+            array_layer_count: 1
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageRegion
+{
+    pub mip_level: u32,
+    pub base_array_layer: u32,
+    pub array_layer_count: u32,
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32
+}
+
+impl Default for ImageRegion
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            mip_level: 0,
+            base_array_layer: 0,
+            array_layer_count: 1,
+            x: 0,
+            y: 0,
+            z: 0,
+            width: 1,
+            height: 1,
+            depth: 1
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct DescriptorSetLayoutBinding
+{
+    pub binding: u32,
+    pub descriptor_type: DescriptorType,
+    pub count: u32,
+    pub stages: u32
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct DescriptorSetLayoutCreateInfo<'a>
+{
+    pub bindings: Vec<DescriptorSetLayoutBinding>,
+    pub label: Option<&'a str>
+}
+
+pub enum DescriptorResource<'a>
+{
+    Buffer { buffer: &'a Buffer, offset: u64, size: u64 },
+    Image(&'a ImageView)
+}
+
+pub struct DescriptorWrite<'a>
+{
+    pub binding: u32,
+    pub resource: DescriptorResource<'a>
+}
+
+pub struct DescriptorSetCreateInfo<'a>
+{
+    pub layout: &'a DescriptorSetLayout,
+    pub writes: &'a [DescriptorWrite<'a>],
+    pub label: Option<&'a str>
+}
+
+pub struct ComputePipelineCreateInfo<'a>
+{
+    pub shader: &'a ShaderModule,
+    pub set_layouts: &'a [&'a DescriptorSetLayout],
+    pub pipeline_cache: Option<&'a PipelineCache>,
+    pub label: Option<&'a str>
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct MemoryBarrier
+{
+    pub src_stage_mask: u32,
+    pub src_access_mask: u32,
+    pub dst_stage_mask: u32,
+    pub dst_access_mask: u32
+}
+
+// moves a resource between queue families. record the same barrier on the releasing queue and
+// then on the acquiring queue, ordered with a semaphore
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct QueueTransfer
+{
+    pub src_queue_family: u32,
+    pub dst_queue_family: u32
+}
+
+pub struct BufferBarrier<'a>
+{
+    pub buffer: &'a Buffer,
+    pub offset: u64,
+    pub size: u64,
+    pub src_stage_mask: u32,
+    pub src_access_mask: u32,
+    pub dst_stage_mask: u32,
+    pub dst_access_mask: u32,
+    pub queue_transfer: Option<QueueTransfer>
+}
+
+pub struct ImageBarrier<'a>
+{
+    pub image: &'a Image,
+    // None covers every mip level and array layer
+    pub subresource_range: Option<ImageSubresourceRange>,
+    pub old_layout: Option<ImageLayout>,
+    pub new_layout: ImageLayout,
+    pub src_stage_mask: u32,
+    pub src_access_mask: u32,
+    pub dst_stage_mask: u32,
+    pub dst_access_mask: u32,
+    pub queue_transfer: Option<QueueTransfer>
+}
+
+#[allow(non_snake_case)]
+pub mod PipelineStatisticFlags
+{
+    pub const INPUT_ASSEMBLY_VERTICES:                    u32 = 1 << 0;
+    pub const INPUT_ASSEMBLY_PRIMITIVES:                  u32 = 1 << 1;
+    pub const VERTEX_SHADER_INVOCATIONS:                  u32 = 1 << 2;
+    pub const GEOMETRY_SHADER_INVOCATIONS:                u32 = 1 << 3;
+    pub const GEOMETRY_SHADER_PRIMITIVES:                 u32 = 1 << 4;
+    pub const CLIPPING_INVOCATIONS:                       u32 = 1 << 5;
+    pub const CLIPPING_PRIMITIVES:                        u32 = 1 << 6;
+    pub const FRAGMENT_SHADER_INVOCATIONS:                u32 = 1 << 7;
+    pub const TESSELLATION_CONTROL_SHADER_PATCHES:        u32 = 1 << 8;
+    pub const TESSELLATION_EVALUATION_SHADER_INVOCATIONS: u32 = 1 << 9;
+    pub const COMPUTE_SHADER_INVOCATIONS:                 u32 = 1 << 10;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum QueryType
+{
+    // results are in nanoseconds
+    Timestamp,
+    // number of samples passing the depth and stencil tests
+    Occlusion,
+    // PipelineStatisticFlags, one result per enabled statistic in bit order
+    PipelineStatistics(u32)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct QueryPoolCreateInfo<'a>
+{
+    pub query_type: QueryType,
+    pub count: u32,
+    pub label: Option<&'a str>
+}
+
+impl Default for QueryPoolCreateInfo<'_>
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            query_type: QueryType::Timestamp,
+            count: 1,
+            label: None
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SubmitInfo<'a>
+{
+    pub wait_semaphores: &'a [&'a Semaphore],
+    pub wait_stage_masks: &'a [u32],
+    pub command_buffers: &'a [&'a CommandBuffer],
+    pub signal_semaphores: &'a [&'a Semaphore],
+    pub fence: Option<&'a Fence>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurfaceCapabilities
+{
+    pub present_modes: Vec<PresentMode>,
+    pub formats: Vec<(Format, ColorSpace)>,
+    pub min_image_count: u32,
+    // None when there is no upper limit
+    pub max_image_count: Option<u32>,
+    // None when the swapchain decides the extent
+    pub current_extent: Option<(u32, u32)>,
+    pub min_extent: (u32, u32),
+    pub max_extent: (u32, u32),
+    pub usage: u32,
+    pub composite_alpha: Vec<CompositeAlpha>
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct SwapchainCreateInfo<'a>
+{
+    // tried in order, Fifo is always supported and used when none of them are
+    pub present_modes: &'a [PresentMode],
+    // tried in order in the requested color space, when none are supported an sRGB format is picked.
+    // a color space the surface doesn't support falls back to SrgbNonlinear
+    pub preferred_formats: &'a [Format],
+    pub color_space: ColorSpace,
+    // None asks for one more than the minimum. the count is clamped to the surface's range and the
+    // driver may still create more images
+    pub image_count: Option<u32>,
+    // ImageUsageFlags on top of COLOR_ATTACHMENT, usages the surface or format can't provide are dropped
+    pub usage: u32,
+    // falls back to Opaque, then to whatever the surface supports
+    pub composite_alpha: CompositeAlpha,
+    pub depth_format: Option<Format>,
+    pub label: Option<&'a str>
+}
+
+impl Default for SwapchainCreateInfo<'_>
+{
+    fn default() -> Self
+    {
+		Self
+		{
+			present_modes: &[ PresentMode::Fifo ],
+			preferred_formats: &[],
+			color_space: ColorSpace::SrgbNonlinear,
+			image_count: None,
+			usage: 0,
+			composite_alpha: CompositeAlpha::Opaque,
+			depth_format: None,
+			label: None
+		}
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct QueueRequest
+{
+    pub kind: QueueKind,
+    pub priority: f32
+}
+
+// a graphics queue that can present is always created, queues listed here come on top of it.
+// creation fails when a required feature is missing, optional features are enabled where supported.
+// no features are enabled by default, pass e.g. FeatureFlags::ALL as optional_features to get everything available
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd)]
+pub struct DeviceCreateInfo<'a>
+{
+    pub queues: Vec<QueueRequest>,
+    pub required_features: u32,
+    pub optional_features: u32,
+    pub required_extensions: Vec<DeviceExtension>,
+    pub optional_extensions: Vec<DeviceExtension>,
+    pub label: Option<&'a str>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedImage
+{
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>
+}
+
+impl CapturedImage
+{
+    // converts tightly packed texels of any color format into RGBA8, keeping srgb encoded values as they are
+    fn from_texels(format: Format, width: u32, height: u32, data: &[u8]) -> Result<Self, ()>
+    {
+        let texel_size = format.texel_size() as usize;
+        if texel_size == 0 || format.has_depth() || data.len() != width as usize * height as usize * texel_size
+        {
+            return Err(());
+        }
+
+        let pixels = match format
+        {
+            Format::R8G8B8A8Unorm | Format::R8G8B8A8Srgb | Format::R8G8B8A8Uint => data.to_vec(),
+            Format::B8G8R8A8Unorm | Format::B8G8R8A8Srgb => data.chunks_exact(4)
+                .flat_map(|texel| [ texel[2], texel[1], texel[0], texel[3] ])
+                .collect(),
+            _ =>
+            {
+                let mut pixels = vec![0; width as usize * height as usize * 4];
+                for (texel, pixel) in data.chunks_exact(texel_size).zip(pixels.chunks_exact_mut(4))
+                {
+                    software::encode_texel(Format::R8G8B8A8Unorm, software::decode_texel(format, texel), pixel);
+                }
+                pixels
+            }
+        };
+
+        Ok(Self { width, height, pixels })
+    }
+
+    pub fn encode_png(&self) -> Vec<u8>
+    {
+        png::encode_rgba8(self.width, self.height, &self.pixels)
+    }
+
+    pub fn write_png<P>(&self, path: P) -> std::io::Result<()> where P: AsRef<std::path::Path>
+    {
+        std::fs::write(path, self.encode_png())
+    }
+}
+
+pub trait AbstractInstance: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn create_surface(&self, window: &qpl::Window) -> Result<Surface, ()>;
+    fn create_device(&self, surface: &Surface, create_info: &DeviceCreateInfo) -> Result<Device, DeviceError>;
+    fn create_swapchain(&self, device: &Device, surface: &Surface, create_info: &SwapchainCreateInfo) -> Result<Swapchain, ()>;
+}
+
+pub trait AbstractDevice: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn get_device_queue(&self) -> Result<Queue, ()>;
+    fn get_queue(&self, kind: QueueKind, index: u32) -> Result<Queue, ()>;
+    fn get_physical_device_properties(&self) -> Result<PhysicalDeviceProperties, ()>;
+    fn enabled_features(&self) -> u32;
+    fn enabled_extensions(&self) -> Vec<DeviceExtension>;
+    fn is_extension_enabled(&self, extension: &DeviceExtension) -> bool;
+    fn create_shader_module(&self, create_info: &ShaderModuleCreateInfo) -> Result<ShaderModule, ShaderModuleError>;
+    fn create_buffer(&self, create_info: &BufferCreateInfo) -> Result<Buffer, ()>;
+    fn create_image(&self, create_info: &ImageCreateInfo) -> Result<Image, ()>;
+    fn buffer_memory_requirements(&self, create_info: &BufferCreateInfo) -> Result<MemoryRequirements, ()>;
+    fn image_memory_requirements(&self, create_info: &ImageCreateInfo) -> Result<MemoryRequirements, ()>;
+    fn allocate_memory(&self, allocate_info: &MemoryAllocateInfo) -> Result<Memory, ()>;
+    fn create_placed_buffer(&self, create_info: &BufferCreateInfo, memory: &Memory, offset: u64) -> Result<Buffer, ()>;
+    fn create_placed_image(&self, create_info: &ImageCreateInfo, memory: &Memory, offset: u64) -> Result<Image, ()>;
+    fn create_image_view(&self, create_info: &ImageViewCreateInfo) -> Result<ImageView, ()>;
+    fn create_descriptor_set_layout(&self, create_info: &DescriptorSetLayoutCreateInfo) -> Result<DescriptorSetLayout, ()>;
+    fn create_descriptor_set(&self, create_info: &DescriptorSetCreateInfo) -> Result<DescriptorSet, ()>;
+    fn create_compute_pipeline(&self, create_info: &ComputePipelineCreateInfo) -> Result<ComputePipeline, PipelineError>;
+    fn create_render_pass(&self, create_info: &RenderPassCreateInfo) -> Result<RenderPass, ()>;
+    fn create_framebuffer(&self, create_info: &FramebufferCreateInfo) -> Result<Framebuffer, ()>;
+    fn create_render_pipeline(&self, create_info: &RenderPipelineCreateInfo) -> Result<RenderPipeline, PipelineError>;
+    fn create_pipeline_cache(&self, data: Option<&[u8]>) -> Result<PipelineCache, ()>;
+    fn create_query_pool(&self, create_info: &QueryPoolCreateInfo) -> Result<QueryPool, ()>;
+    fn create_command_pool(&self, queue: &Queue) -> Result<CommandPool, ()>;
+    fn create_fence(&self, signaled: bool) -> Result<Fence, ()>;
+    fn create_semaphore(&self) -> Result<Semaphore, ()>;
+    fn write_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) -> Result<(), ()>;
+    fn upload_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) -> Result<Fence, ()>;
+    fn upload_image(&self, image: &Image, region: &ImageRegion, data: &[u8]) -> Result<Fence, ()>;
+    fn read_buffer(&self, buffer: &Buffer, offset: u64, size: u64) -> Result<Vec<u8>, ()>;
+    fn read_image(&self, image: &Image, region: &ImageRegion) -> Result<Vec<u8>, ()>;
+    fn wait_idle(&self) -> Result<(), ()>;
+    fn destroy_buffer(&self, buffer: &Buffer);
+    fn destroy_image(&self, image: &Image);
+    fn destroy_image_view(&self, image_view: &ImageView);
+    fn destroy_render_pass(&self, render_pass: &RenderPass);
+    fn destroy_framebuffer(&self, framebuffer: &Framebuffer);
+    fn free_memory(&self, memory: &Memory);
+    fn destroy_shader_module(&self, shader_module: &ShaderModule);
+    fn destroy_compute_pipeline(&self, pipeline: &ComputePipeline);
+    fn destroy_render_pipeline(&self, pipeline: &RenderPipeline);
+}
+
+pub trait AbstractQueue: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn submit(&self, submit_info: &SubmitInfo) -> Result<(), ()>;
+    fn present(&self, swapchain: &Swapchain, image_index: u32, wait_semaphores: &[&Semaphore]) -> Result<(), SwapchainError>;
+    fn wait_idle(&self) -> Result<(), ()>;
+    fn kind(&self) -> QueueKind;
+    fn family_index(&self) -> u32;
+}
+
+pub trait AbstractSurface: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn capabilities(&self, device: &Device) -> Result<SurfaceCapabilities, ()>;
+}
+
+pub trait AbstractSwapchain: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn extent(&self) -> (u32, u32);
+    fn format(&self) -> Format;
+    fn color_space(&self) -> ColorSpace;
+    fn present_mode(&self) -> PresentMode;
+    fn image_views(&self) -> Vec<ImageView>;
+    fn depth_image_views(&self) -> Vec<ImageView>;
+    fn acquire_next_image(&self, semaphore: Option<&Semaphore>, fence: Option<&Fence>) -> Result<u32, SwapchainError>;
+    fn capture_current_image(&self) -> Result<CapturedImage, ()>;
+}
+
+pub trait AbstractImage: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn create_info(&self) -> ImageCreateInfo<'static>;
+    // a subresource's layout as of the last finished encoder, None if unknown or undefined
+    fn current_layout(&self, mip_level: u32, array_layer: u32) -> Option<ImageLayout>;
+    fn set_current_layout(&self, mip_level: u32, array_layer: u32, layout: ImageLayout);
+}
+
+pub trait AbstractImageView: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn image(&self) -> Image;
+    fn subresource_range(&self) -> ImageSubresourceRange;
+}
+
+pub trait AbstractShaderModule: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+}
+
+pub trait AbstractBuffer: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn size(&self) -> u64;
+}
+
+pub trait AbstractMemory: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn size(&self) -> u64;
+}
+
+pub trait AbstractDescriptorSetLayout: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+}
+
+pub trait AbstractDescriptorSet: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+}
+
+pub trait AbstractComputePipeline: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+}
+
+pub trait AbstractPipelineCache: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn data(&self) -> Result<Vec<u8>, ()>;
+}
+
+pub trait AbstractRenderPass: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn create_info(&self) -> &RenderPassCreateInfo<'static>;
+}
+
+pub trait AbstractFramebuffer: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn attachments(&self) -> &[ImageView];
+}
+
+pub trait AbstractRenderPipeline: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+}
+
+pub trait AbstractCommandPool: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn create_command_encoder(&self) -> Result<CommandEncoder, ()>;
+    fn reset(&self) -> Result<(), ()>;
+}
+
+pub trait AbstractCommandEncoder: Send
+{
+    fn as_any(&self) -> &dyn Any;
+    fn bind_compute_pipeline(&mut self, pipeline: &ComputePipeline);
+    fn bind_descriptor_set(&mut self, index: u32, descriptor_set: &DescriptorSet);
+    fn dispatch(&mut self, x: u32, y: u32, z: u32);
+    fn dispatch_indirect(&mut self, buffer: &Buffer, offset: u64);
+    fn begin_render_pass(&mut self, render_pass: &RenderPass, framebuffer: &Framebuffer);
+    fn end_render_pass(&mut self);
+    fn begin_rendering(&mut self, rendering_info: &RenderingInfo);
+    fn end_rendering(&mut self);
+    fn bind_render_pipeline(&mut self, pipeline: &RenderPipeline);
+    fn bind_index_buffer(&mut self, buffer: &Buffer, offset: u64, format: IndexFormat);
+    fn bind_vertex_buffer(&mut self, binding: u32, buffer: &Buffer, offset: u64);
+    fn set_viewport(&mut self, viewport: &Viewport);
+    fn set_scissor(&mut self, scissor: &Scissor);
+    fn set_blend_constants(&mut self, blend_constants: [f32; 4]);
+    fn set_stencil_reference(&mut self, reference: u32);
+    fn set_line_width(&mut self, line_width: f32);
+    fn set_depth_bias(&mut self, depth_bias: &DepthBias);
+    fn draw(&mut self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32);
+    fn draw_indexed(&mut self, index_count: u32, instance_count: u32, first_index: u32, vertex_offset: i32, first_instance: u32);
+    fn draw_indirect(&mut self, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32);
+    fn draw_indexed_indirect(&mut self, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32);
+    fn draw_indirect_count(&mut self, buffer: &Buffer, offset: u64, count_buffer: &Buffer, count_offset: u64, max_draw_count: u32, stride: u32);
+    fn draw_indexed_indirect_count(&mut self, buffer: &Buffer, offset: u64, count_buffer: &Buffer, count_offset: u64, max_draw_count: u32, stride: u32);
+    fn resolve_image(&mut self, src: &Image, src_layout: ImageLayout, dst: &Image, dst_layout: ImageLayout);
+    fn memory_barrier(&mut self, barrier: &MemoryBarrier);
+    fn buffer_barrier(&mut self, barrier: &BufferBarrier);
+    fn image_barrier(&mut self, barrier: &ImageBarrier);
+    fn reset_query_pool(&mut self, query_pool: &QueryPool, first_query: u32, query_count: u32);
+    fn write_timestamp(&mut self, query_pool: &QueryPool, query: u32, stage: u32);
+    fn begin_query(&mut self, query_pool: &QueryPool, query: u32);
+    fn end_query(&mut self, query_pool: &QueryPool, query: u32);
+    fn push_debug_group(&mut self, label: &str);
+    fn pop_debug_group(&mut self);
+    fn insert_debug_marker(&mut self, label: &str);
+    fn finish(self: Box<Self>) -> Result<CommandBuffer, ()>;
+}
+
+pub trait AbstractCommandBuffer: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+}
+
+pub trait AbstractQueryPool: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn create_info(&self) -> QueryPoolCreateInfo<'static>;
+    fn get_results(&self, first_query: u32, query_count: u32, wait: bool) -> Result<Option<Vec<u64>>, ()>;
+}
+
+pub trait AbstractFence: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+    fn wait(&self, timeout_ns: u64) -> Result<bool, ()>;
+    fn reset(&self) -> Result<(), ()>;
+    fn is_signaled(&self) -> Result<bool, ()>;
+}
+
+pub trait AbstractSemaphore: Send + Sync
+{
+    fn as_any(&self) -> &dyn Any;
+}
+
+pub struct Instance
+{
+    api: API,
+    internal: Box<dyn AbstractInstance>
+}
+
+impl Instance
+{
+    pub fn new(api: API, window: &qpl::Window) -> Result<Self, InstanceError>
+    {
+        match api
+        {
+            API::Vulkan =>
+            {
+                match vulkan::VkInstance::new()
+                {
+                    Ok(instance) =>
+                    {
+                        Ok(Self
+                        {
+                            api,
+                            internal: Box::new(instance)
+                        })
+                    },
+                    Err(err) =>
+                    {
+                        Err(err)
+                    }
+                }
+                
+            },
+            API::Software =>
+            {
+                match software::SwInstance::new()
+                {
+                    Ok(instance) =>
+                    {
+                        Ok(Self
+                        {
+                            api,
+                            internal: Box::new(instance)
+                        })
+                    },
+                    Err(err) =>
+                    {
+                        Err(err)
+                    }
+                }
+            },
+            API::OpenGL =>
+            {
+                match opengl::GlInstance::new(window)
+                {
+                    Ok(instance) =>
+                    {
+                        Ok(Self
+                        {
+                            api,
+                            internal: Box::new(instance)
+                        })
+                    },
+                    Err(err) =>
+                    {
+                        Err(err)
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn create_surface(&self, window: &qpl::Window) -> Result<Surface, ()>
+    {
+        self.internal.create_surface(window)
+    }
+
+    pub fn create_device(&self, surface: &Surface, create_info: &DeviceCreateInfo) -> Result<Device, DeviceError>
+    {
+        let invalid_name = create_info.required_extensions.iter().chain(create_info.optional_extensions.iter())
+            .find_map(|extension| match extension
+            {
+                DeviceExtension::Named(name) if name.is_empty() || name.contains('\0') => Some(name.clone()),
+                _ => None
+            });
+        if let Some(name) = invalid_name
+        {
+            return Err(DeviceError::InvalidExtensionName(name));
+        }
+
+        self.internal.create_device(surface, create_info)
+    }
+
+    pub fn create_swapchain(&self, device: &Device, surface: &Surface, create_info: &SwapchainCreateInfo) -> Result<Swapchain, ()>
+    {
+        self.internal.create_swapchain(device, surface, create_info)
+    }
+}
+
+pub struct Device
+{
+    internal: Box<dyn AbstractDevice>
+}
+
+impl Device
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn get_device_queue(&self) -> Result<Queue, ()>
+    {
+        self.internal.get_device_queue()
+    }
+
+    // index counts the queues of one kind in creation order, the always created graphics queue is graphics queue 0.
+    // kinds that were not requested, or that the device had no room for, share the graphics queue at index 0
+    pub fn get_queue(&self, kind: QueueKind, index: u32) -> Result<Queue, ()>
+    {
+        self.internal.get_queue(kind, index)
+    }
+
+    pub fn get_physical_device_properties(&self) -> Result<PhysicalDeviceProperties, ()>
+    {
+        self.internal.get_physical_device_properties()
+    }
+
+    pub fn enabled_features(&self) -> u32
+    {
+        self.internal.enabled_features()
+    }
+
+    // includes extensions enabled for features and as dependencies of requested ones, those without a
+    // DeviceExtension variant are reported as Named
+    pub fn enabled_extensions(&self) -> Vec<DeviceExtension>
+    {
+        self.internal.enabled_extensions()
+    }
+
+    pub fn is_extension_enabled(&self, extension: &DeviceExtension) -> bool
+    {
+        self.internal.is_extension_enabled(extension)
+    }
+
+    pub fn create_shader_module(&self, create_info: &ShaderModuleCreateInfo) -> Result<ShaderModule, ShaderModuleError>
+    {
+        self.internal.create_shader_module(create_info)
+    }
+
+    pub fn create_buffer(&self, create_info: &BufferCreateInfo) -> Result<Buffer, ()>
+    {
+        self.internal.create_buffer(create_info)
+    }
+
+    pub fn create_image(&self, create_info: &ImageCreateInfo) -> Result<Image, ()>
+    {
+        self.internal.create_image(create_info)
+    }
+
+    pub fn create_image_view(&self, create_info: &ImageViewCreateInfo) -> Result<ImageView, ()>
+    {
+        self.internal.create_image_view(create_info)
+    }
+
+    // what create_placed_buffer and create_placed_image need from the memory for a resource with this create info
+    pub fn buffer_memory_requirements(&self, create_info: &BufferCreateInfo) -> Result<MemoryRequirements, ()>
+    {
+        self.internal.buffer_memory_requirements(create_info)
+    }
+
+    pub fn image_memory_requirements(&self, create_info: &ImageCreateInfo) -> Result<MemoryRequirements, ()>
+    {
+        self.internal.image_memory_requirements(create_info)
+    }
+
+    pub fn allocate_memory(&self, allocate_info: &MemoryAllocateInfo) -> Result<Memory, ()>
+    {
+        self.internal.allocate_memory(allocate_info)
+    }
+
+    // placed resources alias whatever else lives in the same range of the memory, their contents are undefined
+    // after another resource used it. offset has to respect the requirements' alignment and the memory has to be one
+    // of their memory types. the software backend keeps separate storage for every resource, so it never aliases
+    pub fn create_placed_buffer(&self, create_info: &BufferCreateInfo, memory: &Memory, offset: u64) -> Result<Buffer, ()>
+    {
+        self.internal.create_placed_buffer(create_info, memory, offset)
+    }
+
+    pub fn create_placed_image(&self, create_info: &ImageCreateInfo, memory: &Memory, offset: u64) -> Result<Image, ()>
+    {
+        self.internal.create_placed_image(create_info, memory, offset)
+    }
+
+    pub fn create_descriptor_set_layout(&self, create_info: &DescriptorSetLayoutCreateInfo) -> Result<DescriptorSetLayout, ()>
+    {
+        self.internal.create_descriptor_set_layout(create_info)
+    }
+
+    pub fn create_descriptor_set(&self, create_info: &DescriptorSetCreateInfo) -> Result<DescriptorSet, ()>
+    {
+        self.internal.create_descriptor_set(create_info)
+    }
+
+    pub fn create_compute_pipeline(&self, create_info: &ComputePipelineCreateInfo) -> Result<ComputePipeline, PipelineError>
+    {
+        self.internal.create_compute_pipeline(create_info)
+    }
+
+    pub fn create_render_pass(&self, create_info: &RenderPassCreateInfo) -> Result<RenderPass, ()>
+    {
+        self.internal.create_render_pass(create_info)
+    }
+
+    pub fn create_framebuffer(&self, create_info: &FramebufferCreateInfo) -> Result<Framebuffer, ()>
+    {
+        self.internal.create_framebuffer(create_info)
+    }
+
+    pub fn create_render_pipeline(&self, create_info: &RenderPipelineCreateInfo) -> Result<RenderPipeline, PipelineError>
+    {
+        self.internal.create_render_pipeline(create_info)
+    }
+
+    // data is what PipelineCache::data returned on an earlier run. blobs written by another device or
+    // driver version, or that got corrupted, are discarded and the cache starts out empty
+    pub fn create_pipeline_cache(&self, data: Option<&[u8]>) -> Result<PipelineCache, ()>
+    {
+        self.internal.create_pipeline_cache(data)
+    }
+
+    // queries start out unavailable, reset them on an encoder before every reuse
+    pub fn create_query_pool(&self, create_info: &QueryPoolCreateInfo) -> Result<QueryPool, ()>
+    {
+        self.internal.create_query_pool(create_info)
+    }
+
+    // command buffers from the pool can only be submitted to queues of the same family
+    pub fn create_command_pool(&self, queue: &Queue) -> Result<CommandPool, ()>
+    {
+        self.internal.create_command_pool(queue)
+    }
+
+    pub fn create_fence(&self, signaled: bool) -> Result<Fence, ()>
+    {
+        self.internal.create_fence(signaled)
+    }
+
+    pub fn create_semaphore(&self) -> Result<Semaphore, ()>
+    {
+        self.internal.create_semaphore()
+    }
+
+    // only valid for buffers in CpuToGpu or GpuToCpu memory
+    pub fn write_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) -> Result<(), ()>
+    {
+        self.internal.write_buffer(buffer, offset, data)
+    }
+
+    // the returned fence is signaled once the upload has executed on the device queue.
+    // it is owned by the staging ring, so wait on it but never reset it
+    pub fn upload_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) -> Result<Fence, ()>
+    {
+        self.internal.upload_buffer(buffer, offset, data)
+    }
+
+    // data is tightly packed texels for every layer of the region, and the image is left in
+    // ShaderReadOnlyOptimal afterwards
+    pub fn upload_image(&self, image: &Image, region: &ImageRegion, data: &[u8]) -> Result<Fence, ()>
+    {
+        self.internal.upload_image(image, region, data)
+    }
+
+    // blocking readbacks. device local buffers and all images need TRANSFER_SRC usage, and image data
+    // comes back as tightly packed texels in the image's format
+    pub fn read_buffer(&self, buffer: &Buffer, offset: u64, size: u64) -> Result<Vec<u8>, ()>
+    {
+        self.internal.read_buffer(buffer, offset, size)
+    }
+
+    pub fn read_image(&self, image: &Image, region: &ImageRegion) -> Result<Vec<u8>, ()>
+    {
+        self.internal.read_image(image, region)
+    }
+
+    pub fn wait_idle(&self) -> Result<(), ()>
+    {
+        self.internal.wait_idle()
+    }
+
+    // dropping the last handle doesn't free the object, destroy it once no submission that uses it is still
+    // executing. every clone of the handle is invalid afterwards
+    pub fn destroy_buffer(&self, buffer: &Buffer)
+    {
+        self.internal.destroy_buffer(buffer)
+    }
+
+    // images owned by a swapchain are destroyed with the swapchain and are rejected here
+    pub fn destroy_image(&self, image: &Image)
+    {
+        self.internal.destroy_image(image)
+    }
+
+    pub fn destroy_image_view(&self, image_view: &ImageView)
+    {
+        self.internal.destroy_image_view(image_view)
+    }
+
+    pub fn destroy_render_pass(&self, render_pass: &RenderPass)
+    {
+        self.internal.destroy_render_pass(render_pass)
+    }
+
+    pub fn destroy_framebuffer(&self, framebuffer: &Framebuffer)
+    {
+        self.internal.destroy_framebuffer(framebuffer)
+    }
+
+    // destroy the resources placed in the memory first
+    pub fn free_memory(&self, memory: &Memory)
+    {
+        self.internal.free_memory(memory)
+    }
+
+    // pipelines keep working after the modules they were created from are destroyed
+    pub fn destroy_shader_module(&self, shader_module: &ShaderModule)
+    {
+        self.internal.destroy_shader_module(shader_module)
+    }
+
+    pub fn destroy_compute_pipeline(&self, pipeline: &ComputePipeline)
+    {
+        self.internal.destroy_compute_pipeline(pipeline)
+    }
+
+    pub fn destroy_render_pipeline(&self, pipeline: &RenderPipeline)
+    {
+        self.internal.destroy_render_pipeline(pipeline)
+    }
+}
+
+#[derive(Clone)]
+pub struct Queue
+{
+    internal: Arc<dyn AbstractQueue>
+}
+
+impl Queue
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    // images take on the layouts the command buffers leave them in once they're submitted, in submission order
+    pub fn submit(&self, submit_info: &SubmitInfo) -> Result<(), ()>
+    {
+        self.internal.submit(submit_info)?;
+
+        for command_buffer in submit_info.command_buffers
+        {
+            for layout in command_buffer.layouts.iter()
+            {
+                layout.apply();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn present(&self, swapchain: &Swapchain, image_index: u32, wait_semaphores: &[&Semaphore]) -> Result<(), SwapchainError>
+    {
+        self.internal.present(swapchain, image_index, wait_semaphores)
+    }
+
+    pub fn wait_idle(&self) -> Result<(), ()>
+    {
+        self.internal.wait_idle()
+    }
+
+    pub fn kind(&self) -> QueueKind
+    {
+        self.internal.kind()
+    }
+
+    pub fn family_index(&self) -> u32
+    {
+        self.internal.family_index()
+    }
+}
+
+#[derive(Clone)]
+pub struct Surface
+{
+    internal: Arc<dyn AbstractSurface>
+}
+
+impl Surface
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    // what a swapchain for this surface can be created with on the device, query it again after a resize
+    pub fn capabilities(&self, device: &Device) -> Result<SurfaceCapabilities, ()>
+    {
+        self.internal.capabilities(device)
+    }
+}
+
+#[derive(Clone)]
+pub struct Swapchain
+{
+    internal: Arc<dyn AbstractSwapchain>
+}
+
+impl Swapchain
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn extent(&self) -> (u32, u32)
+    {
+        self.internal.extent()
+    }
+
+    // the format and color space that were actually chosen, render passes targeting the swapchain
+    // images need this format
+    pub fn format(&self) -> Format
+    {
+        self.internal.format()
+    }
+
+    pub fn color_space(&self) -> ColorSpace
+    {
+        self.internal.color_space()
+    }
+
+    pub fn present_mode(&self) -> PresentMode
+    {
+        self.internal.present_mode()
+    }
+
+    pub fn image_views(&self) -> Vec<ImageView>
+    {
+        self.internal.image_views()
+    }
+
+    // empty unless SwapchainCreateInfo::depth_format was set
+    pub fn depth_image_views(&self) -> Vec<ImageView>
+    {
+        self.internal.depth_image_views()
+    }
+
+    pub fn acquire_next_image(&self, semaphore: Option<&Semaphore>, fence: Option<&Fence>) -> Result<u32, SwapchainError>
+    {
+        self.internal.acquire_next_image(semaphore, fence)
+    }
+
+    // reads back the most recently acquired image. call it once the frame's rendering has been
+    // submitted and before presenting it, blocks until the copy has finished
+    pub fn capture_current_image(&self) -> Result<CapturedImage, ()>
+    {
+        self.internal.capture_current_image()
+    }
+}
+
+#[derive(Clone)]
+pub struct Image
+{
+    internal: Arc<dyn AbstractImage>
+}
+
+impl Image
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn create_info(&self) -> ImageCreateInfo<'static>
+    {
+        self.internal.create_info()
+    }
+}
+
+#[derive(Clone)]
+pub struct ImageView
+{
+    internal: Arc<dyn AbstractImageView>
+}
+
+impl ImageView
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn image(&self) -> Image
+    {
+        self.internal.image()
+    }
+
+    pub fn subresource_range(&self) -> ImageSubresourceRange
+    {
+        self.internal.subresource_range()
+    }
+}
+
+#[derive(Clone)]
+pub struct ShaderModule
+{
+    internal: Arc<dyn AbstractShaderModule>
+}
+
+impl ShaderModule
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+}
+
+#[derive(Clone)]
+pub struct Buffer
+{
+    internal: Arc<dyn AbstractBuffer>
+}
+
+impl Buffer
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn size(&self) -> u64
+    {
+        self.internal.size()
+    }
+}
+
+#[derive(Clone)]
+pub struct Memory
+{
+    internal: Arc<dyn AbstractMemory>
+}
+
+impl Memory
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn size(&self) -> u64
+    {
+        self.internal.size()
+    }
+}
+
+#[derive(Clone)]
+pub struct DescriptorSetLayout
+{
+    internal: Arc<dyn AbstractDescriptorSetLayout>
+}
+
+impl DescriptorSetLayout
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+}
+
+#[derive(Clone)]
+pub struct DescriptorSet
+{
+    internal: Arc<dyn AbstractDescriptorSet>
+}
+
+impl DescriptorSet
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+}
+
+#[derive(Clone)]
+pub struct ComputePipeline
+{
+    internal: Arc<dyn AbstractComputePipeline>
+}
+
+impl ComputePipeline
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+}
+
+#[derive(Clone)]
+pub struct PipelineCache
+{
+    internal: Arc<dyn AbstractPipelineCache>
+}
+
+impl PipelineCache
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn data(&self) -> Result<Vec<u8>, ()>
+    {
+        self.internal.data()
+    }
+}
+
+#[derive(Clone)]
+pub struct RenderPass
+{
+    internal: Arc<dyn AbstractRenderPass>
+}
+
+impl RenderPass
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn create_info(&self) -> &RenderPassCreateInfo<'static>
+    {
+        self.internal.create_info()
+    }
+}
+
+#[derive(Clone)]
+pub struct Framebuffer
+{
+    internal: Arc<dyn AbstractFramebuffer>
+}
+
+impl Framebuffer
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn attachments(&self) -> &[ImageView]
+    {
+        self.internal.attachments()
+    }
+}
+
+#[derive(Clone)]
+pub struct RenderPipeline
+{
+    internal: Arc<dyn AbstractRenderPipeline>
+}
+
+impl RenderPipeline
 {
     pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
 }
 
-pub struct ImageView
+#[derive(Clone)]
+pub struct CommandPool
 {
-    internal: Rc<dyn AbstractImageView>
+    internal: Arc<dyn AbstractCommandPool>
 }
 
-impl ImageView
+impl CommandPool
 {
     pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn create_command_encoder(&self) -> Result<CommandEncoder, ()>
+    {
+        self.internal.create_command_encoder()
+    }
+
+    // command buffers from the pool must not be recording or pending execution
+    pub fn reset(&self) -> Result<(), ()>
+    {
+        self.internal.reset()
+    }
 }
 
-pub struct ShaderModule
+pub struct CommandEncoder
 {
-    internal: Rc<dyn AbstractShaderModule>
+    internal: Box<dyn AbstractCommandEncoder>,
+    tracker: state::ResourceTracker,
+    // the device's enabled FeatureFlags and Limits::line_width_range
+    features: u32,
+    line_width_range: [f32; 2]
 }
 
-impl ShaderModule
+impl CommandEncoder
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn bind_compute_pipeline(&mut self, pipeline: &ComputePipeline)
+    {
+        self.internal.bind_compute_pipeline(pipeline)
+    }
+
+    pub fn bind_descriptor_set(&mut self, index: u32, descriptor_set: &DescriptorSet)
+    {
+        self.internal.bind_descriptor_set(index, descriptor_set)
+    }
+
+    pub fn dispatch(&mut self, x: u32, y: u32, z: u32)
+    {
+        self.internal.dispatch(x, y, z)
+    }
+
+    pub fn dispatch_indirect(&mut self, buffer: &Buffer, offset: u64)
+    {
+        self.use_buffer_range(buffer, offset, 12, BufferAccess::IndirectRead);
+        self.internal.dispatch_indirect(buffer, offset)
+    }
+
+    // attachments are checked against the render pass' initial layouts and end up in its final
+    // layouts. anything the render pass samples or binds has to be declared with use_image/use_buffer before this
+    pub fn begin_render_pass(&mut self, render_pass: &RenderPass, framebuffer: &Framebuffer)
+    {
+        if let Some(barrier) = self.tracker.begin_render_pass(render_pass, framebuffer)
+        {
+            self.internal.memory_barrier(&barrier);
+        }
+        self.internal.begin_render_pass(render_pass, framebuffer)
+    }
+
+    pub fn end_render_pass(&mut self)
+    {
+        self.internal.end_render_pass();
+        self.tracker.end_render_pass()
+    }
+
+    // needs FeatureFlags::DYNAMIC_RENDERING, which vulkan gets from VK_KHR_dynamic_rendering since the instance is
+    // 1.0, so 1.3 drivers go through the extension as well. without it the whole begin_rendering/end_rendering
+    // pair is dropped. attachments are moved into their attachment layouts up front and stay there after
+    // end_rendering, their contents are discarded unless loaded
+    pub fn begin_rendering(&mut self, rendering_info: &RenderingInfo)
+    {
+        if !self.require_feature(FeatureFlags::DYNAMIC_RENDERING, "begin_rendering")
+        {
+            self.tracker.begin_rendering();
+            return;
+        }
+
+        for attachment in rendering_info.color_attachments.iter()
+        {
+            let discard = attachment.operations.load_op != LoadOp::Load;
+            self.use_image_range_as(&attachment.view.image(), Some(&attachment.view.subresource_range()), &ImageAccess::ColorAttachment.access(), discard);
+        }
+
+        if let Some(attachment) = rendering_info.depth_stencil_attachment.as_ref()
+        {
+            let image = attachment.view.image();
+            let discard = attachment.operations.load_op != LoadOp::Load
+                && (!image.create_info().format.has_stencil() || rendering_info.stencil_operations.load_op != LoadOp::Load);
+            self.use_image_range_as(&image, Some(&attachment.view.subresource_range()), &ImageAccess::DepthStencilAttachment.access(), discard);
+        }
+
+        self.tracker.begin_rendering();
+        self.internal.begin_rendering(rendering_info)
+    }
+
+    pub fn end_rendering(&mut self)
+    {
+        // begin_rendering already reported the missing feature
+        if self.features & FeatureFlags::DYNAMIC_RENDERING != 0
+        {
+            self.internal.end_rendering();
+        }
+        self.tracker.end_rendering()
+    }
+
+    pub fn bind_render_pipeline(&mut self, pipeline: &RenderPipeline)
+    {
+        self.internal.bind_render_pipeline(pipeline)
+    }
+
+    pub fn bind_index_buffer(&mut self, buffer: &Buffer, offset: u64, format: IndexFormat)
+    {
+        self.internal.bind_index_buffer(buffer, offset, format)
+    }
+
+    // binding is the index of the layout in the pipeline's vertex_buffers
+    pub fn bind_vertex_buffer(&mut self, binding: u32, buffer: &Buffer, offset: u64)
+    {
+        self.internal.bind_vertex_buffer(binding, buffer, offset)
+    }
+
+    // the set_* calls only affect pipelines that declare the matching DynamicStateFlags, and persist
+    // across pipeline binds until the encoder finishes
+    pub fn set_viewport(&mut self, viewport: &Viewport)
+    {
+        self.internal.set_viewport(viewport)
+    }
+
+    pub fn set_scissor(&mut self, scissor: &Scissor)
+    {
+        self.internal.set_scissor(scissor)
+    }
+
+    pub fn set_blend_constants(&mut self, blend_constants: [f32; 4])
+    {
+        self.internal.set_blend_constants(blend_constants)
+    }
+
+    // sets the reference for front and back faces
+    pub fn set_stencil_reference(&mut self, reference: u32)
+    {
+        self.internal.set_stencil_reference(reference)
+    }
+
+    // widths other than 1.0 need FeatureFlags::WIDE_LINES and have to be inside Limits::line_width_range.
+    // invalid widths are reported and replaced by 1.0 or the closest supported width
+    pub fn set_line_width(&mut self, line_width: f32)
+    {
+        let line_width = if line_width != 1.0 && !self.require_feature(FeatureFlags::WIDE_LINES, "set_line_width")
+        {
+            1.0
+        }
+        else if !(self.line_width_range[0]..=self.line_width_range[1]).contains(&line_width)
+        {
+            state::validation_error(&format!("line width {} is outside of {:?}", line_width, self.line_width_range));
+            line_width.clamp(self.line_width_range[0], self.line_width_range[1])
+        }
+        else
+        {
+            line_width
+        };
+
+        self.internal.set_line_width(line_width)
+    }
+
+    pub fn set_depth_bias(&mut self, depth_bias: &DepthBias)
+    {
+        self.internal.set_depth_bias(depth_bias)
+    }
+
+    pub fn draw(&mut self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32)
+    {
+        self.internal.draw(vertex_count, instance_count, first_vertex, first_instance)
+    }
+
+    pub fn draw_indexed(&mut self, index_count: u32, instance_count: u32, first_index: u32, vertex_offset: i32, first_instance: u32)
+    {
+        self.internal.draw_indexed(index_count, instance_count, first_index, vertex_offset, first_instance)
+    }
+
+    // buffer holds draw_count DrawIndirectCommands, stride bytes apart
+    pub fn draw_indirect(&mut self, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32)
+    {
+        let size = std::mem::size_of::<DrawIndirectCommand>() as u32;
+        state::validate_indirect(draw_count, stride, size, "draw_indirect");
+        self.use_buffer_range(buffer, offset, state::indirect_range(draw_count, stride, size), BufferAccess::IndirectRead);
+        self.internal.draw_indirect(buffer, offset, draw_count, stride)
+    }
+
+    pub fn draw_indexed_indirect(&mut self, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32)
+    {
+        let size = std::mem::size_of::<DrawIndexedIndirectCommand>() as u32;
+        state::validate_indirect(draw_count, stride, size, "draw_indexed_indirect");
+        self.use_buffer_range(buffer, offset, state::indirect_range(draw_count, stride, size), BufferAccess::IndirectRead);
+        self.internal.draw_indexed_indirect(buffer, offset, draw_count, stride)
+    }
+
+    // the draw count is a u32 read from count_buffer when the commands execute, clamped to max_draw_count
+    pub fn draw_indirect_count(&mut self, buffer: &Buffer, offset: u64, count_buffer: &Buffer, count_offset: u64, max_draw_count: u32, stride: u32)
+    {
+        if !self.require_feature(FeatureFlags::DRAW_INDIRECT_COUNT, "draw_indirect_count")
+        {
+            return;
+        }
+
+        let size = std::mem::size_of::<DrawIndirectCommand>() as u32;
+        state::validate_indirect(max_draw_count, stride, size, "draw_indirect_count");
+        self.use_buffer_range(buffer, offset, state::indirect_range(max_draw_count, stride, size), BufferAccess::IndirectRead);
+        self.use_buffer_range(count_buffer, count_offset, 4, BufferAccess::IndirectRead);
+        self.internal.draw_indirect_count(buffer, offset, count_buffer, count_offset, max_draw_count, stride)
+    }
+
+    pub fn draw_indexed_indirect_count(&mut self, buffer: &Buffer, offset: u64, count_buffer: &Buffer, count_offset: u64, max_draw_count: u32, stride: u32)
+    {
+        if !self.require_feature(FeatureFlags::DRAW_INDIRECT_COUNT, "draw_indexed_indirect_count")
+        {
+            return;
+        }
+
+        let size = std::mem::size_of::<DrawIndexedIndirectCommand>() as u32;
+        state::validate_indirect(max_draw_count, stride, size, "draw_indexed_indirect_count");
+        self.use_buffer_range(buffer, offset, state::indirect_range(max_draw_count, stride, size), BufferAccess::IndirectRead);
+        self.use_buffer_range(count_buffer, count_offset, 4, BufferAccess::IndirectRead);
+        self.internal.draw_indexed_indirect_count(buffer, offset, count_buffer, count_offset, max_draw_count, stride)
+    }
+
+    pub fn resolve_image(&mut self, src: &Image, src_layout: ImageLayout, dst: &Image, dst_layout: ImageLayout)
+    {
+        self.tracker.validate_layout(src, None, src_layout, "resolve_image source");
+        self.tracker.validate_layout(dst, None, dst_layout, "resolve_image destination");
+        self.use_image_range_as(src, None, &state::Access { layout: Some(src_layout), ..ImageAccess::TransferRead.access() }, false);
+        self.use_image_range_as(dst, None, &state::Access { layout: Some(dst_layout), ..ImageAccess::TransferWrite.access() }, false);
+        self.internal.resolve_image(src, src_layout, dst, dst_layout)
+    }
+
+    pub fn memory_barrier(&mut self, barrier: &MemoryBarrier)
+    {
+        self.internal.memory_barrier(barrier)
+    }
+
+    pub fn buffer_barrier(&mut self, barrier: &BufferBarrier)
+    {
+        self.tracker.record_buffer_barrier(barrier);
+        self.internal.buffer_barrier(barrier)
+    }
+
+    // explicit barriers keep the tracked state in sync. an old_layout that doesn't match the tracked
+    // one is reported in debug builds
+    pub fn image_barrier(&mut self, barrier: &ImageBarrier)
+    {
+        self.tracker.record_image_barrier(barrier);
+        self.internal.image_barrier(barrier)
+    }
+
+    pub fn reset_query_pool(&mut self, query_pool: &QueryPool, first_query: u32, query_count: u32)
+    {
+        self.tracker.check_outside_render_pass("reset_query_pool");
+        self.internal.reset_query_pool(query_pool, first_query, query_count)
+    }
+
+    // stage is a PipelineStageFlags bit, the timestamp is taken once all earlier commands are done with it
+    pub fn write_timestamp(&mut self, query_pool: &QueryPool, query: u32, stage: u32)
+    {
+        self.internal.write_timestamp(query_pool, query, stage)
+    }
+
+    // occlusion and pipeline statistics queries only
+    pub fn begin_query(&mut self, query_pool: &QueryPool, query: u32)
+    {
+        self.internal.begin_query(query_pool, query)
+    }
+
+    pub fn end_query(&mut self, query_pool: &QueryPool, query: u32)
+    {
+        self.internal.end_query(query_pool, query)
+    }
+
+    // groups nest and show up in captures and validation messages, every push needs a pop on the same
+    // encoder
+    pub fn push_debug_group(&mut self, label: &str)
+    {
+        self.internal.push_debug_group(label)
+    }
+
+    pub fn pop_debug_group(&mut self)
+    {
+        self.internal.pop_debug_group()
+    }
+
+    pub fn insert_debug_marker(&mut self, label: &str)
+    {
+        self.internal.insert_debug_marker(label)
+    }
+
+    // declares how the following commands use a resource. the encoder tracks the layout and last access
+    // of every image subresource and buffer range recorded on it and emits whatever barrier and layout transition the
+    // new access needs. resources bound through descriptor sets and vertex or index buffers aren't seen by the encoder,
+    // so declare them before the dispatch or render pass using them
+    pub fn use_image(&mut self, image: &Image, access: ImageAccess)
+    {
+        self.use_image_range_as(image, None, &access.access(), false)
+    }
+
+    pub fn use_image_range(&mut self, image: &Image, range: &ImageSubresourceRange, access: ImageAccess)
+    {
+        self.use_image_range_as(image, Some(range), &access.access(), false)
+    }
+
+    pub fn use_buffer(&mut self, buffer: &Buffer, access: BufferAccess)
+    {
+        self.use_buffer_range(buffer, 0, buffer.size(), access)
+    }
+
+    pub fn use_buffer_range(&mut self, buffer: &Buffer, offset: u64, size: u64, access: BufferAccess)
+    {
+        if let Some(transition) = self.tracker.use_buffer(buffer, offset, size, &access.access())
+        {
+            self.internal.buffer_barrier(&BufferBarrier
+            {
+                buffer,
+                offset,
+                size,
+                src_stage_mask: transition.src_stage_mask,
+                src_access_mask: transition.src_access_mask,
+                dst_stage_mask: transition.dst_stage_mask,
+                dst_access_mask: transition.dst_access_mask,
+                queue_transfer: None
+            });
+        }
+    }
+
+    // commands needing a feature the device was created without are reported and dropped
+    fn require_feature(&self, feature: u32, what: &str) -> bool
+    {
+        if self.features & feature != feature
+        {
+            state::validation_error(&format!("{} needs a feature the device was created without", what));
+            return false;
+        }
+
+        true
+    }
+
+    fn use_image_range_as(&mut self, image: &Image, range: Option<&ImageSubresourceRange>, access: &state::Access, discard: bool)
+    {
+        for (range, transition) in self.tracker.use_image(image, range, access, discard)
+        {
+            self.internal.image_barrier(&ImageBarrier
+            {
+                image,
+                subresource_range: Some(range),
+                old_layout: transition.old_layout,
+                new_layout: transition.new_layout.unwrap(),
+                src_stage_mask: transition.src_stage_mask,
+                src_access_mask: transition.src_access_mask,
+                dst_stage_mask: transition.dst_stage_mask,
+                dst_access_mask: transition.dst_access_mask,
+                queue_transfer: None
+            });
+        }
+    }
+
+    pub fn finish(self) -> Result<CommandBuffer, ()>
+    {
+        let mut command_buffer = self.internal.finish()?;
+        command_buffer.layouts = Arc::new(self.tracker.finish());
+        Ok(command_buffer)
+    }
+}
+
+#[derive(Clone)]
+pub struct CommandBuffer
+{
+    internal: Arc<dyn AbstractCommandBuffer>,
+    layouts: Arc<Vec<state::FinalLayout>>
+}
+
+impl CommandBuffer
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+}
+
+#[derive(Clone)]
+pub struct QueryPool
+{
+    internal: Arc<dyn AbstractQueryPool>
+}
+
+impl QueryPool
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn create_info(&self) -> QueryPoolCreateInfo<'static>
+    {
+        self.internal.create_info()
+    }
+
+    // None while any of the queries is still pending and wait is false. timestamps come back in
+    // nanoseconds, subtract two of them for the time spent in between
+    pub fn get_results(&self, first_query: u32, query_count: u32, wait: bool) -> Result<Option<Vec<u64>>, ()>
+    {
+        self.internal.get_results(first_query, query_count, wait)
+    }
+}
+
+#[derive(Clone)]
+pub struct Fence
+{
+    internal: Arc<dyn AbstractFence>
+}
+
+impl Fence
+{
+    pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
+
+    pub fn wait(&self, timeout_ns: u64) -> Result<bool, ()>
+    {
+        self.internal.wait(timeout_ns)
+    }
+
+    pub fn reset(&self) -> Result<(), ()>
+    {
+        self.internal.reset()
+    }
+
+    pub fn is_signaled(&self) -> Result<bool, ()>
+    {
+        self.internal.is_signaled()
+    }
+}
+
+#[derive(Clone)]
+pub struct Semaphore
+{
+    internal: Arc<dyn AbstractSemaphore>
+}
+
+impl Semaphore
 {
     pub fn downcast_ref<T>(&self) -> Option<&T> where T: Any { self.internal.as_any().downcast_ref::<T>() }
-}
\ No newline at end of file
+}
+
+fn validate_render_pipeline_stages(stages: &[ShaderStage], primitive: Primitive) -> Result<(), PipelineError>
+{
+    if !stages.contains(&ShaderStage::Vertex) || stages.contains(&ShaderStage::Compute)
+    {
+        return Err(PipelineError::InvalidShaderStage);
+    }
+
+    let has_control = stages.contains(&ShaderStage::TessellationControl);
+    let has_evaluation = stages.contains(&ShaderStage::TessellationEvaluation);
+
+    if has_control != has_evaluation
+    {
+        return Err(PipelineError::InvalidShaderStage);
+    }
+
+    match primitive
+    {
+        Primitive::PatchList(control_points) if !has_evaluation || control_points == 0 => Err(PipelineError::InvalidPrimitive),
+        Primitive::PatchList(_) => Ok(()),
+        _ if has_evaluation => Err(PipelineError::InvalidPrimitive),
+        _ => Ok(())
+    }
+}
+
+// every location is fed by one attribute, and attributes have to fit inside their stride and a u32
+fn validate_vertex_buffers(vertex_buffers: &[VertexBufferLayout]) -> Result<(), PipelineError>
+{
+    let mut locations: Vec<u32> = Vec::new();
+
+    for layout in vertex_buffers.iter()
+    {
+        for attribute in layout.attributes.iter()
+        {
+            let end = attribute.offset.checked_add(attribute.format.size());
+            if locations.contains(&attribute.location) || end.map_or(true, |end| layout.stride != 0 && end > layout.stride)
+            {
+                return Err(PipelineError::InvalidVertexLayout);
+            }
+
+            locations.push(attribute.location);
+        }
+    }
+
+    Ok(())
+}
+
+// returns the state of every color attachment, and whether they differ which needs independentBlend
+fn resolve_color_targets(color_targets: &[ColorTargetState], attachment_count: usize) -> Result<(Vec<ColorTargetState>, bool), PipelineError>
+{
+    let targets = match color_targets.len()
+    {
+        0 => vec![ ColorTargetState::default(); attachment_count ],
+        count if count == attachment_count => color_targets.to_vec(),
+        _ => return Err(PipelineError::InvalidColorTargets)
+    };
+
+    // dual source blending reads both fragment outputs for a single attachment
+    if targets.len() > 1 && targets.iter().any(|target| target.uses_dual_source())
+    {
+        return Err(PipelineError::InvalidColorTargets);
+    }
+
+    let independent = targets.windows(2).any(|pair| pair[0] != pair[1]);
+    Ok((targets, independent))
+}
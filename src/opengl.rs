@@ -1,31 +1,40 @@
-use crate::*;
+use gl::types::{GLbitfield, GLboolean, GLchar, GLenum, GLint, GLintptr, GLsizei, GLsizeiptr, GLsync, GLuint};
+use std::{collections::HashMap, ffi::{CStr, CString}, ptr, sync::{atomic::{AtomicBool, Ordering}, Mutex}};
 
-/*pub struct GlInstance
+use super::*;
+
+// qpl makes the context current on the thread that creates it and GL has no notion of other threads, so the
+// instance and everything created from it must only be used on the thread that called Instance::new. the
+// handles are Send + Sync only because the shared traits require it
+pub struct GlContext
+{
+    pub handle: qpl::GLContext
+}
+
+unsafe impl Send for GlContext {}
+unsafe impl Sync for GlContext {}
+
+pub struct GlInstance
 {
-    handle: qpl::GLContext
+    pub context: Arc<GlContext>
 }
 
 impl GlInstance
 {
-    pub fn new(api: API, window: &qpl::Window) -> Result<GlInstance, InstanceError>
+    pub fn new(window: &qpl::Window) -> Result<Self, InstanceError>
     {
+        // the default framebuffer is only ever blitted to, swapchains bring their own depth images
         let create_info = qpl::GLContextCreateInfo
         {
-            version: (3, 2),
+            version: (4, 3),
+            depth_bits: 0,
+            stencil_bits: 0,
+            vsync: true,
             ..Default::default()
         };
 
-        match window.gl_create_context(&create_info)
-        {
-            Ok(handle) =>
-            {
-                Ok(GlInstance { handle })
-            },
-            Err(gl_error) =>
-            {
-                Err(InstanceError::ApiNotSupported)
-            }
-        }
+        let handle = window.gl_create_context(&create_info).map_err(|_| InstanceError::ApiNotSupported)?;
+        Ok(Self { context: Arc::new(GlContext { handle }) })
     }
 }
 
@@ -35,162 +44,2937 @@ impl AbstractInstance for GlInstance
 
     fn create_surface(&self, window: &qpl::Window) -> Result<Surface, ()>
     {
-        Ok(Surface { internal: Rc::new(GlSurface { }) })
+        Ok(Surface { internal: Arc::new(GlSurface { width: window.width, height: window.height }) })
     }
 
-    fn enumerate_physical_devices(&self) -> Result<Vec<PhysicalDevice>, ()>
+    fn create_device(&self, _surface: &Surface, create_info: &DeviceCreateInfo) -> Result<Device, DeviceError>
     {
-        Ok(vec![ PhysicalDevice { internal: Rc::new(GlPhysicalDevice {  }) } ])
-    }
+        let extension_names = gl_extension_names();
+        let supported_features = gl_features(&extension_names);
 
-    fn get_physical_device_properties(&self, physical_device: &PhysicalDevice) -> Result<PhysicalDeviceProperties, ()>
-    {
-        let device_name_ptr = unsafe { gl::GetString(gl::RENDERER) };
-        let device_name = unsafe { std::ffi::CStr::from_ptr(device_name_ptr as _) }.to_str().unwrap().to_owned();
+        let missing_features = create_info.required_features & !supported_features;
+        if missing_features != 0
+        {
+            return Err(DeviceError::MissingFeatures(missing_features));
+        }
 
-        Ok(PhysicalDeviceProperties
+        let missing_extensions: Vec<DeviceExtension> = create_info.required_extensions.iter()
+            .filter(|extension| !gl_extension_supported(extension, &extension_names))
+            .cloned()
+            .collect();
+        if !missing_extensions.is_empty()
         {
-            vendor_id: 0,
-            device_id: 0,
-            device_type: DeviceType::Other,
-            device_name
-        })
-    }
+            return Err(DeviceError::MissingExtensions(missing_extensions));
+        }
 
-    fn create_logical_device(&self, physical_device: &PhysicalDevice, surface: &Surface) -> Result<Device, ()>
-    {
-        Ok(Device { internal: Box::new(GlDevice {  }) })
+        let extensions = create_info.required_extensions.iter()
+            .chain(create_info.optional_extensions.iter().filter(|extension| gl_extension_supported(extension, &extension_names)))
+            .cloned()
+            .collect();
+
+        unsafe
+        {
+            // image data is always tightly packed
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        }
+
+        Ok(Device { internal: Box::new(GlDevice
+        {
+            properties: gl_properties(supported_features),
+            features: create_info.required_features | (create_info.optional_features & supported_features),
+            extensions
+        }) })
     }
 
-    fn create_swapchain(&self, physical_device: &PhysicalDevice, device: &Device, surface: &Surface) -> Result<Swapchain, ()>
+    fn create_swapchain(&self, device: &Device, surface: &Surface, create_info: &SwapchainCreateInfo) -> Result<Swapchain, ()>
     {
-        Ok(Swapchain { internal: Rc::new(GlSwapchain {  }) })
+        let surface = surface.downcast_ref::<GlSurface>().unwrap();
+        let format = create_info.preferred_formats.iter()
+            .find(|format| GL_SURFACE_FORMATS.contains(format))
+            .copied()
+            .unwrap_or(Format::R8G8B8A8Srgb);
+
+        // sRGB formats can't be bound as storage images
+        let mut supported_usage = ImageUsageFlags::TRANSFER_DST | ImageUsageFlags::SAMPLED | ImageUsageFlags::STORAGE;
+        if matches!(format, Format::R8G8B8A8Srgb | Format::B8G8R8A8Srgb)
+        {
+            supported_usage &= !ImageUsageFlags::STORAGE;
+        }
+        let usage = ImageUsageFlags::COLOR_ATTACHMENT | ImageUsageFlags::TRANSFER_SRC | (create_info.usage & supported_usage);
+
+        let image = device.create_image(&ImageCreateInfo { format, width: surface.width, height: surface.height, usage, label: create_info.label, ..Default::default() })?;
+        image.downcast_ref::<GlImage>().unwrap().swapchain.store(true, Ordering::Relaxed);
+        let view = device.create_image_view(&ImageViewCreateInfo
+        {
+            image: &image,
+            base_mip_level: 0,
+            mip_level_count: 1,
+            base_array_layer: 0,
+            array_layer_count: 1,
+            label: None
+        })?;
+
+        let mut depth_views = Vec::new();
+        if let Some(depth_format) = create_info.depth_format
+        {
+            if !depth_format.has_depth()
+            {
+                return Err(());
+            }
+
+            let depth_image = device.create_image(&ImageCreateInfo
+            {
+                format: depth_format,
+                width: surface.width,
+                height: surface.height,
+                usage: ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+                ..Default::default()
+            })?;
+            depth_views.push(device.create_image_view(&ImageViewCreateInfo
+            {
+                image: &depth_image,
+                base_mip_level: 0,
+                mip_level_count: 1,
+                base_array_layer: 0,
+                array_layer_count: 1,
+                label: None
+            })?);
+        }
+
+        // present blits out of this framebuffer into the window's
+        let mut framebuffer = 0;
+        unsafe
+        {
+            gl::GenFramebuffers(1, &mut framebuffer);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer);
+            gl::FramebufferTexture2D(gl::READ_FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, image.downcast_ref::<GlImage>().unwrap().handle, 0);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        }
+        object_label(gl::FRAMEBUFFER, framebuffer, create_info.label);
+        check_error()?;
+
+        Ok(Swapchain { internal: Arc::new(GlSwapchain
+        {
+            context: self.context.clone(),
+            image,
+            views: vec![ view ],
+            depth_views,
+            extent: (surface.width, surface.height),
+            format,
+            framebuffer
+        }) })
     }
 }
 
+// every format is stored as RGBA8 and the swapchain is blitted to the window, so the window's own pixel
+// format doesn't matter
+const GL_SURFACE_FORMATS: [Format; 4] = [ Format::R8G8B8A8Srgb, Format::R8G8B8A8Unorm, Format::B8G8R8A8Srgb, Format::B8G8R8A8Unorm ];
+
+#[derive(Clone)]
 pub struct GlSurface
 {
-    
+    pub width: u32,
+    pub height: u32
 }
 
 impl AbstractSurface for GlSurface
 {
     fn as_any(&self) -> &dyn Any { self }
+
+    // the context is created with vsync on and a swapchain is a single image that present blits into the
+    // window's back buffer, so neither the present mode nor the image count can be chosen
+    fn capabilities(&self, _device: &Device) -> Result<SurfaceCapabilities, ()>
+    {
+        Ok(SurfaceCapabilities
+        {
+            present_modes: vec![ PresentMode::Fifo ],
+            formats: GL_SURFACE_FORMATS.iter().map(|format| (*format, ColorSpace::SrgbNonlinear)).collect(),
+            min_image_count: 1,
+            max_image_count: Some(1),
+            current_extent: Some((self.width, self.height)),
+            min_extent: (self.width, self.height),
+            max_extent: (self.width, self.height),
+            usage: ImageUsageFlags::COLOR_ATTACHMENT | ImageUsageFlags::TRANSFER_SRC | ImageUsageFlags::TRANSFER_DST |
+                ImageUsageFlags::SAMPLED | ImageUsageFlags::STORAGE,
+            composite_alpha: vec![ CompositeAlpha::Opaque ]
+        })
+    }
 }
 
-pub struct GlPhysicalDevice
+// everything a 4.3 core context has, on top of what its extensions add. depth bias clamps and indirect draw
+// counts only became core in 4.6
+fn gl_features(extension_names: &[String]) -> u32
 {
+    let mut features = FeatureFlags::GEOMETRY_SHADER | FeatureFlags::TESSELLATION_SHADER | FeatureFlags::DEPTH_CLAMP |
+        FeatureFlags::SAMPLE_RATE_SHADING | FeatureFlags::MULTI_DRAW_INDIRECT | FeatureFlags::DRAW_INDIRECT_FIRST_INSTANCE |
+        FeatureFlags::VERTEX_ATTRIBUTE_DIVISOR | FeatureFlags::FILL_MODE_NON_SOLID | FeatureFlags::INDEPENDENT_BLEND |
+        FeatureFlags::DUAL_SOURCE_BLEND | FeatureFlags::FRAGMENT_STORES_AND_ATOMICS | FeatureFlags::VERTEX_PIPELINE_STORES_AND_ATOMICS |
+        FeatureFlags::SHADER_FLOAT64 | FeatureFlags::DYNAMIC_RENDERING;
+    if get_floats(gl::ALIASED_LINE_WIDTH_RANGE)[1] > 1.0
+    {
+        features |= FeatureFlags::WIDE_LINES;
+    }
+    if get_floats(gl::POINT_SIZE_RANGE)[1] > 1.0
+    {
+        features |= FeatureFlags::LARGE_POINTS;
+    }
+    if extension_names.iter().any(|name| name == "GL_ARB_gpu_shader_int64")
+    {
+        features |= FeatureFlags::SHADER_INT64;
+    }
+    if extension_names.iter().any(|name| name == "GL_ARB_pipeline_statistics_query")
+    {
+        features |= FeatureFlags::PIPELINE_STATISTICS_QUERY;
+    }
 
+    features
 }
 
-impl AbstractPhysicalDevice for GlPhysicalDevice
+fn gl_extension_names() -> Vec<String>
 {
-    fn as_any(&self) -> &dyn Any { self }
+    (0..get_integer(gl::NUM_EXTENSIONS))
+        .map(|index| unsafe { CStr::from_ptr(gl::GetStringi(gl::EXTENSIONS, index) as _) }.to_string_lossy().into_owned())
+        .collect()
 }
 
-pub struct GlDevice
+// GL extensions are on as soon as the driver has them, Named takes names like "GL_ARB_bindless_texture".
+// begin_rendering and instance divisors are core in 4.3
+fn gl_extension_supported(extension: &DeviceExtension, extension_names: &[String]) -> bool
+{
+    match extension
+    {
+        DeviceExtension::DynamicRendering | DeviceExtension::VertexAttributeDivisor => true,
+        DeviceExtension::Named(name) => extension_names.contains(name),
+        _ => false
+    }
+}
+
+fn gl_properties(features: u32) -> PhysicalDeviceProperties
 {
+    let max_viewport_dimensions = get_integers::<2>(gl::MAX_VIEWPORT_DIMS);
+
+    PhysicalDeviceProperties
+    {
+        vendor_id: 0,
+        device_id: 0,
+        device_type: DeviceType::Other,
+        device_name: get_string(gl::RENDERER),
+        features,
+        limits: Limits
+        {
+            max_image_dimension_2d: get_integer(gl::MAX_TEXTURE_SIZE),
+            max_image_dimension_3d: get_integer(gl::MAX_3D_TEXTURE_SIZE),
+            max_image_array_layers: get_integer(gl::MAX_ARRAY_TEXTURE_LAYERS),
+            max_uniform_buffer_range: get_integer(gl::MAX_UNIFORM_BLOCK_SIZE),
+            max_storage_buffer_range: get_integer(gl::MAX_SHADER_STORAGE_BLOCK_SIZE),
+            // every set binds into the same binding points
+            max_bound_descriptor_sets: u32::MAX,
+            max_vertex_input_attributes: get_integer(gl::MAX_VERTEX_ATTRIBS),
+            max_vertex_input_bindings: get_integer(gl::MAX_VERTEX_ATTRIB_BINDINGS),
+            max_vertex_input_attribute_offset: get_integer(gl::MAX_VERTEX_ATTRIB_RELATIVE_OFFSET),
+            // 4.3 has no query for it, 2048 is the minimum GL 4.4 guarantees
+            max_vertex_input_binding_stride: 2048,
+            max_color_attachments: get_integer(gl::MAX_COLOR_ATTACHMENTS),
+            max_framebuffer_width: get_integer(gl::MAX_FRAMEBUFFER_WIDTH),
+            max_framebuffer_height: get_integer(gl::MAX_FRAMEBUFFER_HEIGHT),
+            max_viewport_dimensions: [ max_viewport_dimensions[0] as u32, max_viewport_dimensions[1] as u32 ],
+            max_compute_workgroup_count: std::array::from_fn(|index| get_indexed_integer(gl::MAX_COMPUTE_WORK_GROUP_COUNT, index as u32)),
+            max_compute_workgroup_size: std::array::from_fn(|index| get_indexed_integer(gl::MAX_COMPUTE_WORK_GROUP_SIZE, index as u32)),
+            max_compute_workgroup_invocations: get_integer(gl::MAX_COMPUTE_WORK_GROUP_INVOCATIONS),
+            max_draw_indirect_count: u32::MAX,
+            min_uniform_buffer_offset_alignment: get_integer(gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT) as u64,
+            min_storage_buffer_offset_alignment: get_integer(gl::SHADER_STORAGE_BUFFER_OFFSET_ALIGNMENT) as u64,
+            timestamp_period: 1.0,
+            point_size_range: get_floats(gl::POINT_SIZE_RANGE),
+            line_width_range: get_floats(gl::ALIASED_LINE_WIDTH_RANGE)
+        }
+    }
+}
 
+pub struct GlDevice
+{
+    pub properties: PhysicalDeviceProperties,
+    pub features: u32,
+    pub extensions: Vec<DeviceExtension>
 }
 
 impl AbstractDevice for GlDevice
 {
     fn as_any(&self) -> &dyn Any { self }
-    
+
     fn get_device_queue(&self) -> Result<Queue, ()>
     {
-        Ok(Queue { internal: Rc::new(GlQueue {  }) })
+        self.get_queue(QueueKind::Graphics, 0)
+    }
+
+    // there is only the context's command stream, every queue submits into it
+    fn get_queue(&self, kind: QueueKind, _index: u32) -> Result<Queue, ()>
+    {
+        Ok(Queue { internal: Arc::new(GlQueue { kind }) })
+    }
+
+    fn get_physical_device_properties(&self) -> Result<PhysicalDeviceProperties, ()>
+    {
+        Ok(self.properties.clone())
     }
-    
+
+    fn enabled_features(&self) -> u32
+    {
+        self.features
+    }
+
+    fn enabled_extensions(&self) -> Vec<DeviceExtension>
+    {
+        self.extensions.clone()
+    }
+
+    fn is_extension_enabled(&self, extension: &DeviceExtension) -> bool
+    {
+        self.extensions.contains(extension)
+    }
+
+    // takes GLSL for GL 4.3. sets don't exist in GL, descriptors are bound to the binding qualifiers of the
+    // shader no matter which set they are in
     fn create_shader_module(&self, create_info: &ShaderModuleCreateInfo) -> Result<ShaderModule, ShaderModuleError>
     {
-        let ShaderModuleSource::Glsl(source) = create_info.source.clone() else { panic!() };
+        let ShaderModuleSource::Glsl(source) = &create_info.source else { return Err(ShaderModuleError::UnsupportedSource("the opengl backend takes GLSL".to_string())) };
+        let source_cstr = CString::new(source.as_str()).map_err(|_| ShaderModuleError::CompilationFailed("the source contains a nul byte".to_string()))?;
+
+        let handle = unsafe { gl::CreateShader(gl_shader_stage(create_info.stage)) };
+        unsafe
+        {
+            gl::ShaderSource(handle, 1, &source_cstr.as_ptr(), ptr::null());
+            gl::CompileShader(handle);
+        }
+
+        let mut status = 0;
+        unsafe { gl::GetShaderiv(handle, gl::COMPILE_STATUS, &mut status) };
+        if status == gl::FALSE as GLint
+        {
+            let info_log = shader_info_log(handle);
+            unsafe { gl::DeleteShader(handle) };
+            return Err(ShaderModuleError::CompilationFailed(info_log));
+        }
+        object_label(gl::SHADER, handle, create_info.label);
+
+        Ok(ShaderModule { internal: Arc::new(GlShaderModule { handle, stage: create_info.stage, source: source.as_str().into() }) })
+    }
 
-        let handle = unsafe { gl::CreateShader(match create_info.stage
+    fn create_buffer(&self, create_info: &BufferCreateInfo) -> Result<Buffer, ()>
+    {
+        let size = GLsizeiptr::try_from(create_info.size).map_err(|_| ())?;
+        let usage = match create_info.location
         {
-            ShaderStage::Vertex => gl::VERTEX_SHADER,
-            ShaderStage::Fragment => gl::FRAGMENT_SHADER,
-            ShaderStage::Geometry => gl::GEOMETRY_SHADER
-        }) };
+            MemoryLocation::GpuOnly => gl::STATIC_DRAW,
+            MemoryLocation::CpuToGpu => gl::DYNAMIC_DRAW,
+            MemoryLocation::GpuToCpu => gl::DYNAMIC_READ
+        };
 
+        let mut handle = 0;
         unsafe
         {
-            let source_cstr = std::ffi::CString::new(source).unwrap();
-            gl::ShaderSource(handle, 1, &source_cstr.as_ptr() as _, std::ptr::null());
+            gl::GenBuffers(1, &mut handle);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, handle);
+            gl::BufferData(gl::COPY_WRITE_BUFFER, size, ptr::null(), usage);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+        }
+        if check_error().is_err()
+        {
+            unsafe { gl::DeleteBuffers(1, &handle) };
+            return Err(());
+        }
+        object_label(gl::BUFFER, handle, create_info.label);
 
-            gl::CompileShader(handle);
-            if check_shader_error(handle, gl::COMPILE_STATUS, false, "Error compiling shader")
+        Ok(Buffer { internal: Arc::new(GlBuffer { handle, size: create_info.size }) })
+    }
+
+    fn create_image(&self, create_info: &ImageCreateInfo) -> Result<Image, ()>
+    {
+        let (internal_format, _, _) = gl_format(create_info.format).ok_or(())?;
+        if create_info.width == 0 || create_info.height == 0 || create_info.depth == 0 || create_info.mip_levels == 0 ||
+            create_info.array_layers == 0 || create_info.samples == 0
+        {
+            return Err(());
+        }
+
+        let target = gl_image_target(create_info);
+        let (width, height, depth) = (create_info.width as GLsizei, create_info.height as GLsizei, create_info.depth as GLsizei);
+        let (mip_levels, array_layers, samples) = (create_info.mip_levels as GLsizei, create_info.array_layers as GLsizei, create_info.samples as GLsizei);
+
+        let mut handle = 0;
+        unsafe
+        {
+            gl::GenTextures(1, &mut handle);
+            gl::BindTexture(target, handle);
+            match target
             {
-                return Err(ShaderModuleError::CompilationFailed);
+                gl::TEXTURE_2D => gl::TexStorage2D(target, mip_levels, internal_format, width, height),
+                gl::TEXTURE_2D_ARRAY => gl::TexStorage3D(target, mip_levels, internal_format, width, height, array_layers),
+                gl::TEXTURE_3D => gl::TexStorage3D(target, mip_levels, internal_format, width, height, depth),
+                gl::TEXTURE_2D_MULTISAMPLE => gl::TexStorage2DMultisample(target, samples, internal_format, width, height, gl::TRUE),
+                _ => gl::TexStorage3DMultisample(target, samples, internal_format, width, height, array_layers, gl::TRUE)
             }
+            gl::BindTexture(target, 0);
+        }
+        if check_error().is_err()
+        {
+            unsafe { gl::DeleteTextures(1, &handle) };
+            return Err(());
         }
+        object_label(gl::TEXTURE, handle, create_info.label);
 
-        Ok(ShaderModule { internal: Rc::new(GlShaderModule { handle, stage: create_info.stage }) })
+        Ok(Image { internal: Arc::new(GlImage
+        {
+            handle,
+            target,
+            create_info: ImageCreateInfo { label: None, ..*create_info },
+            swapchain: Arc::new(AtomicBool::new(false)),
+            layouts: Arc::new(Mutex::new(vec![ None; (create_info.mip_levels * create_info.array_layers) as usize ]))
+        }) })
     }
-}
 
-pub struct GlQueue
-{
+    fn buffer_memory_requirements(&self, create_info: &BufferCreateInfo) -> Result<MemoryRequirements, ()>
+    {
+        Ok(MemoryRequirements { size: create_info.size, alignment: MEMORY_ALIGNMENT, memory_type_bits: 1 })
+    }
 
-}
+    fn image_memory_requirements(&self, create_info: &ImageCreateInfo) -> Result<MemoryRequirements, ()>
+    {
+        gl_format(create_info.format).ok_or(())?;
 
-impl AbstractQueue for GlQueue
-{
-    fn as_any(&self) -> &dyn Any { self }
-}
+        let texel_size = create_info.format.texel_size() as u64;
+        let layer_size: u64 = (0..create_info.mip_levels)
+            .map(|mip_level| mip_extent(create_info, mip_level))
+            .map(|(width, height, depth)| width as u64 * height as u64 * depth as u64 * texel_size)
+            .sum();
 
-pub struct GlSwapchain
-{
+        Ok(MemoryRequirements
+        {
+            size: layer_size * create_info.array_layers as u64 * create_info.samples as u64,
+            alignment: MEMORY_ALIGNMENT,
+            memory_type_bits: 1
+        })
+    }
 
-}
+    // GL can't place objects in memory it didn't allocate for them, so just like on the software backend
+    // placed resources get storage of their own and the allocation only exists to check placements against
+    fn allocate_memory(&self, allocate_info: &MemoryAllocateInfo) -> Result<Memory, ()>
+    {
+        if allocate_info.memory_type_bits & 1 == 0
+        {
+            return Err(());
+        }
 
-impl AbstractSwapchain for GlSwapchain
-{
-    fn as_any(&self) -> &dyn Any { self }
-}
+        Ok(Memory { internal: Arc::new(GlMemory { size: allocate_info.size }) })
+    }
 
-pub struct GlShaderModule
-{
-    handle: u32,
-    stage: ShaderStage
-}
+    fn create_placed_buffer(&self, create_info: &BufferCreateInfo, memory: &Memory, offset: u64) -> Result<Buffer, ()>
+    {
+        software::check_placement(&self.buffer_memory_requirements(create_info)?, memory, offset)?;
+        self.create_buffer(create_info)
+    }
 
-impl AbstractShaderModule for GlShaderModule
-{
-    fn as_any(&self) -> &dyn Any { self }
-}
+    fn create_placed_image(&self, create_info: &ImageCreateInfo, memory: &Memory, offset: u64) -> Result<Image, ()>
+    {
+        software::check_placement(&self.image_memory_requirements(create_info)?, memory, offset)?;
+        self.create_image(create_info)
+    }
 
+    fn create_image_view(&self, create_info: &ImageViewCreateInfo) -> Result<ImageView, ()>
+    {
+        let image = create_info.image.downcast_ref::<GlImage>().unwrap();
+        let (internal_format, _, _) = gl_format(image.create_info.format).ok_or(())?;
 
+        let mip_level_end = create_info.base_mip_level.checked_add(create_info.mip_level_count);
+        let array_layer_end = create_info.base_array_layer.checked_add(create_info.array_layer_count);
+        if create_info.mip_level_count == 0 || create_info.array_layer_count == 0 ||
+            mip_level_end.map_or(true, |end| end > image.create_info.mip_levels) ||
+            array_layer_end.map_or(true, |end| end > image.create_info.array_layers)
+        {
+            return Err(());
+        }
 
-unsafe fn check_shader_error(shader: u32, flag: u32, is_program: bool, error_message: &str) -> bool
-{
-    let mut success: i32 = 0;
-    let mut error: [i8; 1024] = [0; 1024];
+        let target = match image.target
+        {
+            gl::TEXTURE_3D => gl::TEXTURE_3D,
+            gl::TEXTURE_2D_MULTISAMPLE | gl::TEXTURE_2D_MULTISAMPLE_ARRAY if create_info.array_layer_count > 1 => gl::TEXTURE_2D_MULTISAMPLE_ARRAY,
+            gl::TEXTURE_2D_MULTISAMPLE | gl::TEXTURE_2D_MULTISAMPLE_ARRAY => gl::TEXTURE_2D_MULTISAMPLE,
+            _ if create_info.array_layer_count > 1 => gl::TEXTURE_2D_ARRAY,
+            _ => gl::TEXTURE_2D
+        };
 
-    if is_program
+        let mut handle = 0;
+        unsafe
+        {
+            gl::GenTextures(1, &mut handle);
+            gl::TextureView(handle, target, image.handle, internal_format, create_info.base_mip_level, create_info.mip_level_count,
+                create_info.base_array_layer, create_info.array_layer_count);
+        }
+        if check_error().is_err()
+        {
+            unsafe { gl::DeleteTextures(1, &handle) };
+            return Err(());
+        }
+        object_label(gl::TEXTURE, handle, create_info.label);
+
+        Ok(ImageView { internal: Arc::new(GlImageView
+        {
+            handle,
+            target,
+            internal_format,
+            parent: create_info.image.clone(),
+            range: ImageSubresourceRange
+            {
+                base_mip_level: create_info.base_mip_level,
+                mip_level_count: create_info.mip_level_count,
+                base_array_layer: create_info.base_array_layer,
+                array_layer_count: create_info.array_layer_count
+            }
+        }) })
+    }
+
+    fn create_descriptor_set_layout(&self, create_info: &DescriptorSetLayoutCreateInfo) -> Result<DescriptorSetLayout, ()>
+    {
+        Ok(DescriptorSetLayout { internal: Arc::new(GlDescriptorSetLayout { bindings: create_info.bindings.clone() }) })
+    }
+
+    fn create_descriptor_set(&self, create_info: &DescriptorSetCreateInfo) -> Result<DescriptorSet, ()>
     {
-        gl::GetProgramiv(shader, flag, &mut success);
+        let layout = create_info.layout.downcast_ref::<GlDescriptorSetLayout>().unwrap();
+        let mut descriptors: Vec<(u32, GlDescriptor)> = Vec::with_capacity(create_info.writes.len());
+
+        for write in create_info.writes
+        {
+            let binding = layout.bindings.iter().find(|binding| binding.binding == write.binding).ok_or(())?;
+
+            let descriptor = match (&write.resource, binding.descriptor_type)
+            {
+                (DescriptorResource::Buffer { buffer, offset, size }, DescriptorType::UniformBuffer | DescriptorType::StorageBuffer) =>
+                {
+                    let target = if binding.descriptor_type == DescriptorType::UniformBuffer { gl::UNIFORM_BUFFER } else { gl::SHADER_STORAGE_BUFFER };
+                    GlDescriptor::Buffer { target, handle: buffer.downcast_ref::<GlBuffer>().unwrap().handle, offset: *offset, size: *size }
+                },
+                (DescriptorResource::Image(view), DescriptorType::StorageImage) =>
+                {
+                    let view = view.downcast_ref::<GlImageView>().unwrap();
+                    GlDescriptor::Image
+                    {
+                        handle: view.handle,
+                        layered: matches!(view.target, gl::TEXTURE_2D_ARRAY | gl::TEXTURE_3D | gl::TEXTURE_2D_MULTISAMPLE_ARRAY),
+                        internal_format: view.internal_format
+                    }
+                },
+                _ => { return Err(()); }
+            };
+
+            descriptors.push((write.binding, descriptor));
+        }
+
+        Ok(DescriptorSet { internal: Arc::new(GlDescriptorSet { descriptors }) })
     }
-    else
+
+    fn create_compute_pipeline(&self, create_info: &ComputePipelineCreateInfo) -> Result<ComputePipeline, PipelineError>
     {
-        gl::GetShaderiv(shader, flag, &mut success);
+        let shader = create_info.shader.downcast_ref::<GlShaderModule>().ok_or(PipelineError::UnsupportedShaderSource)?;
+        if shader.stage != ShaderStage::Compute
+        {
+            return Err(PipelineError::InvalidShaderStage);
+        }
+
+        let program = link_program(&[ shader ], create_info.pipeline_cache)?;
+        object_label(gl::PROGRAM, program, create_info.label);
+        Ok(ComputePipeline { internal: Arc::new(GlComputePipeline { program }) })
     }
 
-    if success == gl::FALSE as i32
+    // the encoder has no next_subpass, so only the first subpass is ever rendered and the framebuffer object
+    // only gets its attachments
+    fn create_render_pass(&self, create_info: &RenderPassCreateInfo) -> Result<RenderPass, ()>
     {
-        if is_program
+        if create_info.subpasses.is_empty()
         {
-            gl::GetProgramInfoLog(shader, 1024, 0 as *mut i32, error.as_mut_ptr());
+            return Err(());
         }
-        else
+
+        Ok(RenderPass { internal: Arc::new(GlRenderPass { create_info: RenderPassCreateInfo
+        {
+            attachments: create_info.attachments.clone(),
+            subpasses: create_info.subpasses.clone(),
+            dependencies: create_info.dependencies.clone(),
+            label: None
+        } }) })
+    }
+
+    fn create_framebuffer(&self, create_info: &FramebufferCreateInfo) -> Result<Framebuffer, ()>
+    {
+        let render_pass = create_info.render_pass.downcast_ref::<GlRenderPass>().unwrap();
+        let subpass = &render_pass.create_info.subpasses[0];
+        let attachment = |reference: &AttachmentReference| -> Result<GlAttachment, ()>
+        {
+            let description = render_pass.create_info.attachments.get(reference.attachment as usize).ok_or(())?;
+            let view = create_info.attachments.get(reference.attachment as usize).ok_or(())?;
+
+            Ok(GlAttachment
+            {
+                view: view.downcast_ref::<GlImageView>().unwrap().clone(),
+                operations: description.operations,
+                stencil_operations: description.stencil_operations
+            })
+        };
+
+        let mut framebuffer = GlFramebuffer
+        {
+            handle: 0,
+            color_attachments: subpass.color_attachments.iter().map(attachment).collect::<Result<_, _>>()?,
+            resolve_attachments: subpass.resolve_attachments.iter()
+                .map(|reference| create_info.attachments.get(reference.attachment as usize).map(|view| view.downcast_ref::<GlImageView>().unwrap().clone()).ok_or(()))
+                .collect::<Result<_, _>>()?,
+            depth_stencil_attachment: subpass.depth_stencil_attachment.as_ref().map(attachment).transpose()?,
+            views: create_info.attachments.iter().map(|view| (*view).clone()).collect(),
+            extent: (create_info.width, create_info.height)
+        };
+
+        unsafe { gl::GenFramebuffers(1, &mut framebuffer.handle) };
+        if framebuffer.attach(framebuffer.handle).is_err()
+        {
+            unsafe { gl::DeleteFramebuffers(1, &framebuffer.handle) };
+            return Err(());
+        }
+        object_label(gl::FRAMEBUFFER, framebuffer.handle, create_info.label);
+
+        Ok(Framebuffer { internal: Arc::new(framebuffer) })
+    }
+
+    // every pipeline gets its own program and a vertex array that holds the vertex layout, vertex buffers are
+    // only bound to it when drawing. depth is in GL's -1 to 1 clip space
+    fn create_render_pipeline(&self, create_info: &RenderPipelineCreateInfo) -> Result<RenderPipeline, PipelineError>
+    {
+        let color_attachment_count = match create_info.rendering
+        {
+            PipelineRendering::RenderPass { render_pass, subpass } =>
+            {
+                let render_pass = render_pass.downcast_ref::<GlRenderPass>().unwrap();
+                render_pass.create_info.subpasses.get(subpass as usize).ok_or(PipelineError::CreationFailed)?.color_attachments.len()
+            },
+            PipelineRendering::Dynamic { color_formats, .. } if self.features & FeatureFlags::DYNAMIC_RENDERING != 0 => color_formats.len(),
+            PipelineRendering::Dynamic { .. } => return Err(PipelineError::FeatureNotSupported)
+        };
+
+        let shaders: Vec<&GlShaderModule> = create_info.stages.iter()
+            .map(|stage| stage.downcast_ref::<GlShaderModule>().ok_or(PipelineError::UnsupportedShaderSource))
+            .collect::<Result<_, _>>()?;
+
+        let stages: Vec<ShaderStage> = shaders.iter().map(|shader| shader.stage).collect();
+        validate_render_pipeline_stages(&stages, create_info.primitive)?;
+        validate_vertex_buffers(create_info.vertex_buffers)?;
+
+        let samples = create_info.multisample.samples;
+        if !samples.is_power_of_two() || samples > get_integer(gl::MAX_SAMPLES)
+        {
+            return Err(PipelineError::InvalidSampleCount);
+        }
+
+        let (color_targets, independent_blend) = resolve_color_targets(create_info.color_targets, color_attachment_count)?;
+
+        // GL reads a divisor of 0 as per vertex data, so it can't stand in for vulkan's divisor of 0
+        let divisors: Vec<u32> = create_info.vertex_buffers.iter()
+            .filter_map(|layout| match layout.step_mode { VertexStepMode::Instance(divisor) if divisor != 1 => Some(divisor), _ => None })
+            .collect();
+        if divisors.contains(&0)
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        let required_features = [
+            (!divisors.is_empty(), FeatureFlags::VERTEX_ATTRIBUTE_DIVISOR),
+            (stages.contains(&ShaderStage::TessellationEvaluation), FeatureFlags::TESSELLATION_SHADER),
+            (stages.contains(&ShaderStage::Geometry), FeatureFlags::GEOMETRY_SHADER),
+            (create_info.multisample.min_sample_shading.is_some(), FeatureFlags::SAMPLE_RATE_SHADING),
+            (independent_blend, FeatureFlags::INDEPENDENT_BLEND),
+            (color_targets.iter().any(|target| target.uses_dual_source()), FeatureFlags::DUAL_SOURCE_BLEND),
+            (create_info.depth_clamp, FeatureFlags::DEPTH_CLAMP),
+            (create_info.polygon_mode != PolygonMode::Fill, FeatureFlags::FILL_MODE_NON_SOLID),
+            (create_info.depth_bias.is_some_and(|depth_bias| depth_bias.clamp != 0.0), FeatureFlags::DEPTH_BIAS_CLAMP)
+        ];
+        if required_features.iter().any(|(required, feature)| *required && self.features & feature == 0)
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        let program = link_program(&shaders, create_info.pipeline_cache)?;
+
+        let mut vertex_array = 0;
+        unsafe
         {
-            gl::GetShaderInfoLog(shader, 1024, 0 as *mut i32, error.as_mut_ptr());
+            gl::GenVertexArrays(1, &mut vertex_array);
+            gl::BindVertexArray(vertex_array);
+            for (binding, layout) in create_info.vertex_buffers.iter().enumerate()
+            {
+                for attribute in layout.attributes.iter()
+                {
+                    let (size, data_type, normalized, integer) = gl_vertex_format(attribute.format);
+                    gl::EnableVertexAttribArray(attribute.location);
+                    if integer
+                    {
+                        gl::VertexAttribIFormat(attribute.location, size, data_type, attribute.offset);
+                    }
+                    else
+                    {
+                        gl::VertexAttribFormat(attribute.location, size, data_type, normalized as GLboolean, attribute.offset);
+                    }
+                    gl::VertexAttribBinding(attribute.location, binding as GLuint);
+                }
+
+                let divisor = match layout.step_mode { VertexStepMode::Vertex => 0, VertexStepMode::Instance(divisor) => divisor };
+                gl::VertexBindingDivisor(binding as GLuint, divisor);
+            }
+            gl::BindVertexArray(0);
         }
+        object_label(gl::PROGRAM, program, create_info.label);
+        object_label(gl::VERTEX_ARRAY, vertex_array, create_info.label);
+
+        Ok(RenderPipeline { internal: Arc::new(GlRenderPipeline
+        {
+            program,
+            vertex_array,
+            strides: create_info.vertex_buffers.iter().map(|layout| layout.stride).collect(),
+            primitive: create_info.primitive,
+            polygon_mode: create_info.polygon_mode,
+            cull_mode: create_info.cull_mode,
+            front_face: create_info.front_face,
+            viewport: create_info.viewport,
+            depth_stencil: create_info.depth_stencil,
+            depth_bias: create_info.depth_bias,
+            depth_clamp: create_info.depth_clamp,
+            multisample: create_info.multisample,
+            color_targets,
+            dynamic_state: create_info.dynamic_state
+        }) })
+    }
+
+    // GL has no cache object, the cache keeps a program binary per set of shader sources and pipelines
+    // created with it load from there before linking
+    fn create_pipeline_cache(&self, data: Option<&[u8]>) -> Result<PipelineCache, ()>
+    {
+        let driver = gl_driver();
+        let programs = data.and_then(|blob| pipeline_cache_programs(&driver, blob)).unwrap_or_default();
+
+        Ok(PipelineCache { internal: Arc::new(GlPipelineCache { driver, programs: Arc::new(Mutex::new(programs)) }) })
+    }
+
+    // timestamps come from glQueryCounter, which counts nanoseconds just like GL_TIME_ELAPSED, so unlike
+    // vulkan's ticks they need no conversion
+    fn create_query_pool(&self, create_info: &QueryPoolCreateInfo) -> Result<QueryPool, ()>
+    {
+        let targets = match create_info.query_type
+        {
+            QueryType::Timestamp =>
+            {
+                let mut counter_bits = 0;
+                unsafe { gl::GetQueryiv(gl::TIMESTAMP, gl::QUERY_COUNTER_BITS, &mut counter_bits) };
+                if counter_bits == 0
+                {
+                    return Err(());
+                }
+                vec![ gl::TIMESTAMP ]
+            },
+            QueryType::Occlusion => vec![ gl::SAMPLES_PASSED ],
+            QueryType::PipelineStatistics(statistics) =>
+            {
+                if self.features & FeatureFlags::PIPELINE_STATISTICS_QUERY == 0 || statistics == 0 || statistics >> GL_PIPELINE_STATISTICS.len() != 0
+                {
+                    return Err(());
+                }
+                GL_PIPELINE_STATISTICS.iter().enumerate()
+                    .filter(|(bit, _)| statistics & (1 << bit) != 0)
+                    .map(|(_, target)| *target)
+                    .collect()
+            }
+        };
+
+        let mut handles = vec![0; create_info.count as usize * targets.len()];
+        unsafe { gl::GenQueries(handles.len() as GLsizei, handles.as_mut_ptr()) };
+        check_error()?;
+
+        Ok(QueryPool { internal: Arc::new(GlQueryPool
+        {
+            create_info: QueryPoolCreateInfo { query_type: create_info.query_type, count: create_info.count, label: None },
+            targets,
+            handles,
+            written: Arc::new(Mutex::new(vec![ false; create_info.count as usize ])),
+            label: create_info.label.map(Arc::from)
+        }) })
+    }
+
+    fn create_command_pool(&self, _queue: &Queue) -> Result<CommandPool, ()>
+    {
+        Ok(CommandPool { internal: Arc::new(GlCommandPool { features: self.features, line_width_range: self.properties.limits.line_width_range }) })
+    }
 
-        println!("[SHADER] {}: {}", error_message, unsafe { std::ffi::CStr::from_ptr(error.as_ptr()) }.to_str().unwrap());
+    fn create_fence(&self, signaled: bool) -> Result<Fence, ()>
+    {
+        let state = if signaled { GlFenceState::Signaled } else { GlFenceState::Unsignaled };
+        Ok(Fence { internal: Arc::new(GlFence { state: Arc::new(Mutex::new(state)) }) })
+    }
 
-        return true;
+    // a single context executes everything in submission order, there is nothing to wait on
+    fn create_semaphore(&self) -> Result<Semaphore, ()>
+    {
+        Ok(Semaphore { internal: Arc::new(GlSemaphore { }) })
     }
 
-    false
-}*/
\ No newline at end of file
+    fn write_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) -> Result<(), ()>
+    {
+        let buffer = buffer.downcast_ref::<GlBuffer>().unwrap();
+        if offset.checked_add(data.len() as u64).map_or(true, |end| end > buffer.size)
+        {
+            return Err(());
+        }
+
+        unsafe
+        {
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, buffer.handle);
+            gl::BufferSubData(gl::COPY_WRITE_BUFFER, offset as GLintptr, data.len() as GLsizeiptr, data.as_ptr() as _);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+        }
+        check_error()
+    }
+
+    fn upload_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) -> Result<Fence, ()>
+    {
+        self.write_buffer(buffer, offset, data)?;
+        self.fence_after_submitted()
+    }
+
+    fn upload_image(&self, image: &Image, region: &ImageRegion, data: &[u8]) -> Result<Fence, ()>
+    {
+        let image = image.downcast_ref::<GlImage>().unwrap();
+        let (_, format, data_type) = gl_format(image.create_info.format).ok_or(())?;
+        let (z, depth) = image.region_slices(region)?;
+        let row_size = region.width as usize * image.create_info.format.texel_size() as usize;
+        if image.create_info.samples != 1 || data.len() != row_size * region.height as usize * depth as usize
+        {
+            return Err(());
+        }
+
+        unsafe
+        {
+            gl::BindTexture(image.target, image.handle);
+            if image.target == gl::TEXTURE_2D
+            {
+                gl::TexSubImage2D(image.target, region.mip_level as GLint, region.x as GLint, region.y as GLint, region.width as GLsizei,
+                    region.height as GLsizei, format, data_type, data.as_ptr() as _);
+            }
+            else
+            {
+                gl::TexSubImage3D(image.target, region.mip_level as GLint, region.x as GLint, region.y as GLint, z as GLint, region.width as GLsizei,
+                    region.height as GLsizei, depth as GLsizei, format, data_type, data.as_ptr() as _);
+            }
+            gl::BindTexture(image.target, 0);
+        }
+        check_error()?;
+
+        self.fence_after_submitted()
+    }
+
+    // glGetBufferSubData waits for the commands writing the buffer on its own
+    fn read_buffer(&self, buffer: &Buffer, offset: u64, size: u64) -> Result<Vec<u8>, ()>
+    {
+        let buffer = buffer.downcast_ref::<GlBuffer>().unwrap();
+        if offset.checked_add(size).map_or(true, |end| end > buffer.size)
+        {
+            return Err(());
+        }
+
+        let mut data = vec![0u8; size as usize];
+        unsafe
+        {
+            gl::BindBuffer(gl::COPY_READ_BUFFER, buffer.handle);
+            gl::GetBufferSubData(gl::COPY_READ_BUFFER, offset as GLintptr, size as GLsizeiptr, data.as_mut_ptr() as _);
+            gl::BindBuffer(gl::COPY_READ_BUFFER, 0);
+        }
+        check_error()?;
+
+        Ok(data)
+    }
+
+    fn read_image(&self, image: &Image, region: &ImageRegion) -> Result<Vec<u8>, ()>
+    {
+        image.downcast_ref::<GlImage>().unwrap().read_region(region)
+    }
+
+    fn wait_idle(&self) -> Result<(), ()>
+    {
+        unsafe { gl::Finish() };
+        Ok(())
+    }
+
+    fn destroy_buffer(&self, buffer: &Buffer)
+    {
+        unsafe { gl::DeleteBuffers(1, &buffer.downcast_ref::<GlBuffer>().unwrap().handle) };
+    }
+
+    fn destroy_image(&self, image: &Image)
+    {
+        let image = image.downcast_ref::<GlImage>().unwrap();
+        if image.swapchain.load(Ordering::Relaxed)
+        {
+            state::validation_error("swapchain images are destroyed together with their swapchain");
+            return;
+        }
+
+        unsafe { gl::DeleteTextures(1, &image.handle) };
+    }
+
+    fn destroy_image_view(&self, image_view: &ImageView)
+    {
+        unsafe { gl::DeleteTextures(1, &image_view.downcast_ref::<GlImageView>().unwrap().handle) };
+    }
+
+    fn destroy_render_pass(&self, _render_pass: &RenderPass) {}
+
+    fn destroy_framebuffer(&self, framebuffer: &Framebuffer)
+    {
+        unsafe { gl::DeleteFramebuffers(1, &framebuffer.downcast_ref::<GlFramebuffer>().unwrap().handle) };
+    }
+
+    fn free_memory(&self, _memory: &Memory) {}
+
+    fn destroy_shader_module(&self, shader_module: &ShaderModule)
+    {
+        unsafe { gl::DeleteShader(shader_module.downcast_ref::<GlShaderModule>().unwrap().handle) };
+    }
+
+    fn destroy_compute_pipeline(&self, pipeline: &ComputePipeline)
+    {
+        unsafe { gl::DeleteProgram(pipeline.downcast_ref::<GlComputePipeline>().unwrap().program) };
+    }
+
+    fn destroy_render_pipeline(&self, pipeline: &RenderPipeline)
+    {
+        let pipeline = pipeline.downcast_ref::<GlRenderPipeline>().unwrap();
+        unsafe
+        {
+            gl::DeleteProgram(pipeline.program);
+            gl::DeleteVertexArrays(1, &pipeline.vertex_array);
+        }
+    }
+}
+
+impl GlDevice
+{
+    fn fence_after_submitted(&self) -> Result<Fence, ()>
+    {
+        let fence = self.create_fence(false)?;
+        fence.downcast_ref::<GlFence>().unwrap().signal_after_submitted();
+        Ok(fence)
+    }
+}
+
+const MEMORY_ALIGNMENT: u64 = 16;
+
+fn check_error() -> Result<(), ()>
+{
+    // GL keeps a flag per error kind, drain all of them so the next check starts clean
+    let mut result = Ok(());
+    while unsafe { gl::GetError() } != gl::NO_ERROR
+    {
+        result = Err(());
+    }
+
+    result
+}
+
+fn get_integer(name: GLenum) -> u32
+{
+    let mut value = 0;
+    unsafe { gl::GetIntegerv(name, &mut value) };
+    value.max(0) as u32
+}
+
+fn get_integers<const N: usize>(name: GLenum) -> [GLint; N]
+{
+    let mut values = [0; N];
+    unsafe { gl::GetIntegerv(name, values.as_mut_ptr()) };
+    values
+}
+
+fn get_indexed_integer(name: GLenum, index: u32) -> u32
+{
+    let mut value = 0;
+    unsafe { gl::GetIntegeri_v(name, index, &mut value) };
+    value.max(0) as u32
+}
+
+fn get_floats(name: GLenum) -> [f32; 2]
+{
+    let mut values = [0.0; 2];
+    unsafe { gl::GetFloatv(name, values.as_mut_ptr()) };
+    values
+}
+
+fn get_string(name: GLenum) -> String
+{
+    let string = unsafe { gl::GetString(name) };
+    if string.is_null()
+    {
+        return String::new();
+    }
+
+    unsafe { CStr::from_ptr(string as _) }.to_string_lossy().into_owned()
+}
+
+fn shader_info_log(handle: GLuint) -> String
+{
+    let mut length = 0;
+    unsafe { gl::GetShaderiv(handle, gl::INFO_LOG_LENGTH, &mut length) };
+
+    let mut info_log = vec![0u8; length.max(1) as usize];
+    unsafe { gl::GetShaderInfoLog(handle, length, ptr::null_mut(), info_log.as_mut_ptr() as _) };
+    String::from_utf8_lossy(&info_log).trim_end_matches('\0').to_string()
+}
+
+fn program_info_log(handle: GLuint) -> String
+{
+    let mut length = 0;
+    unsafe { gl::GetProgramiv(handle, gl::INFO_LOG_LENGTH, &mut length) };
+
+    let mut info_log = vec![0u8; length.max(1) as usize];
+    unsafe { gl::GetProgramInfoLog(handle, length, ptr::null_mut(), info_log.as_mut_ptr() as _) };
+    String::from_utf8_lossy(&info_log).trim_end_matches('\0').to_string()
+}
+
+// shaders are compiled when their module is created, so link errors are mostly mismatched interfaces
+// between stages. the driver's log goes to the validation target
+fn link_program(shaders: &[&GlShaderModule], pipeline_cache: Option<&PipelineCache>) -> Result<GLuint, PipelineError>
+{
+    let pipeline_cache = pipeline_cache.map(|pipeline_cache| pipeline_cache.downcast_ref::<GlPipelineCache>().unwrap());
+    let key = program_key(shaders);
+    if let Some(program) = pipeline_cache.and_then(|pipeline_cache| pipeline_cache.load(&key))
+    {
+        return Ok(program);
+    }
+
+    let program = unsafe { gl::CreateProgram() };
+    unsafe
+    {
+        if pipeline_cache.is_some()
+        {
+            gl::ProgramParameteri(program, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, gl::TRUE as GLint);
+        }
+        for shader in shaders
+        {
+            gl::AttachShader(program, shader.handle);
+        }
+        gl::LinkProgram(program);
+        for shader in shaders
+        {
+            gl::DetachShader(program, shader.handle);
+        }
+    }
+
+    let mut status = 0;
+    unsafe { gl::GetProgramiv(program, gl::LINK_STATUS, &mut status) };
+    if status == gl::FALSE as GLint
+    {
+        state::validation_error(&format!("linking failed: {}", program_info_log(program)));
+        unsafe { gl::DeleteProgram(program) };
+        return Err(PipelineError::CreationFailed);
+    }
+
+    if let Some(pipeline_cache) = pipeline_cache
+    {
+        pipeline_cache.store(key, program);
+    }
+
+    Ok(program)
+}
+
+// programs are cached by the stages and sources they are linked from, in full so two programs can never
+// share a binary
+fn program_key(shaders: &[&GlShaderModule]) -> Vec<u8>
+{
+    let mut key = Vec::new();
+    for shader in shaders
+    {
+        key.push(shader.stage as u8);
+        key.extend_from_slice(&(shader.source.len() as u64).to_le_bytes());
+        key.extend_from_slice(shader.source.as_bytes());
+    }
+
+    key
+}
+
+// binaries only load into the driver that wrote them
+fn gl_driver() -> String
+{
+    format!("{}\n{}\n{}", get_string(gl::VENDOR), get_string(gl::RENDERER), get_string(gl::VERSION))
+}
+
+fn gl_shader_stage(stage: ShaderStage) -> GLenum
+{
+    match stage
+    {
+        ShaderStage::Vertex => gl::VERTEX_SHADER,
+        ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+        ShaderStage::Geometry => gl::GEOMETRY_SHADER,
+        ShaderStage::Compute => gl::COMPUTE_SHADER,
+        ShaderStage::TessellationControl => gl::TESS_CONTROL_SHADER,
+        ShaderStage::TessellationEvaluation => gl::TESS_EVALUATION_SHADER
+    }
+}
+
+// internal format, then the format and type of client side data. GL has no BGRA storage, those formats
+// are stored as RGBA and only swizzled on upload and readback
+fn gl_format(format: Format) -> Option<(GLenum, GLenum, GLenum)>
+{
+    match format
+    {
+        Format::Undefined => None,
+        Format::R8Unorm => Some((gl::R8, gl::RED, gl::UNSIGNED_BYTE)),
+        Format::R8Snorm => Some((gl::R8_SNORM, gl::RED, gl::BYTE)),
+        Format::R8Uint => Some((gl::R8UI, gl::RED_INTEGER, gl::UNSIGNED_BYTE)),
+        Format::R8Sint => Some((gl::R8I, gl::RED_INTEGER, gl::BYTE)),
+        Format::R16Uint => Some((gl::R16UI, gl::RED_INTEGER, gl::UNSIGNED_SHORT)),
+        Format::R16Sint => Some((gl::R16I, gl::RED_INTEGER, gl::SHORT)),
+        Format::R16Unorm => Some((gl::R16, gl::RED, gl::UNSIGNED_SHORT)),
+        Format::R16Snorm => Some((gl::R16_SNORM, gl::RED, gl::SHORT)),
+        Format::R16Float => Some((gl::R16F, gl::RED, gl::HALF_FLOAT)),
+        Format::R32Uint => Some((gl::R32UI, gl::RED_INTEGER, gl::UNSIGNED_INT)),
+        Format::R32Sint => Some((gl::R32I, gl::RED_INTEGER, gl::INT)),
+        Format::R32Float => Some((gl::R32F, gl::RED, gl::FLOAT)),
+        Format::R8G8Unorm => Some((gl::RG8, gl::RG, gl::UNSIGNED_BYTE)),
+        Format::R16G16Float => Some((gl::RG16F, gl::RG, gl::HALF_FLOAT)),
+        Format::R32G32Float => Some((gl::RG32F, gl::RG, gl::FLOAT)),
+        Format::R8G8B8A8Unorm => Some((gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE)),
+        Format::R8G8B8A8Srgb => Some((gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE)),
+        Format::R8G8B8A8Uint => Some((gl::RGBA8UI, gl::RGBA_INTEGER, gl::UNSIGNED_BYTE)),
+        Format::B8G8R8A8Unorm => Some((gl::RGBA8, gl::BGRA, gl::UNSIGNED_BYTE)),
+        Format::B8G8R8A8Srgb => Some((gl::SRGB8_ALPHA8, gl::BGRA, gl::UNSIGNED_BYTE)),
+        Format::A2B10G10R10Unorm => Some((gl::RGB10_A2, gl::RGBA, gl::UNSIGNED_INT_2_10_10_10_REV)),
+        Format::R16G16B16A16Float => Some((gl::RGBA16F, gl::RGBA, gl::HALF_FLOAT)),
+        Format::R32G32B32A32Uint => Some((gl::RGBA32UI, gl::RGBA_INTEGER, gl::UNSIGNED_INT)),
+        Format::R32G32B32A32Float => Some((gl::RGBA32F, gl::RGBA, gl::FLOAT)),
+        Format::D16Unorm => Some((gl::DEPTH_COMPONENT16, gl::DEPTH_COMPONENT, gl::UNSIGNED_SHORT)),
+        Format::D32Float => Some((gl::DEPTH_COMPONENT32F, gl::DEPTH_COMPONENT, gl::FLOAT)),
+        Format::D24UnormS8Uint => Some((gl::DEPTH24_STENCIL8, gl::DEPTH_STENCIL, gl::UNSIGNED_INT_24_8)),
+        Format::D32FloatS8Uint => Some((gl::DEPTH32F_STENCIL8, gl::DEPTH_STENCIL, gl::FLOAT_32_UNSIGNED_INT_24_8_REV))
+    }
+}
+
+fn gl_image_target(create_info: &ImageCreateInfo) -> GLenum
+{
+    match (create_info.samples > 1, create_info.depth > 1, create_info.array_layers > 1)
+    {
+        (true, _, true) => gl::TEXTURE_2D_MULTISAMPLE_ARRAY,
+        (true, _, false) => gl::TEXTURE_2D_MULTISAMPLE,
+        (false, true, _) => gl::TEXTURE_3D,
+        (false, false, true) => gl::TEXTURE_2D_ARRAY,
+        (false, false, false) => gl::TEXTURE_2D
+    }
+}
+
+fn mip_extent(create_info: &ImageCreateInfo, mip_level: u32) -> (u32, u32, u32)
+{
+    ((create_info.width >> mip_level).max(1), (create_info.height >> mip_level).max(1), (create_info.depth >> mip_level).max(1))
+}
+
+// component count, component type, normalized, and whether the shader reads it as an integer
+fn gl_vertex_format(format: VertexFormat) -> (GLint, GLenum, bool, bool)
+{
+    match format
+    {
+        VertexFormat::Float32 => (1, gl::FLOAT, false, false),
+        VertexFormat::Float32x2 => (2, gl::FLOAT, false, false),
+        VertexFormat::Float32x3 => (3, gl::FLOAT, false, false),
+        VertexFormat::Float32x4 => (4, gl::FLOAT, false, false),
+        VertexFormat::Uint32 => (1, gl::UNSIGNED_INT, false, true),
+        VertexFormat::Uint32x2 => (2, gl::UNSIGNED_INT, false, true),
+        VertexFormat::Uint32x3 => (3, gl::UNSIGNED_INT, false, true),
+        VertexFormat::Uint32x4 => (4, gl::UNSIGNED_INT, false, true),
+        VertexFormat::Sint32 => (1, gl::INT, false, true),
+        VertexFormat::Sint32x2 => (2, gl::INT, false, true),
+        VertexFormat::Sint32x3 => (3, gl::INT, false, true),
+        VertexFormat::Sint32x4 => (4, gl::INT, false, true),
+        VertexFormat::Float16x2 => (2, gl::HALF_FLOAT, false, false),
+        VertexFormat::Float16x4 => (4, gl::HALF_FLOAT, false, false),
+        VertexFormat::Unorm16x2 => (2, gl::UNSIGNED_SHORT, true, false),
+        VertexFormat::Unorm16x4 => (4, gl::UNSIGNED_SHORT, true, false),
+        VertexFormat::Unorm8x4 => (4, gl::UNSIGNED_BYTE, true, false),
+        VertexFormat::Snorm8x4 => (4, gl::BYTE, true, false),
+        VertexFormat::Uint8x4 => (4, gl::UNSIGNED_BYTE, false, true)
+    }
+}
+
+fn gl_primitive(primitive: Primitive) -> GLenum
+{
+    match primitive
+    {
+        Primitive::PointList => gl::POINTS,
+        Primitive::LineList => gl::LINES,
+        Primitive::LineStrip => gl::LINE_STRIP,
+        Primitive::TriangleList => gl::TRIANGLES,
+        Primitive::TriangleStrip => gl::TRIANGLE_STRIP,
+        Primitive::PatchList(_) => gl::PATCHES
+    }
+}
+
+fn gl_index_format(format: IndexFormat) -> (GLenum, u64)
+{
+    match format
+    {
+        IndexFormat::Uint16 => (gl::UNSIGNED_SHORT, 2),
+        IndexFormat::Uint32 => (gl::UNSIGNED_INT, 4)
+    }
+}
+
+fn gl_compare_op(compare_op: CompareOp) -> GLenum
+{
+    match compare_op
+    {
+        CompareOp::Never => gl::NEVER,
+        CompareOp::Less => gl::LESS,
+        CompareOp::Equal => gl::EQUAL,
+        CompareOp::LessOrEqual => gl::LEQUAL,
+        CompareOp::Greater => gl::GREATER,
+        CompareOp::NotEqual => gl::NOTEQUAL,
+        CompareOp::GreaterOrEqual => gl::GEQUAL,
+        CompareOp::Always => gl::ALWAYS
+    }
+}
+
+fn gl_stencil_op(stencil_op: StencilOp) -> GLenum
+{
+    match stencil_op
+    {
+        StencilOp::Keep => gl::KEEP,
+        StencilOp::Zero => gl::ZERO,
+        StencilOp::Replace => gl::REPLACE,
+        StencilOp::IncrementAndClamp => gl::INCR,
+        StencilOp::DecrementAndClamp => gl::DECR,
+        StencilOp::Invert => gl::INVERT,
+        StencilOp::IncrementAndWrap => gl::INCR_WRAP,
+        StencilOp::DecrementAndWrap => gl::DECR_WRAP
+    }
+}
+
+fn gl_blend_factor(factor: BlendFactor) -> GLenum
+{
+    match factor
+    {
+        BlendFactor::Zero => gl::ZERO,
+        BlendFactor::One => gl::ONE,
+        BlendFactor::SrcColor => gl::SRC_COLOR,
+        BlendFactor::OneMinusSrcColor => gl::ONE_MINUS_SRC_COLOR,
+        BlendFactor::DstColor => gl::DST_COLOR,
+        BlendFactor::OneMinusDstColor => gl::ONE_MINUS_DST_COLOR,
+        BlendFactor::SrcAlpha => gl::SRC_ALPHA,
+        BlendFactor::OneMinusSrcAlpha => gl::ONE_MINUS_SRC_ALPHA,
+        BlendFactor::DstAlpha => gl::DST_ALPHA,
+        BlendFactor::OneMinusDstAlpha => gl::ONE_MINUS_DST_ALPHA,
+        BlendFactor::ConstantColor => gl::CONSTANT_COLOR,
+        BlendFactor::OneMinusConstantColor => gl::ONE_MINUS_CONSTANT_COLOR,
+        BlendFactor::ConstantAlpha => gl::CONSTANT_ALPHA,
+        BlendFactor::OneMinusConstantAlpha => gl::ONE_MINUS_CONSTANT_ALPHA,
+        BlendFactor::SrcAlphaSaturate => gl::SRC_ALPHA_SATURATE,
+        BlendFactor::Src1Color => gl::SRC1_COLOR,
+        BlendFactor::OneMinusSrc1Color => gl::ONE_MINUS_SRC1_COLOR,
+        BlendFactor::Src1Alpha => gl::SRC1_ALPHA,
+        BlendFactor::OneMinusSrc1Alpha => gl::ONE_MINUS_SRC1_ALPHA
+    }
+}
+
+fn gl_blend_op(blend_op: BlendOp) -> GLenum
+{
+    match blend_op
+    {
+        BlendOp::Add => gl::FUNC_ADD,
+        BlendOp::Subtract => gl::FUNC_SUBTRACT,
+        BlendOp::ReverseSubtract => gl::FUNC_REVERSE_SUBTRACT,
+        BlendOp::Min => gl::MIN,
+        BlendOp::Max => gl::MAX
+    }
+}
+
+// names objects through KHR_debug, core since 4.3, so captures and the driver's messages show them
+fn object_label(identifier: GLenum, name: GLuint, label: Option<&str>)
+{
+    if let Some(label) = label
+    {
+        let (length, label) = gl_label(label, gl::MAX_LABEL_LENGTH);
+        unsafe { gl::ObjectLabel(identifier, name, length, label) };
+    }
+}
+
+// GL refuses labels that are longer than its limit instead of cutting them off
+fn gl_label(label: &str, max_length: GLenum) -> (GLsizei, *const GLchar)
+{
+    let length = label.len().min(get_integer(max_length).saturating_sub(1) as usize);
+    (length as GLsizei, label.as_ptr() as *const GLchar)
+}
+
+fn set_enabled(capability: GLenum, enabled: bool)
+{
+    unsafe { if enabled { gl::Enable(capability) } else { gl::Disable(capability) } };
+}
+
+// the ARB_pipeline_statistics_query targets in PipelineStatisticFlags bit order. gl only has bindings up to
+// 4.5, so the ones that only became core in 4.6 are spelled out
+const GL_PIPELINE_STATISTICS: [GLenum; 11] = [
+    0x82EE, // GL_VERTICES_SUBMITTED_ARB
+    0x82EF, // GL_PRIMITIVES_SUBMITTED_ARB
+    0x82F0, // GL_VERTEX_SHADER_INVOCATIONS_ARB
+    gl::GEOMETRY_SHADER_INVOCATIONS,
+    0x82F3, // GL_GEOMETRY_SHADER_PRIMITIVES_EMITTED_ARB
+    0x82F6, // GL_CLIPPING_INPUT_PRIMITIVES_ARB
+    0x82F7, // GL_CLIPPING_OUTPUT_PRIMITIVES_ARB
+    0x82F4, // GL_FRAGMENT_SHADER_INVOCATIONS_ARB
+    0x82F1, // GL_TESS_CONTROL_SHADER_PATCHES_ARB
+    0x82F2, // GL_TESS_EVALUATION_SHADER_INVOCATIONS_ARB
+    0x82F5  // GL_COMPUTE_SHADER_INVOCATIONS_ARB
+];
+
+#[derive(Clone)]
+pub struct GlQueue
+{
+    pub kind: QueueKind
+}
+
+impl AbstractQueue for GlQueue
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn submit(&self, submit_info: &SubmitInfo) -> Result<(), ()>
+    {
+        for command_buffer in submit_info.command_buffers
+        {
+            let command_buffer = command_buffer.downcast_ref::<GlCommandBuffer>().unwrap();
+            for command in command_buffer.commands.iter()
+            {
+                command.execute();
+            }
+        }
+
+        if let Some(fence) = submit_info.fence
+        {
+            fence.downcast_ref::<GlFence>().unwrap().signal_after_submitted();
+        }
+
+        unsafe { gl::Flush() };
+        check_error()
+    }
+
+    // row 0 of every image is the top row in memory just like on the other backends, while GL puts row 0 of
+    // the window at the bottom, so the blit flips. shaders written against vulkan's y down clip space show
+    // up the right way round
+    fn present(&self, swapchain: &Swapchain, image_index: u32, _wait_semaphores: &[&Semaphore]) -> Result<(), SwapchainError>
+    {
+        let swapchain = swapchain.downcast_ref::<GlSwapchain>().unwrap();
+        if image_index != 0
+        {
+            return Err(SwapchainError::Failed);
+        }
+
+        let (width, height) = (swapchain.extent.0 as GLint, swapchain.extent.1 as GLint);
+        unsafe
+        {
+            gl::Disable(gl::FRAMEBUFFER_SRGB);
+            gl::Disable(gl::SCISSOR_TEST);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, swapchain.framebuffer);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+            gl::BlitFramebuffer(0, 0, width, height, 0, height, width, 0, gl::COLOR_BUFFER_BIT, gl::NEAREST);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        swapchain.context.handle.swap_buffers();
+
+        check_error().map_err(|_| SwapchainError::Failed)
+    }
+
+    fn wait_idle(&self) -> Result<(), ()>
+    {
+        unsafe { gl::Finish() };
+        Ok(())
+    }
+
+    fn kind(&self) -> QueueKind
+    {
+        self.kind
+    }
+
+    fn family_index(&self) -> u32
+    {
+        0
+    }
+}
+
+pub struct GlSwapchain
+{
+    pub context: Arc<GlContext>,
+    pub image: Image,
+    pub views: Vec<ImageView>,
+    pub depth_views: Vec<ImageView>,
+    pub extent: (u32, u32),
+    pub format: Format,
+    pub framebuffer: GLuint
+}
+
+impl AbstractSwapchain for GlSwapchain
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn extent(&self) -> (u32, u32)
+    {
+        self.extent
+    }
+
+    fn format(&self) -> Format
+    {
+        self.format
+    }
+
+    fn color_space(&self) -> ColorSpace
+    {
+        ColorSpace::SrgbNonlinear
+    }
+
+    fn present_mode(&self) -> PresentMode
+    {
+        PresentMode::Fifo
+    }
+
+    fn image_views(&self) -> Vec<ImageView>
+    {
+        self.views.clone()
+    }
+
+    fn depth_image_views(&self) -> Vec<ImageView>
+    {
+        self.depth_views.clone()
+    }
+
+    // the single image is always available, present copies it out before the next frame can touch it
+    fn acquire_next_image(&self, _semaphore: Option<&Semaphore>, fence: Option<&Fence>) -> Result<u32, SwapchainError>
+    {
+        if let Some(fence) = fence
+        {
+            *fence.downcast_ref::<GlFence>().unwrap().state.lock().unwrap() = GlFenceState::Signaled;
+        }
+
+        Ok(0)
+    }
+
+    fn capture_current_image(&self) -> Result<CapturedImage, ()>
+    {
+        let (width, height) = self.extent;
+        let data = self.image.downcast_ref::<GlImage>().unwrap().read_region(&ImageRegion { width, height, ..Default::default() })?;
+
+        CapturedImage::from_texels(self.format, width, height, &data)
+    }
+}
+
+#[derive(Clone)]
+pub struct GlShaderModule
+{
+    pub handle: GLuint,
+    pub stage: ShaderStage,
+    // the pipeline cache's key
+    pub source: Arc<str>
+}
+
+impl AbstractShaderModule for GlShaderModule
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct GlMemory
+{
+    pub size: u64
+}
+
+impl AbstractMemory for GlMemory
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn size(&self) -> u64 { self.size }
+}
+
+#[derive(Clone)]
+pub struct GlBuffer
+{
+    pub handle: GLuint,
+    pub size: u64
+}
+
+impl AbstractBuffer for GlBuffer
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn size(&self) -> u64 { self.size }
+}
+
+#[derive(Clone)]
+pub struct GlImage
+{
+    pub handle: GLuint,
+    pub target: GLenum,
+    pub create_info: ImageCreateInfo<'static>,
+    pub swapchain: Arc<AtomicBool>,
+    // only tracked for validation, GL has no layouts
+    pub layouts: Arc<Mutex<Vec<Option<ImageLayout>>>>
+}
+
+impl GlImage
+{
+    // the first slice and slice count of a region, slices are array layers or the depth of 3D images
+    fn region_slices(&self, region: &ImageRegion) -> Result<(u32, u32), ()>
+    {
+        if region.mip_level >= self.create_info.mip_levels || region.array_layer_count == 0 ||
+            region.base_array_layer.checked_add(region.array_layer_count).map_or(true, |end| end > self.create_info.array_layers)
+        {
+            return Err(());
+        }
+
+        let (width, height, depth) = mip_extent(&self.create_info, region.mip_level);
+        if region.width == 0 || region.height == 0 || region.depth == 0 ||
+            region.x.checked_add(region.width).map_or(true, |end| end > width) ||
+            region.y.checked_add(region.height).map_or(true, |end| end > height) ||
+            region.z.checked_add(region.depth).map_or(true, |end| end > depth)
+        {
+            return Err(());
+        }
+
+        Ok(if self.target == gl::TEXTURE_3D { (region.z, region.depth) } else { (region.base_array_layer, region.array_layer_count) })
+    }
+
+    // 4.3 has no glGetTextureSubImage, so the whole mip level is read and the region cut out of it
+    fn read_region(&self, region: &ImageRegion) -> Result<Vec<u8>, ()>
+    {
+        let (_, format, data_type) = gl_format(self.create_info.format).ok_or(())?;
+        let (first_slice, slice_count) = self.region_slices(region)?;
+        if self.create_info.samples != 1
+        {
+            return Err(());
+        }
+
+        let texel_size = self.create_info.format.texel_size() as usize;
+        let (width, height, depth) = mip_extent(&self.create_info, region.mip_level);
+        let slices = if self.target == gl::TEXTURE_3D { depth } else { self.create_info.array_layers };
+
+        let mut level = vec![0u8; width as usize * height as usize * slices as usize * texel_size];
+        unsafe
+        {
+            gl::BindTexture(self.target, self.handle);
+            gl::GetTexImage(self.target, region.mip_level as GLint, format, data_type, level.as_mut_ptr() as _);
+            gl::BindTexture(self.target, 0);
+        }
+        check_error()?;
+
+        let row_size = region.width as usize * texel_size;
+        let mut data = Vec::with_capacity(row_size * region.height as usize * slice_count as usize);
+        for slice in first_slice..first_slice + slice_count
+        {
+            for y in region.y..region.y + region.height
+            {
+                let offset = ((slice as usize * height as usize + y as usize) * width as usize + region.x as usize) * texel_size;
+                data.extend_from_slice(&level[offset..offset + row_size]);
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+impl AbstractImage for GlImage
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_info(&self) -> ImageCreateInfo<'static>
+    {
+        self.create_info
+    }
+
+    fn current_layout(&self, mip_level: u32, array_layer: u32) -> Option<ImageLayout>
+    {
+        self.layouts.lock().unwrap()[(mip_level * self.create_info.array_layers + array_layer) as usize]
+    }
+
+    fn set_current_layout(&self, mip_level: u32, array_layer: u32, layout: ImageLayout)
+    {
+        self.layouts.lock().unwrap()[(mip_level * self.create_info.array_layers + array_layer) as usize] = Some(layout);
+    }
+}
+
+#[derive(Clone)]
+pub struct GlImageView
+{
+    pub handle: GLuint,
+    pub target: GLenum,
+    pub internal_format: GLenum,
+    pub parent: Image,
+    pub range: ImageSubresourceRange
+}
+
+impl AbstractImageView for GlImageView
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn image(&self) -> Image
+    {
+        self.parent.clone()
+    }
+
+    fn subresource_range(&self) -> ImageSubresourceRange
+    {
+        self.range
+    }
+}
+
+#[derive(Clone)]
+pub struct GlDescriptorSetLayout
+{
+    pub bindings: Vec<DescriptorSetLayoutBinding>
+}
+
+impl AbstractDescriptorSetLayout for GlDescriptorSetLayout
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub enum GlDescriptor
+{
+    Buffer { target: GLenum, handle: GLuint, offset: u64, size: u64 },
+    Image { handle: GLuint, layered: bool, internal_format: GLenum }
+}
+
+#[derive(Clone)]
+pub struct GlDescriptorSet
+{
+    pub descriptors: Vec<(u32, GlDescriptor)>
+}
+
+impl AbstractDescriptorSet for GlDescriptorSet
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+impl GlDescriptorSet
+{
+    fn bind(&self)
+    {
+        for (binding, descriptor) in self.descriptors.iter()
+        {
+            match *descriptor
+            {
+                GlDescriptor::Buffer { target, handle, offset, size } => unsafe
+                {
+                    gl::BindBufferRange(target, *binding, handle, offset as GLintptr, size as GLsizeiptr)
+                },
+                GlDescriptor::Image { handle, layered, internal_format } => unsafe
+                {
+                    gl::BindImageTexture(*binding, handle, 0, layered as GLboolean, 0, gl::READ_WRITE, internal_format)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GlComputePipeline
+{
+    pub program: GLuint
+}
+
+impl AbstractComputePipeline for GlComputePipeline
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct GlRenderPass
+{
+    pub create_info: RenderPassCreateInfo<'static>
+}
+
+impl AbstractRenderPass for GlRenderPass
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_info(&self) -> &RenderPassCreateInfo<'static>
+    {
+        &self.create_info
+    }
+}
+
+#[derive(Clone)]
+pub struct GlAttachment
+{
+    pub view: GlImageView,
+    pub operations: Operations,
+    pub stencil_operations: Operations
+}
+
+impl GlAttachment
+{
+    fn format(&self) -> Format
+    {
+        self.view.parent.create_info().format
+    }
+}
+
+// the attachments of the first subpass. begin_rendering has no framebuffer object to render into, its
+// framebuffers have a handle of 0 and get a framebuffer object for as long as they are rendered to
+#[derive(Clone)]
+pub struct GlFramebuffer
+{
+    pub handle: GLuint,
+    pub color_attachments: Vec<GlAttachment>,
+    pub resolve_attachments: Vec<GlImageView>,
+    pub depth_stencil_attachment: Option<GlAttachment>,
+    pub views: Vec<ImageView>,
+    pub extent: (u32, u32)
+}
+
+impl AbstractFramebuffer for GlFramebuffer
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn attachments(&self) -> &[ImageView]
+    {
+        &self.views
+    }
+}
+
+impl GlFramebuffer
+{
+    // whole views are attached, so array views render layered
+    fn attach(&self, handle: GLuint) -> Result<(), ()>
+    {
+        let draw_buffers: Vec<GLenum> = (0..self.color_attachments.len() as GLenum).map(|index| gl::COLOR_ATTACHMENT0 + index).collect();
+        let status = unsafe
+        {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, handle);
+            for (attachment_point, attachment) in draw_buffers.iter().zip(self.color_attachments.iter())
+            {
+                gl::FramebufferTexture(gl::FRAMEBUFFER, *attachment_point, attachment.view.handle, 0);
+            }
+            if let Some(attachment) = self.depth_stencil_attachment.as_ref()
+            {
+                let attachment_point = if attachment.format().has_stencil() { gl::DEPTH_STENCIL_ATTACHMENT } else { gl::DEPTH_ATTACHMENT };
+                gl::FramebufferTexture(gl::FRAMEBUFFER, attachment_point, attachment.view.handle, 0);
+            }
+            gl::DrawBuffers(draw_buffers.len() as GLsizei, draw_buffers.as_ptr());
+            gl::ReadBuffer(if draw_buffers.is_empty() { gl::NONE } else { gl::COLOR_ATTACHMENT0 });
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+
+        if status == gl::FRAMEBUFFER_COMPLETE { Ok(()) } else { Err(()) }
+    }
+
+    fn begin(&self)
+    {
+        let mut handle = self.handle;
+        if handle == 0
+        {
+            unsafe { gl::GenFramebuffers(1, &mut handle) };
+            if self.attach(handle).is_err()
+            {
+                state::validation_error("the attachments of begin_rendering don't make a complete framebuffer");
+            }
+        }
+
+        unsafe
+        {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, handle);
+            gl::Enable(gl::FRAMEBUFFER_SRGB);
+        }
+        self.invalidate(|operations| operations.load_op == LoadOp::DontCare);
+        self.clear();
+    }
+
+    // the write masks and the scissor still hold whatever the last draw left there, and clears go through them
+    fn clear(&self)
+    {
+        unsafe
+        {
+            gl::Disable(gl::SCISSOR_TEST);
+            for (index, attachment) in self.color_attachments.iter().enumerate()
+            {
+                let LoadOp::Clear(r, g, b, a) = attachment.operations.load_op else { continue };
+
+                gl::ColorMaski(index as GLuint, gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+                match gl_format(attachment.format())
+                {
+                    Some((gl::R8I | gl::R16I | gl::R32I, _, _)) =>
+                        gl::ClearBufferiv(gl::COLOR, index as GLint, [ r as GLint, g as GLint, b as GLint, a as GLint ].as_ptr()),
+                    Some((_, gl::RED_INTEGER | gl::RGBA_INTEGER, _)) =>
+                        gl::ClearBufferuiv(gl::COLOR, index as GLint, [ r as GLuint, g as GLuint, b as GLuint, a as GLuint ].as_ptr()),
+                    _ => gl::ClearBufferfv(gl::COLOR, index as GLint, [ r, g, b, a ].as_ptr())
+                }
+            }
+
+            if let Some(attachment) = self.depth_stencil_attachment.as_ref()
+            {
+                let depth = match attachment.operations.load_op { LoadOp::Clear(depth, ..) => Some(depth), _ => None };
+                let stencil = match attachment.stencil_operations.load_op
+                {
+                    LoadOp::Clear(stencil, ..) if attachment.format().has_stencil() => Some(stencil as u32 as GLint),
+                    _ => None
+                };
+
+                gl::DepthMask(gl::TRUE);
+                gl::StencilMask(!0);
+                match (depth, stencil)
+                {
+                    (Some(depth), Some(stencil)) => gl::ClearBufferfi(gl::DEPTH_STENCIL, 0, depth, stencil),
+                    (Some(depth), None) => gl::ClearBufferfv(gl::DEPTH, 0, &depth),
+                    (None, Some(stencil)) => gl::ClearBufferiv(gl::STENCIL, 0, &stencil),
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+
+    // resolve attachments are blitted to like present does, as raw texels
+    fn end(&self)
+    {
+        let handle = get_integer(gl::DRAW_FRAMEBUFFER_BINDING);
+        unsafe
+        {
+            gl::Disable(gl::FRAMEBUFFER_SRGB);
+            gl::Disable(gl::SCISSOR_TEST);
+        }
+
+        if !self.resolve_attachments.is_empty()
+        {
+            let (width, height) = (self.extent.0 as GLint, self.extent.1 as GLint);
+            let mut resolve = 0;
+            unsafe
+            {
+                gl::GenFramebuffers(1, &mut resolve);
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, handle);
+                gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, resolve);
+                for (index, view) in self.resolve_attachments.iter().enumerate()
+                {
+                    gl::ReadBuffer(gl::COLOR_ATTACHMENT0 + index as GLenum);
+                    gl::FramebufferTexture(gl::DRAW_FRAMEBUFFER, gl::COLOR_ATTACHMENT0, view.handle, 0);
+                    gl::BlitFramebuffer(0, 0, width, height, 0, 0, width, height, gl::COLOR_BUFFER_BIT, gl::NEAREST);
+                }
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+                gl::DeleteFramebuffers(1, &resolve);
+                gl::BindFramebuffer(gl::FRAMEBUFFER, handle);
+            }
+        }
+
+        self.invalidate(|operations| operations.store_op == StoreOp::DontCare);
+        unsafe
+        {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            if self.handle == 0
+            {
+                gl::DeleteFramebuffers(1, &handle);
+            }
+        }
+    }
+
+    // tells the driver which attachments it doesn't have to load or store
+    fn invalidate(&self, discard: impl Fn(&Operations) -> bool)
+    {
+        let mut attachments: Vec<GLenum> = self.color_attachments.iter().enumerate()
+            .filter(|(_, attachment)| discard(&attachment.operations))
+            .map(|(index, _)| gl::COLOR_ATTACHMENT0 + index as GLenum)
+            .collect();
+
+        if let Some(attachment) = self.depth_stencil_attachment.as_ref()
+        {
+            if discard(&attachment.operations)
+            {
+                attachments.push(gl::DEPTH_ATTACHMENT);
+            }
+            if attachment.format().has_stencil() && discard(&attachment.stencil_operations)
+            {
+                attachments.push(gl::STENCIL_ATTACHMENT);
+            }
+        }
+
+        if !attachments.is_empty()
+        {
+            unsafe { gl::InvalidateFramebuffer(gl::FRAMEBUFFER, attachments.len() as GLsizei, attachments.as_ptr()) };
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GlRenderPipeline
+{
+    pub program: GLuint,
+    pub vertex_array: GLuint,
+    pub strides: Vec<u32>,
+    pub primitive: Primitive,
+    pub polygon_mode: PolygonMode,
+    pub cull_mode: Option<CullMode>,
+    pub front_face: FrontFace,
+    pub viewport: Viewport,
+    pub depth_stencil: DepthStencilState,
+    pub depth_bias: Option<DepthBias>,
+    pub depth_clamp: bool,
+    pub multisample: MultisampleState,
+    pub color_targets: Vec<ColorTargetState>,
+    pub dynamic_state: u32
+}
+
+impl AbstractRenderPipeline for GlRenderPipeline
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+// only read for the states a pipeline declares dynamic
+#[derive(Clone)]
+pub struct GlDynamicState
+{
+    pub viewport: Option<Viewport>,
+    pub scissor: Option<Scissor>,
+    pub blend_constants: [f32; 4],
+    pub stencil_reference: u32,
+    pub line_width: f32,
+    pub depth_bias: Option<DepthBias>
+}
+
+impl Default for GlDynamicState
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            viewport: None,
+            scissor: None,
+            blend_constants: [ 0.0, 0.0, 0.0, 0.0 ],
+            stencil_reference: 0,
+            line_width: 1.0,
+            depth_bias: None
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GlDrawState
+{
+    pub pipeline: GlRenderPipeline,
+    pub descriptor_sets: Vec<Option<GlDescriptorSet>>,
+    pub vertex_buffers: Vec<Option<(GLuint, u64)>>,
+    pub index_buffer: Option<(GLuint, u64, IndexFormat)>,
+    pub dynamic_state: GlDynamicState
+}
+
+impl GlDrawState
+{
+    // GL state outlives pipelines, so every draw sets all of it instead of keeping track of what changed
+    fn apply(&self)
+    {
+        let pipeline = &self.pipeline;
+        let dynamic = |flag: u32| pipeline.dynamic_state & flag != 0;
+
+        // draw_state makes sure dynamic viewports and scissors are set
+        let viewport = if dynamic(DynamicStateFlags::VIEWPORT) { self.dynamic_state.viewport.unwrap() } else { pipeline.viewport };
+        let scissor = match dynamic(DynamicStateFlags::SCISSOR)
+        {
+            true => self.dynamic_state.scissor.unwrap(),
+            false => Scissor { x: viewport.x as i32, y: viewport.y as i32, width: viewport.width as u32, height: viewport.height as u32 }
+        };
+        let depth_bias = if dynamic(DynamicStateFlags::DEPTH_BIAS) { self.dynamic_state.depth_bias } else { pipeline.depth_bias };
+
+        unsafe
+        {
+            gl::UseProgram(pipeline.program);
+            self.descriptor_sets.iter().flatten().for_each(GlDescriptorSet::bind);
+
+            gl::BindVertexArray(pipeline.vertex_array);
+            for (binding, stride) in pipeline.strides.iter().enumerate()
+            {
+                let (buffer, offset) = self.vertex_buffers.get(binding).copied().flatten().unwrap_or((0, 0));
+                gl::BindVertexBuffer(binding as GLuint, buffer, offset as GLintptr, *stride as GLsizei);
+            }
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.index_buffer.map_or(0, |(buffer, ..)| buffer));
+            if let Primitive::PatchList(control_points) = pipeline.primitive
+            {
+                gl::PatchParameteri(gl::PATCH_VERTICES, control_points as GLint);
+            }
+
+            // framebuffer objects keep row 0 at y 0 just like vulkan, so viewports and scissors carry over
+            gl::ViewportIndexedf(0, viewport.x, viewport.y, viewport.width, viewport.height);
+            gl::DepthRangef(viewport.min_depth, viewport.max_depth);
+            gl::Enable(gl::SCISSOR_TEST);
+            gl::Scissor(scissor.x, scissor.y, scissor.width as GLsizei, scissor.height as GLsizei);
+
+            gl::PolygonMode(gl::FRONT_AND_BACK, match pipeline.polygon_mode
+            {
+                PolygonMode::Fill => gl::FILL,
+                PolygonMode::Line => gl::LINE,
+                PolygonMode::Point => gl::POINT
+            });
+            set_enabled(gl::CULL_FACE, pipeline.cull_mode.is_some());
+            gl::CullFace(if pipeline.cull_mode == Some(CullMode::Front) { gl::FRONT } else { gl::BACK });
+            // vulkan measures winding with y pointing down and GL with y pointing up, with row 0 in the same
+            // place the same triangle winds the other way round
+            gl::FrontFace(if pipeline.front_face == FrontFace::Clockwise { gl::CCW } else { gl::CW });
+            set_enabled(gl::DEPTH_CLAMP, pipeline.depth_clamp);
+            for capability in [ gl::POLYGON_OFFSET_FILL, gl::POLYGON_OFFSET_LINE, gl::POLYGON_OFFSET_POINT ]
+            {
+                set_enabled(capability, depth_bias.is_some());
+            }
+            if let Some(depth_bias) = depth_bias
+            {
+                gl::PolygonOffset(depth_bias.slope_factor, depth_bias.constant_factor);
+            }
+            gl::LineWidth(if dynamic(DynamicStateFlags::LINE_WIDTH) { self.dynamic_state.line_width } else { 1.0 });
+
+            let depth_stencil = &pipeline.depth_stencil;
+            set_enabled(gl::DEPTH_TEST, depth_stencil.depth_test_enable);
+            gl::DepthMask(depth_stencil.depth_write_enable as GLboolean);
+            gl::DepthFunc(gl_compare_op(depth_stencil.depth_compare_op));
+            set_enabled(gl::STENCIL_TEST, depth_stencil.stencil_test_enable);
+            for (face, state) in [ (gl::FRONT, &depth_stencil.front), (gl::BACK, &depth_stencil.back) ]
+            {
+                let reference = if dynamic(DynamicStateFlags::STENCIL_REFERENCE) { self.dynamic_state.stencil_reference } else { state.reference };
+                gl::StencilFuncSeparate(face, gl_compare_op(state.compare_op), reference as GLint, state.compare_mask);
+                gl::StencilOpSeparate(face, gl_stencil_op(state.fail_op), gl_stencil_op(state.depth_fail_op), gl_stencil_op(state.pass_op));
+                gl::StencilMaskSeparate(face, state.write_mask);
+            }
+
+            for (index, target) in pipeline.color_targets.iter().enumerate()
+            {
+                let index = index as GLuint;
+                if target.blend_enable { gl::Enablei(gl::BLEND, index) } else { gl::Disablei(gl::BLEND, index) }
+                gl::BlendFuncSeparatei(index, gl_blend_factor(target.src_color_factor), gl_blend_factor(target.dst_color_factor),
+                    gl_blend_factor(target.src_alpha_factor), gl_blend_factor(target.dst_alpha_factor));
+                gl::BlendEquationSeparatei(index, gl_blend_op(target.color_op), gl_blend_op(target.alpha_op));
+                let mask = |flag: u32| (target.write_mask & flag != 0) as GLboolean;
+                gl::ColorMaski(index, mask(ColorWriteFlags::R), mask(ColorWriteFlags::G), mask(ColorWriteFlags::B), mask(ColorWriteFlags::A));
+            }
+            let blend_constants = if dynamic(DynamicStateFlags::BLEND_CONSTANTS) { self.dynamic_state.blend_constants } else { [ 0.0, 0.0, 0.0, 0.0 ] };
+            gl::BlendColor(blend_constants[0], blend_constants[1], blend_constants[2], blend_constants[3]);
+
+            set_enabled(gl::SAMPLE_ALPHA_TO_COVERAGE, pipeline.multisample.alpha_to_coverage_enable);
+            set_enabled(gl::SAMPLE_SHADING, pipeline.multisample.min_sample_shading.is_some());
+            if let Some(min_sample_shading) = pipeline.multisample.min_sample_shading
+            {
+                gl::MinSampleShading(min_sample_shading);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GlQueryPool
+{
+    pub create_info: QueryPoolCreateInfo<'static>,
+    // what each query counts, statistics queries have one GL query per enabled statistic
+    pub targets: Vec<GLenum>,
+    pub handles: Vec<GLuint>,
+    // GL has no reset, a query that wasn't written since reset_query_pool has no result yet. only touched when
+    // submitted commands execute
+    pub written: Arc<Mutex<Vec<bool>>>,
+    // names from glGenQueries only become queries once they are first begun, so they are labelled from there
+    pub label: Option<Arc<str>>
+}
+
+impl GlQueryPool
+{
+    fn label_query(&self, query: u32)
+    {
+        if !self.written.lock().unwrap()[query as usize]
+        {
+            for handle in self.query_handles(query)
+            {
+                object_label(gl::QUERY, *handle, self.label.as_deref());
+            }
+        }
+    }
+
+    fn query_handles(&self, query: u32) -> &[GLuint]
+    {
+        let start = query as usize * self.targets.len();
+        &self.handles[start..start + self.targets.len()]
+    }
+}
+
+impl AbstractQueryPool for GlQueryPool
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_info(&self) -> QueryPoolCreateInfo<'static>
+    {
+        self.create_info
+    }
+
+    // submissions have already been replayed, so waiting on a query nothing wrote to would never finish
+    fn get_results(&self, first_query: u32, query_count: u32, wait: bool) -> Result<Option<Vec<u64>>, ()>
+    {
+        let written = self.written.lock().unwrap();
+        let queries = written.get(first_query as usize..first_query as usize + query_count as usize).ok_or(())?;
+        if queries.iter().any(|written| !written)
+        {
+            return if wait { Err(()) } else { Ok(None) };
+        }
+
+        let start = first_query as usize * self.targets.len();
+        let handles = &self.handles[start..start + query_count as usize * self.targets.len()];
+        if !wait
+        {
+            let mut available = 0;
+            for handle in handles
+            {
+                unsafe { gl::GetQueryObjectuiv(*handle, gl::QUERY_RESULT_AVAILABLE, &mut available) };
+                if available == gl::FALSE as GLuint
+                {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let results = handles.iter()
+            .map(|handle|
+            {
+                let mut result = 0;
+                unsafe { gl::GetQueryObjectui64v(*handle, gl::QUERY_RESULT, &mut result) };
+                result
+            })
+            .collect();
+        check_error()?;
+
+        Ok(Some(results))
+    }
+}
+
+// the same framing as vulkan's pipeline cache blobs: a header with a checksum of the driver's vendor, renderer
+// and version strings, the payload's size and a checksum of the payload, so blobs from another driver or
+// truncated files are dropped as a whole. the payload is a list of length prefixed keys, binary formats and
+// binaries
+const PIPELINE_CACHE_MAGIC: [u8; 4] = *b"QRLG";
+const PIPELINE_CACHE_VERSION: u32 = 1;
+const PIPELINE_CACHE_HEADER_SIZE: usize = 24;
+
+fn pipeline_cache_header(driver: &str, data: &[u8]) -> Vec<u8>
+{
+    let mut header = Vec::with_capacity(PIPELINE_CACHE_HEADER_SIZE);
+    header.extend_from_slice(&PIPELINE_CACHE_MAGIC);
+    header.extend_from_slice(&PIPELINE_CACHE_VERSION.to_le_bytes());
+    header.extend_from_slice(&(!crate::png::crc32(!0, driver.as_bytes())).to_le_bytes());
+    header.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    header.extend_from_slice(&(!crate::png::crc32(!0, data)).to_le_bytes());
+    header
+}
+
+fn pipeline_cache_programs(driver: &str, blob: &[u8]) -> Option<HashMap<Vec<u8>, GlProgramBinary>>
+{
+    if blob.len() < PIPELINE_CACHE_HEADER_SIZE
+    {
+        return None;
+    }
+
+    let (header, mut data) = blob.split_at(PIPELINE_CACHE_HEADER_SIZE);
+    if header != pipeline_cache_header(driver, data).as_slice()
+    {
+        return None;
+    }
+
+    let mut programs = HashMap::new();
+    while !data.is_empty()
+    {
+        let key = take_bytes(&mut data)?;
+        let format = take_u32(&mut data)?;
+        let binary = take_bytes(&mut data)?;
+        programs.insert(key.to_vec(), GlProgramBinary { format, data: binary.to_vec() });
+    }
+
+    Some(programs)
+}
+
+fn take_u32(data: &mut &[u8]) -> Option<u32>
+{
+    if data.len() < 4
+    {
+        return None;
+    }
+
+    let (value, rest) = data.split_at(4);
+    *data = rest;
+    Some(u32::from_le_bytes(value.try_into().unwrap()))
+}
+
+fn take_bytes<'a>(data: &mut &'a [u8]) -> Option<&'a [u8]>
+{
+    let length = take_u32(data)? as usize;
+    if data.len() < length
+    {
+        return None;
+    }
+
+    let (bytes, rest) = data.split_at(length);
+    *data = rest;
+    Some(bytes)
+}
+
+#[derive(Clone)]
+pub struct GlProgramBinary
+{
+    pub format: GLenum,
+    pub data: Vec<u8>
+}
+
+#[derive(Clone)]
+pub struct GlPipelineCache
+{
+    pub driver: String,
+    pub programs: Arc<Mutex<HashMap<Vec<u8>, GlProgramBinary>>>
+}
+
+impl GlPipelineCache
+{
+    // drivers can still reject a binary that passed the header checks, it is dropped and the program gets
+    // linked from source again
+    fn load(&self, key: &[u8]) -> Option<GLuint>
+    {
+        let mut programs = self.programs.lock().unwrap();
+        let binary = programs.get(key)?;
+
+        let program = unsafe { gl::CreateProgram() };
+        let mut status = 0;
+        unsafe
+        {
+            gl::ProgramBinary(program, binary.format, binary.data.as_ptr() as _, binary.data.len() as GLsizei);
+            gl::GetProgramiv(program, gl::LINK_STATUS, &mut status);
+        }
+
+        if status == gl::FALSE as GLint
+        {
+            unsafe { gl::DeleteProgram(program) };
+            programs.remove(key);
+            let _ = check_error();
+            return None;
+        }
+
+        Some(program)
+    }
+
+    // drivers without binary formats report a length of 0, nothing is cached then
+    fn store(&self, key: Vec<u8>, program: GLuint)
+    {
+        let mut length = 0;
+        unsafe { gl::GetProgramiv(program, gl::PROGRAM_BINARY_LENGTH, &mut length) };
+        if length <= 0
+        {
+            return;
+        }
+
+        let mut data = vec![0u8; length as usize];
+        let (mut written, mut format) = (0, 0);
+        unsafe { gl::GetProgramBinary(program, length, &mut written, &mut format, data.as_mut_ptr() as _) };
+        data.truncate(written.max(0) as usize);
+
+        self.programs.lock().unwrap().insert(key, GlProgramBinary { format, data });
+    }
+}
+
+impl AbstractPipelineCache for GlPipelineCache
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn data(&self) -> Result<Vec<u8>, ()>
+    {
+        let mut data = Vec::new();
+        for (key, binary) in self.programs.lock().unwrap().iter()
+        {
+            data.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            data.extend_from_slice(key);
+            data.extend_from_slice(&binary.format.to_le_bytes());
+            data.extend_from_slice(&(binary.data.len() as u32).to_le_bytes());
+            data.extend_from_slice(&binary.data);
+        }
+
+        let mut blob = pipeline_cache_header(&self.driver, &data);
+        blob.extend_from_slice(&data);
+        Ok(blob)
+    }
+}
+
+#[derive(Clone)]
+pub struct GlCommandPool
+{
+    pub features: u32,
+    pub line_width_range: [f32; 2]
+}
+
+impl AbstractCommandPool for GlCommandPool
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_command_encoder(&self) -> Result<CommandEncoder, ()>
+    {
+        Ok(CommandEncoder { internal: Box::new(GlCommandEncoder
+        {
+            commands: Vec::new(),
+            compute_pipeline: None,
+            render_pipeline: None,
+            descriptor_sets: Vec::new(),
+            vertex_buffers: Vec::new(),
+            index_buffer: None,
+            framebuffer: None,
+            dynamic_state: GlDynamicState::default()
+        }), tracker: Default::default(), features: self.features, line_width_range: self.line_width_range })
+    }
+
+    fn reset(&self) -> Result<(), ()>
+    {
+        Ok(())
+    }
+}
+
+// commands are recorded on the cpu and replayed on the context by Queue::submit, since GL has no command
+// buffers of its own
+#[derive(Clone)]
+pub enum GlCommand
+{
+    Dispatch { program: GLuint, descriptor_sets: Vec<Option<GlDescriptorSet>>, group_count: [u32; 3] },
+    DispatchIndirect { program: GLuint, descriptor_sets: Vec<Option<GlDescriptorSet>>, buffer: GLuint, offset: u64 },
+    BeginRenderPass(GlFramebuffer),
+    EndRenderPass(GlFramebuffer),
+    Draw { state: GlDrawState, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32 },
+    DrawIndexed { state: GlDrawState, index_count: u32, instance_count: u32, first_index: u32, vertex_offset: i32, first_instance: u32 },
+    DrawIndirect { state: GlDrawState, indexed: bool, buffer: GLuint, offset: u64, draw_count: u32, stride: u32 },
+    ResolveImage { src: GlImage, dst: GlImage },
+    MemoryBarrier(GLbitfield),
+    ResetQueries { query_pool: GlQueryPool, first_query: u32, query_count: u32 },
+    WriteTimestamp { query_pool: GlQueryPool, query: u32 },
+    BeginQuery { query_pool: GlQueryPool, query: u32 },
+    EndQuery { query_pool: GlQueryPool, query: u32 },
+    PushDebugGroup(String),
+    PopDebugGroup,
+    InsertDebugMarker(String)
+}
+
+impl GlCommand
+{
+    fn execute(&self)
+    {
+        match self
+        {
+            GlCommand::Dispatch { program, descriptor_sets, group_count } => unsafe
+            {
+                gl::UseProgram(*program);
+                descriptor_sets.iter().flatten().for_each(GlDescriptorSet::bind);
+                gl::DispatchCompute(group_count[0], group_count[1], group_count[2]);
+            },
+            GlCommand::DispatchIndirect { program, descriptor_sets, buffer, offset } => unsafe
+            {
+                gl::UseProgram(*program);
+                descriptor_sets.iter().flatten().for_each(GlDescriptorSet::bind);
+                gl::BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, *buffer);
+                gl::DispatchComputeIndirect(*offset as GLintptr);
+                gl::BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, 0);
+            },
+            GlCommand::BeginRenderPass(framebuffer) => framebuffer.begin(),
+            GlCommand::EndRenderPass(framebuffer) => framebuffer.end(),
+            GlCommand::Draw { state, vertex_count, instance_count, first_vertex, first_instance } =>
+            {
+                state.apply();
+                unsafe
+                {
+                    gl::DrawArraysInstancedBaseInstance(gl_primitive(state.pipeline.primitive), *first_vertex as GLint, *vertex_count as GLsizei,
+                        *instance_count as GLsizei, *first_instance);
+                }
+            },
+            GlCommand::DrawIndexed { state, index_count, instance_count, first_index, vertex_offset, first_instance } =>
+            {
+                // draw_state makes sure indexed draws have one
+                let Some((_, offset, format)) = state.index_buffer else { return };
+                let (index_type, index_size) = gl_index_format(format);
+
+                state.apply();
+                unsafe
+                {
+                    gl::DrawElementsInstancedBaseVertexBaseInstance(gl_primitive(state.pipeline.primitive), *index_count as GLsizei, index_type,
+                        (offset + *first_index as u64 * index_size) as usize as *const _, *instance_count as GLsizei, *vertex_offset, *first_instance);
+                }
+            },
+            GlCommand::DrawIndirect { state, indexed, buffer, offset, draw_count, stride } =>
+            {
+                let mode = gl_primitive(state.pipeline.primitive);
+                state.apply();
+                unsafe
+                {
+                    gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, *buffer);
+                    match state.index_buffer.filter(|_| *indexed)
+                    {
+                        Some((_, _, format)) => gl::MultiDrawElementsIndirect(mode, gl_index_format(format).0, *offset as usize as *const _,
+                            *draw_count as GLsizei, *stride as GLsizei),
+                        None => gl::MultiDrawArraysIndirect(mode, *offset as usize as *const _, *draw_count as GLsizei, *stride as GLsizei)
+                    }
+                    gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);
+                }
+            },
+            GlCommand::ResolveImage { src, dst } => resolve_image(src, dst),
+            GlCommand::MemoryBarrier(barriers) => unsafe { gl::MemoryBarrier(*barriers) },
+            GlCommand::ResetQueries { query_pool, first_query, query_count } =>
+            {
+                let mut written = query_pool.written.lock().unwrap();
+                let end = (*first_query as usize + *query_count as usize).min(written.len());
+                written[(*first_query as usize).min(end)..end].fill(false);
+            },
+            GlCommand::WriteTimestamp { query_pool, query } =>
+            {
+                unsafe { gl::QueryCounter(query_pool.query_handles(*query)[0], gl::TIMESTAMP) };
+                query_pool.label_query(*query);
+                query_pool.written.lock().unwrap()[*query as usize] = true;
+            },
+            GlCommand::BeginQuery { query_pool, query } =>
+            {
+                for (target, handle) in query_pool.targets.iter().zip(query_pool.query_handles(*query))
+                {
+                    unsafe { gl::BeginQuery(*target, *handle) };
+                }
+                query_pool.label_query(*query);
+            },
+            GlCommand::EndQuery { query_pool, query } =>
+            {
+                for target in query_pool.targets.iter()
+                {
+                    unsafe { gl::EndQuery(*target) };
+                }
+                query_pool.written.lock().unwrap()[*query as usize] = true;
+            },
+            GlCommand::PushDebugGroup(label) =>
+            {
+                let (length, label) = gl_label(label, gl::MAX_DEBUG_MESSAGE_LENGTH);
+                unsafe { gl::PushDebugGroup(gl::DEBUG_SOURCE_APPLICATION, 0, length, label) };
+            },
+            GlCommand::PopDebugGroup => unsafe { gl::PopDebugGroup() },
+            GlCommand::InsertDebugMarker(label) =>
+            {
+                let (length, label) = gl_label(label, gl::MAX_DEBUG_MESSAGE_LENGTH);
+                unsafe { gl::DebugMessageInsert(gl::DEBUG_SOURCE_APPLICATION, gl::DEBUG_TYPE_MARKER, 0, gl::DEBUG_SEVERITY_NOTIFICATION, length, label) };
+            }
+        }
+    }
+}
+
+// GL orders everything but incoherent writes from shaders on its own, so only barriers after shader
+// writes need glMemoryBarrier, with the bits of whatever reads or writes afterwards
+fn gl_barriers(src_access_mask: u32, dst_access_mask: u32) -> GLbitfield
+{
+    if src_access_mask & (AccessFlags::SHADER_WRITE | AccessFlags::MEMORY_WRITE) == 0
+    {
+        return 0;
+    }
+
+    let barriers = [
+        (AccessFlags::INDIRECT_COMMAND_READ, gl::COMMAND_BARRIER_BIT),
+        (AccessFlags::INDEX_READ, gl::ELEMENT_ARRAY_BARRIER_BIT),
+        (AccessFlags::VERTEX_ATTRIBUTE_READ, gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT),
+        (AccessFlags::UNIFORM_READ, gl::UNIFORM_BARRIER_BIT),
+        (AccessFlags::SHADER_READ | AccessFlags::SHADER_WRITE | AccessFlags::INPUT_ATTACHMENT_READ,
+            gl::SHADER_STORAGE_BARRIER_BIT | gl::SHADER_IMAGE_ACCESS_BARRIER_BIT | gl::TEXTURE_FETCH_BARRIER_BIT),
+        (AccessFlags::COLOR_ATTACHMENT_READ | AccessFlags::COLOR_ATTACHMENT_WRITE | AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ |
+            AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE, gl::FRAMEBUFFER_BARRIER_BIT),
+        (AccessFlags::TRANSFER_READ | AccessFlags::TRANSFER_WRITE | AccessFlags::HOST_READ | AccessFlags::HOST_WRITE,
+            gl::BUFFER_UPDATE_BARRIER_BIT | gl::TEXTURE_UPDATE_BARRIER_BIT | gl::PIXEL_BUFFER_BARRIER_BIT),
+        (AccessFlags::MEMORY_READ | AccessFlags::MEMORY_WRITE, gl::ALL_BARRIER_BITS)
+    ];
+
+    barriers.into_iter()
+        .filter(|(access, _)| dst_access_mask & access != 0)
+        .fold(0, |bits, (_, barrier)| bits | barrier)
+}
+
+// blits every layer of the first mip level, as raw texels like present
+fn resolve_image(src: &GlImage, dst: &GlImage)
+{
+    let attach = |target: GLenum, image: &GlImage, layer: u32| unsafe
+    {
+        if image.create_info.array_layers > 1
+        {
+            gl::FramebufferTextureLayer(target, gl::COLOR_ATTACHMENT0, image.handle, 0, layer as GLint);
+        }
+        else
+        {
+            gl::FramebufferTexture(target, gl::COLOR_ATTACHMENT0, image.handle, 0);
+        }
+    };
+
+    let width = src.create_info.width.min(dst.create_info.width) as GLint;
+    let height = src.create_info.height.min(dst.create_info.height) as GLint;
+    let mut framebuffers = [ 0; 2 ];
+    unsafe
+    {
+        gl::Disable(gl::FRAMEBUFFER_SRGB);
+        gl::Disable(gl::SCISSOR_TEST);
+        gl::GenFramebuffers(2, framebuffers.as_mut_ptr());
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffers[0]);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, framebuffers[1]);
+        for layer in 0..src.create_info.array_layers.min(dst.create_info.array_layers)
+        {
+            attach(gl::READ_FRAMEBUFFER, src, layer);
+            attach(gl::DRAW_FRAMEBUFFER, dst, layer);
+            gl::BlitFramebuffer(0, 0, width, height, 0, 0, width, height, gl::COLOR_BUFFER_BIT, gl::NEAREST);
+        }
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        gl::DeleteFramebuffers(2, framebuffers.as_ptr());
+    }
+}
+
+pub struct GlCommandEncoder
+{
+    pub commands: Vec<GlCommand>,
+    pub compute_pipeline: Option<GlComputePipeline>,
+    pub render_pipeline: Option<GlRenderPipeline>,
+    pub descriptor_sets: Vec<Option<GlDescriptorSet>>,
+    pub vertex_buffers: Vec<Option<(GLuint, u64)>>,
+    pub index_buffer: Option<(GLuint, u64, IndexFormat)>,
+    pub framebuffer: Option<GlFramebuffer>,
+    pub dynamic_state: GlDynamicState
+}
+
+impl GlCommandEncoder
+{
+    // draws without a bound pipeline, outside of a render pass, without the dynamic viewport or scissor the
+    // pipeline needs or indexed draws without an index buffer are reported and dropped
+    fn draw_state(&self, indexed: bool) -> Option<GlDrawState>
+    {
+        let Some(pipeline) = self.render_pipeline.clone() else
+        {
+            state::validation_error("a render pipeline must be bound before drawing");
+            return None;
+        };
+        if self.framebuffer.is_none()
+        {
+            state::validation_error("draws must be recorded inside a render pass");
+            return None;
+        }
+        if indexed && self.index_buffer.is_none()
+        {
+            state::validation_error("an index buffer must be bound before indexed draws");
+            return None;
+        }
+        if pipeline.dynamic_state & DynamicStateFlags::VIEWPORT != 0 && self.dynamic_state.viewport.is_none()
+        {
+            state::validation_error("the pipeline has a dynamic viewport but set_viewport was never called");
+            return None;
+        }
+        if pipeline.dynamic_state & DynamicStateFlags::SCISSOR != 0 && self.dynamic_state.scissor.is_none()
+        {
+            state::validation_error("the pipeline has a dynamic scissor but set_scissor was never called");
+            return None;
+        }
+
+        Some(GlDrawState
+        {
+            pipeline,
+            descriptor_sets: self.descriptor_sets.clone(),
+            vertex_buffers: self.vertex_buffers.clone(),
+            index_buffer: self.index_buffer,
+            dynamic_state: self.dynamic_state.clone()
+        })
+    }
+
+    // the commands' first_index counts from the start of the index buffer, GL has no way to move that by
+    // the offset the index buffer was bound with
+    fn record_draw_indirect(&mut self, indexed: bool, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32)
+    {
+        let Some(state) = self.draw_state(indexed) else { return };
+        if indexed && state.index_buffer.is_some_and(|(_, offset, _)| offset != 0)
+        {
+            state::validation_error("indexed indirect draws need an index buffer bound at offset 0 on opengl");
+            return;
+        }
+
+        self.commands.push(GlCommand::DrawIndirect { state, indexed, buffer: buffer.downcast_ref::<GlBuffer>().unwrap().handle, offset, draw_count, stride });
+    }
+
+    fn barrier(&mut self, src_access_mask: u32, dst_access_mask: u32)
+    {
+        let barriers = gl_barriers(src_access_mask, dst_access_mask);
+        if barriers != 0
+        {
+            self.commands.push(GlCommand::MemoryBarrier(barriers));
+        }
+    }
+}
+
+impl AbstractCommandEncoder for GlCommandEncoder
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn bind_compute_pipeline(&mut self, pipeline: &ComputePipeline)
+    {
+        self.compute_pipeline = Some(pipeline.downcast_ref::<GlComputePipeline>().unwrap().clone());
+    }
+
+    fn bind_descriptor_set(&mut self, index: u32, descriptor_set: &DescriptorSet)
+    {
+        let index = index as usize;
+        if self.descriptor_sets.len() <= index
+        {
+            self.descriptor_sets.resize(index + 1, None);
+        }
+
+        self.descriptor_sets[index] = Some(descriptor_set.downcast_ref::<GlDescriptorSet>().unwrap().clone());
+    }
+
+    fn dispatch(&mut self, x: u32, y: u32, z: u32)
+    {
+        let Some(pipeline) = self.compute_pipeline.as_ref() else
+        {
+            state::validation_error("a compute pipeline must be bound before dispatch");
+            return;
+        };
+
+        self.commands.push(GlCommand::Dispatch
+        {
+            program: pipeline.program,
+            descriptor_sets: self.descriptor_sets.clone(),
+            group_count: [ x, y, z ]
+        });
+    }
+
+    fn dispatch_indirect(&mut self, buffer: &Buffer, offset: u64)
+    {
+        let Some(pipeline) = self.compute_pipeline.as_ref() else
+        {
+            state::validation_error("a compute pipeline must be bound before dispatch");
+            return;
+        };
+
+        self.commands.push(GlCommand::DispatchIndirect
+        {
+            program: pipeline.program,
+            descriptor_sets: self.descriptor_sets.clone(),
+            buffer: buffer.downcast_ref::<GlBuffer>().unwrap().handle,
+            offset
+        });
+    }
+
+    fn begin_render_pass(&mut self, _render_pass: &RenderPass, framebuffer: &Framebuffer)
+    {
+        let framebuffer = framebuffer.downcast_ref::<GlFramebuffer>().unwrap().clone();
+
+        self.framebuffer = Some(framebuffer.clone());
+        self.commands.push(GlCommand::BeginRenderPass(framebuffer));
+    }
+
+    fn end_render_pass(&mut self)
+    {
+        if let Some(framebuffer) = self.framebuffer.take()
+        {
+            self.commands.push(GlCommand::EndRenderPass(framebuffer));
+        }
+    }
+
+    fn begin_rendering(&mut self, rendering_info: &RenderingInfo)
+    {
+        let attachment = |attachment: &RenderingAttachment| GlAttachment
+        {
+            view: attachment.view.downcast_ref::<GlImageView>().unwrap().clone(),
+            operations: attachment.operations,
+            stencil_operations: rendering_info.stencil_operations
+        };
+
+        let framebuffer = GlFramebuffer
+        {
+            handle: 0,
+            color_attachments: rendering_info.color_attachments.iter().map(attachment).collect(),
+            resolve_attachments: Vec::new(),
+            depth_stencil_attachment: rendering_info.depth_stencil_attachment.as_ref().map(attachment),
+            views: rendering_info.color_attachments.iter().chain(rendering_info.depth_stencil_attachment.iter()).map(|attachment| attachment.view.clone()).collect(),
+            extent: rendering_info.extent()
+        };
+
+        self.framebuffer = Some(framebuffer.clone());
+        self.commands.push(GlCommand::BeginRenderPass(framebuffer));
+    }
+
+    fn end_rendering(&mut self)
+    {
+        self.end_render_pass();
+    }
+
+    fn bind_render_pipeline(&mut self, pipeline: &RenderPipeline)
+    {
+        self.render_pipeline = Some(pipeline.downcast_ref::<GlRenderPipeline>().unwrap().clone());
+    }
+
+    fn bind_index_buffer(&mut self, buffer: &Buffer, offset: u64, format: IndexFormat)
+    {
+        self.index_buffer = Some((buffer.downcast_ref::<GlBuffer>().unwrap().handle, offset, format));
+    }
+
+    fn bind_vertex_buffer(&mut self, binding: u32, buffer: &Buffer, offset: u64)
+    {
+        let binding = binding as usize;
+        if self.vertex_buffers.len() <= binding
+        {
+            self.vertex_buffers.resize(binding + 1, None);
+        }
+
+        self.vertex_buffers[binding] = Some((buffer.downcast_ref::<GlBuffer>().unwrap().handle, offset));
+    }
+
+    fn set_viewport(&mut self, viewport: &Viewport)
+    {
+        self.dynamic_state.viewport = Some(*viewport);
+    }
+
+    fn set_scissor(&mut self, scissor: &Scissor)
+    {
+        self.dynamic_state.scissor = Some(*scissor);
+    }
+
+    fn set_blend_constants(&mut self, blend_constants: [f32; 4])
+    {
+        self.dynamic_state.blend_constants = blend_constants;
+    }
+
+    fn set_stencil_reference(&mut self, reference: u32)
+    {
+        self.dynamic_state.stencil_reference = reference;
+    }
+
+    fn set_line_width(&mut self, line_width: f32)
+    {
+        self.dynamic_state.line_width = line_width;
+    }
+
+    fn set_depth_bias(&mut self, depth_bias: &DepthBias)
+    {
+        self.dynamic_state.depth_bias = Some(*depth_bias);
+    }
+
+    fn draw(&mut self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32)
+    {
+        let Some(state) = self.draw_state(false) else { return };
+        self.commands.push(GlCommand::Draw { state, vertex_count, instance_count, first_vertex, first_instance });
+    }
+
+    fn draw_indexed(&mut self, index_count: u32, instance_count: u32, first_index: u32, vertex_offset: i32, first_instance: u32)
+    {
+        let Some(state) = self.draw_state(true) else { return };
+        self.commands.push(GlCommand::DrawIndexed { state, index_count, instance_count, first_index, vertex_offset, first_instance });
+    }
+
+    fn draw_indirect(&mut self, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32)
+    {
+        self.record_draw_indirect(false, buffer, offset, draw_count, stride)
+    }
+
+    fn draw_indexed_indirect(&mut self, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32)
+    {
+        self.record_draw_indirect(true, buffer, offset, draw_count, stride)
+    }
+
+    // never reached, the device has no DRAW_INDIRECT_COUNT before GL 4.6
+    fn draw_indirect_count(&mut self, _buffer: &Buffer, _offset: u64, _count_buffer: &Buffer, _count_offset: u64, _max_draw_count: u32, _stride: u32) {}
+
+    fn draw_indexed_indirect_count(&mut self, _buffer: &Buffer, _offset: u64, _count_buffer: &Buffer, _count_offset: u64, _max_draw_count: u32, _stride: u32) {}
+
+    fn resolve_image(&mut self, src: &Image, _src_layout: ImageLayout, dst: &Image, _dst_layout: ImageLayout)
+    {
+        self.commands.push(GlCommand::ResolveImage
+        {
+            src: src.downcast_ref::<GlImage>().unwrap().clone(),
+            dst: dst.downcast_ref::<GlImage>().unwrap().clone()
+        });
+    }
+
+    fn memory_barrier(&mut self, barrier: &MemoryBarrier)
+    {
+        self.barrier(barrier.src_access_mask, barrier.dst_access_mask);
+    }
+
+    // there is only one queue family, so queue transfers have nothing to do either
+    fn buffer_barrier(&mut self, barrier: &BufferBarrier)
+    {
+        self.barrier(barrier.src_access_mask, barrier.dst_access_mask);
+    }
+
+    fn image_barrier(&mut self, barrier: &ImageBarrier)
+    {
+        self.barrier(barrier.src_access_mask, barrier.dst_access_mask);
+    }
+
+    fn reset_query_pool(&mut self, query_pool: &QueryPool, first_query: u32, query_count: u32)
+    {
+        self.commands.push(GlCommand::ResetQueries { query_pool: query_pool.downcast_ref::<GlQueryPool>().unwrap().clone(), first_query, query_count });
+    }
+
+    // glQueryCounter is taken once all earlier commands are done, which covers every stage
+    fn write_timestamp(&mut self, query_pool: &QueryPool, query: u32, _stage: u32)
+    {
+        let query_pool = query_pool.downcast_ref::<GlQueryPool>().unwrap();
+        if query_pool.create_info.query_type != QueryType::Timestamp || query >= query_pool.create_info.count
+        {
+            state::validation_error("write_timestamp needs a query inside a timestamp query pool");
+            return;
+        }
+
+        self.commands.push(GlCommand::WriteTimestamp { query_pool: query_pool.clone(), query });
+    }
+
+    fn begin_query(&mut self, query_pool: &QueryPool, query: u32)
+    {
+        let query_pool = query_pool.downcast_ref::<GlQueryPool>().unwrap();
+        if query_pool.create_info.query_type == QueryType::Timestamp || query >= query_pool.create_info.count
+        {
+            state::validation_error("begin_query needs a query inside an occlusion or pipeline statistics query pool");
+            return;
+        }
+
+        self.commands.push(GlCommand::BeginQuery { query_pool: query_pool.clone(), query });
+    }
+
+    fn end_query(&mut self, query_pool: &QueryPool, query: u32)
+    {
+        let query_pool = query_pool.downcast_ref::<GlQueryPool>().unwrap();
+        if query_pool.create_info.query_type == QueryType::Timestamp || query >= query_pool.create_info.count
+        {
+            state::validation_error("end_query needs a query inside an occlusion or pipeline statistics query pool");
+            return;
+        }
+
+        self.commands.push(GlCommand::EndQuery { query_pool: query_pool.clone(), query });
+    }
+
+    fn push_debug_group(&mut self, label: &str)
+    {
+        self.commands.push(GlCommand::PushDebugGroup(label.to_string()));
+    }
+
+    fn pop_debug_group(&mut self)
+    {
+        self.commands.push(GlCommand::PopDebugGroup);
+    }
+
+    fn insert_debug_marker(&mut self, label: &str)
+    {
+        self.commands.push(GlCommand::InsertDebugMarker(label.to_string()));
+    }
+
+    fn finish(self: Box<Self>) -> Result<CommandBuffer, ()>
+    {
+        Ok(CommandBuffer { internal: Arc::new(GlCommandBuffer { commands: Arc::new(self.commands) }), layouts: Arc::default() })
+    }
+}
+
+#[derive(Clone)]
+pub struct GlCommandBuffer
+{
+    pub commands: Arc<Vec<GlCommand>>
+}
+
+impl AbstractCommandBuffer for GlCommandBuffer
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+pub struct GlSync(GLsync);
+
+// see GlContext, syncs are only ever touched on the context's thread
+unsafe impl Send for GlSync {}
+
+pub enum GlFenceState
+{
+    Unsignaled,
+    Signaled,
+    Pending(GlSync)
+}
+
+#[derive(Clone)]
+pub struct GlFence
+{
+    pub state: Arc<Mutex<GlFenceState>>
+}
+
+impl GlFence
+{
+    // signals once everything submitted to the context so far has finished
+    fn signal_after_submitted(&self)
+    {
+        let sync = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+        if let GlFenceState::Pending(old) = std::mem::replace(&mut *self.state.lock().unwrap(), GlFenceState::Pending(GlSync(sync)))
+        {
+            unsafe { gl::DeleteSync(old.0) };
+        }
+    }
+}
+
+impl AbstractFence for GlFence
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn wait(&self, timeout_ns: u64) -> Result<bool, ()>
+    {
+        let mut state = self.state.lock().unwrap();
+        let GlFenceState::Pending(sync) = &*state else { return Ok(matches!(*state, GlFenceState::Signaled)) };
+
+        match unsafe { gl::ClientWaitSync(sync.0, gl::SYNC_FLUSH_COMMANDS_BIT, timeout_ns) }
+        {
+            gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED =>
+            {
+                unsafe { gl::DeleteSync(sync.0) };
+                *state = GlFenceState::Signaled;
+                Ok(true)
+            },
+            gl::TIMEOUT_EXPIRED => Ok(false),
+            _ => Err(())
+        }
+    }
+
+    fn reset(&self) -> Result<(), ()>
+    {
+        if let GlFenceState::Pending(sync) = std::mem::replace(&mut *self.state.lock().unwrap(), GlFenceState::Unsignaled)
+        {
+            unsafe { gl::DeleteSync(sync.0) };
+        }
+
+        Ok(())
+    }
+
+    fn is_signaled(&self) -> Result<bool, ()>
+    {
+        self.wait(0)
+    }
+}
+
+#[derive(Clone)]
+pub struct GlSemaphore
+{
+
+}
+
+impl AbstractSemaphore for GlSemaphore
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn pipeline_cache_blobs_only_load_into_the_driver_that_wrote_them()
+    {
+        let programs = HashMap::from([ (b"compute".to_vec(), GlProgramBinary { format: 7, data: vec![ 1, 2, 3 ] }) ]);
+        let cache = GlPipelineCache { driver: "vendor\nrenderer\n4.3".to_string(), programs: Arc::new(Mutex::new(programs)) };
+        let blob = cache.data().unwrap();
+
+        let loaded = pipeline_cache_programs(&cache.driver, &blob).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(matches!(loaded.get(b"compute".as_slice()), Some(binary) if binary.format == 7 && binary.data == [ 1, 2, 3 ]));
+
+        assert!(pipeline_cache_programs("vendor\nrenderer\n4.6", &blob).is_none());
+        assert!(pipeline_cache_programs(&cache.driver, &blob[..blob.len() - 1]).is_none());
+    }
+}
@@ -0,0 +1,143 @@
+// minimal PNG writer for captures. pixel data goes into uncompressed deflate blocks, which keeps
+// the encoder tiny and the output exact at the cost of file size
+
+const SIGNATURE: [u8; 8] = [ 0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A ];
+const MAX_STORED_BLOCK: usize = 65535;
+
+pub fn encode_rgba8(width: u32, height: u32, pixels: &[u8]) -> Vec<u8>
+{
+    let row_size = width as usize * 4;
+
+    // every scanline starts with its filter type, 0 meaning unfiltered
+    let mut scanlines = Vec::with_capacity((row_size + 1) * height as usize);
+    for row in pixels.chunks_exact(row_size.max(1)).take(height as usize)
+    {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[ 8, 6, 0, 0, 0 ]);
+
+    let mut png = Vec::new();
+    png.extend_from_slice(&SIGNATURE);
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8])
+{
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+
+    let crc = crc32(crc32(!0, kind), data);
+    png.extend_from_slice(&(!crc).to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8>
+{
+    let mut stream = Vec::with_capacity(data.len() + data.len() / MAX_STORED_BLOCK * 5 + 11);
+    stream.extend_from_slice(&[ 0x78, 0x01 ]);
+
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none()
+    {
+        stream.extend_from_slice(&[ 1, 0, 0, 0xFF, 0xFF ]);
+    }
+
+    while let Some(block) = blocks.next()
+    {
+        let length = block.len() as u16;
+        stream.push(if blocks.peek().is_none() { 1 } else { 0 });
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+pub(crate) fn crc32(mut crc: u32, data: &[u8]) -> u32
+{
+    for byte in data
+    {
+        crc ^= *byte as u32;
+        for _ in 0..8
+        {
+            crc = if crc & 1 != 0 { 0xEDB88320 ^ (crc >> 1) } else { crc >> 1 };
+        }
+    }
+
+    crc
+}
+
+fn adler32(data: &[u8]) -> u32
+{
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552)
+    {
+        for byte in chunk
+        {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn encodes_a_known_image()
+    {
+        let pixels = [ 255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 128, 255, 255, 255, 0 ];
+        let expected =
+        [
+            0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x02,
+            0x00, 0x00, 0x00, 0x02, 0x08, 0x06, 0x00, 0x00, 0x00, 0x72, 0xB6, 0x0D, 0x24, 0x00, 0x00, 0x00, 0x1D, 0x49, 0x44, 0x41,
+            0x54, 0x78, 0x01, 0x01, 0x12, 0x00, 0xED, 0xFF, 0x00, 0xFF, 0x00, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0x00, 0x00,
+            0xFF, 0x80, 0xFF, 0xFF, 0xFF, 0x00, 0x46, 0x4E, 0x08, 0x79, 0xE6, 0x15, 0x75, 0x91, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45,
+            0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82
+        ];
+
+        assert_eq!(encode_rgba8(2, 2, &pixels), expected);
+    }
+
+    #[test]
+    fn splits_large_images_into_stored_blocks()
+    {
+        let pixels: Vec<u8> = (0..300 * 200 * 4).map(|index| (index * 7 % 251) as u8).collect();
+        let png = encode_rgba8(300, 200, &pixels);
+
+        let idat_length = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        let stream = &png[41..41 + idat_length];
+        let mut offset = 2;
+        let mut scanlines = Vec::new();
+        loop
+        {
+            let last = stream[offset] == 1;
+            let length = u16::from_le_bytes([ stream[offset + 1], stream[offset + 2] ]) as usize;
+            assert_eq!(!length as u16, u16::from_le_bytes([ stream[offset + 3], stream[offset + 4] ]));
+            scanlines.extend_from_slice(&stream[offset + 5..offset + 5 + length]);
+            offset += 5 + length;
+            if last { break; }
+        }
+
+        assert_eq!(scanlines.len(), 200 * (300 * 4 + 1));
+        assert!(scanlines.chunks(300 * 4 + 1).zip(pixels.chunks(300 * 4)).all(|(scanline, row)| scanline[0] == 0 && &scanline[1..] == row));
+        assert_eq!(&stream[offset..], adler32(&scanlines).to_be_bytes());
+        assert_eq!(&png[png.len() - 12..], [ 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82 ]);
+    }
+}
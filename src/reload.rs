@@ -0,0 +1,251 @@
+use super::*;
+use std::{path::{Path, PathBuf}, sync::{atomic::{AtomicU64, Ordering}, Mutex}, time::SystemTime};
+
+impl ShaderModuleSource
+{
+    // picks the source kind from the extension. .spv files are SPIR-V, .wgsl files are WGSL and the usual
+    // glslang stage extensions and .glsl are GLSL. the vulkan backend compiles GLSL and WGSL when the module is created,
+    // so a watched source file is recompiled on every change
+    pub fn from_file(path: &Path) -> Result<Self, ShaderModuleError>
+    {
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("");
+        match extension
+        {
+            "spv" =>
+            {
+                let bytes = std::fs::read(path).map_err(|err| ShaderModuleError::Io(format!("{}: {}", path.display(), err)))?;
+                if bytes.len() % 4 != 0
+                {
+                    return Err(ShaderModuleError::CompilationFailed(format!("{}: size is not a multiple of 4", path.display())));
+                }
+
+                let code: Vec<u32> = bytes.chunks_exact(4).map(|word| u32::from_le_bytes([ word[0], word[1], word[2], word[3] ])).collect();
+                if code.first() != Some(&SPIRV_MAGIC)
+                {
+                    return Err(ShaderModuleError::CompilationFailed(format!("{}: not a SPIR-V module", path.display())));
+                }
+
+                Ok(ShaderModuleSource::Spirv(code))
+            },
+            "glsl" | "vert" | "frag" | "geom" | "comp" | "tesc" | "tese" =>
+            {
+                let source = std::fs::read_to_string(path).map_err(|err| ShaderModuleError::Io(format!("{}: {}", path.display(), err)))?;
+                Ok(ShaderModuleSource::Glsl(source))
+            },
+            "wgsl" =>
+            {
+                let source = std::fs::read_to_string(path).map_err(|err| ShaderModuleError::Io(format!("{}: {}", path.display(), err)))?;
+                Ok(ShaderModuleSource::Wgsl(source))
+            },
+            _ => Err(ShaderModuleError::UnsupportedSource(format!("{}: unknown shader extension", path.display())))
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime>
+{
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// a shader module backed by a file. clones share the module, so every holder sees a reload
+#[derive(Clone)]
+pub struct WatchedShader
+{
+    module: Arc<Mutex<ShaderModule>>,
+    generation: Arc<AtomicU64>
+}
+
+impl WatchedShader
+{
+    // the last version that compiled. the next ShaderWatcher::poll that reloads the file destroys it, so create
+    // pipelines from it before then
+    pub fn module(&self) -> ShaderModule
+    {
+        self.module.lock().unwrap().clone()
+    }
+
+    // bumped every time a new version of the file replaces the module
+    pub fn generation(&self) -> u64
+    {
+        self.generation.load(Ordering::Acquire)
+    }
+}
+
+struct WatchedFile
+{
+    path: PathBuf,
+    stage: ShaderStage,
+    modified: Option<SystemTime>,
+    shader: WatchedShader
+}
+
+// polls the modification time of every loaded shader file, there is no file system notification
+// behind it. call poll once per frame, before updating the pipelines using the shaders
+#[derive(Default)]
+pub struct ShaderWatcher
+{
+    files: Vec<WatchedFile>
+}
+
+impl ShaderWatcher
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn load<P>(&mut self, device: &Device, stage: ShaderStage, path: P) -> Result<WatchedShader, ShaderModuleError> where P: AsRef<Path>
+    {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified_time(&path);
+        let module = device.create_shader_module(&ShaderModuleCreateInfo { stage, source: ShaderModuleSource::from_file(&path)?, label: path.to_str() })?;
+
+        let shader = WatchedShader { module: Arc::new(Mutex::new(module)), generation: Arc::new(AtomicU64::new(0)) };
+        self.files.push(WatchedFile { path, stage, modified, shader: shader.clone() });
+        Ok(shader)
+    }
+
+    // recompiles every file that changed since the last poll. a file that fails to compile keeps its
+    // last good module, its diagnostics are returned and it is retried once the file changes again
+    pub fn poll(&mut self, device: &Device) -> Vec<(PathBuf, ShaderModuleError)>
+    {
+        let mut errors = Vec::new();
+
+        for file in self.files.iter_mut()
+        {
+            let modified = modified_time(&file.path);
+            if modified.is_none() || modified == file.modified
+            {
+                continue;
+            }
+            file.modified = modified;
+
+            let module = ShaderModuleSource::from_file(&file.path)
+                .and_then(|source| device.create_shader_module(&ShaderModuleCreateInfo { stage: file.stage, source, label: file.path.to_str() }));
+            match module
+            {
+                Ok(module) =>
+                {
+                    // pipelines built from the old module don't need it anymore
+                    let old = std::mem::replace(&mut *file.shader.module.lock().unwrap(), module);
+                    device.destroy_shader_module(&old);
+                    file.shader.generation.fetch_add(1, Ordering::AcqRel);
+                },
+                Err(err) => errors.push((file.path.clone(), err))
+            }
+        }
+
+        errors
+    }
+}
+
+type PipelineBuilder<T> = Box<dyn Fn(&[ShaderModule]) -> Result<T, PipelineError> + Send>;
+
+// the pipelines a WatchedPipeline can rebuild
+pub trait WatchablePipeline
+{
+    fn destroy(&self, device: &Device);
+}
+
+impl WatchablePipeline for ComputePipeline
+{
+    fn destroy(&self, device: &Device)
+    {
+        device.destroy_compute_pipeline(self);
+    }
+}
+
+impl WatchablePipeline for RenderPipeline
+{
+    fn destroy(&self, device: &Device)
+    {
+        device.destroy_render_pipeline(self);
+    }
+}
+
+// a pipeline built from watched shaders. the builder gets the current modules in the order the shaders
+// were given and creates a ComputePipeline or RenderPipeline from them
+pub struct WatchedPipeline<T>
+{
+    shaders: Vec<WatchedShader>,
+    generations: Vec<u64>,
+    builder: PipelineBuilder<T>,
+    pipeline: T
+}
+
+impl<T> WatchedPipeline<T> where T: WatchablePipeline
+{
+    pub fn new<F>(shaders: &[&WatchedShader], builder: F) -> Result<Self, PipelineError> where F: Fn(&[ShaderModule]) -> Result<T, PipelineError> + Send + 'static
+    {
+        let shaders: Vec<WatchedShader> = shaders.iter().map(|shader| (*shader).clone()).collect();
+        let generations = shaders.iter().map(|shader| shader.generation()).collect();
+        let modules: Vec<ShaderModule> = shaders.iter().map(|shader| shader.module()).collect();
+        let pipeline = builder(&modules)?;
+
+        Ok(Self { shaders, generations, builder: Box::new(builder), pipeline })
+    }
+
+    pub fn pipeline(&self) -> &T
+    {
+        &self.pipeline
+    }
+
+    // rebuilds the pipeline if any of its shaders got reloaded and reports whether it did. the old
+    // pipeline stays in use when the rebuild fails. a successful rebuild waits for the device to go idle before
+    // destroying the old pipeline, reloads are rare enough for the stall not to matter
+    pub fn update(&mut self, device: &Device) -> Result<bool, PipelineError>
+    {
+        let generations: Vec<u64> = self.shaders.iter().map(|shader| shader.generation()).collect();
+        if generations == self.generations
+        {
+            return Ok(false);
+        }
+
+        self.generations = generations;
+        let modules: Vec<ShaderModule> = self.shaders.iter().map(|shader| shader.module()).collect();
+        let old = std::mem::replace(&mut self.pipeline, (self.builder)(&modules)?);
+
+        // when the wait fails the old pipeline may still be in use, leaking it is the safe choice
+        if device.wait_idle().is_ok()
+        {
+            old.destroy(device);
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn write_temp(name: &str, contents: &[u8]) -> PathBuf
+    {
+        let path = std::env::temp_dir().join(format!("qrl-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn from_file_picks_the_source_kind()
+    {
+        let spirv = write_temp("shader.spv", &[ SPIRV_MAGIC.to_le_bytes(), 0x10000u32.to_le_bytes() ].concat());
+        assert!(matches!(ShaderModuleSource::from_file(&spirv), Ok(ShaderModuleSource::Spirv(code)) if code == [ SPIRV_MAGIC, 0x10000 ]));
+
+        let truncated = write_temp("truncated.spv", &SPIRV_MAGIC.to_le_bytes()[..3]);
+        assert!(matches!(ShaderModuleSource::from_file(&truncated), Err(ShaderModuleError::CompilationFailed(_))));
+
+        let glsl = write_temp("shader.frag", b"#version 450\nvoid main() {}\n");
+        assert!(matches!(ShaderModuleSource::from_file(&glsl), Ok(ShaderModuleSource::Glsl(source)) if source == "#version 450\nvoid main() {}\n"));
+
+        let wgsl = write_temp("shader.wgsl", b"@compute @workgroup_size(1) fn main() {}\n");
+        assert!(matches!(ShaderModuleSource::from_file(&wgsl), Ok(ShaderModuleSource::Wgsl(source)) if source == "@compute @workgroup_size(1) fn main() {}\n"));
+
+        assert!(matches!(ShaderModuleSource::from_file(Path::new("missing.spv")), Err(ShaderModuleError::Io(_))));
+
+        for path in [ spirv, truncated, glsl, wgsl ]
+        {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,3291 @@
+use std::{cell::{Cell, RefCell}, sync::{atomic::{AtomicBool, Ordering}, Mutex}, time::Instant};
+
+use super::*;
+
+pub struct SwInstance
+{
+
+}
+
+impl SwInstance
+{
+    pub fn new() -> Result<Self, InstanceError>
+    {
+        Ok(Self { })
+    }
+}
+
+impl AbstractInstance for SwInstance
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_surface(&self, window: &qpl::Window) -> Result<Surface, ()>
+    {
+        Ok(Surface { internal: Arc::new(SwSurface { width: window.width, height: window.height }) })
+    }
+
+    fn create_device(&self, _surface: &Surface, create_info: &DeviceCreateInfo) -> Result<Device, DeviceError>
+    {
+        let missing_features = create_info.required_features & !SW_FEATURES;
+        if missing_features != 0
+        {
+            return Err(DeviceError::MissingFeatures(missing_features));
+        }
+
+        let missing_extensions: Vec<DeviceExtension> = create_info.required_extensions.iter()
+            .filter(|extension| !SW_EXTENSIONS.contains(extension))
+            .cloned()
+            .collect();
+        if !missing_extensions.is_empty()
+        {
+            return Err(DeviceError::MissingExtensions(missing_extensions));
+        }
+
+        Ok(Device { internal: Box::new(SwDevice { }) })
+    }
+
+    fn create_swapchain(&self, _device: &Device, _surface: &Surface, _create_info: &SwapchainCreateInfo) -> Result<Swapchain, ()>
+    {
+        // TODO: qpl has no way to blit a software framebuffer to a window yet
+        Err(())
+    }
+}
+
+#[derive(Clone)]
+pub struct SwSurface
+{
+    pub width: u32,
+    pub height: u32
+}
+
+impl AbstractSurface for SwSurface
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn capabilities(&self, _device: &Device) -> Result<SurfaceCapabilities, ()>
+    {
+        // TODO: same as create_swapchain, there is nothing to present to yet
+        Err(())
+    }
+}
+
+// shaders are plain rust functions, so the shader feature flags come for free
+const SW_FEATURES: u32 = FeatureFlags::DEPTH_CLAMP | FeatureFlags::DEPTH_BIAS_CLAMP | FeatureFlags::PIPELINE_STATISTICS_QUERY |
+    FeatureFlags::MULTI_DRAW_INDIRECT | FeatureFlags::DRAW_INDIRECT_FIRST_INSTANCE | FeatureFlags::DRAW_INDIRECT_COUNT |
+    FeatureFlags::VERTEX_ATTRIBUTE_DIVISOR | FeatureFlags::FILL_MODE_NON_SOLID | FeatureFlags::WIDE_LINES |
+    FeatureFlags::INDEPENDENT_BLEND | FeatureFlags::DUAL_SOURCE_BLEND | FeatureFlags::FRAGMENT_STORES_AND_ATOMICS |
+    FeatureFlags::VERTEX_PIPELINE_STORES_AND_ATOMICS | FeatureFlags::SHADER_FLOAT64 | FeatureFlags::SHADER_INT64 |
+    FeatureFlags::SHADER_INT16 | FeatureFlags::DYNAMIC_RENDERING;
+
+// the extensions behind the features above, reported so code written against vulkan finds them
+const SW_EXTENSIONS: [DeviceExtension; 3] = [ DeviceExtension::DrawIndirectCount, DeviceExtension::VertexAttributeDivisor, DeviceExtension::DynamicRendering ];
+
+#[derive(Clone)]
+pub struct SwDevice
+{
+
+}
+
+impl AbstractDevice for SwDevice
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn get_device_queue(&self) -> Result<Queue, ()>
+    {
+        self.get_queue(QueueKind::Graphics, 0)
+    }
+
+    fn get_queue(&self, kind: QueueKind, _index: u32) -> Result<Queue, ()>
+    {
+        // every queue executes its submissions immediately, so they are all the same family
+        Ok(Queue { internal: Arc::new(SwQueue { kind }) })
+    }
+
+    fn get_physical_device_properties(&self) -> Result<PhysicalDeviceProperties, ()>
+    {
+        Ok(PhysicalDeviceProperties
+        {
+            vendor_id: 0,
+            device_id: 0,
+            device_type: DeviceType::CPU,
+            device_name: "qrl software".to_owned(),
+            features: SW_FEATURES,
+            limits: Limits
+            {
+                max_image_dimension_2d: 16384,
+                max_image_dimension_3d: 2048,
+                max_image_array_layers: 2048,
+                max_uniform_buffer_range: u32::MAX,
+                max_storage_buffer_range: u32::MAX,
+                max_bound_descriptor_sets: u32::MAX,
+                max_vertex_input_attributes: u32::MAX,
+                max_vertex_input_bindings: u32::MAX,
+                max_vertex_input_attribute_offset: u32::MAX,
+                max_vertex_input_binding_stride: u32::MAX,
+                max_color_attachments: SW_MAX_COLOR_ATTACHMENTS as u32,
+                max_framebuffer_width: 16384,
+                max_framebuffer_height: 16384,
+                max_viewport_dimensions: [ 16384, 16384 ],
+                max_compute_workgroup_count: [ u32::MAX; 3 ],
+                max_compute_workgroup_size: [ u32::MAX; 3 ],
+                max_compute_workgroup_invocations: u32::MAX,
+                max_draw_indirect_count: u32::MAX,
+                min_uniform_buffer_offset_alignment: 1,
+                min_storage_buffer_offset_alignment: 1,
+                timestamp_period: 1.0,
+                point_size_range: [ 1.0, 1.0 ],
+                line_width_range: [ 1.0, f32::MAX ]
+            }
+        })
+    }
+
+    // everything the software device supports is always on
+    fn enabled_features(&self) -> u32
+    {
+        SW_FEATURES
+    }
+
+    fn enabled_extensions(&self) -> Vec<DeviceExtension>
+    {
+        SW_EXTENSIONS.to_vec()
+    }
+
+    fn is_extension_enabled(&self, extension: &DeviceExtension) -> bool
+    {
+        SW_EXTENSIONS.contains(extension)
+    }
+
+    fn create_shader_module(&self, create_info: &ShaderModuleCreateInfo) -> Result<ShaderModule, ShaderModuleError>
+    {
+        let ShaderModuleSource::Software(shader) = create_info.source else { return Err(ShaderModuleError::UnsupportedSource("the software backend takes SoftwareShader functions".to_string())) };
+
+        Ok(ShaderModule { internal: Arc::new(SwShaderModule { shader, stage: create_info.stage }) })
+    }
+
+    fn create_buffer(&self, create_info: &BufferCreateInfo) -> Result<Buffer, ()>
+    {
+        Ok(Buffer { internal: Arc::new(SwBuffer { data: Arc::new(Mutex::new(vec![0; create_info.size as usize])) }) })
+    }
+
+    fn create_image(&self, create_info: &ImageCreateInfo) -> Result<Image, ()>
+    {
+        let mut image = SwImage::new(create_info)?;
+        image.data = Arc::new(Mutex::new(vec![0; image.layer_size() * create_info.array_layers as usize]));
+
+        Ok(Image { internal: Arc::new(image) })
+    }
+
+    fn buffer_memory_requirements(&self, create_info: &BufferCreateInfo) -> Result<MemoryRequirements, ()>
+    {
+        Ok(MemoryRequirements { size: create_info.size, alignment: MEMORY_ALIGNMENT, memory_type_bits: 1 })
+    }
+
+    fn image_memory_requirements(&self, create_info: &ImageCreateInfo) -> Result<MemoryRequirements, ()>
+    {
+        let image = SwImage::new(create_info)?;
+        let size = (image.layer_size() * create_info.array_layers as usize) as u64;
+
+        Ok(MemoryRequirements { size, alignment: MEMORY_ALIGNMENT, memory_type_bits: 1 })
+    }
+
+    // placed resources get storage of their own, the allocation only exists to check placements against
+    fn allocate_memory(&self, allocate_info: &MemoryAllocateInfo) -> Result<Memory, ()>
+    {
+        if allocate_info.memory_type_bits & 1 == 0
+        {
+            return Err(());
+        }
+
+        Ok(Memory { internal: Arc::new(SwMemory { size: allocate_info.size }) })
+    }
+
+    fn create_placed_buffer(&self, create_info: &BufferCreateInfo, memory: &Memory, offset: u64) -> Result<Buffer, ()>
+    {
+        check_placement(&self.buffer_memory_requirements(create_info)?, memory, offset)?;
+        self.create_buffer(create_info)
+    }
+
+    fn create_placed_image(&self, create_info: &ImageCreateInfo, memory: &Memory, offset: u64) -> Result<Image, ()>
+    {
+        check_placement(&self.image_memory_requirements(create_info)?, memory, offset)?;
+        self.create_image(create_info)
+    }
+
+    fn create_image_view(&self, create_info: &ImageViewCreateInfo) -> Result<ImageView, ()>
+    {
+        let image = create_info.image.downcast_ref::<SwImage>().unwrap();
+
+        Ok(ImageView { internal: Arc::new(SwImageView
+        {
+            image: image.clone(),
+            base_mip_level: create_info.base_mip_level,
+            base_array_layer: create_info.base_array_layer,
+            parent: create_info.image.clone(),
+            range: ImageSubresourceRange
+            {
+                base_mip_level: create_info.base_mip_level,
+                mip_level_count: create_info.mip_level_count,
+                base_array_layer: create_info.base_array_layer,
+                array_layer_count: create_info.array_layer_count
+            }
+        }) })
+    }
+
+    fn create_descriptor_set_layout(&self, create_info: &DescriptorSetLayoutCreateInfo) -> Result<DescriptorSetLayout, ()>
+    {
+        Ok(DescriptorSetLayout { internal: Arc::new(SwDescriptorSetLayout { bindings: create_info.bindings.clone() }) })
+    }
+
+    fn create_descriptor_set(&self, create_info: &DescriptorSetCreateInfo) -> Result<DescriptorSet, ()>
+    {
+        let layout = create_info.layout.downcast_ref::<SwDescriptorSetLayout>().unwrap();
+        let mut descriptors: Vec<(u32, SwDescriptor)> = Vec::with_capacity(create_info.writes.len());
+
+        for write in create_info.writes
+        {
+            if !layout.bindings.iter().any(|binding| binding.binding == write.binding)
+            {
+                return Err(());
+            }
+
+            let descriptor = match &write.resource
+            {
+                DescriptorResource::Buffer { buffer, offset, size } =>
+                {
+                    let buffer = buffer.downcast_ref::<SwBuffer>().unwrap();
+                    SwDescriptor::Buffer { buffer: buffer.clone(), offset: *offset as usize, size: *size as usize }
+                },
+                DescriptorResource::Image(view) =>
+                {
+                    SwDescriptor::Image(view.downcast_ref::<SwImageView>().unwrap().clone())
+                }
+            };
+
+            descriptors.push((write.binding, descriptor));
+        }
+
+        Ok(DescriptorSet { internal: Arc::new(SwDescriptorSet { descriptors }) })
+    }
+
+    fn create_compute_pipeline(&self, create_info: &ComputePipelineCreateInfo) -> Result<ComputePipeline, PipelineError>
+    {
+        let shader = create_info.shader.downcast_ref::<SwShaderModule>().ok_or(PipelineError::UnsupportedShaderSource)?;
+
+        match (shader.stage, shader.shader)
+        {
+            (ShaderStage::Compute, SoftwareShader::Compute { workgroup_size, main }) =>
+            {
+                Ok(ComputePipeline { internal: Arc::new(SwComputePipeline { workgroup_size, main }) })
+            },
+            _ => Err(PipelineError::InvalidShaderStage)
+        }
+    }
+
+    fn create_render_pass(&self, create_info: &RenderPassCreateInfo) -> Result<RenderPass, ()>
+    {
+        if create_info.attachments.iter().any(|attachment| attachment.samples != 1)
+        {
+            return Err(());
+        }
+
+        Ok(RenderPass { internal: Arc::new(SwRenderPass { create_info: RenderPassCreateInfo
+        {
+            attachments: create_info.attachments.clone(),
+            subpasses: create_info.subpasses.clone(),
+            dependencies: create_info.dependencies.clone(),
+            label: None
+        } }) })
+    }
+
+    fn create_framebuffer(&self, create_info: &FramebufferCreateInfo) -> Result<Framebuffer, ()>
+    {
+        let attachments: Vec<SwImageView> = create_info.attachments.iter()
+            .map(|view| view.downcast_ref::<SwImageView>().unwrap().clone())
+            .collect();
+
+        let views: Vec<ImageView> = create_info.attachments.iter().map(|view| (*view).clone()).collect();
+
+        Ok(Framebuffer { internal: Arc::new(SwFramebuffer { attachments, views, width: create_info.width, height: create_info.height }) })
+    }
+
+    fn create_render_pipeline(&self, create_info: &RenderPipelineCreateInfo) -> Result<RenderPipeline, PipelineError>
+    {
+        let shaders: Vec<&SwShaderModule> = create_info.stages.iter()
+            .map(|stage| stage.downcast_ref::<SwShaderModule>().ok_or(PipelineError::UnsupportedShaderSource))
+            .collect::<Result<_, _>>()?;
+
+        let stages: Vec<ShaderStage> = shaders.iter().map(|shader| shader.stage).collect();
+        validate_render_pipeline_stages(&stages, create_info.primitive)?;
+        validate_vertex_buffers(create_info.vertex_buffers)?;
+
+        if create_info.multisample.samples != 1
+        {
+            return Err(PipelineError::InvalidSampleCount);
+        }
+
+        // begin_rendering records a single subpass render pass, so dynamic pipelines draw with subpass 0
+        let (subpass, color_attachment_count) = match create_info.rendering
+        {
+            PipelineRendering::RenderPass { render_pass, subpass } =>
+            {
+                let render_pass = render_pass.downcast_ref::<SwRenderPass>().unwrap();
+                (subpass, render_pass.create_info.subpasses.get(subpass as usize).ok_or(PipelineError::CreationFailed)?.color_attachments.len())
+            },
+            PipelineRendering::Dynamic { color_formats, .. } => (0, color_formats.len())
+        };
+        let (color_targets, _) = resolve_color_targets(create_info.color_targets, color_attachment_count)?;
+
+        let mut vertex = None;
+        let mut fragment = None;
+        for shader in shaders.iter()
+        {
+            match (shader.stage, shader.shader)
+            {
+                (ShaderStage::Vertex, SoftwareShader::Vertex { main }) => vertex = Some(main),
+                (ShaderStage::Fragment, SoftwareShader::Fragment { main }) => fragment = Some(main),
+                // TODO: geometry and tessellation stages
+                _ => return Err(PipelineError::InvalidShaderStage)
+            }
+        }
+
+        Ok(RenderPipeline { internal: Arc::new(SwRenderPipeline
+        {
+            vertex: vertex.ok_or(PipelineError::InvalidShaderStage)?,
+            fragment,
+            vertex_buffers: create_info.vertex_buffers.iter()
+                .map(|layout| SwVertexBufferLayout { stride: layout.stride, step_mode: layout.step_mode, attributes: layout.attributes.to_vec() })
+                .collect(),
+            primitive: create_info.primitive,
+            polygon_mode: create_info.polygon_mode,
+            cull_mode: create_info.cull_mode,
+            front_face: create_info.front_face,
+            viewport: create_info.viewport,
+            depth_stencil: create_info.depth_stencil,
+            depth_bias: create_info.depth_bias,
+            depth_clamp: create_info.depth_clamp,
+            color_targets,
+            dynamic_state: create_info.dynamic_state,
+            subpass
+        }) })
+    }
+
+    // software pipelines are plain functions, there is nothing to cache
+    fn create_pipeline_cache(&self, _data: Option<&[u8]>) -> Result<PipelineCache, ()>
+    {
+        Ok(PipelineCache { internal: Arc::new(SwPipelineCache { }) })
+    }
+
+    fn create_query_pool(&self, create_info: &QueryPoolCreateInfo) -> Result<QueryPool, ()>
+    {
+        if let QueryType::PipelineStatistics(0) = create_info.query_type
+        {
+            return Err(());
+        }
+
+        Ok(QueryPool { internal: Arc::new(SwQueryPool
+        {
+            create_info: QueryPoolCreateInfo { query_type: create_info.query_type, count: create_info.count, label: None },
+            results: Arc::new(Mutex::new(vec![ None; create_info.count as usize ])),
+            epoch: Instant::now()
+        }) })
+    }
+
+    fn create_command_pool(&self, _queue: &Queue) -> Result<CommandPool, ()>
+    {
+        Ok(CommandPool { internal: Arc::new(SwCommandPool { }) })
+    }
+
+    fn create_fence(&self, signaled: bool) -> Result<Fence, ()>
+    {
+        Ok(Fence { internal: Arc::new(SwFence { signaled: Arc::new(AtomicBool::new(signaled)) }) })
+    }
+
+    fn create_semaphore(&self) -> Result<Semaphore, ()>
+    {
+        Ok(Semaphore { internal: Arc::new(SwSemaphore { }) })
+    }
+
+    fn write_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) -> Result<(), ()>
+    {
+        let buffer = buffer.downcast_ref::<SwBuffer>().unwrap();
+        let mut buffer_data = buffer.data.lock().unwrap();
+        let offset = offset as usize;
+
+        if offset.checked_add(data.len()).map_or(true, |end| end > buffer_data.len())
+        {
+            return Err(());
+        }
+
+        buffer_data[offset..offset + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn upload_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) -> Result<Fence, ()>
+    {
+        // there is no device memory to stage into, so uploads complete immediately
+        self.write_buffer(buffer, offset, data)?;
+        self.create_fence(true)
+    }
+
+    fn upload_image(&self, image: &Image, region: &ImageRegion, data: &[u8]) -> Result<Fence, ()>
+    {
+        let image = image.downcast_ref::<SwImage>().unwrap();
+        let row_offsets = image.region_row_offsets(region)?;
+        let row_size = region.width as usize * image.format.texel_size() as usize;
+        if data.len() != row_size * row_offsets.len()
+        {
+            return Err(());
+        }
+
+        let mut image_data = image.data.lock().unwrap();
+        for (offset, row) in row_offsets.into_iter().zip(data.chunks_exact(row_size))
+        {
+            image_data[offset..offset + row_size].copy_from_slice(row);
+        }
+
+        self.create_fence(true)
+    }
+
+    fn read_buffer(&self, buffer: &Buffer, offset: u64, size: u64) -> Result<Vec<u8>, ()>
+    {
+        let buffer = buffer.downcast_ref::<SwBuffer>().unwrap();
+        let buffer_data = buffer.data.lock().unwrap();
+        let (offset, size) = (offset as usize, size as usize);
+
+        if offset.checked_add(size).map_or(true, |end| end > buffer_data.len())
+        {
+            return Err(());
+        }
+
+        Ok(buffer_data[offset..offset + size].to_vec())
+    }
+
+    fn read_image(&self, image: &Image, region: &ImageRegion) -> Result<Vec<u8>, ()>
+    {
+        let image = image.downcast_ref::<SwImage>().unwrap();
+        let row_offsets = image.region_row_offsets(region)?;
+        let row_size = region.width as usize * image.format.texel_size() as usize;
+
+        let image_data = image.data.lock().unwrap();
+        Ok(row_offsets.into_iter().flat_map(|offset| image_data[offset..offset + row_size].iter().copied()).collect())
+    }
+
+    fn wait_idle(&self) -> Result<(), ()>
+    {
+        // software submissions run to completion inside Queue::submit
+        Ok(())
+    }
+
+    // software objects are plain memory that is freed with the last handle
+    fn destroy_buffer(&self, _buffer: &Buffer) {}
+
+    fn destroy_image(&self, _image: &Image) {}
+
+    fn destroy_image_view(&self, _image_view: &ImageView) {}
+
+    fn destroy_render_pass(&self, _render_pass: &RenderPass) {}
+
+    fn destroy_framebuffer(&self, _framebuffer: &Framebuffer) {}
+
+    fn free_memory(&self, _memory: &Memory) {}
+
+    fn destroy_shader_module(&self, _shader_module: &ShaderModule) {}
+
+    fn destroy_compute_pipeline(&self, _pipeline: &ComputePipeline) {}
+
+    fn destroy_render_pipeline(&self, _pipeline: &RenderPipeline) {}
+}
+
+const MEMORY_ALIGNMENT: u64 = 16;
+
+pub(crate) fn check_placement(requirements: &MemoryRequirements, memory: &Memory, offset: u64) -> Result<(), ()>
+{
+    let fits = offset.checked_add(requirements.size).is_some_and(|end| end <= memory.size());
+    if offset % requirements.alignment != 0 || !fits
+    {
+        return Err(());
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct SwQueue
+{
+    pub kind: QueueKind
+}
+
+impl AbstractQueue for SwQueue
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn submit(&self, submit_info: &SubmitInfo) -> Result<(), ()>
+    {
+        for command_buffer in submit_info.command_buffers
+        {
+            let command_buffer = command_buffer.downcast_ref::<SwCommandBuffer>().unwrap();
+            let counters = SwCounters::default();
+
+            for command in command_buffer.commands.iter()
+            {
+                command.execute(&counters);
+            }
+        }
+
+        if let Some(fence) = submit_info.fence
+        {
+            fence.downcast_ref::<SwFence>().unwrap().signaled.store(true, Ordering::Release);
+        }
+
+        Ok(())
+    }
+
+    fn present(&self, _swapchain: &Swapchain, _image_index: u32, _wait_semaphores: &[&Semaphore]) -> Result<(), SwapchainError>
+    {
+        Err(SwapchainError::Failed)
+    }
+
+    fn wait_idle(&self) -> Result<(), ()>
+    {
+        Ok(())
+    }
+
+    fn kind(&self) -> QueueKind
+    {
+        self.kind
+    }
+
+    fn family_index(&self) -> u32
+    {
+        0
+    }
+}
+
+#[derive(Clone)]
+pub struct SwShaderModule
+{
+    pub shader: SoftwareShader,
+    pub stage: ShaderStage
+}
+
+impl AbstractShaderModule for SwShaderModule
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct SwMemory
+{
+    pub size: u64
+}
+
+impl AbstractMemory for SwMemory
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn size(&self) -> u64 { self.size }
+}
+
+#[derive(Clone)]
+pub struct SwBuffer
+{
+    pub data: Arc<Mutex<Vec<u8>>>
+}
+
+impl AbstractBuffer for SwBuffer
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn size(&self) -> u64 { self.data.lock().unwrap().len() as u64 }
+}
+
+#[derive(Clone)]
+pub struct SwImage
+{
+    pub data: Arc<Mutex<Vec<u8>>>,
+    pub format: Format,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub mip_levels: u32,
+    pub array_layers: u32,
+    pub usage: u32,
+    // only tracked for validation, the rasterizer doesn't care about layouts
+    pub layouts: Arc<Mutex<Vec<Option<ImageLayout>>>>
+}
+
+impl SwImage
+{
+    // without storage, callers size data with layer_size once the image is described
+    fn new(create_info: &ImageCreateInfo) -> Result<Self, ()>
+    {
+        // the software rasterizer only ever produces a single sample
+        if create_info.format.texel_size() == 0 || create_info.samples != 1
+        {
+            return Err(());
+        }
+
+        Ok(Self
+        {
+            data: Arc::new(Mutex::new(Vec::new())),
+            format: create_info.format,
+            width: create_info.width,
+            height: create_info.height,
+            depth: create_info.depth,
+            mip_levels: create_info.mip_levels,
+            array_layers: create_info.array_layers,
+            usage: create_info.usage,
+            layouts: Arc::new(Mutex::new(vec![ None; (create_info.mip_levels * create_info.array_layers) as usize ]))
+        })
+    }
+
+    pub fn mip_extent(&self, mip_level: u32) -> (u32, u32, u32)
+    {
+        ((self.width >> mip_level).max(1), (self.height >> mip_level).max(1), (self.depth >> mip_level).max(1))
+    }
+
+    fn mip_size(&self, mip_level: u32) -> usize
+    {
+        let (width, height, depth) = self.mip_extent(mip_level);
+        width as usize * height as usize * depth as usize * self.format.texel_size() as usize
+    }
+
+    fn layer_size(&self) -> usize
+    {
+        (0..self.mip_levels).map(|mip_level| self.mip_size(mip_level)).sum()
+    }
+
+    // layers are stored one after another, each holding its full mip chain
+    pub fn texel_offset(&self, mip_level: u32, array_layer: u32, x: u32, y: u32, z: u32) -> usize
+    {
+        let (width, height, _) = self.mip_extent(mip_level);
+        let subresource_offset = array_layer as usize * self.layer_size() + (0..mip_level).map(|level| self.mip_size(level)).sum::<usize>();
+
+        subresource_offset + ((z as usize * height as usize + y as usize) * width as usize + x as usize) * self.format.texel_size() as usize
+    }
+
+    // byte offset of every texel row in the region, in the order ImageRegion data is packed
+    fn region_row_offsets(&self, region: &ImageRegion) -> Result<Vec<usize>, ()>
+    {
+        if region.mip_level >= self.mip_levels || region.array_layer_count == 0 ||
+            region.base_array_layer + region.array_layer_count > self.array_layers
+        {
+            return Err(());
+        }
+
+        let (width, height, depth) = self.mip_extent(region.mip_level);
+        if region.width == 0 || region.height == 0 || region.depth == 0 ||
+            region.x + region.width > width || region.y + region.height > height || region.z + region.depth > depth
+        {
+            return Err(());
+        }
+
+        let mut offsets = Vec::with_capacity((region.array_layer_count * region.depth * region.height) as usize);
+        for layer in region.base_array_layer..region.base_array_layer + region.array_layer_count
+        {
+            for z in region.z..region.z + region.depth
+            {
+                for y in region.y..region.y + region.height
+                {
+                    offsets.push(self.texel_offset(region.mip_level, layer, region.x, y, z));
+                }
+            }
+        }
+
+        Ok(offsets)
+    }
+
+    pub fn read_texel(&self, mip_level: u32, array_layer: u32, x: u32, y: u32, z: u32) -> [f32; 4]
+    {
+        let offset = self.texel_offset(mip_level, array_layer, x, y, z);
+        let size = self.format.texel_size() as usize;
+
+        decode_texel(self.format, &self.data.lock().unwrap()[offset..offset + size])
+    }
+
+    pub fn write_texel(&self, mip_level: u32, array_layer: u32, x: u32, y: u32, z: u32, value: [f32; 4])
+    {
+        let offset = self.texel_offset(mip_level, array_layer, x, y, z);
+        let size = self.format.texel_size() as usize;
+
+        encode_texel(self.format, value, &mut self.data.lock().unwrap()[offset..offset + size]);
+    }
+}
+
+impl AbstractImage for SwImage
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_info(&self) -> ImageCreateInfo<'static>
+    {
+        ImageCreateInfo
+        {
+            format: self.format,
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            mip_levels: self.mip_levels,
+            array_layers: self.array_layers,
+            samples: 1,
+            usage: self.usage,
+            label: None
+        }
+    }
+
+    fn current_layout(&self, mip_level: u32, array_layer: u32) -> Option<ImageLayout>
+    {
+        self.layouts.lock().unwrap()[(mip_level * self.array_layers + array_layer) as usize]
+    }
+
+    fn set_current_layout(&self, mip_level: u32, array_layer: u32, layout: ImageLayout)
+    {
+        self.layouts.lock().unwrap()[(mip_level * self.array_layers + array_layer) as usize] = Some(layout);
+    }
+}
+
+#[derive(Clone)]
+pub struct SwImageView
+{
+    pub image: SwImage,
+    pub base_mip_level: u32,
+    pub base_array_layer: u32,
+    pub parent: Image,
+    pub range: ImageSubresourceRange
+}
+
+impl SwImageView
+{
+    // z addresses depth for 3D images and the array layer for everything else
+    fn coordinates(&self, coord: [u32; 3]) -> (u32, u32, u32, u32, u32)
+    {
+        if self.image.depth > 1
+        {
+            (self.base_mip_level, self.base_array_layer, coord[0], coord[1], coord[2])
+        }
+        else
+        {
+            (self.base_mip_level, self.base_array_layer + coord[2], coord[0], coord[1], 0)
+        }
+    }
+
+    pub fn read_texel(&self, coord: [u32; 3]) -> [f32; 4]
+    {
+        let (mip_level, array_layer, x, y, z) = self.coordinates(coord);
+        self.image.read_texel(mip_level, array_layer, x, y, z)
+    }
+
+    pub fn write_texel(&self, coord: [u32; 3], value: [f32; 4])
+    {
+        let (mip_level, array_layer, x, y, z) = self.coordinates(coord);
+        self.image.write_texel(mip_level, array_layer, x, y, z, value)
+    }
+}
+
+impl AbstractImageView for SwImageView
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn image(&self) -> Image
+    {
+        self.parent.clone()
+    }
+
+    fn subresource_range(&self) -> ImageSubresourceRange
+    {
+        self.range
+    }
+}
+
+#[derive(Clone)]
+pub struct SwDescriptorSetLayout
+{
+    pub bindings: Vec<DescriptorSetLayoutBinding>
+}
+
+impl AbstractDescriptorSetLayout for SwDescriptorSetLayout
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub enum SwDescriptor
+{
+    Buffer { buffer: SwBuffer, offset: usize, size: usize },
+    Image(SwImageView)
+}
+
+#[derive(Clone)]
+pub struct SwDescriptorSet
+{
+    pub descriptors: Vec<(u32, SwDescriptor)>
+}
+
+impl AbstractDescriptorSet for SwDescriptorSet
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct SwComputePipeline
+{
+    pub workgroup_size: [u32; 3],
+    pub main: fn(&ComputeInvocation)
+}
+
+impl AbstractComputePipeline for SwComputePipeline
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct SwVertexBufferLayout
+{
+    pub stride: u32,
+    pub step_mode: VertexStepMode,
+    pub attributes: Vec<VertexAttribute>
+}
+
+#[derive(Clone)]
+pub struct SwRenderPipeline
+{
+    pub vertex: fn(&VertexInvocation) -> VertexOutput,
+    pub fragment: Option<fn(&FragmentInvocation) -> FragmentOutput>,
+    pub vertex_buffers: Vec<SwVertexBufferLayout>,
+    pub primitive: Primitive,
+    pub polygon_mode: PolygonMode,
+    pub cull_mode: Option<CullMode>,
+    pub front_face: FrontFace,
+    pub viewport: Viewport,
+    pub depth_stencil: DepthStencilState,
+    pub depth_bias: Option<DepthBias>,
+    pub depth_clamp: bool,
+    pub color_targets: Vec<ColorTargetState>,
+    pub dynamic_state: u32,
+    pub subpass: u32
+}
+
+impl AbstractRenderPipeline for SwRenderPipeline
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct SwQueryPool
+{
+    pub create_info: QueryPoolCreateInfo<'static>,
+    pub results: Arc<Mutex<Vec<Option<Vec<u64>>>>>,
+    pub epoch: Instant
+}
+
+impl AbstractQueryPool for SwQueryPool
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_info(&self) -> QueryPoolCreateInfo<'static>
+    {
+        self.create_info
+    }
+
+    // submissions execute right away, so waiting on a query nothing wrote to would never finish
+    fn get_results(&self, first_query: u32, query_count: u32, wait: bool) -> Result<Option<Vec<u64>>, ()>
+    {
+        let results = self.results.lock().unwrap();
+        let queries = results.get(first_query as usize..first_query as usize + query_count as usize).ok_or(())?;
+        if queries.iter().any(|query| query.is_none())
+        {
+            return if wait { Err(()) } else { Ok(None) };
+        }
+
+        Ok(Some(queries.iter().flat_map(|query| query.as_ref().unwrap().iter().copied()).collect()))
+    }
+}
+
+#[derive(Clone)]
+pub struct SwPipelineCache
+{
+
+}
+
+impl AbstractPipelineCache for SwPipelineCache
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn data(&self) -> Result<Vec<u8>, ()>
+    {
+        Ok(Vec::new())
+    }
+}
+
+#[derive(Clone)]
+pub struct SwRenderPass
+{
+    pub create_info: RenderPassCreateInfo<'static>
+}
+
+impl AbstractRenderPass for SwRenderPass
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_info(&self) -> &RenderPassCreateInfo<'static>
+    {
+        &self.create_info
+    }
+}
+
+#[derive(Clone)]
+pub struct SwFramebuffer
+{
+    pub attachments: Vec<SwImageView>,
+    pub views: Vec<ImageView>,
+    pub width: u32,
+    pub height: u32
+}
+
+impl AbstractFramebuffer for SwFramebuffer
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn attachments(&self) -> &[ImageView]
+    {
+        &self.views
+    }
+}
+
+#[derive(Clone)]
+pub struct SwCommandPool
+{
+
+}
+
+impl AbstractCommandPool for SwCommandPool
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_command_encoder(&self) -> Result<CommandEncoder, ()>
+    {
+        Ok(CommandEncoder { internal: Box::new(SwCommandEncoder
+        {
+            commands: Vec::new(),
+            compute_pipeline: None,
+            render_pipeline: None,
+            descriptor_sets: Vec::new(),
+            vertex_buffers: Vec::new(),
+            index_buffer: None,
+            render_pass: None,
+            dynamic_state: Default::default()
+        }), tracker: Default::default(), features: SW_FEATURES, line_width_range: [ 1.0, f32::MAX ] })
+    }
+
+    fn reset(&self) -> Result<(), ()>
+    {
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub enum SwCommand
+{
+    Dispatch { pipeline: SwComputePipeline, descriptor_sets: Vec<Option<SwDescriptorSet>>, group_count: [u32; 3] },
+    DispatchIndirect { pipeline: SwComputePipeline, descriptor_sets: Vec<Option<SwDescriptorSet>>, buffer: SwBuffer, offset: usize },
+    BeginRenderPass { render_pass: SwRenderPass, framebuffer: SwFramebuffer },
+    Draw { state: SwDrawState, indexed: bool, draw: DrawIndexedIndirectCommand },
+    DrawIndirect { state: SwDrawState, indexed: bool, buffer: SwBuffer, offset: usize, draw_count: u32, stride: usize, count: Option<(SwBuffer, usize)> },
+    ResolveImage { src: SwImage, dst: SwImage },
+    ResetQueries { query_pool: SwQueryPool, first_query: u32, query_count: u32 },
+    WriteTimestamp { query_pool: SwQueryPool, query: u32 },
+    BeginQuery { query_pool: SwQueryPool, query: u32 },
+    EndQuery { query_pool: SwQueryPool, query: u32 }
+}
+
+// what the commands of one command buffer counted so far. queries keep a snapshot from begin_query and
+// report the difference at end_query
+#[derive(Default)]
+pub struct SwCounters
+{
+    samples_passed: Cell<u64>,
+    // indexed by PipelineStatisticFlags bit
+    statistics: [Cell<u64>; 11],
+    queries: RefCell<Vec<(SwQueryPool, u32, SwSnapshot)>>
+}
+
+#[derive(Copy, Clone)]
+struct SwSnapshot
+{
+    samples_passed: u64,
+    statistics: [u64; 11]
+}
+
+impl SwCounters
+{
+    fn add(&self, statistic: u32, count: u64)
+    {
+        let counter = &self.statistics[statistic.trailing_zeros() as usize];
+        counter.set(counter.get() + count);
+    }
+
+    fn sample_passed(&self)
+    {
+        self.samples_passed.set(self.samples_passed.get() + 1);
+    }
+
+    fn snapshot(&self) -> SwSnapshot
+    {
+        SwSnapshot { samples_passed: self.samples_passed.get(), statistics: std::array::from_fn(|index| self.statistics[index].get()) }
+    }
+}
+
+impl SwCommand
+{
+    fn execute(&self, counters: &SwCounters)
+    {
+        match self
+        {
+            SwCommand::Dispatch { pipeline, descriptor_sets, group_count } =>
+            {
+                dispatch(pipeline, descriptor_sets, *group_count, counters);
+            },
+            SwCommand::DispatchIndirect { pipeline, descriptor_sets, buffer, offset } =>
+            {
+                let group_count =
+                {
+                    let data = buffer.data.lock().unwrap();
+                    [ read_u32(&data, *offset), read_u32(&data, *offset + 4), read_u32(&data, *offset + 8) ]
+                };
+
+                dispatch(pipeline, descriptor_sets, group_count, counters);
+            },
+            SwCommand::BeginRenderPass { render_pass, framebuffer } =>
+            {
+                for (attachment, view) in render_pass.create_info.attachments.iter().zip(framebuffer.attachments.iter())
+                {
+                    if let LoadOp::Clear(r, g, b, a) = attachment.operations.load_op
+                    {
+                        clear_view(view, framebuffer, [ r, g, b, a ]);
+                    }
+                }
+            },
+            SwCommand::Draw { state, indexed, draw } =>
+            {
+                rasterize(state, *indexed, draw, counters);
+            },
+            SwCommand::DrawIndirect { state, indexed, buffer, offset, draw_count, stride, count } =>
+            {
+                // arguments are read when the command runs, so earlier dispatches can write them
+                let draw_count = match count
+                {
+                    Some((count_buffer, count_offset)) => read_u32(&count_buffer.data.lock().unwrap(), *count_offset).min(*draw_count),
+                    None => *draw_count
+                };
+
+                let draws: Vec<DrawIndexedIndirectCommand> =
+                {
+                    let data = buffer.data.lock().unwrap();
+                    (0..draw_count as usize).map(|draw| read_draw(&data, offset + draw * stride, *indexed)).collect()
+                };
+
+                for draw in draws.iter()
+                {
+                    rasterize(state, *indexed, draw, counters);
+                }
+            },
+            SwCommand::ResolveImage { src, dst } =>
+            {
+                // single sampled images resolve to a plain copy of every layer
+                for array_layer in 0..src.array_layers.min(dst.array_layers)
+                {
+                    for y in 0..src.height.min(dst.height)
+                    {
+                        for x in 0..src.width.min(dst.width)
+                        {
+                            dst.write_texel(0, array_layer, x, y, 0, src.read_texel(0, array_layer, x, y, 0));
+                        }
+                    }
+                }
+            },
+            SwCommand::ResetQueries { query_pool, first_query, query_count } =>
+            {
+                let mut results = query_pool.results.lock().unwrap();
+                for query in results.iter_mut().skip(*first_query as usize).take(*query_count as usize)
+                {
+                    *query = None;
+                }
+            },
+            SwCommand::WriteTimestamp { query_pool, query } =>
+            {
+                let timestamp = query_pool.epoch.elapsed().as_nanos() as u64;
+                query_pool.results.lock().unwrap()[*query as usize] = Some(vec![ timestamp ]);
+            },
+            SwCommand::BeginQuery { query_pool, query } =>
+            {
+                counters.queries.borrow_mut().push((query_pool.clone(), *query, counters.snapshot()));
+            },
+            SwCommand::EndQuery { query_pool, query } =>
+            {
+                let mut queries = counters.queries.borrow_mut();
+                let Some(index) = queries.iter().position(|(pool, begun, _)| Arc::ptr_eq(&pool.results, &query_pool.results) && begun == query) else
+                {
+                    return state::validation_error("end_query without a matching begin_query");
+                };
+                let (_, _, begin) = queries.remove(index);
+                let end = counters.snapshot();
+
+                let values = match query_pool.create_info.query_type
+                {
+                    QueryType::PipelineStatistics(statistics) => (0..11)
+                        .filter(|bit| statistics & (1 << bit) != 0)
+                        .map(|bit| end.statistics[bit] - begin.statistics[bit])
+                        .collect(),
+                    _ => vec![ end.samples_passed - begin.samples_passed ]
+                };
+                query_pool.results.lock().unwrap()[*query as usize] = Some(values);
+            }
+        }
+    }
+}
+
+fn clear_view(view: &SwImageView, framebuffer: &SwFramebuffer, value: [f32; 4])
+{
+    for y in 0..framebuffer.height
+    {
+        for x in 0..framebuffer.width
+        {
+            view.write_texel([ x, y, 0 ], value);
+        }
+    }
+}
+
+// only read for the states a pipeline declares dynamic
+#[derive(Clone)]
+pub struct SwDynamicState
+{
+    pub viewport: Option<Viewport>,
+    pub scissor: Option<Scissor>,
+    pub blend_constants: [f32; 4],
+    pub stencil_reference: u32,
+    pub line_width: f32,
+    pub depth_bias: Option<DepthBias>
+}
+
+impl Default for SwDynamicState
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            viewport: None,
+            scissor: None,
+            blend_constants: [ 0.0, 0.0, 0.0, 0.0 ],
+            stencil_reference: 0,
+            line_width: 1.0,
+            depth_bias: None
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SwDrawState
+{
+    pub pipeline: SwRenderPipeline,
+    pub descriptor_sets: Vec<Option<SwDescriptorSet>>,
+    pub vertex_buffers: Vec<Option<(SwBuffer, usize)>>,
+    pub index_buffer: Option<(SwBuffer, usize, IndexFormat)>,
+    pub render_pass: SwRenderPass,
+    pub framebuffer: SwFramebuffer,
+    pub dynamic_state: SwDynamicState
+}
+
+pub struct SwCommandEncoder
+{
+    pub commands: Vec<SwCommand>,
+    pub compute_pipeline: Option<SwComputePipeline>,
+    pub render_pipeline: Option<SwRenderPipeline>,
+    pub descriptor_sets: Vec<Option<SwDescriptorSet>>,
+    pub vertex_buffers: Vec<Option<(SwBuffer, usize)>>,
+    pub index_buffer: Option<(SwBuffer, usize, IndexFormat)>,
+    pub render_pass: Option<(SwRenderPass, SwFramebuffer)>,
+    pub dynamic_state: SwDynamicState
+}
+
+impl SwCommandEncoder
+{
+    // draws without a bound pipeline, outside of a render pass, without the dynamic viewport or scissor the
+    // pipeline needs or indexed draws without an index buffer are reported and dropped
+    fn draw_state(&self, indexed: bool) -> Option<SwDrawState>
+    {
+        let Some(pipeline) = self.render_pipeline.clone() else
+        {
+            state::validation_error("a render pipeline must be bound before drawing");
+            return None;
+        };
+        let Some((render_pass, framebuffer)) = self.render_pass.clone() else
+        {
+            state::validation_error("draws must be recorded inside a render pass");
+            return None;
+        };
+        if indexed && self.index_buffer.is_none()
+        {
+            state::validation_error("an index buffer must be bound before indexed draws");
+            return None;
+        }
+        if pipeline.dynamic_state & DynamicStateFlags::VIEWPORT != 0 && self.dynamic_state.viewport.is_none()
+        {
+            state::validation_error("the pipeline has a dynamic viewport but set_viewport was never called");
+            return None;
+        }
+        if pipeline.dynamic_state & DynamicStateFlags::SCISSOR != 0 && self.dynamic_state.scissor.is_none()
+        {
+            state::validation_error("the pipeline has a dynamic scissor but set_scissor was never called");
+            return None;
+        }
+
+        Some(SwDrawState
+        {
+            pipeline,
+            descriptor_sets: self.descriptor_sets.clone(),
+            vertex_buffers: self.vertex_buffers.clone(),
+            index_buffer: self.index_buffer.clone(),
+            render_pass,
+            framebuffer,
+            dynamic_state: self.dynamic_state.clone()
+        })
+    }
+
+    fn record_draw_indirect(&mut self, indexed: bool, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32, count: Option<(&Buffer, u64)>)
+    {
+        let Some(state) = self.draw_state(indexed) else { return };
+
+        self.commands.push(SwCommand::DrawIndirect
+        {
+            state,
+            indexed,
+            buffer: buffer.downcast_ref::<SwBuffer>().unwrap().clone(),
+            offset: offset as usize,
+            draw_count,
+            stride: stride as usize,
+            count: count.map(|(count_buffer, count_offset)| (count_buffer.downcast_ref::<SwBuffer>().unwrap().clone(), count_offset as usize))
+        });
+    }
+}
+
+impl AbstractCommandEncoder for SwCommandEncoder
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn bind_compute_pipeline(&mut self, pipeline: &ComputePipeline)
+    {
+        self.compute_pipeline = Some(pipeline.downcast_ref::<SwComputePipeline>().unwrap().clone());
+    }
+
+    fn bind_descriptor_set(&mut self, index: u32, descriptor_set: &DescriptorSet)
+    {
+        let index = index as usize;
+        if self.descriptor_sets.len() <= index
+        {
+            self.descriptor_sets.resize(index + 1, None);
+        }
+
+        self.descriptor_sets[index] = Some(descriptor_set.downcast_ref::<SwDescriptorSet>().unwrap().clone());
+    }
+
+    fn dispatch(&mut self, x: u32, y: u32, z: u32)
+    {
+        let Some(pipeline) = self.compute_pipeline.clone() else
+        {
+            state::validation_error("a compute pipeline must be bound before dispatch");
+            return;
+        };
+
+        self.commands.push(SwCommand::Dispatch
+        {
+            pipeline,
+            descriptor_sets: self.descriptor_sets.clone(),
+            group_count: [ x, y, z ]
+        });
+    }
+
+    fn dispatch_indirect(&mut self, buffer: &Buffer, offset: u64)
+    {
+        let Some(pipeline) = self.compute_pipeline.clone() else
+        {
+            state::validation_error("a compute pipeline must be bound before dispatch");
+            return;
+        };
+
+        self.commands.push(SwCommand::DispatchIndirect
+        {
+            pipeline,
+            descriptor_sets: self.descriptor_sets.clone(),
+            buffer: buffer.downcast_ref::<SwBuffer>().unwrap().clone(),
+            offset: offset as usize
+        });
+    }
+
+    fn begin_render_pass(&mut self, render_pass: &RenderPass, framebuffer: &Framebuffer)
+    {
+        let render_pass = render_pass.downcast_ref::<SwRenderPass>().unwrap().clone();
+        let framebuffer = framebuffer.downcast_ref::<SwFramebuffer>().unwrap().clone();
+
+        self.render_pass = Some((render_pass.clone(), framebuffer.clone()));
+        self.commands.push(SwCommand::BeginRenderPass { render_pass, framebuffer });
+    }
+
+    fn end_render_pass(&mut self)
+    {
+        self.render_pass = None;
+    }
+
+    fn begin_rendering(&mut self, rendering_info: &RenderingInfo)
+    {
+        let color_attachment_count = rendering_info.color_attachments.len() as u32;
+        let attachments: Vec<&RenderingAttachment> = rendering_info.color_attachments.iter().chain(rendering_info.depth_stencil_attachment.iter()).collect();
+
+        let render_pass = SwRenderPass { create_info: RenderPassCreateInfo
+        {
+            attachments: attachments.iter()
+                .map(|attachment|
+                {
+                    let format = attachment.view.image().create_info().format;
+                    AttachmentDescription
+                    {
+                        format,
+                        samples: 1,
+                        operations: attachment.operations,
+                        stencil_operations: rendering_info.stencil_operations,
+                        initial_layout: None,
+                        final_layout: if format.has_depth() { ImageLayout::DepthStencilAttachmentOptimal } else { ImageLayout::ColorAttachmentOptimal }
+                    }
+                })
+                .collect(),
+            subpasses: vec![ SubpassDescription
+            {
+                color_attachments: (0..color_attachment_count)
+                    .map(|attachment| AttachmentReference { attachment, layout: ImageLayout::ColorAttachmentOptimal })
+                    .collect(),
+                resolve_attachments: Vec::new(),
+                depth_stencil_attachment: rendering_info.depth_stencil_attachment.as_ref()
+                    .map(|_| AttachmentReference { attachment: color_attachment_count, layout: ImageLayout::DepthStencilAttachmentOptimal })
+            } ],
+            dependencies: Vec::new(),
+            label: None
+        } };
+
+        let (width, height) = rendering_info.extent();
+        let framebuffer = SwFramebuffer
+        {
+            attachments: attachments.iter().map(|attachment| attachment.view.downcast_ref::<SwImageView>().unwrap().clone()).collect(),
+            views: attachments.iter().map(|attachment| attachment.view.clone()).collect(),
+            width,
+            height
+        };
+
+        self.render_pass = Some((render_pass.clone(), framebuffer.clone()));
+        self.commands.push(SwCommand::BeginRenderPass { render_pass, framebuffer });
+    }
+
+    fn end_rendering(&mut self)
+    {
+        self.render_pass = None;
+    }
+
+    fn bind_render_pipeline(&mut self, pipeline: &RenderPipeline)
+    {
+        self.render_pipeline = Some(pipeline.downcast_ref::<SwRenderPipeline>().unwrap().clone());
+    }
+
+    fn bind_index_buffer(&mut self, buffer: &Buffer, offset: u64, format: IndexFormat)
+    {
+        self.index_buffer = Some((buffer.downcast_ref::<SwBuffer>().unwrap().clone(), offset as usize, format));
+    }
+
+    fn bind_vertex_buffer(&mut self, binding: u32, buffer: &Buffer, offset: u64)
+    {
+        let binding = binding as usize;
+        if self.vertex_buffers.len() <= binding
+        {
+            self.vertex_buffers.resize(binding + 1, None);
+        }
+
+        self.vertex_buffers[binding] = Some((buffer.downcast_ref::<SwBuffer>().unwrap().clone(), offset as usize));
+    }
+
+    fn set_viewport(&mut self, viewport: &Viewport)
+    {
+        self.dynamic_state.viewport = Some(*viewport);
+    }
+
+    fn set_scissor(&mut self, scissor: &Scissor)
+    {
+        self.dynamic_state.scissor = Some(*scissor);
+    }
+
+    fn set_blend_constants(&mut self, blend_constants: [f32; 4])
+    {
+        self.dynamic_state.blend_constants = blend_constants;
+    }
+
+    fn set_stencil_reference(&mut self, reference: u32)
+    {
+        self.dynamic_state.stencil_reference = reference;
+    }
+
+    fn set_line_width(&mut self, line_width: f32)
+    {
+        self.dynamic_state.line_width = line_width;
+    }
+
+    fn set_depth_bias(&mut self, depth_bias: &DepthBias)
+    {
+        self.dynamic_state.depth_bias = Some(*depth_bias);
+    }
+
+    fn draw(&mut self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32)
+    {
+        let Some(state) = self.draw_state(false) else { return };
+        let draw = DrawIndexedIndirectCommand { index_count: vertex_count, instance_count, first_index: first_vertex, vertex_offset: 0, first_instance };
+
+        self.commands.push(SwCommand::Draw { state, indexed: false, draw });
+    }
+
+    fn draw_indexed(&mut self, index_count: u32, instance_count: u32, first_index: u32, vertex_offset: i32, first_instance: u32)
+    {
+        let Some(state) = self.draw_state(true) else { return };
+        let draw = DrawIndexedIndirectCommand { index_count, instance_count, first_index, vertex_offset, first_instance };
+
+        self.commands.push(SwCommand::Draw { state, indexed: true, draw });
+    }
+
+    fn draw_indirect(&mut self, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32)
+    {
+        self.record_draw_indirect(false, buffer, offset, draw_count, stride, None)
+    }
+
+    fn draw_indexed_indirect(&mut self, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32)
+    {
+        self.record_draw_indirect(true, buffer, offset, draw_count, stride, None)
+    }
+
+    fn draw_indirect_count(&mut self, buffer: &Buffer, offset: u64, count_buffer: &Buffer, count_offset: u64, max_draw_count: u32, stride: u32)
+    {
+        self.record_draw_indirect(false, buffer, offset, max_draw_count, stride, Some((count_buffer, count_offset)))
+    }
+
+    fn draw_indexed_indirect_count(&mut self, buffer: &Buffer, offset: u64, count_buffer: &Buffer, count_offset: u64, max_draw_count: u32, stride: u32)
+    {
+        self.record_draw_indirect(true, buffer, offset, max_draw_count, stride, Some((count_buffer, count_offset)))
+    }
+
+    fn resolve_image(&mut self, src: &Image, _src_layout: ImageLayout, dst: &Image, _dst_layout: ImageLayout)
+    {
+        self.commands.push(SwCommand::ResolveImage
+        {
+            src: src.downcast_ref::<SwImage>().unwrap().clone(),
+            dst: dst.downcast_ref::<SwImage>().unwrap().clone()
+        });
+    }
+
+    fn reset_query_pool(&mut self, query_pool: &QueryPool, first_query: u32, query_count: u32)
+    {
+        self.commands.push(SwCommand::ResetQueries { query_pool: query_pool.downcast_ref::<SwQueryPool>().unwrap().clone(), first_query, query_count });
+    }
+
+    fn write_timestamp(&mut self, query_pool: &QueryPool, query: u32, _stage: u32)
+    {
+        self.commands.push(SwCommand::WriteTimestamp { query_pool: query_pool.downcast_ref::<SwQueryPool>().unwrap().clone(), query });
+    }
+
+    fn begin_query(&mut self, query_pool: &QueryPool, query: u32)
+    {
+        self.commands.push(SwCommand::BeginQuery { query_pool: query_pool.downcast_ref::<SwQueryPool>().unwrap().clone(), query });
+    }
+
+    fn end_query(&mut self, query_pool: &QueryPool, query: u32)
+    {
+        self.commands.push(SwCommand::EndQuery { query_pool: query_pool.downcast_ref::<SwQueryPool>().unwrap().clone(), query });
+    }
+
+    fn push_debug_group(&mut self, _label: &str)
+    {
+
+    }
+
+    fn pop_debug_group(&mut self)
+    {
+
+    }
+
+    fn insert_debug_marker(&mut self, _label: &str)
+    {
+
+    }
+
+    fn memory_barrier(&mut self, _barrier: &MemoryBarrier)
+    {
+        // commands execute in order on the cpu, so there is nothing to wait on
+    }
+
+    fn buffer_barrier(&mut self, _barrier: &BufferBarrier)
+    {
+
+    }
+
+    fn image_barrier(&mut self, _barrier: &ImageBarrier)
+    {
+
+    }
+
+    fn finish(self: Box<Self>) -> Result<CommandBuffer, ()>
+    {
+        Ok(CommandBuffer { internal: Arc::new(SwCommandBuffer { commands: Arc::new(self.commands) }), layouts: Arc::default() })
+    }
+}
+
+#[derive(Clone)]
+pub struct SwCommandBuffer
+{
+    pub commands: Arc<Vec<SwCommand>>
+}
+
+impl AbstractCommandBuffer for SwCommandBuffer
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct SwFence
+{
+    pub signaled: Arc<AtomicBool>
+}
+
+impl AbstractFence for SwFence
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn wait(&self, _timeout_ns: u64) -> Result<bool, ()>
+    {
+        Ok(self.signaled.load(Ordering::Acquire))
+    }
+
+    fn reset(&self) -> Result<(), ()>
+    {
+        self.signaled.store(false, Ordering::Release);
+        Ok(())
+    }
+
+    fn is_signaled(&self) -> Result<bool, ()>
+    {
+        Ok(self.signaled.load(Ordering::Acquire))
+    }
+}
+
+#[derive(Clone)]
+pub struct SwSemaphore
+{
+
+}
+
+impl AbstractSemaphore for SwSemaphore
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+// descriptor access shared by every software shader stage
+#[derive(Clone, Copy)]
+pub struct ShaderResources<'a>
+{
+    descriptor_sets: &'a [Option<SwDescriptorSet>]
+}
+
+impl<'a> ShaderResources<'a>
+{
+    fn descriptor(&self, set: u32, binding: u32) -> &SwDescriptor
+    {
+        self.descriptor_sets.get(set as usize)
+            .and_then(|descriptor_set| descriptor_set.as_ref())
+            .and_then(|descriptor_set| descriptor_set.descriptors.iter().find(|(index, _)| *index == binding))
+            .map(|(_, descriptor)| descriptor)
+            .unwrap_or_else(|| panic!("nothing is bound to set {} binding {}", set, binding))
+    }
+
+    fn buffer(&self, set: u32, binding: u32, offset: u64, length: usize) -> (&SwBuffer, usize)
+    {
+        let SwDescriptor::Buffer { buffer, offset: base, size } = self.descriptor(set, binding) else { panic!("set {} binding {} is not a buffer", set, binding) };
+        let offset = offset as usize;
+
+        assert!(offset + length <= *size, "buffer access out of bounds");
+        (buffer, base + offset)
+    }
+
+    fn image(&self, set: u32, binding: u32) -> &SwImageView
+    {
+        let SwDescriptor::Image(view) = self.descriptor(set, binding) else { panic!("set {} binding {} is not an image", set, binding) };
+        view
+    }
+
+    pub fn buffer_size(&self, set: u32, binding: u32) -> u64
+    {
+        let SwDescriptor::Buffer { size, .. } = self.descriptor(set, binding) else { panic!("set {} binding {} is not a buffer", set, binding) };
+        *size as u64
+    }
+
+    pub fn load_u32(&self, set: u32, binding: u32, offset: u64) -> u32
+    {
+        let (buffer, offset) = self.buffer(set, binding, offset, 4);
+        read_u32(&buffer.data.lock().unwrap(), offset)
+    }
+
+    pub fn store_u32(&self, set: u32, binding: u32, offset: u64, value: u32)
+    {
+        let (buffer, offset) = self.buffer(set, binding, offset, 4);
+        buffer.data.lock().unwrap()[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn load_f32(&self, set: u32, binding: u32, offset: u64) -> f32
+    {
+        f32::from_bits(self.load_u32(set, binding, offset))
+    }
+
+    pub fn store_f32(&self, set: u32, binding: u32, offset: u64, value: f32)
+    {
+        self.store_u32(set, binding, offset, value.to_bits())
+    }
+
+    pub fn atomic_add_u32(&self, set: u32, binding: u32, offset: u64, value: u32) -> u32
+    {
+        let (buffer, offset) = self.buffer(set, binding, offset, 4);
+        let mut data = buffer.data.lock().unwrap();
+        let previous = read_u32(&data, offset);
+        data[offset..offset + 4].copy_from_slice(&previous.wrapping_add(value).to_le_bytes());
+        previous
+    }
+
+    pub fn load_texel(&self, set: u32, binding: u32, coord: [u32; 3]) -> [f32; 4]
+    {
+        self.image(set, binding).read_texel(coord)
+    }
+
+    pub fn store_texel(&self, set: u32, binding: u32, coord: [u32; 3], value: [f32; 4])
+    {
+        self.image(set, binding).write_texel(coord, value)
+    }
+}
+
+pub struct ComputeInvocation<'a>
+{
+    pub global_invocation_id: [u32; 3],
+    pub local_invocation_id: [u32; 3],
+    pub workgroup_id: [u32; 3],
+    pub num_workgroups: [u32; 3],
+    resources: ShaderResources<'a>
+}
+
+impl<'a> std::ops::Deref for ComputeInvocation<'a>
+{
+    type Target = ShaderResources<'a>;
+
+    fn deref(&self) -> &Self::Target { &self.resources }
+}
+
+pub struct VertexInvocation<'a>
+{
+    pub vertex_index: u32,
+    pub instance_index: u32,
+    first_instance: u32,
+    layouts: &'a [SwVertexBufferLayout],
+    vertex_buffers: &'a [Option<(SwBuffer, usize)>],
+    resources: ShaderResources<'a>
+}
+
+impl<'a> VertexInvocation<'a>
+{
+    // missing components read as 0, 0, 0, 1 and integer formats are converted like texels. locations the
+    // pipeline has no attribute for, unbound vertex buffers and fetches past the end of the buffer read as 0, 0, 0, 1
+    // as well, like vulkan's robust buffer access
+    pub fn attribute(&self, location: u32) -> [f32; 4]
+    {
+        const MISSING: [f32; 4] = [ 0.0, 0.0, 0.0, 1.0 ];
+
+        let Some((binding, layout, attribute)) = self.layouts.iter().enumerate()
+            .find_map(|(binding, layout)| layout.attributes.iter().find(|attribute| attribute.location == location).map(|attribute| (binding, layout, attribute))) else { return MISSING };
+        let Some((buffer, offset)) = self.vertex_buffers.get(binding).and_then(|buffer| buffer.as_ref()) else { return MISSING };
+
+        // instanced data starts at first_instance and then advances every divisor instances
+        let element = match layout.step_mode
+        {
+            VertexStepMode::Vertex => self.vertex_index,
+            VertexStepMode::Instance(0) => self.first_instance,
+            VertexStepMode::Instance(divisor) => self.first_instance + (self.instance_index - self.first_instance) / divisor
+        };
+
+        let start = offset + element as usize * layout.stride as usize + attribute.offset as usize;
+        let data = buffer.data.lock().unwrap();
+        if start + attribute.format.size() as usize > data.len()
+        {
+            return MISSING;
+        }
+
+        decode_vertex(attribute.format, &data[start..])
+    }
+}
+
+impl<'a> std::ops::Deref for VertexInvocation<'a>
+{
+    type Target = ShaderResources<'a>;
+
+    fn deref(&self) -> &Self::Target { &self.resources }
+}
+
+// position is in clip space, varyings are interpolated perspective correct for the fragment stage
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VertexOutput
+{
+    pub position: [f32; 4],
+    pub varyings: Vec<f32>
+}
+
+pub struct FragmentInvocation<'a>
+{
+    // pixel center, window depth and 1/w like gl_FragCoord
+    pub frag_coord: [f32; 4],
+    pub front_facing: bool,
+    pub varyings: &'a [f32],
+    resources: ShaderResources<'a>
+}
+
+impl<'a> std::ops::Deref for FragmentInvocation<'a>
+{
+    type Target = ShaderResources<'a>;
+
+    fn deref(&self) -> &Self::Target { &self.resources }
+}
+
+pub const SW_MAX_COLOR_ATTACHMENTS: usize = 8;
+
+// colors are indexed by the subpass' color attachments, depth overrides the interpolated depth and
+// dual_source is what Src1 blend factors read
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct FragmentOutput
+{
+    pub colors: [[f32; 4]; SW_MAX_COLOR_ATTACHMENTS],
+    pub dual_source: [f32; 4],
+    pub depth: Option<f32>,
+    pub discard: bool
+}
+
+impl FragmentOutput
+{
+    pub fn color(value: [f32; 4]) -> Self
+    {
+        let mut output = Self::default();
+        output.colors[0] = value;
+        output
+    }
+
+    pub fn discard() -> Self
+    {
+        Self { discard: true, ..Default::default() }
+    }
+}
+
+fn dispatch(pipeline: &SwComputePipeline, descriptor_sets: &[Option<SwDescriptorSet>], group_count: [u32; 3], counters: &SwCounters)
+{
+    let size = pipeline.workgroup_size;
+    let invocations = group_count.iter().chain(size.iter()).map(|count| *count as u64).product();
+    counters.add(PipelineStatisticFlags::COMPUTE_SHADER_INVOCATIONS, invocations);
+
+    for group_z in 0..group_count[2] { for group_y in 0..group_count[1] { for group_x in 0..group_count[0]
+    {
+        for local_z in 0..size[2] { for local_y in 0..size[1] { for local_x in 0..size[0]
+        {
+            let invocation = ComputeInvocation
+            {
+                global_invocation_id: [ group_x * size[0] + local_x, group_y * size[1] + local_y, group_z * size[2] + local_z ],
+                local_invocation_id: [ local_x, local_y, local_z ],
+                workgroup_id: [ group_x, group_y, group_z ],
+                num_workgroups: group_count,
+                resources: ShaderResources { descriptor_sets }
+            };
+
+            (pipeline.main)(&invocation);
+        } } }
+    } } }
+}
+
+// non indexed draws keep their first vertex in first_index
+fn read_draw(data: &[u8], offset: usize, indexed: bool) -> DrawIndexedIndirectCommand
+{
+    if indexed
+    {
+        DrawIndexedIndirectCommand
+        {
+            index_count: read_u32(data, offset),
+            instance_count: read_u32(data, offset + 4),
+            first_index: read_u32(data, offset + 8),
+            vertex_offset: read_u32(data, offset + 12) as i32,
+            first_instance: read_u32(data, offset + 16)
+        }
+    }
+    else
+    {
+        DrawIndexedIndirectCommand
+        {
+            index_count: read_u32(data, offset),
+            instance_count: read_u32(data, offset + 4),
+            first_index: read_u32(data, offset + 8),
+            vertex_offset: 0,
+            first_instance: read_u32(data, offset + 12)
+        }
+    }
+}
+
+fn rasterize(state: &SwDrawState, indexed: bool, draw: &DrawIndexedIndirectCommand, counters: &SwCounters)
+{
+    let pipeline = &state.pipeline;
+    let subpass = &state.render_pass.create_info.subpasses[pipeline.subpass as usize];
+    let attachment = |reference: &AttachmentReference| &state.framebuffer.attachments[reference.attachment as usize];
+
+    let dynamic = |flag: u32| pipeline.dynamic_state & flag != 0;
+    // draw_state made sure dynamic viewports and scissors are set
+    let viewport = match dynamic(DynamicStateFlags::VIEWPORT)
+    {
+        true => state.dynamic_state.viewport.unwrap_or(pipeline.viewport),
+        false => pipeline.viewport
+    };
+
+    // like vulkan pipelines without a dynamic scissor clip to their viewport
+    let scissor = match dynamic(DynamicStateFlags::SCISSOR)
+    {
+        true => state.dynamic_state.scissor.unwrap_or(Scissor { x: 0, y: 0, width: 0, height: 0 }),
+        false => Scissor { x: pipeline.viewport.x as i32, y: pipeline.viewport.y as i32, width: pipeline.viewport.width as u32, height: pipeline.viewport.height as u32 }
+    };
+
+    let rasterizer = SwRasterizer
+    {
+        pipeline,
+        resources: ShaderResources { descriptor_sets: &state.descriptor_sets },
+        colors: subpass.color_attachments.iter().map(attachment).collect(),
+        depth: subpass.depth_stencil_attachment.as_ref().map(attachment),
+        viewport,
+        bounds:
+        [
+            scissor.x.max(0) as u32,
+            scissor.y.max(0) as u32,
+            (scissor.x.max(0) as u32).saturating_add(scissor.width).min(state.framebuffer.width),
+            (scissor.y.max(0) as u32).saturating_add(scissor.height).min(state.framebuffer.height)
+        ],
+        depth_bias: if dynamic(DynamicStateFlags::DEPTH_BIAS) { state.dynamic_state.depth_bias } else { pipeline.depth_bias },
+        blend_constants: if dynamic(DynamicStateFlags::BLEND_CONSTANTS) { state.dynamic_state.blend_constants } else { [ 0.0, 0.0, 0.0, 0.0 ] },
+        line_width: if dynamic(DynamicStateFlags::LINE_WIDTH) { state.dynamic_state.line_width } else { 1.0 },
+        counters
+    };
+
+    let indices: Vec<u32> = if indexed
+    {
+        // draw_state makes sure indexed draws have one
+        let Some((buffer, offset, format)) = state.index_buffer.as_ref() else { return };
+        let data = buffer.data.lock().unwrap();
+
+        (draw.first_index..draw.first_index + draw.index_count).map(|index|
+        {
+            let index = match format
+            {
+                IndexFormat::Uint16 => u16::from_le_bytes([ data[offset + index as usize * 2], data[offset + index as usize * 2 + 1] ]) as u32,
+                IndexFormat::Uint32 => read_u32(&data, offset + index as usize * 4)
+            };
+
+            index.wrapping_add_signed(draw.vertex_offset)
+        }).collect()
+    }
+    else
+    {
+        (draw.first_index..draw.first_index + draw.index_count).collect()
+    };
+
+    for instance_index in draw.first_instance..draw.first_instance + draw.instance_count
+    {
+        // every vertex is shaded, there is no post transform cache
+        counters.add(PipelineStatisticFlags::INPUT_ASSEMBLY_VERTICES, indices.len() as u64);
+        counters.add(PipelineStatisticFlags::VERTEX_SHADER_INVOCATIONS, indices.len() as u64);
+
+        let vertices: Vec<Option<SwVertex>> = indices.iter()
+            .map(|&vertex_index| rasterizer.to_window((pipeline.vertex)(&VertexInvocation
+            {
+                vertex_index,
+                instance_index,
+                first_instance: draw.first_instance,
+                layouts: &pipeline.vertex_buffers,
+                vertex_buffers: &state.vertex_buffers,
+                resources: rasterizer.resources
+            })))
+            .collect();
+
+        match pipeline.primitive
+        {
+            Primitive::PointList => vertices.chunks_exact(1).for_each(|point| rasterizer.primitive(point, &[ 0 ])),
+            Primitive::LineList => vertices.chunks_exact(2).for_each(|line| rasterizer.primitive(line, &[ 0, 1 ])),
+            Primitive::LineStrip => vertices.windows(2).for_each(|line| rasterizer.primitive(line, &[ 0, 1 ])),
+            Primitive::TriangleList => vertices.chunks_exact(3).for_each(|triangle| rasterizer.primitive(triangle, &[ 0, 1, 2 ])),
+            // every other strip triangle swaps its first two vertices to keep the winding consistent
+            Primitive::TriangleStrip => vertices.windows(3).enumerate()
+                .for_each(|(index, triangle)| rasterizer.primitive(triangle, if index % 2 == 0 { &[ 0, 1, 2 ] } else { &[ 1, 0, 2 ] })),
+            // TODO: tessellation
+            Primitive::PatchList(_) => {}
+        }
+    }
+}
+
+struct SwVertex
+{
+    // window x and y, normalized device z and 1/w
+    window: [f32; 4],
+    varyings: Vec<f32>
+}
+
+struct SwRasterizer<'a>
+{
+    pipeline: &'a SwRenderPipeline,
+    resources: ShaderResources<'a>,
+    colors: Vec<&'a SwImageView>,
+    depth: Option<&'a SwImageView>,
+    viewport: Viewport,
+    // scissor clamped to the framebuffer, min x, min y, max x, max y with the max exclusive
+    bounds: [u32; 4],
+    depth_bias: Option<DepthBias>,
+    blend_constants: [f32; 4],
+    line_width: f32,
+    counters: &'a SwCounters
+}
+
+impl<'a> SwRasterizer<'a>
+{
+    fn to_window(&self, output: VertexOutput) -> Option<SwVertex>
+    {
+        let [ x, y, z, w ] = output.position;
+        let viewport = &self.viewport;
+
+        // TODO: clip against the near plane instead of dropping primitives with vertices behind the eye
+        if w <= 0.0
+        {
+            return None;
+        }
+
+        Some(SwVertex
+        {
+            window: [ viewport.x + (x / w + 1.0) * 0.5 * viewport.width, viewport.y + (y / w + 1.0) * 0.5 * viewport.height, z / w, 1.0 / w ],
+            varyings: output.varyings
+        })
+    }
+
+    fn primitive(&self, vertices: &[Option<SwVertex>], order: &[usize])
+    {
+        self.counters.add(PipelineStatisticFlags::INPUT_ASSEMBLY_PRIMITIVES, 1);
+        self.counters.add(PipelineStatisticFlags::CLIPPING_INVOCATIONS, 1);
+
+        let Some(vertices) = order.iter().map(|index| vertices[*index].as_ref()).collect::<Option<Vec<&SwVertex>>>() else { return };
+        self.counters.add(PipelineStatisticFlags::CLIPPING_PRIMITIVES, 1);
+
+        match vertices[..]
+        {
+            [ a ] => self.point(a, true, 0.0),
+            [ a, b ] => self.line(a, b, true, 0.0),
+            [ a, b, c ] => self.triangle([ a, b, c ]),
+            _ => {}
+        }
+    }
+
+    fn triangle(&self, vertices: [&SwVertex; 3])
+    {
+        let [ a, b, c ] = vertices.map(|vertex| vertex.window);
+        let area = edge(a, b, c[0], c[1]);
+        if area == 0.0 || area.is_nan()
+        {
+            return;
+        }
+
+        // same convention as vulkan, y points down so counter clockwise triangles have a negative area here
+        let front_facing = match self.pipeline.front_face
+        {
+            FrontFace::CounterClockwise => area < 0.0,
+            FrontFace::Clockwise => area > 0.0
+        };
+
+        match (self.pipeline.cull_mode, front_facing)
+        {
+            (Some(CullMode::Front), true) | (Some(CullMode::Back), false) => return,
+            _ => {}
+        }
+
+        let bias = self.depth_bias.map_or(0.0, |depth_bias| self.triangle_bias(&depth_bias, [ a, b, c ], area));
+
+        match self.pipeline.polygon_mode
+        {
+            PolygonMode::Point => return vertices.iter().for_each(|vertex| self.point(vertex, front_facing, bias)),
+            PolygonMode::Line => return (0..3).for_each(|index| self.line(vertices[index], vertices[(index + 1) % 3], front_facing, bias)),
+            PolygonMode::Fill => {}
+        }
+
+        let vertices = if area > 0.0 { vertices } else { [ vertices[0], vertices[2], vertices[1] ] };
+        let [ a, b, c ] = vertices.map(|vertex| vertex.window);
+        let area = area.abs();
+
+        let min_x = a[0].min(b[0]).min(c[0]).floor().max(self.bounds[0] as f32) as u32;
+        let min_y = a[1].min(b[1]).min(c[1]).floor().max(self.bounds[1] as f32) as u32;
+        let max_x = (a[0].max(b[0]).max(c[0]).ceil().max(0.0) as u32).min(self.bounds[2]);
+        let max_y = (a[1].max(b[1]).max(c[1]).ceil().max(0.0) as u32).min(self.bounds[3]);
+
+        for y in min_y..max_y
+        {
+            for x in min_x..max_x
+            {
+                let (center_x, center_y) = (x as f32 + 0.5, y as f32 + 0.5);
+                let weights = [ edge(b, c, center_x, center_y), edge(c, a, center_x, center_y), edge(a, b, center_x, center_y) ];
+                let edges = [ (b, c), (c, a), (a, b) ];
+
+                // top-left rule, pixels on a shared edge belong to exactly one triangle
+                if weights.iter().zip(edges.iter()).any(|(weight, (from, to))| *weight < 0.0 || (*weight == 0.0 && !is_top_left(*from, *to)))
+                {
+                    continue;
+                }
+
+                self.fragment(x, y, &vertices, &weights.map(|weight| weight / area), front_facing, bias);
+            }
+        }
+    }
+
+    // one pixel per step along the major axis, the end point is left to the next segment. wide lines
+    // repeat every pixel along the minor axis like vulkan's non strict lines
+    fn line(&self, a: &SwVertex, b: &SwVertex, front_facing: bool, bias: f32)
+    {
+        let (dx, dy) = (b.window[0] - a.window[0], b.window[1] - a.window[1]);
+        let steps = dx.abs().max(dy.abs()).round().max(1.0) as u32;
+        let width = self.line_width.round().max(1.0) as i64;
+        let x_major = dx.abs() >= dy.abs();
+
+        for step in 0..steps
+        {
+            let t = step as f32 / steps as f32;
+            let (x, y) = ((a.window[0] + dx * t).floor() as i64, (a.window[1] + dy * t).floor() as i64);
+
+            for offset in (0..width).map(|index| index - (width - 1) / 2)
+            {
+                let (x, y) = if x_major { (x, y + offset) } else { (x + offset, y) };
+
+                if x >= 0 && y >= 0
+                {
+                    self.fragment(x as u32, y as u32, &[ a, b ], &[ 1.0 - t, t ], front_facing, bias);
+                }
+            }
+        }
+    }
+
+    // TODO: point size
+    fn point(&self, vertex: &SwVertex, front_facing: bool, bias: f32)
+    {
+        if vertex.window[0] >= 0.0 && vertex.window[1] >= 0.0
+        {
+            self.fragment(vertex.window[0] as u32, vertex.window[1] as u32, &[ vertex ], &[ 1.0 ], front_facing, bias);
+        }
+    }
+
+    // max depth slope times slope_factor plus the depth target's smallest step times constant_factor
+    fn triangle_bias(&self, depth_bias: &DepthBias, [ a, b, c ]: [[f32; 4]; 3], area: f32) -> f32
+    {
+        let scale = self.viewport.max_depth - self.viewport.min_depth;
+        let dz_dx = ((b[2] - a[2]) * (c[1] - a[1]) - (c[2] - a[2]) * (b[1] - a[1])) / area * scale;
+        let dz_dy = ((c[2] - a[2]) * (b[0] - a[0]) - (b[2] - a[2]) * (c[0] - a[0])) / area * scale;
+        let slope = dz_dx.abs().max(dz_dy.abs());
+
+        let resolution = match self.depth.map(|target| target.image.format)
+        {
+            Some(Format::D16Unorm) => 1.0 / 65535.0,
+            Some(Format::D24UnormS8Uint) => 1.0 / 16777215.0,
+            // floating point depth steps with the exponent of the largest depth in the triangle
+            _ =>
+            {
+                let max_depth = (self.viewport.min_depth + a[2].max(b[2]).max(c[2]) * scale).abs().max(f32::MIN_POSITIVE);
+                2f32.powi(max_depth.log2().floor() as i32 - 23)
+            }
+        };
+
+        let bias = slope * depth_bias.slope_factor + resolution * depth_bias.constant_factor;
+        match depth_bias.clamp
+        {
+            clamp if clamp > 0.0 => bias.min(clamp),
+            clamp if clamp < 0.0 => bias.max(clamp),
+            _ => bias
+        }
+    }
+
+    fn fragment(&self, x: u32, y: u32, vertices: &[&SwVertex], weights: &[f32], front_facing: bool, bias: f32)
+    {
+        if x < self.bounds[0] || y < self.bounds[1] || x >= self.bounds[2] || y >= self.bounds[3]
+        {
+            return;
+        }
+
+        let interpolate = |value: &dyn Fn(&SwVertex) -> f32| -> f32 { vertices.iter().zip(weights.iter()).map(|(vertex, weight)| value(vertex) * weight).sum() };
+        let inverse_w = interpolate(&|vertex| vertex.window[3]);
+
+        let z = interpolate(&|vertex| vertex.window[2]);
+        let z = match self.pipeline.depth_clamp
+        {
+            true => z.clamp(0.0, 1.0),
+            false if (0.0..=1.0).contains(&z) => z,
+            false => return
+        };
+
+        let viewport = &self.viewport;
+        let depth = viewport.min_depth + z * (viewport.max_depth - viewport.min_depth) + bias;
+
+        let varying_count = vertices.iter().map(|vertex| vertex.varyings.len()).min().unwrap_or(0);
+        let varyings: Vec<f32> = (0..varying_count)
+            .map(|index| interpolate(&|vertex| vertex.varyings[index] * vertex.window[3]) / inverse_w)
+            .collect();
+
+        if self.pipeline.fragment.is_some()
+        {
+            self.counters.add(PipelineStatisticFlags::FRAGMENT_SHADER_INVOCATIONS, 1);
+        }
+
+        let output = match self.pipeline.fragment
+        {
+            Some(main) => main(&FragmentInvocation
+            {
+                frag_coord: [ x as f32 + 0.5, y as f32 + 0.5, depth, inverse_w ],
+                front_facing,
+                varyings: &varyings,
+                resources: self.resources
+            }),
+            None => FragmentOutput::default()
+        };
+
+        if output.discard
+        {
+            return;
+        }
+
+        let depth = output.depth.unwrap_or(depth);
+        let depth_stencil = &self.pipeline.depth_stencil;
+
+        // TODO: stencil test
+        if let Some(target) = self.depth.filter(|_| depth_stencil.depth_test_enable)
+        {
+            if !compare(depth_stencil.depth_compare_op, depth, target.read_texel([ x, y, 0 ])[0])
+            {
+                return;
+            }
+
+            if depth_stencil.depth_write_enable
+            {
+                target.write_texel([ x, y, 0 ], [ depth, 0.0, 0.0, 1.0 ]);
+            }
+        }
+        self.counters.sample_passed();
+
+        if self.pipeline.fragment.is_some()
+        {
+            for ((target, color), color_target) in self.colors.iter().zip(output.colors.iter()).zip(self.pipeline.color_targets.iter())
+            {
+                let full_write = color_target.write_mask & ColorWriteFlags::ALL == ColorWriteFlags::ALL;
+                if !color_target.blend_enable && full_write
+                {
+                    target.write_texel([ x, y, 0 ], *color);
+                    continue;
+                }
+
+                let dst = target.read_texel([ x, y, 0 ]);
+                let mut value = match blend_range(target.image.format).filter(|_| color_target.blend_enable)
+                {
+                    Some(range) => blend(color_target, *color, output.dual_source, dst, self.blend_constants, range),
+                    None => *color
+                };
+
+                for (channel, value) in value.iter_mut().enumerate()
+                {
+                    if color_target.write_mask & (1 << channel) == 0
+                    {
+                        *value = dst[channel];
+                    }
+                }
+
+                target.write_texel([ x, y, 0 ], value);
+            }
+        }
+    }
+}
+
+fn edge(a: [f32; 4], b: [f32; 4], x: f32, y: f32) -> f32
+{
+    (b[0] - a[0]) * (y - a[1]) - (b[1] - a[1]) * (x - a[0])
+}
+
+// for triangles with a positive area, y pointing down
+fn is_top_left(from: [f32; 4], to: [f32; 4]) -> bool
+{
+    (from[1] == to[1] && to[0] > from[0]) || to[1] < from[1]
+}
+
+// normalized formats clamp every blend input to what they can store, integer formats don't blend
+fn blend_range(format: Format) -> Option<(f32, f32)>
+{
+    match format
+    {
+        Format::R8Uint | Format::R8Sint | Format::R16Uint | Format::R16Sint | Format::R32Uint | Format::R32Sint |
+        Format::R8G8B8A8Uint | Format::R32G32B32A32Uint => None,
+        Format::R8Snorm | Format::R16Snorm => Some((-1.0, 1.0)),
+        Format::R16Float | Format::R32Float | Format::R16G16Float | Format::R32G32Float | Format::R16G16B16A16Float |
+        Format::R32G32B32A32Float => Some((f32::NEG_INFINITY, f32::INFINITY)),
+        _ => Some((0.0, 1.0))
+    }
+}
+
+// follows the blend factor and operation tables in the vulkan spec, srgb targets blend in linear space
+fn blend(state: &ColorTargetState, src: [f32; 4], src1: [f32; 4], dst: [f32; 4], constants: [f32; 4], (min, max): (f32, f32)) -> [f32; 4]
+{
+    let clamp = |value: [f32; 4]| value.map(|component| component.clamp(min, max));
+    let (src, src1, dst, constants) = (clamp(src), clamp(src1), clamp(dst), clamp(constants));
+
+    let factor = |factor: BlendFactor, channel: usize| -> f32
+    {
+        match factor
+        {
+            BlendFactor::Zero => 0.0,
+            BlendFactor::One => 1.0,
+            BlendFactor::SrcColor => src[channel],
+            BlendFactor::OneMinusSrcColor => 1.0 - src[channel],
+            BlendFactor::DstColor => dst[channel],
+            BlendFactor::OneMinusDstColor => 1.0 - dst[channel],
+            BlendFactor::SrcAlpha => src[3],
+            BlendFactor::OneMinusSrcAlpha => 1.0 - src[3],
+            BlendFactor::DstAlpha => dst[3],
+            BlendFactor::OneMinusDstAlpha => 1.0 - dst[3],
+            BlendFactor::ConstantColor => constants[channel],
+            BlendFactor::OneMinusConstantColor => 1.0 - constants[channel],
+            BlendFactor::ConstantAlpha => constants[3],
+            BlendFactor::OneMinusConstantAlpha => 1.0 - constants[3],
+            BlendFactor::SrcAlphaSaturate if channel == 3 => 1.0,
+            BlendFactor::SrcAlphaSaturate => src[3].min(1.0 - dst[3]),
+            BlendFactor::Src1Color => src1[channel],
+            BlendFactor::OneMinusSrc1Color => 1.0 - src1[channel],
+            BlendFactor::Src1Alpha => src1[3],
+            BlendFactor::OneMinusSrc1Alpha => 1.0 - src1[3]
+        }
+    };
+
+    let mut result = [ 0.0; 4 ];
+    for (channel, result) in result.iter_mut().enumerate()
+    {
+        let (src_factor, dst_factor, op) = match channel
+        {
+            3 => (state.src_alpha_factor, state.dst_alpha_factor, state.alpha_op),
+            _ => (state.src_color_factor, state.dst_color_factor, state.color_op)
+        };
+
+        let (source, destination) = (src[channel] * factor(src_factor, channel), dst[channel] * factor(dst_factor, channel));
+        *result = match op
+        {
+            BlendOp::Add => source + destination,
+            BlendOp::Subtract => source - destination,
+            BlendOp::ReverseSubtract => destination - source,
+            BlendOp::Min => src[channel].min(dst[channel]),
+            BlendOp::Max => src[channel].max(dst[channel])
+        };
+    }
+
+    result
+}
+
+fn compare(op: CompareOp, reference: f32, stored: f32) -> bool
+{
+    match op
+    {
+        CompareOp::Never => false,
+        CompareOp::Less => reference < stored,
+        CompareOp::Equal => reference == stored,
+        CompareOp::LessOrEqual => reference <= stored,
+        CompareOp::Greater => reference > stored,
+        CompareOp::NotEqual => reference != stored,
+        CompareOp::GreaterOrEqual => reference >= stored,
+        CompareOp::Always => true
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32
+{
+    u32::from_le_bytes([ data[offset], data[offset + 1], data[offset + 2], data[offset + 3] ])
+}
+
+fn decode_vertex(format: VertexFormat, bytes: &[u8]) -> [f32; 4]
+{
+    let word = |index: usize| read_u32(bytes, index * 4);
+    let half = |index: usize| f16_to_f32(u16::from_le_bytes([ bytes[index * 2], bytes[index * 2 + 1] ]));
+    let unorm16 = |index: usize| u16::from_le_bytes([ bytes[index * 2], bytes[index * 2 + 1] ]) as f32 / 65535.0;
+    let components = |count: usize, component: &dyn Fn(usize) -> f32|
+    {
+        let mut value = [ 0.0, 0.0, 0.0, 1.0 ];
+        for (index, component_value) in value.iter_mut().enumerate().take(count)
+        {
+            *component_value = component(index);
+        }
+        value
+    };
+
+    match format
+    {
+        VertexFormat::Float32 => components(1, &|index| f32::from_bits(word(index))),
+        VertexFormat::Float32x2 => components(2, &|index| f32::from_bits(word(index))),
+        VertexFormat::Float32x3 => components(3, &|index| f32::from_bits(word(index))),
+        VertexFormat::Float32x4 => components(4, &|index| f32::from_bits(word(index))),
+        VertexFormat::Uint32 => components(1, &|index| word(index) as f32),
+        VertexFormat::Uint32x2 => components(2, &|index| word(index) as f32),
+        VertexFormat::Uint32x3 => components(3, &|index| word(index) as f32),
+        VertexFormat::Uint32x4 => components(4, &|index| word(index) as f32),
+        VertexFormat::Sint32 => components(1, &|index| word(index) as i32 as f32),
+        VertexFormat::Sint32x2 => components(2, &|index| word(index) as i32 as f32),
+        VertexFormat::Sint32x3 => components(3, &|index| word(index) as i32 as f32),
+        VertexFormat::Sint32x4 => components(4, &|index| word(index) as i32 as f32),
+        VertexFormat::Float16x2 => components(2, &half),
+        VertexFormat::Float16x4 => components(4, &half),
+        VertexFormat::Unorm16x2 => components(2, &unorm16),
+        VertexFormat::Unorm16x4 => components(4, &unorm16),
+        VertexFormat::Unorm8x4 => components(4, &|index| bytes[index] as f32 / 255.0),
+        VertexFormat::Snorm8x4 => components(4, &|index| (bytes[index] as i8 as f32 / 127.0).max(-1.0)),
+        VertexFormat::Uint8x4 => components(4, &|index| bytes[index] as f32)
+    }
+}
+
+pub fn decode_texel(format: Format, bytes: &[u8]) -> [f32; 4]
+{
+    let unorm8 = |index: usize| bytes[index] as f32 / 255.0;
+    let half = |index: usize| f16_to_f32(u16::from_le_bytes([ bytes[index], bytes[index + 1] ]));
+    let float = |index: usize| f32::from_le_bytes([ bytes[index], bytes[index + 1], bytes[index + 2], bytes[index + 3] ]);
+    let word = |index: usize| u32::from_le_bytes([ bytes[index], bytes[index + 1], bytes[index + 2], bytes[index + 3] ]);
+
+    match format
+    {
+        Format::Undefined => [ 0.0, 0.0, 0.0, 0.0 ],
+        Format::R8Unorm => [ unorm8(0), 0.0, 0.0, 1.0 ],
+        Format::R8Snorm => [ (bytes[0] as i8 as f32 / 127.0).max(-1.0), 0.0, 0.0, 1.0 ],
+        Format::R8Uint => [ bytes[0] as f32, 0.0, 0.0, 1.0 ],
+        Format::R8Sint => [ bytes[0] as i8 as f32, 0.0, 0.0, 1.0 ],
+        Format::R16Uint => [ u16::from_le_bytes([ bytes[0], bytes[1] ]) as f32, 0.0, 0.0, 1.0 ],
+        Format::R16Sint => [ i16::from_le_bytes([ bytes[0], bytes[1] ]) as f32, 0.0, 0.0, 1.0 ],
+        Format::R16Unorm | Format::D16Unorm => [ u16::from_le_bytes([ bytes[0], bytes[1] ]) as f32 / 65535.0, 0.0, 0.0, 1.0 ],
+        Format::R16Snorm => [ (i16::from_le_bytes([ bytes[0], bytes[1] ]) as f32 / 32767.0).max(-1.0), 0.0, 0.0, 1.0 ],
+        Format::R16Float => [ half(0), 0.0, 0.0, 1.0 ],
+        Format::R32Uint => [ word(0) as f32, 0.0, 0.0, 1.0 ],
+        Format::R32Sint => [ word(0) as i32 as f32, 0.0, 0.0, 1.0 ],
+        Format::R32Float | Format::D32Float | Format::D32FloatS8Uint => [ float(0), 0.0, 0.0, 1.0 ],
+        Format::R8G8Unorm => [ unorm8(0), unorm8(1), 0.0, 1.0 ],
+        Format::R16G16Float => [ half(0), half(2), 0.0, 1.0 ],
+        Format::R32G32Float => [ float(0), float(4), 0.0, 1.0 ],
+        Format::R8G8B8A8Unorm => [ unorm8(0), unorm8(1), unorm8(2), unorm8(3) ],
+        Format::R8G8B8A8Srgb => [ srgb_to_linear(unorm8(0)), srgb_to_linear(unorm8(1)), srgb_to_linear(unorm8(2)), unorm8(3) ],
+        Format::R8G8B8A8Uint => [ bytes[0] as f32, bytes[1] as f32, bytes[2] as f32, bytes[3] as f32 ],
+        Format::B8G8R8A8Unorm => [ unorm8(2), unorm8(1), unorm8(0), unorm8(3) ],
+        Format::B8G8R8A8Srgb => [ srgb_to_linear(unorm8(2)), srgb_to_linear(unorm8(1)), srgb_to_linear(unorm8(0)), unorm8(3) ],
+        Format::A2B10G10R10Unorm =>
+        {
+            let packed = word(0);
+            [
+                (packed & 0x3FF) as f32 / 1023.0,
+                ((packed >> 10) & 0x3FF) as f32 / 1023.0,
+                ((packed >> 20) & 0x3FF) as f32 / 1023.0,
+                (packed >> 30) as f32 / 3.0
+            ]
+        },
+        Format::R16G16B16A16Float => [ half(0), half(2), half(4), half(6) ],
+        Format::R32G32B32A32Uint => [ word(0) as f32, word(4) as f32, word(8) as f32, word(12) as f32 ],
+        Format::R32G32B32A32Float => [ float(0), float(4), float(8), float(12) ],
+        Format::D24UnormS8Uint => [ (word(0) & 0xFFFFFF) as f32 / 16777215.0, 0.0, 0.0, 1.0 ]
+    }
+}
+
+pub fn encode_texel(format: Format, value: [f32; 4], bytes: &mut [u8])
+{
+    let unorm8 = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let snorm8 = |value: f32| (value.clamp(-1.0, 1.0) * 127.0).round() as i8 as u8;
+    let unorm16 = |value: f32| (value.clamp(0.0, 1.0) * 65535.0).round() as u16;
+
+    match format
+    {
+        Format::Undefined => {},
+        Format::R8Unorm => { bytes[0] = unorm8(value[0]); },
+        Format::R8Snorm => { bytes[0] = snorm8(value[0]); },
+        Format::R8Uint => { bytes[0] = value[0] as u8; },
+        Format::R8Sint => { bytes[0] = value[0] as i8 as u8; },
+        Format::R16Uint => { bytes.copy_from_slice(&(value[0] as u16).to_le_bytes()); },
+        Format::R16Sint => { bytes.copy_from_slice(&(value[0] as i16).to_le_bytes()); },
+        Format::R16Unorm | Format::D16Unorm => { bytes.copy_from_slice(&unorm16(value[0]).to_le_bytes()); },
+        Format::R16Snorm => { bytes.copy_from_slice(&((value[0].clamp(-1.0, 1.0) * 32767.0).round() as i16).to_le_bytes()); },
+        Format::R16Float => { bytes.copy_from_slice(&f32_to_f16(value[0]).to_le_bytes()); },
+        Format::R32Uint => { bytes.copy_from_slice(&(value[0] as u32).to_le_bytes()); },
+        Format::R32Sint => { bytes.copy_from_slice(&(value[0] as i32).to_le_bytes()); },
+        Format::R32Float | Format::D32Float => { bytes.copy_from_slice(&value[0].to_le_bytes()); },
+        Format::D32FloatS8Uint => { bytes[0..4].copy_from_slice(&value[0].to_le_bytes()); },
+        Format::R8G8Unorm => { bytes[0] = unorm8(value[0]); bytes[1] = unorm8(value[1]); },
+        Format::R16G16Float =>
+        {
+            bytes[0..2].copy_from_slice(&f32_to_f16(value[0]).to_le_bytes());
+            bytes[2..4].copy_from_slice(&f32_to_f16(value[1]).to_le_bytes());
+        },
+        Format::R32G32Float =>
+        {
+            bytes[0..4].copy_from_slice(&value[0].to_le_bytes());
+            bytes[4..8].copy_from_slice(&value[1].to_le_bytes());
+        },
+        Format::R8G8B8A8Unorm =>
+        {
+            bytes.copy_from_slice(&[ unorm8(value[0]), unorm8(value[1]), unorm8(value[2]), unorm8(value[3]) ]);
+        },
+        Format::R8G8B8A8Srgb =>
+        {
+            bytes.copy_from_slice(&[ unorm8(linear_to_srgb(value[0])), unorm8(linear_to_srgb(value[1])), unorm8(linear_to_srgb(value[2])), unorm8(value[3]) ]);
+        },
+        Format::R8G8B8A8Uint =>
+        {
+            bytes.copy_from_slice(&[ value[0] as u8, value[1] as u8, value[2] as u8, value[3] as u8 ]);
+        },
+        Format::B8G8R8A8Unorm =>
+        {
+            bytes.copy_from_slice(&[ unorm8(value[2]), unorm8(value[1]), unorm8(value[0]), unorm8(value[3]) ]);
+        },
+        Format::B8G8R8A8Srgb =>
+        {
+            bytes.copy_from_slice(&[ unorm8(linear_to_srgb(value[2])), unorm8(linear_to_srgb(value[1])), unorm8(linear_to_srgb(value[0])), unorm8(value[3]) ]);
+        },
+        Format::A2B10G10R10Unorm =>
+        {
+            let unorm10 = |value: f32| (value.clamp(0.0, 1.0) * 1023.0).round() as u32;
+            let packed = unorm10(value[0]) | (unorm10(value[1]) << 10) | (unorm10(value[2]) << 20) | (((value[3].clamp(0.0, 1.0) * 3.0).round() as u32) << 30);
+            bytes.copy_from_slice(&packed.to_le_bytes());
+        },
+        Format::R16G16B16A16Float =>
+        {
+            for (index, component) in value.iter().enumerate()
+            {
+                bytes[index * 2..index * 2 + 2].copy_from_slice(&f32_to_f16(*component).to_le_bytes());
+            }
+        },
+        Format::R32G32B32A32Uint =>
+        {
+            for (index, component) in value.iter().enumerate()
+            {
+                bytes[index * 4..index * 4 + 4].copy_from_slice(&(*component as u32).to_le_bytes());
+            }
+        },
+        Format::R32G32B32A32Float =>
+        {
+            for (index, component) in value.iter().enumerate()
+            {
+                bytes[index * 4..index * 4 + 4].copy_from_slice(&component.to_le_bytes());
+            }
+        },
+        Format::D24UnormS8Uint =>
+        {
+            let stencil = bytes[3] as u32;
+            let packed = ((value[0].clamp(0.0, 1.0) * 16777215.0).round() as u32) | (stencil << 24);
+            bytes.copy_from_slice(&packed.to_le_bytes());
+        }
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32
+{
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> f32
+{
+    if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+fn f16_to_f32(half: u16) -> f32
+{
+    let sign = ((half >> 15) as u32) << 31;
+    let exponent = ((half >> 10) & 0x1F) as u32;
+    let mantissa = (half & 0x3FF) as u32;
+
+    let bits = match (exponent, mantissa)
+    {
+        (0, 0) => sign,
+        (0, _) =>
+        {
+            // subnormal half, renormalize it for f32
+            let shift = mantissa.leading_zeros() - 21;
+            sign | ((113 - shift) << 23) | (((mantissa << shift) & 0x3FF) << 13)
+        },
+        (0x1F, _) => sign | 0x7F800000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13)
+    };
+
+    f32::from_bits(bits)
+}
+
+fn f32_to_f16(value: f32) -> u16
+{
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x7FFFFF;
+
+    if exponent == 0xFF
+    {
+        return sign | 0x7C00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+
+    let exponent = exponent - 112;
+    if exponent >= 0x1F
+    {
+        return sign | 0x7C00;
+    }
+
+    if exponent <= 0
+    {
+        if exponent < -10
+        {
+            return sign;
+        }
+
+        let mantissa = mantissa | 0x800000;
+        let shift = (14 - exponent) as u32;
+        let rounded = (mantissa + (1 << (shift - 1))) >> shift;
+        return sign | rounded as u16;
+    }
+
+    let rounded = (((exponent as u32) << 10) | (mantissa >> 13)) + ((mantissa >> 12) & 1);
+    sign | rounded as u16
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn device() -> Device
+    {
+        Device { internal: Box::new(SwDevice { }) }
+    }
+
+    fn submit(device: &Device, encoder: CommandEncoder)
+    {
+        let command_buffer = encoder.finish().unwrap();
+        device.get_device_queue().unwrap().submit(&SubmitInfo { command_buffers: &[ &command_buffer ], ..Default::default() }).unwrap();
+    }
+
+    fn color_attachment(format: Format, load_op: LoadOp) -> AttachmentDescription
+    {
+        AttachmentDescription
+        {
+            format,
+            samples: 1,
+            operations: Operations { load_op, store_op: StoreOp::Store },
+            stencil_operations: Operations { load_op: LoadOp::DontCare, store_op: StoreOp::DontCare },
+            initial_layout: None,
+            final_layout: ImageLayout::ColorAttachmentOptimal
+        }
+    }
+
+    fn color_render_pass(device: &Device, attachments: Vec<AttachmentDescription>) -> RenderPass
+    {
+        let color_attachments = (0..attachments.len() as u32)
+            .map(|attachment| AttachmentReference { attachment, layout: ImageLayout::ColorAttachmentOptimal })
+            .collect();
+
+        device.create_render_pass(&RenderPassCreateInfo
+        {
+            attachments,
+            subpasses: vec![ SubpassDescription { color_attachments, resolve_attachments: vec![], depth_stencil_attachment: None } ],
+            dependencies: vec![],
+            label: None
+        }).unwrap()
+    }
+
+    fn view(device: &Device, image: &Image) -> ImageView
+    {
+        device.create_image_view(&ImageViewCreateInfo { image, base_mip_level: 0, mip_level_count: 1, base_array_layer: 0, array_layer_count: 1, label: None }).unwrap()
+    }
+
+    fn double(invocation: &ComputeInvocation)
+    {
+        let index = invocation.global_invocation_id[0] as u64;
+        let value = invocation.load_f32(0, 0, index * 4);
+        invocation.store_f32(0, 0, index * 4, value * 2.0);
+        invocation.atomic_add_u32(0, 1, 0, 1);
+        invocation.store_texel(0, 2, [ invocation.global_invocation_id[0], 0, 0 ], [ value / 10.0, 0.5, 1.0, 1.0 ]);
+    }
+
+    // a triangle strip covering the whole viewport at depth 0.5
+    fn quad(invocation: &VertexInvocation) -> VertexOutput
+    {
+        let [ x, y ] = [ [ -1.0, -1.0 ], [ 1.0, -1.0 ], [ -1.0, 1.0 ], [ 1.0, 1.0 ] ][invocation.vertex_index as usize];
+        VertexOutput { position: [ x, y, 0.5, 1.0 ], varyings: vec![] }
+    }
+
+    // the upper left half of the viewport at depth 0.25
+    fn corner(invocation: &VertexInvocation) -> VertexOutput
+    {
+        let [ x, y ] = [ [ -1.0, -1.0 ], [ 1.0, -1.0 ], [ -1.0, 1.0 ] ][invocation.vertex_index as usize];
+        VertexOutput { position: [ x, y, 0.25, 1.0 ], varyings: vec![] }
+    }
+
+    fn red(_invocation: &FragmentInvocation) -> FragmentOutput
+    {
+        FragmentOutput::color([ 1.0, 0.0, 0.0, 1.0 ])
+    }
+
+    fn green(_invocation: &FragmentInvocation) -> FragmentOutput
+    {
+        FragmentOutput::color([ 0.0, 1.0, 0.0, 1.0 ])
+    }
+
+    fn shader(device: &Device, stage: ShaderStage, source: SoftwareShader) -> ShaderModule
+    {
+        device.create_shader_module(&ShaderModuleCreateInfo { stage, source: ShaderModuleSource::Software(source), label: None }).unwrap()
+    }
+
+    fn render_pipeline(device: &Device, render_pass: &RenderPass, size: f32, stages: &[&ShaderModule], primitive: Primitive, depth_stencil: DepthStencilState, dynamic_state: u32) -> RenderPipeline
+    {
+        device.create_render_pipeline(&RenderPipelineCreateInfo
+        {
+            stages,
+            vertex_buffers: &[],
+            primitive,
+            polygon_mode: PolygonMode::Fill,
+            cull_mode: None,
+            front_face: FrontFace::CounterClockwise,
+            viewport: Viewport { x: 0.0, y: 0.0, width: size, height: size, min_depth: 0.0, max_depth: 1.0 },
+            depth_stencil,
+            depth_bias: None,
+            depth_clamp: false,
+            multisample: Default::default(),
+            color_targets: &[],
+            dynamic_state,
+            set_layouts: &[],
+            rendering: PipelineRendering::RenderPass { render_pass, subpass: 0 },
+            pipeline_cache: None,
+            label: None
+        }).unwrap()
+    }
+
+    #[test]
+    fn dispatch_indirect_runs_compute_shaders()
+    {
+        let device = device();
+        let values = device.create_buffer(&BufferCreateInfo { size: 32, usage: BufferUsageFlags::STORAGE, location: MemoryLocation::CpuToGpu, label: None }).unwrap();
+        let counter = device.create_buffer(&BufferCreateInfo { size: 16, usage: BufferUsageFlags::STORAGE | BufferUsageFlags::INDIRECT, location: MemoryLocation::CpuToGpu, label: None }).unwrap();
+        device.write_buffer(&values, 0, &(0..8).flat_map(|value| (value as f32).to_le_bytes()).collect::<Vec<u8>>()).unwrap();
+        device.write_buffer(&counter, 4, &[ 2, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0 ]).unwrap();
+        let image = device.create_image(&ImageCreateInfo { width: 8, usage: ImageUsageFlags::STORAGE, ..Default::default() }).unwrap();
+        let image_view = view(&device, &image);
+
+        let layout = device.create_descriptor_set_layout(&DescriptorSetLayoutCreateInfo
+        {
+            bindings: vec!
+            [
+                DescriptorSetLayoutBinding { binding: 0, descriptor_type: DescriptorType::StorageBuffer, count: 1, stages: ShaderStageFlags::COMPUTE },
+                DescriptorSetLayoutBinding { binding: 1, descriptor_type: DescriptorType::StorageBuffer, count: 1, stages: ShaderStageFlags::COMPUTE },
+                DescriptorSetLayoutBinding { binding: 2, descriptor_type: DescriptorType::StorageImage, count: 1, stages: ShaderStageFlags::COMPUTE }
+            ],
+            label: None
+        }).unwrap();
+        let descriptor_set = device.create_descriptor_set(&DescriptorSetCreateInfo
+        {
+            layout: &layout,
+            writes: &
+            [
+                DescriptorWrite { binding: 0, resource: DescriptorResource::Buffer { buffer: &values, offset: 0, size: 32 } },
+                DescriptorWrite { binding: 1, resource: DescriptorResource::Buffer { buffer: &counter, offset: 0, size: 16 } },
+                DescriptorWrite { binding: 2, resource: DescriptorResource::Image(&image_view) }
+            ],
+            label: None
+        }).unwrap();
+        let shader = device.create_shader_module(&ShaderModuleCreateInfo
+        {
+            stage: ShaderStage::Compute,
+            source: ShaderModuleSource::Software(SoftwareShader::Compute { workgroup_size: [ 4, 1, 1 ], main: double }),
+            label: None
+        }).unwrap();
+        let pipeline = device.create_compute_pipeline(&ComputePipelineCreateInfo { shader: &shader, set_layouts: &[ &layout ], pipeline_cache: None, label: None }).unwrap();
+
+        let pool = device.create_command_pool(&device.get_device_queue().unwrap()).unwrap();
+        let mut encoder = pool.create_command_encoder().unwrap();
+        encoder.dispatch(1, 1, 1);
+        encoder.bind_compute_pipeline(&pipeline);
+        encoder.bind_descriptor_set(0, &descriptor_set);
+        encoder.dispatch_indirect(&counter, 4);
+        submit(&device, encoder);
+
+        let values: Vec<f32> = device.read_buffer(&values, 0, 32).unwrap().chunks(4).map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap())).collect();
+        assert_eq!(values, [ 0.0, 2.0, 4.0, 6.0, 8.0, 10.0, 12.0, 14.0 ]);
+        assert_eq!(device.read_buffer(&counter, 0, 4).unwrap(), [ 8, 0, 0, 0 ]);
+        assert_eq!(device.read_image(&image, &ImageRegion { x: 3, ..Default::default() }).unwrap(), [ 77, 128, 255, 255 ]);
+    }
+
+    #[test]
+    fn uploads_land_in_the_region()
+    {
+        let device = device();
+        let buffer = device.create_buffer(&BufferCreateInfo { size: 8, usage: BufferUsageFlags::TRANSFER_DST, location: MemoryLocation::GpuOnly, label: None }).unwrap();
+        assert!(device.upload_buffer(&buffer, 4, &[ 1, 2, 3, 4 ]).unwrap().wait(u64::MAX).unwrap());
+        assert_eq!(device.read_buffer(&buffer, 0, 8).unwrap(), [ 0, 0, 0, 0, 1, 2, 3, 4 ]);
+        assert!(device.upload_buffer(&buffer, 6, &[ 1, 2, 3 ]).is_err());
+
+        let image = device.create_image(&ImageCreateInfo { format: Format::R8Unorm, width: 4, height: 4, mip_levels: 2, array_layers: 2, ..Default::default() }).unwrap();
+        let region = ImageRegion { base_array_layer: 1, x: 1, y: 2, width: 2, height: 2, ..Default::default() };
+        device.upload_image(&image, &region, &[ 10, 11, 12, 13 ]).unwrap();
+        device.upload_image(&image, &ImageRegion { mip_level: 1, width: 2, height: 2, ..Default::default() }, &[ 20, 21, 22, 23 ]).unwrap();
+        assert_eq!(device.read_image(&image, &region).unwrap(), [ 10, 11, 12, 13 ]);
+        assert_eq!(device.read_image(&image, &ImageRegion { mip_level: 1, width: 2, height: 2, ..Default::default() }).unwrap(), [ 20, 21, 22, 23 ]);
+        assert_eq!(device.read_image(&image, &ImageRegion { width: 4, height: 4, ..Default::default() }).unwrap(), [ 0; 16 ]);
+
+        assert!(device.upload_image(&image, &region, &[ 0; 3 ]).is_err());
+        assert!(device.upload_image(&image, &ImageRegion { x: 3, width: 2, ..Default::default() }, &[ 0; 2 ]).is_err());
+        assert!(device.upload_image(&image, &ImageRegion { mip_level: 2, ..Default::default() }, &[ 0 ]).is_err());
+    }
+
+    #[test]
+    fn f16_conversions_round_trip()
+    {
+        for half in 0..=u16::MAX
+        {
+            let value = f16_to_f32(half);
+            if value.is_nan()
+            {
+                assert!(f32_to_f16(value) & 0x7C00 == 0x7C00 && f32_to_f16(value) & 0x3FF != 0);
+                continue;
+            }
+
+            assert_eq!(f32_to_f16(value), half, "{:#06x} -> {}", half, value);
+        }
+
+        assert_eq!(f32_to_f16(1.0), 0x3C00);
+        assert_eq!(f32_to_f16(-2.0), 0xC000);
+        assert_eq!(f32_to_f16(65504.0), 0x7BFF);
+        assert_eq!(f32_to_f16(65520.0), 0x7C00);
+        assert_eq!(f32_to_f16(f32::INFINITY), 0x7C00);
+        assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0xFC00);
+        assert_eq!(f32_to_f16(2.0f32.powi(-14)), 0x0400);
+        assert_eq!(f32_to_f16(2.0f32.powi(-24)), 0x0001);
+        assert_eq!(f32_to_f16(2.0f32.powi(-26)), 0x0000);
+        assert_eq!(f32_to_f16(-0.0), 0x8000);
+        assert_eq!(f16_to_f32(0x3555), 0.333_251_95);
+    }
+
+    #[test]
+    fn blend_factors_and_operations()
+    {
+        let alpha = ColorTargetState
+        {
+            blend_enable: true,
+            src_color_factor: BlendFactor::SrcAlpha,
+            dst_color_factor: BlendFactor::OneMinusSrcAlpha,
+            src_alpha_factor: BlendFactor::One,
+            dst_alpha_factor: BlendFactor::OneMinusSrcAlpha,
+            ..Default::default()
+        };
+        let (src, dst, zero, unorm) = ([ 1.0, 0.0, 0.5, 0.5 ], [ 0.0, 0.5, 1.0, 1.0 ], [ 0.0; 4 ], (0.0, 1.0));
+        assert_eq!(blend(&alpha, src, zero, dst, zero, unorm), [ 0.5, 0.25, 0.75, 1.0 ]);
+
+        let op = |color_op: BlendOp, factor: BlendFactor| ColorTargetState { blend_enable: true, src_color_factor: factor, dst_color_factor: factor, color_op, src_alpha_factor: factor, dst_alpha_factor: factor, alpha_op: color_op, ..Default::default() };
+        assert_eq!(blend(&op(BlendOp::Subtract, BlendFactor::One), [ 0.75; 4 ], zero, [ 0.5; 4 ], zero, unorm), [ 0.25; 4 ]);
+        assert_eq!(blend(&op(BlendOp::ReverseSubtract, BlendFactor::One), [ 0.75; 4 ], zero, [ 0.5; 4 ], zero, unorm), [ -0.25; 4 ]);
+
+        // min and max ignore the factors
+        assert_eq!(blend(&op(BlendOp::Min, BlendFactor::Zero), [ 0.75; 4 ], zero, [ 0.5; 4 ], zero, unorm), [ 0.5; 4 ]);
+        assert_eq!(blend(&op(BlendOp::Max, BlendFactor::Zero), [ 0.75; 4 ], zero, [ 0.5; 4 ], zero, unorm), [ 0.75; 4 ]);
+
+        let constant = ColorTargetState { src_color_factor: BlendFactor::ConstantColor, dst_color_factor: BlendFactor::OneMinusConstantAlpha, src_alpha_factor: BlendFactor::SrcAlphaSaturate, dst_alpha_factor: BlendFactor::Zero, ..alpha };
+        assert_eq!(blend(&constant, [ 1.0, 1.0, 1.0, 0.5 ], zero, [ 1.0; 4 ], [ 0.25, 0.5, 0.75, 0.75 ], unorm), [ 0.5, 0.75, 1.0, 0.5 ]);
+
+        let saturate = ColorTargetState { src_color_factor: BlendFactor::SrcAlphaSaturate, dst_color_factor: BlendFactor::Zero, ..alpha };
+        assert_eq!(blend(&saturate, [ 1.0, 1.0, 1.0, 0.75 ], zero, [ 0.0, 0.0, 0.0, 0.5 ], zero, unorm), [ 0.5, 0.5, 0.5, 0.875 ]);
+
+        let dual_source = ColorTargetState { src_color_factor: BlendFactor::Src1Color, dst_color_factor: BlendFactor::OneMinusSrc1Alpha, ..alpha };
+        assert_eq!(blend(&dual_source, [ 1.0; 4 ], [ 0.25, 0.5, 0.0, 0.75 ], [ 1.0; 4 ], zero, unorm), [ 0.5, 0.75, 0.25, 1.0 ]);
+
+        // inputs clamp to what the target can store, float targets keep them
+        let add = ColorTargetState { blend_enable: true, dst_color_factor: BlendFactor::One, dst_alpha_factor: BlendFactor::One, ..Default::default() };
+        assert_eq!(blend(&add, [ 2.0, -1.0, 0.5, 1.0 ], zero, [ 0.0; 4 ], zero, unorm), [ 1.0, 0.0, 0.5, 1.0 ]);
+        assert_eq!(blend(&add, [ 2.0, -1.0, 0.5, 1.0 ], zero, [ 0.0; 4 ], zero, (-1.0, 1.0)), [ 1.0, -1.0, 0.5, 1.0 ]);
+        assert_eq!(blend(&add, [ 2.0, -1.0, 0.5, 1.0 ], zero, [ 0.0; 4 ], zero, (f32::NEG_INFINITY, f32::INFINITY)), [ 2.0, -1.0, 0.5, 1.0 ]);
+    }
+
+    #[test]
+    fn normalized_texels_round_to_nearest()
+    {
+        for format in [ Format::R8G8B8A8Unorm, Format::R8G8B8A8Srgb, Format::B8G8R8A8Srgb ]
+        {
+            for value in 0..=255u8
+            {
+                let mut bytes = [ 0; 4 ];
+                encode_texel(format, decode_texel(format, &[ value, value, value, value ]), &mut bytes);
+                assert_eq!(bytes, [ value; 4 ], "{:?} {}", format, value);
+            }
+        }
+
+        let encode = |format: Format, value: [f32; 4]| { let mut bytes = [ 0; 4 ]; encode_texel(format, value, &mut bytes); bytes };
+        assert_eq!(encode(Format::R8G8B8A8Unorm, [ 0.5, 0.2, 1.5, -0.5 ]), [ 128, 51, 255, 0 ]);
+        assert_eq!(encode(Format::R8G8B8A8Srgb, [ 0.5, 0.2, 0.002, 0.5 ]), [ 188, 124, 7, 128 ]);
+        assert_eq!(encode(Format::B8G8R8A8Srgb, [ 0.5, 0.2, 0.002, 0.5 ]), [ 7, 124, 188, 128 ]);
+        assert_eq!(decode_texel(Format::R8G8B8A8Srgb, &[ 0, 255, 10, 51 ]), [ 0.0, 1.0, 10.0 / 255.0 / 12.92, 0.2 ]);
+    }
+
+    fn translucent_red(_invocation: &FragmentInvocation) -> FragmentOutput
+    {
+        let mut output = FragmentOutput::color([ 1.0, 0.0, 0.0, 0.5 ]);
+        output.colors[1] = output.colors[0];
+        output
+    }
+
+    #[test]
+    fn blended_draws_land_on_known_pixels()
+    {
+        let device = device();
+        let formats = [ Format::R8G8B8A8Unorm, Format::R8G8B8A8Srgb ];
+        let images: Vec<Image> = formats.iter().map(|format| device.create_image(&ImageCreateInfo { format: *format, width: 2, height: 2, usage: ImageUsageFlags::COLOR_ATTACHMENT, ..Default::default() }).unwrap()).collect();
+        let views: Vec<ImageView> = images.iter().map(|image| view(&device, image)).collect();
+        let render_pass = color_render_pass(&device, formats.iter().map(|format| color_attachment(*format, LoadOp::Clear(0.2, 0.4, 1.0, 1.0))).collect());
+        let framebuffer = device.create_framebuffer(&FramebufferCreateInfo { render_pass: &render_pass, attachments: &[ &views[0], &views[1] ], width: 2, height: 2, layers: 1, label: None }).unwrap();
+
+        let vertex = shader(&device, ShaderStage::Vertex, SoftwareShader::Vertex { main: quad });
+        let fragment = shader(&device, ShaderStage::Fragment, SoftwareShader::Fragment { main: translucent_red });
+        let alpha = ColorTargetState
+        {
+            blend_enable: true,
+            src_color_factor: BlendFactor::SrcAlpha,
+            dst_color_factor: BlendFactor::OneMinusSrcAlpha,
+            src_alpha_factor: BlendFactor::One,
+            dst_alpha_factor: BlendFactor::OneMinusSrcAlpha,
+            ..Default::default()
+        };
+        let pipeline = device.create_render_pipeline(&RenderPipelineCreateInfo
+        {
+            stages: &[ &vertex, &fragment ],
+            vertex_buffers: &[],
+            primitive: Primitive::TriangleStrip,
+            polygon_mode: PolygonMode::Fill,
+            cull_mode: None,
+            front_face: FrontFace::CounterClockwise,
+            viewport: Viewport { x: 0.0, y: 0.0, width: 2.0, height: 2.0, min_depth: 0.0, max_depth: 1.0 },
+            depth_stencil: Default::default(),
+            depth_bias: None,
+            depth_clamp: false,
+            multisample: Default::default(),
+            color_targets: &[ alpha, alpha ],
+            dynamic_state: 0,
+            set_layouts: &[],
+            rendering: PipelineRendering::RenderPass { render_pass: &render_pass, subpass: 0 },
+            pipeline_cache: None,
+            label: None
+        }).unwrap();
+
+        let pool = device.create_command_pool(&device.get_device_queue().unwrap()).unwrap();
+        let mut encoder = pool.create_command_encoder().unwrap();
+        encoder.begin_render_pass(&render_pass, &framebuffer);
+        encoder.bind_render_pipeline(&pipeline);
+        encoder.draw(4, 1, 0, 0);
+        encoder.end_render_pass();
+        submit(&device, encoder);
+
+        // the srgb target decodes the stored clear color, blends in linear space and encodes the result again
+        let region = ImageRegion { width: 2, height: 2, ..Default::default() };
+        assert_eq!(device.read_image(&images[0], &region).unwrap(), [ 153, 51, 128, 255 ].repeat(4));
+        assert_eq!(device.read_image(&images[1], &region).unwrap(), [ 204, 124, 188, 255 ].repeat(4));
+    }
+
+    #[test]
+    fn readback_returns_the_uploaded_bytes()
+    {
+        let device = device();
+        let buffer = device.create_buffer(&BufferCreateInfo { size: 8, usage: BufferUsageFlags::TRANSFER_SRC, location: MemoryLocation::GpuOnly, label: None }).unwrap();
+        device.upload_buffer(&buffer, 0, &[ 1, 2, 3, 4, 5, 6, 7, 8 ]).unwrap();
+        assert_eq!(device.read_buffer(&buffer, 2, 3).unwrap(), [ 3, 4, 5 ]);
+        assert!(device.read_buffer(&buffer, 6, 3).is_err());
+
+        let capture = CapturedImage::from_texels(Format::B8G8R8A8Unorm, 2, 1, &[ 1, 2, 3, 4, 5, 6, 7, 8 ]).unwrap();
+        assert_eq!(capture.pixels, [ 3, 2, 1, 4, 7, 6, 5, 8 ]);
+        let texels: Vec<u8> = [ 1.0f32, 0.0, 0.5, 1.0 ].iter().flat_map(|value| value.to_le_bytes()).collect();
+        assert_eq!(CapturedImage::from_texels(Format::R32G32B32A32Float, 1, 1, &texels).unwrap().pixels, [ 255, 0, 128, 255 ]);
+        let texels: Vec<u8> = [ 0x3C00u16, 0x0000, 0x3800, 0xBC00 ].iter().flat_map(|value| value.to_le_bytes()).collect();
+        assert_eq!(CapturedImage::from_texels(Format::R16G16B16A16Float, 1, 1, &texels).unwrap().pixels, [ 255, 0, 128, 0 ]);
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn handles_are_shared_across_threads()
+    {
+        assert_send_sync::<Instance>();
+        assert_send_sync::<Device>();
+        assert_send_sync::<Queue>();
+        assert_send_sync::<Swapchain>();
+        assert_send_sync::<Image>();
+        assert_send_sync::<Buffer>();
+        assert_send_sync::<CommandPool>();
+        assert_send_sync::<CommandBuffer>();
+        assert_send_sync::<Fence>();
+
+        let device = Arc::new(device());
+        let buffer = device.create_buffer(&BufferCreateInfo { size: 64, usage: 0, location: MemoryLocation::CpuToGpu, label: None }).unwrap();
+        let threads: Vec<_> = (0..8u8)
+            .map(|index|
+            {
+                let device = device.clone();
+                let buffer = buffer.clone();
+                std::thread::spawn(move || device.write_buffer(&buffer, index as u64 * 8, &[ index; 8 ]).unwrap())
+            })
+            .collect();
+        for thread in threads
+        {
+            thread.join().unwrap();
+        }
+
+        let data = device.read_buffer(&buffer, 0, 64).unwrap();
+        assert!(data.chunks(8).enumerate().all(|(index, chunk)| chunk.iter().all(|byte| *byte == index as u8)));
+    }
+
+    #[test]
+    fn render_pass_clears_attachments()
+    {
+        let device = device();
+        let image = device.create_image(&ImageCreateInfo { width: 4, height: 4, usage: ImageUsageFlags::COLOR_ATTACHMENT, ..Default::default() }).unwrap();
+        let view = view(&device, &image);
+        let render_pass = color_render_pass(&device, vec![ color_attachment(Format::R8G8B8A8Unorm, LoadOp::Clear(1.0, 0.0, 0.0, 1.0)) ]);
+        let framebuffer = device.create_framebuffer(&FramebufferCreateInfo { render_pass: &render_pass, attachments: &[ &view ], width: 4, height: 4, layers: 1, label: None }).unwrap();
+
+        let pool = device.create_command_pool(&device.get_device_queue().unwrap()).unwrap();
+        let mut encoder = pool.create_command_encoder().unwrap();
+        encoder.begin_render_pass(&render_pass, &framebuffer);
+        encoder.end_render_pass();
+        submit(&device, encoder);
+
+        let pixels = device.read_image(&image, &ImageRegion { width: 4, height: 4, ..Default::default() }).unwrap();
+        assert!(pixels.chunks(4).all(|pixel| pixel == [ 255, 0, 0, 255 ]));
+    }
+
+    #[test]
+    fn submitted_command_buffers_persist_layouts()
+    {
+        let device = device();
+        let image = device.create_image(&ImageCreateInfo { width: 4, height: 4, array_layers: 2, usage: ImageUsageFlags::COLOR_ATTACHMENT, ..Default::default() }).unwrap();
+        let view = view(&device, &image);
+        let attachment = AttachmentDescription { final_layout: ImageLayout::ShaderReadOnlyOptimal, ..color_attachment(Format::R8G8B8A8Unorm, LoadOp::DontCare) };
+        let render_pass = color_render_pass(&device, vec![ attachment ]);
+        let framebuffer = device.create_framebuffer(&FramebufferCreateInfo { render_pass: &render_pass, attachments: &[ &view ], width: 4, height: 4, layers: 1, label: None }).unwrap();
+
+        let pool = device.create_command_pool(&device.get_device_queue().unwrap()).unwrap();
+        let mut encoder = pool.create_command_encoder().unwrap();
+        encoder.begin_render_pass(&render_pass, &framebuffer);
+        encoder.end_render_pass();
+        encoder.image_barrier(&ImageBarrier
+        {
+            image: &image,
+            subresource_range: Some(ImageSubresourceRange { base_mip_level: 0, mip_level_count: 1, base_array_layer: 1, array_layer_count: 1 }),
+            old_layout: None,
+            new_layout: ImageLayout::TransferDstOptimal,
+            src_stage_mask: PipelineStageFlags::TOP_OF_PIPE,
+            src_access_mask: 0,
+            dst_stage_mask: PipelineStageFlags::TRANSFER_BIT,
+            dst_access_mask: AccessFlags::TRANSFER_WRITE,
+            queue_transfer: None
+        });
+
+        // nothing reaches the image before the command buffer is submitted
+        let command_buffer = encoder.finish().unwrap();
+        assert_eq!(image.internal.current_layout(0, 0), None);
+        device.get_device_queue().unwrap().submit(&SubmitInfo { command_buffers: &[ &command_buffer ], ..Default::default() }).unwrap();
+        assert_eq!(image.internal.current_layout(0, 0), Some(ImageLayout::ShaderReadOnlyOptimal));
+        assert_eq!(image.internal.current_layout(0, 1), Some(ImageLayout::TransferDstOptimal));
+    }
+
+    #[test]
+    fn queries_count_samples_and_invocations()
+    {
+        let device = device();
+        let image = device.create_image(&ImageCreateInfo { width: 4, height: 4, usage: ImageUsageFlags::COLOR_ATTACHMENT, ..Default::default() }).unwrap();
+        let view = view(&device, &image);
+        let render_pass = color_render_pass(&device, vec![ color_attachment(Format::R8G8B8A8Unorm, LoadOp::Clear(0.0, 0.0, 0.0, 1.0)) ]);
+        let framebuffer = device.create_framebuffer(&FramebufferCreateInfo { render_pass: &render_pass, attachments: &[ &view ], width: 4, height: 4, layers: 1, label: None }).unwrap();
+        let vertex = shader(&device, ShaderStage::Vertex, SoftwareShader::Vertex { main: quad });
+        let fragment = shader(&device, ShaderStage::Fragment, SoftwareShader::Fragment { main: red });
+        let pipeline = render_pipeline(&device, &render_pass, 4.0, &[ &vertex, &fragment ], Primitive::TriangleStrip, Default::default(), 0);
+
+        let statistics = PipelineStatisticFlags::INPUT_ASSEMBLY_VERTICES | PipelineStatisticFlags::INPUT_ASSEMBLY_PRIMITIVES
+            | PipelineStatisticFlags::VERTEX_SHADER_INVOCATIONS | PipelineStatisticFlags::CLIPPING_PRIMITIVES
+            | PipelineStatisticFlags::FRAGMENT_SHADER_INVOCATIONS | PipelineStatisticFlags::COMPUTE_SHADER_INVOCATIONS;
+        let occlusion = device.create_query_pool(&QueryPoolCreateInfo { query_type: QueryType::Occlusion, count: 2, label: None }).unwrap();
+        let statistics = device.create_query_pool(&QueryPoolCreateInfo { query_type: QueryType::PipelineStatistics(statistics), count: 1, label: None }).unwrap();
+
+        let pool = device.create_command_pool(&device.get_device_queue().unwrap()).unwrap();
+        let mut encoder = pool.create_command_encoder().unwrap();
+        encoder.reset_query_pool(&occlusion, 0, 2);
+        encoder.reset_query_pool(&statistics, 0, 1);
+        encoder.begin_render_pass(&render_pass, &framebuffer);
+        encoder.bind_render_pipeline(&pipeline);
+        encoder.begin_query(&occlusion, 0);
+        encoder.begin_query(&statistics, 0);
+        encoder.draw(4, 1, 0, 0);
+        encoder.end_query(&statistics, 0);
+        encoder.end_query(&occlusion, 0);
+        encoder.begin_query(&occlusion, 1);
+        encoder.end_query(&occlusion, 1);
+        encoder.end_render_pass();
+        submit(&device, encoder);
+
+        assert_eq!(occlusion.get_results(0, 2, true).unwrap(), Some(vec![ 16, 0 ]));
+        assert_eq!(statistics.get_results(0, 1, true).unwrap(), Some(vec![ 4, 2, 4, 2, 16, 0 ]));
+    }
+
+    #[test]
+    fn triangles_cover_their_pixels_and_test_depth()
+    {
+        let device = device();
+        let color = device.create_image(&ImageCreateInfo { width: 8, height: 8, usage: ImageUsageFlags::COLOR_ATTACHMENT, ..Default::default() }).unwrap();
+        let depth = device.create_image(&ImageCreateInfo { format: Format::D32Float, width: 8, height: 8, usage: ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT, ..Default::default() }).unwrap();
+        let (color_view, depth_view) = (view(&device, &color), view(&device, &depth));
+        let render_pass = device.create_render_pass(&RenderPassCreateInfo
+        {
+            attachments: vec!
+            [
+                color_attachment(Format::R8G8B8A8Unorm, LoadOp::Clear(0.0, 0.0, 0.0, 1.0)),
+                AttachmentDescription { final_layout: ImageLayout::DepthStencilAttachmentOptimal, ..color_attachment(Format::D32Float, LoadOp::Clear(1.0, 0.0, 0.0, 0.0)) }
+            ],
+            subpasses: vec![ SubpassDescription
+            {
+                color_attachments: vec![ AttachmentReference { attachment: 0, layout: ImageLayout::ColorAttachmentOptimal } ],
+                resolve_attachments: vec![],
+                depth_stencil_attachment: Some(AttachmentReference { attachment: 1, layout: ImageLayout::DepthStencilAttachmentOptimal })
+            } ],
+            dependencies: vec![],
+            label: None
+        }).unwrap();
+        let framebuffer = device.create_framebuffer(&FramebufferCreateInfo { render_pass: &render_pass, attachments: &[ &color_view, &depth_view ], width: 8, height: 8, layers: 1, label: None }).unwrap();
+
+        let depth_stencil = DepthStencilState { depth_test_enable: true, depth_write_enable: true, ..Default::default() };
+        let shaders = [ (corner as fn(&VertexInvocation) -> VertexOutput, red as fn(&FragmentInvocation) -> FragmentOutput), (quad, green) ];
+        let pipelines: Vec<(RenderPipeline, Primitive)> = shaders.iter().zip([ Primitive::TriangleList, Primitive::TriangleStrip ]).map(|((vertex, fragment), primitive)|
+        {
+            let vertex = shader(&device, ShaderStage::Vertex, SoftwareShader::Vertex { main: *vertex });
+            let fragment = shader(&device, ShaderStage::Fragment, SoftwareShader::Fragment { main: *fragment });
+            (render_pipeline(&device, &render_pass, 8.0, &[ &vertex, &fragment ], primitive, depth_stencil, 0), primitive)
+        }).collect();
+
+        // the quad behind the triangle only shows where the triangle isn't
+        let pool = device.create_command_pool(&device.get_device_queue().unwrap()).unwrap();
+        let mut encoder = pool.create_command_encoder().unwrap();
+        encoder.begin_render_pass(&render_pass, &framebuffer);
+        for (pipeline, primitive) in pipelines.iter()
+        {
+            encoder.bind_render_pipeline(pipeline);
+            encoder.draw(if *primitive == Primitive::TriangleList { 3 } else { 4 }, 1, 0, 0);
+        }
+        encoder.end_render_pass();
+        submit(&device, encoder);
+
+        let region = ImageRegion { width: 8, height: 8, ..Default::default() };
+        let colors = device.read_image(&color, &region).unwrap();
+        let depths = device.read_image(&depth, &region).unwrap();
+        for (index, (color, depth)) in colors.chunks(4).zip(depths.chunks(4)).enumerate()
+        {
+            // pixel centers on the diagonal lie on the triangle's bottom right edge, which the top-left rule leaves out
+            let (x, y) = (index % 8, index / 8);
+            let (expected_color, expected_depth) = if x + y <= 6 { ([ 255, 0, 0, 255 ], 0.25) } else { ([ 0, 255, 0, 255 ], 0.5) };
+            assert_eq!(color, expected_color, "pixel {} {}", x, y);
+            assert_eq!(f32::from_le_bytes(depth.try_into().unwrap()), expected_depth, "pixel {} {}", x, y);
+        }
+    }
+
+    #[test]
+    fn missing_vertex_attributes_read_as_defaults()
+    {
+        let buffer = SwBuffer { data: Arc::new(Mutex::new([ 1.0f32, 2.0, 3.0, 4.0 ].iter().flat_map(|value| value.to_le_bytes()).collect())) };
+        let layouts = [ SwVertexBufferLayout
+        {
+            stride: 8,
+            step_mode: VertexStepMode::Vertex,
+            attributes: vec![ VertexAttribute { location: 0, offset: 0, format: VertexFormat::Float32x2 } ]
+        } ];
+        let bound = [ Some((buffer, 0)) ];
+        let invocation = |vertex_index: u32, vertex_buffers: &[Option<(SwBuffer, usize)>]| VertexInvocation
+        {
+            vertex_index,
+            instance_index: 0,
+            first_instance: 0,
+            layouts: &layouts,
+            vertex_buffers,
+            resources: ShaderResources { descriptor_sets: &[] }
+        }.attribute(0);
+
+        assert_eq!(invocation(1, &bound), [ 3.0, 4.0, 0.0, 1.0 ]);
+        // past the end of the buffer, nothing bound and a location without an attribute
+        assert_eq!(invocation(2, &bound), [ 0.0, 0.0, 0.0, 1.0 ]);
+        assert_eq!(invocation(0, &[]), [ 0.0, 0.0, 0.0, 1.0 ]);
+        assert_eq!(VertexInvocation { vertex_index: 0, instance_index: 0, first_instance: 0, layouts: &layouts, vertex_buffers: &bound, resources: ShaderResources { descriptor_sets: &[] } }.attribute(1), [ 0.0, 0.0, 0.0, 1.0 ]);
+    }
+
+    #[test]
+    fn line_widths_need_wide_lines()
+    {
+        let device = device();
+        let pool = device.create_command_pool(&device.get_device_queue().unwrap()).unwrap();
+        let mut encoder = pool.create_command_encoder().unwrap();
+        let line_width = |encoder: &CommandEncoder| encoder.downcast_ref::<SwCommandEncoder>().unwrap().dynamic_state.line_width;
+
+        encoder.set_line_width(4.0);
+        assert_eq!(line_width(&encoder), 4.0);
+        encoder.line_width_range = [ 1.0, 2.0 ];
+        encoder.set_line_width(4.0);
+        assert_eq!(line_width(&encoder), 2.0);
+        encoder.features &= !FeatureFlags::WIDE_LINES;
+        encoder.set_line_width(2.0);
+        assert_eq!(line_width(&encoder), 1.0);
+    }
+
+    #[test]
+    fn draws_without_state_are_dropped()
+    {
+        let device = device();
+        let pool = device.create_command_pool(&device.get_device_queue().unwrap()).unwrap();
+        let mut encoder = pool.create_command_encoder().unwrap();
+        encoder.draw(3, 1, 0, 0);
+        encoder.draw_indexed(3, 1, 0, 0, 0);
+
+        let image = device.create_image(&ImageCreateInfo { width: 4, height: 4, usage: ImageUsageFlags::COLOR_ATTACHMENT, ..Default::default() }).unwrap();
+        let view = view(&device, &image);
+        let render_pass = color_render_pass(&device, vec![ color_attachment(Format::R8G8B8A8Unorm, LoadOp::DontCare) ]);
+        let framebuffer = device.create_framebuffer(&FramebufferCreateInfo { render_pass: &render_pass, attachments: &[ &view ], width: 4, height: 4, layers: 1, label: None }).unwrap();
+        let vertex = shader(&device, ShaderStage::Vertex, SoftwareShader::Vertex { main: quad });
+        let pipeline = render_pipeline(&device, &render_pass, 4.0, &[ &vertex ], Primitive::TriangleStrip, Default::default(), 0);
+        let arguments = device.create_buffer(&BufferCreateInfo { size: 24, usage: BufferUsageFlags::INDIRECT, location: MemoryLocation::CpuToGpu, label: None }).unwrap();
+
+        encoder.begin_render_pass(&render_pass, &framebuffer);
+        encoder.bind_render_pipeline(&pipeline);
+        encoder.draw_indexed(4, 1, 0, 0, 0);
+        encoder.draw_indexed_indirect(&arguments, 0, 1, 20);
+        encoder.features &= !FeatureFlags::DRAW_INDIRECT_COUNT;
+        encoder.draw_indirect_count(&arguments, 0, &arguments, 20, 1, 16);
+        let dynamic = render_pipeline(&device, &render_pass, 4.0, &[ &vertex ], Primitive::TriangleStrip, Default::default(), DynamicStateFlags::VIEWPORT | DynamicStateFlags::SCISSOR);
+        encoder.bind_render_pipeline(&dynamic);
+        encoder.draw(4, 1, 0, 0);
+        encoder.set_viewport(&Viewport { x: 0.0, y: 0.0, width: 4.0, height: 4.0, min_depth: 0.0, max_depth: 1.0 });
+        encoder.draw(4, 1, 0, 0);
+        encoder.end_render_pass();
+
+        assert!(encoder.downcast_ref::<SwCommandEncoder>().unwrap().commands.iter().all(|command| matches!(command, SwCommand::BeginRenderPass { .. })));
+        submit(&device, encoder);
+    }
+
+    // the left half of the viewport at depth 0.5
+    fn left_half(invocation: &VertexInvocation) -> VertexOutput
+    {
+        let [ x, y ] = [ [ -1.0, -1.0 ], [ 0.0, -1.0 ], [ -1.0, 1.0 ], [ 0.0, 1.0 ] ][invocation.vertex_index as usize];
+        VertexOutput { position: [ x, y, 0.5, 1.0 ], varyings: vec![] }
+    }
+
+    fn rendering_info<'a>(color_attachments: &'a [RenderingAttachment<'a>], depth_view: &'a ImageView) -> RenderingInfo<'a>
+    {
+        RenderingInfo
+        {
+            color_attachments,
+            depth_stencil_attachment: Some(RenderingAttachment { view: depth_view, operations: Operations { load_op: LoadOp::Clear(1.0, 0.0, 0.0, 0.0), store_op: StoreOp::DontCare } }),
+            stencil_operations: Operations { load_op: LoadOp::DontCare, store_op: StoreOp::DontCare }
+        }
+    }
+
+    #[test]
+    fn dynamic_rendering_draws_without_a_render_pass()
+    {
+        let device = device();
+        let color = device.create_image(&ImageCreateInfo { width: 4, height: 4, usage: ImageUsageFlags::COLOR_ATTACHMENT, ..Default::default() }).unwrap();
+        let depth = device.create_image(&ImageCreateInfo { format: Format::D32Float, width: 4, height: 4, usage: ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT, ..Default::default() }).unwrap();
+        let (color_view, depth_view) = (view(&device, &color), view(&device, &depth));
+        let vertex = shader(&device, ShaderStage::Vertex, SoftwareShader::Vertex { main: left_half });
+        let fragment = shader(&device, ShaderStage::Fragment, SoftwareShader::Fragment { main: red });
+        let pipeline = device.create_render_pipeline(&RenderPipelineCreateInfo
+        {
+            stages: &[ &vertex, &fragment ],
+            vertex_buffers: &[],
+            primitive: Primitive::TriangleStrip,
+            polygon_mode: PolygonMode::Fill,
+            cull_mode: None,
+            front_face: FrontFace::CounterClockwise,
+            viewport: Viewport { x: 0.0, y: 0.0, width: 4.0, height: 4.0, min_depth: 0.0, max_depth: 1.0 },
+            depth_stencil: DepthStencilState { depth_test_enable: true, depth_write_enable: true, ..Default::default() },
+            depth_bias: None,
+            depth_clamp: false,
+            multisample: Default::default(),
+            color_targets: &[],
+            dynamic_state: 0,
+            set_layouts: &[],
+            rendering: PipelineRendering::Dynamic { color_formats: &[ Format::R8G8B8A8Unorm ], depth_stencil_format: Some(Format::D32Float) },
+            pipeline_cache: None,
+            label: None
+        }).unwrap();
+
+        let color_attachments = [ RenderingAttachment { view: &color_view, operations: Operations { load_op: LoadOp::Clear(0.0, 0.0, 1.0, 1.0), store_op: StoreOp::Store } } ];
+        let pool = device.create_command_pool(&device.get_device_queue().unwrap()).unwrap();
+        let mut encoder = pool.create_command_encoder().unwrap();
+        encoder.begin_rendering(&rendering_info(&color_attachments, &depth_view));
+        encoder.bind_render_pipeline(&pipeline);
+        encoder.draw(4, 1, 0, 0);
+        encoder.end_rendering();
+        submit(&device, encoder);
+
+        let colors = device.read_image(&color, &ImageRegion { width: 4, height: 4, ..Default::default() }).unwrap();
+        for (index, color) in colors.chunks(4).enumerate()
+        {
+            let expected = if index % 4 < 2 { [ 255, 0, 0, 255 ] } else { [ 0, 0, 255, 255 ] };
+            assert_eq!(color, expected, "pixel {} {}", index % 4, index / 4);
+        }
+
+        // without the feature the pair records nothing and the attachments keep their contents
+        let load = [ RenderingAttachment { view: &color_view, operations: Operations { load_op: LoadOp::Clear(0.0, 1.0, 0.0, 1.0), store_op: StoreOp::Store } } ];
+        let mut encoder = pool.create_command_encoder().unwrap();
+        encoder.features &= !FeatureFlags::DYNAMIC_RENDERING;
+        encoder.begin_rendering(&rendering_info(&load, &depth_view));
+        encoder.end_rendering();
+        assert!(encoder.downcast_ref::<SwCommandEncoder>().unwrap().commands.is_empty());
+        submit(&device, encoder);
+        assert_eq!(device.read_image(&color, &ImageRegion { width: 4, height: 4, ..Default::default() }).unwrap(), colors);
+    }
+
+    #[test]
+    fn devices_reject_invalid_extension_names()
+    {
+        let instance = Instance { api: API::Software, internal: Box::new(SwInstance::new().unwrap()) };
+        let surface = Surface { internal: Arc::new(SwSurface { width: 4, height: 4 }) };
+        let create_device = |optional_extensions: Vec<DeviceExtension>| instance.create_device(&surface, &DeviceCreateInfo { optional_extensions, ..Default::default() }).err();
+
+        assert_eq!(create_device(vec![ DeviceExtension::Named("VK_KHR_ray\0query".to_string()) ]), Some(DeviceError::InvalidExtensionName("VK_KHR_ray\0query".to_string())));
+        assert_eq!(create_device(vec![ DeviceExtension::Named(String::new()) ]), Some(DeviceError::InvalidExtensionName(String::new())));
+        assert_eq!(create_device(vec![ DeviceExtension::Named("VK_KHR_ray_query".to_string()) ]), None);
+    }
+
+    #[test]
+    fn vertex_layouts_reject_attributes_past_their_stride()
+    {
+        let validate = |stride: u32, offset: u32|
+        {
+            let attributes = [ VertexAttribute { location: 0, offset, format: VertexFormat::Float32x4 } ];
+            validate_vertex_buffers(&[ VertexBufferLayout { stride, step_mode: VertexStepMode::Vertex, attributes: &attributes } ])
+        };
+
+        assert_eq!(validate(16, 0), Ok(()));
+        assert_eq!(validate(16, 4), Err(PipelineError::InvalidVertexLayout));
+        assert_eq!(validate(0, u32::MAX - 16), Ok(()));
+        assert_eq!(validate(0, u32::MAX - 8), Err(PipelineError::InvalidVertexLayout));
+        assert_eq!(validate(16, u32::MAX), Err(PipelineError::InvalidVertexLayout));
+    }
+
+    #[test]
+    fn render_pipeline_stages()
+    {
+        use ShaderStage::*;
+
+        assert_eq!(validate_render_pipeline_stages(&[ Vertex, Fragment ], Primitive::TriangleList), Ok(()));
+        assert_eq!(validate_render_pipeline_stages(&[ Vertex, TessellationControl, TessellationEvaluation, Fragment ], Primitive::PatchList(3)), Ok(()));
+        assert_eq!(validate_render_pipeline_stages(&[ Vertex, Fragment ], Primitive::PatchList(3)), Err(PipelineError::InvalidPrimitive));
+        assert_eq!(validate_render_pipeline_stages(&[ Vertex, TessellationControl, TessellationEvaluation ], Primitive::TriangleList), Err(PipelineError::InvalidPrimitive));
+        assert_eq!(validate_render_pipeline_stages(&[ Vertex, TessellationEvaluation ], Primitive::PatchList(3)), Err(PipelineError::InvalidShaderStage));
+    }
+}
@@ -0,0 +1,658 @@
+use super::*;
+use std::collections::HashMap;
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum ImageAccess
+{
+    ColorAttachment,
+    DepthStencilAttachment,
+    DepthStencilRead,
+    // ShaderStageFlags
+    ShaderRead(u32),
+    ShaderWrite(u32),
+    TransferRead,
+    TransferWrite,
+    Present
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum BufferAccess
+{
+    VertexRead,
+    IndexRead,
+    IndirectRead,
+    // ShaderStageFlags
+    UniformRead(u32),
+    ShaderRead(u32),
+    ShaderWrite(u32),
+    TransferRead,
+    TransferWrite,
+    HostRead
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Access
+{
+    pub stage: u32,
+    pub access: u32,
+    pub layout: Option<ImageLayout>,
+    pub write: bool
+}
+
+fn pipeline_stages(stages: u32) -> u32
+{
+    let mut flags = 0;
+
+    if stages & ShaderStageFlags::VERTEX != 0 { flags |= PipelineStageFlags::VERTEX_SHADER; }
+    if stages & ShaderStageFlags::TESSELLATION_CONTROL != 0 { flags |= PipelineStageFlags::TESSELLATION_CONTROL_SHADER; }
+    if stages & ShaderStageFlags::TESSELLATION_EVALUATION != 0 { flags |= PipelineStageFlags::TESSELLATION_EVALUATION_SHADER; }
+    if stages & ShaderStageFlags::GEOMETRY != 0 { flags |= PipelineStageFlags::GEOMETRY_SHADER; }
+    if stages & ShaderStageFlags::FRAGMENT != 0 { flags |= PipelineStageFlags::FRAGMENT_SHADER; }
+    if stages & ShaderStageFlags::COMPUTE != 0 { flags |= PipelineStageFlags::COMPUTE_SHADER; }
+
+    flags
+}
+
+impl ImageAccess
+{
+    pub(crate) fn access(&self) -> Access
+    {
+        let (stage, access, layout, write) = match self
+        {
+            ImageAccess::ColorAttachment => (
+                PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                AccessFlags::COLOR_ATTACHMENT_READ | AccessFlags::COLOR_ATTACHMENT_WRITE,
+                ImageLayout::ColorAttachmentOptimal,
+                true),
+            ImageAccess::DepthStencilAttachment => (
+                PipelineStageFlags::EARLY_FRAGMENT_TESTS | PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ | AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                ImageLayout::DepthStencilAttachmentOptimal,
+                true),
+            ImageAccess::DepthStencilRead => (
+                PipelineStageFlags::EARLY_FRAGMENT_TESTS | PipelineStageFlags::LATE_FRAGMENT_TESTS | PipelineStageFlags::FRAGMENT_SHADER,
+                AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ | AccessFlags::SHADER_READ,
+                ImageLayout::StencilStencilReadOnlyOptimal,
+                false),
+            ImageAccess::ShaderRead(stages) => (pipeline_stages(*stages), AccessFlags::SHADER_READ, ImageLayout::ShaderReadOnlyOptimal, false),
+            ImageAccess::ShaderWrite(stages) => (pipeline_stages(*stages), AccessFlags::SHADER_READ | AccessFlags::SHADER_WRITE, ImageLayout::General, true),
+            ImageAccess::TransferRead => (PipelineStageFlags::TRANSFER_BIT, AccessFlags::TRANSFER_READ, ImageLayout::TransferSrcOptimal, false),
+            ImageAccess::TransferWrite => (PipelineStageFlags::TRANSFER_BIT, AccessFlags::TRANSFER_WRITE, ImageLayout::TransferDstOptimal, true),
+            ImageAccess::Present => (PipelineStageFlags::BOTTOM_OF_PIPE, 0, ImageLayout::PresentSrc, false)
+        };
+
+        Access { stage, access, layout: Some(layout), write }
+    }
+
+    pub(crate) fn usage(&self) -> u32
+    {
+        match self
+        {
+            ImageAccess::ColorAttachment => ImageUsageFlags::COLOR_ATTACHMENT,
+            ImageAccess::DepthStencilAttachment => ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+            ImageAccess::DepthStencilRead => ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | ImageUsageFlags::SAMPLED,
+            ImageAccess::ShaderRead(_) => ImageUsageFlags::SAMPLED,
+            ImageAccess::ShaderWrite(_) => ImageUsageFlags::STORAGE,
+            ImageAccess::TransferRead => ImageUsageFlags::TRANSFER_SRC,
+            ImageAccess::TransferWrite => ImageUsageFlags::TRANSFER_DST,
+            ImageAccess::Present => 0
+        }
+    }
+}
+
+impl BufferAccess
+{
+    pub(crate) fn access(&self) -> Access
+    {
+        let (stage, access, write) = match self
+        {
+            BufferAccess::VertexRead => (PipelineStageFlags::VERTEX_INPUT, AccessFlags::VERTEX_ATTRIBUTE_READ, false),
+            BufferAccess::IndexRead => (PipelineStageFlags::VERTEX_INPUT, AccessFlags::INDEX_READ, false),
+            BufferAccess::IndirectRead => (PipelineStageFlags::DRAW_INDIRECT, AccessFlags::INDIRECT_COMMAND_READ, false),
+            BufferAccess::UniformRead(stages) => (pipeline_stages(*stages), AccessFlags::UNIFORM_READ, false),
+            BufferAccess::ShaderRead(stages) => (pipeline_stages(*stages), AccessFlags::SHADER_READ, false),
+            BufferAccess::ShaderWrite(stages) => (pipeline_stages(*stages), AccessFlags::SHADER_READ | AccessFlags::SHADER_WRITE, true),
+            BufferAccess::TransferRead => (PipelineStageFlags::TRANSFER_BIT, AccessFlags::TRANSFER_READ, false),
+            BufferAccess::TransferWrite => (PipelineStageFlags::TRANSFER_BIT, AccessFlags::TRANSFER_WRITE, true),
+            BufferAccess::HostRead => (PipelineStageFlags::HOST, AccessFlags::HOST_READ, false)
+        };
+
+        Access { stage, access, layout: None, write }
+    }
+
+    pub(crate) fn usage(&self) -> u32
+    {
+        match self
+        {
+            BufferAccess::VertexRead => BufferUsageFlags::VERTEX,
+            BufferAccess::IndexRead => BufferUsageFlags::INDEX,
+            BufferAccess::IndirectRead => BufferUsageFlags::INDIRECT,
+            BufferAccess::UniformRead(_) => BufferUsageFlags::UNIFORM,
+            BufferAccess::ShaderRead(_) | BufferAccess::ShaderWrite(_) => BufferUsageFlags::STORAGE,
+            BufferAccess::TransferRead => BufferUsageFlags::TRANSFER_SRC,
+            BufferAccess::TransferWrite => BufferUsageFlags::TRANSFER_DST,
+            BufferAccess::HostRead => 0
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Transition
+{
+    pub old_layout: Option<ImageLayout>,
+    pub new_layout: Option<ImageLayout>,
+    pub src_stage_mask: u32,
+    pub src_access_mask: u32,
+    pub dst_stage_mask: u32,
+    pub dst_access_mask: u32
+}
+
+// what the gpu last did to a resource. reads only need a barrier when they aren't already covered by
+// the barrier that made the last write visible, writes wait on every read since that write
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub(crate) struct ResourceState
+{
+    pub layout: Option<ImageLayout>,
+    write_stage: u32,
+    write_access: u32,
+    read_stage: u32,
+    visible_stage: u32,
+    visible_access: u32
+}
+
+impl ResourceState
+{
+    pub(crate) fn from_access(access: Option<Access>) -> Self
+    {
+        match access
+        {
+            Some(access) => Self
+            {
+                layout: access.layout,
+                write_stage: access.stage,
+                write_access: if access.write { access.access } else { 0 },
+                read_stage: 0,
+                visible_stage: 0,
+                visible_access: 0
+            },
+            None => Self::default()
+        }
+    }
+
+    pub(crate) fn transition(&mut self, next: &Access, discard: bool) -> Option<Transition>
+    {
+        let old_layout = if discard { None } else { self.layout };
+        let layout_change = next.layout.is_some() && (discard || next.layout != self.layout);
+
+        if next.write || layout_change
+        {
+            let mut src_stage_mask = self.write_stage | self.read_stage;
+            let src_access_mask = self.write_access;
+            if src_stage_mask == 0 && !layout_change
+            {
+                self.write_stage = next.stage;
+                self.write_access = next.access;
+                self.visible_stage = next.stage;
+                self.visible_access = next.access;
+                return None;
+            }
+
+            // nothing earlier in the graph touched the resource. waiting on the destination stage
+            // chains the transition onto a semaphore wait at that stage, e.g. the swapchain acquire
+            if src_stage_mask == 0
+            {
+                src_stage_mask = next.stage;
+            }
+
+            *self = Self
+            {
+                layout: next.layout.or(self.layout),
+                write_stage: next.stage,
+                write_access: if next.write { next.access } else { 0 },
+                read_stage: 0,
+                visible_stage: next.stage,
+                visible_access: next.access
+            };
+
+            return Some(Transition
+            {
+                old_layout,
+                new_layout: next.layout,
+                src_stage_mask,
+                src_access_mask,
+                dst_stage_mask: next.stage,
+                dst_access_mask: next.access
+            });
+        }
+
+        self.read_stage |= next.stage;
+        if self.write_stage == 0 || (next.stage & !self.visible_stage == 0 && next.access & !self.visible_access == 0)
+        {
+            return None;
+        }
+
+        self.visible_stage |= next.stage;
+        self.visible_access |= next.access;
+
+        Some(Transition
+        {
+            old_layout,
+            new_layout: self.layout,
+            src_stage_mask: self.write_stage,
+            src_access_mask: self.write_access,
+            dst_stage_mask: next.stage,
+            dst_access_mask: next.access
+        })
+    }
+
+    // another resource placed over the same memory was used in the meantime. its accesses count as writes, so
+    // the next access waits on all of them whatever it is
+    pub(crate) fn alias(&mut self, other: &ResourceState)
+    {
+        self.write_stage |= other.write_stage | other.read_stage;
+        self.write_access |= other.write_access;
+        self.visible_stage = 0;
+        self.visible_access = 0;
+    }
+
+    // an explicit barrier makes everything before it visible to its destination scope, later accesses
+    // outside of that scope are treated like they follow a write there
+    fn after_barrier(&mut self, layout: Option<ImageLayout>, stage: u32, access: u32)
+    {
+        *self = Self
+        {
+            layout: layout.or(self.layout),
+            write_stage: stage,
+            write_access: 0,
+            read_stage: 0,
+            visible_stage: stage,
+            visible_access: access
+        };
+    }
+}
+
+pub(crate) fn validation_error(message: &str)
+{
+    log::error!(target: "qrl::validation", "{}", message);
+}
+
+pub(crate) fn validate_indirect(draw_count: u32, stride: u32, size: u32, what: &str)
+{
+    if cfg!(debug_assertions) && draw_count > 1 && (stride % 4 != 0 || stride < size)
+    {
+        validation_error(&format!("{} stride {} must be a multiple of 4 and at least {}", what, stride, size));
+    }
+}
+
+// bytes touched by draw_count indirect commands, the last one only needs its own size
+pub(crate) fn indirect_range(draw_count: u32, stride: u32, size: u32) -> u64
+{
+    match draw_count
+    {
+        0 => 0,
+        _ => (draw_count - 1) as u64 * stride as u64 + size as u64
+    }
+}
+
+struct TrackedImage
+{
+    // keeps the image alive so its address can't be reused while the encoder tracks it
+    image: Image,
+    mip_levels: u32,
+    array_layers: u32,
+    // array layers of mip level 0, then of mip level 1 and so on
+    subresources: Vec<ResourceState>
+}
+
+impl TrackedImage
+{
+    fn resolve(&self, range: Option<&ImageSubresourceRange>) -> ImageSubresourceRange
+    {
+        let range = range.copied().unwrap_or(ImageSubresourceRange
+        {
+            base_mip_level: 0,
+            mip_level_count: self.mip_levels,
+            base_array_layer: 0,
+            array_layer_count: self.array_layers
+        });
+
+        let base_mip_level = range.base_mip_level.min(self.mip_levels);
+        let base_array_layer = range.base_array_layer.min(self.array_layers);
+        ImageSubresourceRange
+        {
+            base_mip_level,
+            mip_level_count: range.mip_level_count.min(self.mip_levels - base_mip_level),
+            base_array_layer,
+            array_layer_count: range.array_layer_count.min(self.array_layers - base_array_layer)
+        }
+    }
+
+    fn subresources(&self, range: &ImageSubresourceRange) -> impl Iterator<Item = (u32, u32, usize)>
+    {
+        let array_layers = self.array_layers;
+        let layers = range.base_array_layer..range.base_array_layer + range.array_layer_count;
+        (range.base_mip_level..range.base_mip_level + range.mip_level_count)
+            .flat_map(move |mip| layers.clone().map(move |layer| (mip, layer, (mip * array_layers + layer) as usize)))
+    }
+}
+
+struct TrackedBuffer
+{
+    _buffer: Buffer,
+    // sorted, non overlapping [start, end) ranges. bytes nothing touched yet have no entry
+    ranges: Vec<(u64, u64, ResourceState)>
+}
+
+impl TrackedBuffer
+{
+    fn split(&mut self, at: u64)
+    {
+        if let Some(index) = self.ranges.iter().position(|(start, end, _)| *start < at && at < *end)
+        {
+            let (start, end, state) = self.ranges[index];
+            self.ranges[index] = (start, at, state);
+            self.ranges.insert(index + 1, (at, end, state));
+        }
+    }
+
+    // splits and fills the entries so [start, end) is covered exactly, returns their indices
+    fn cover(&mut self, start: u64, end: u64) -> std::ops::Range<usize>
+    {
+        self.split(start);
+        self.split(end);
+
+        let first = self.ranges.iter().position(|(range_start, _, _)| *range_start >= start).unwrap_or(self.ranges.len());
+        let mut index = first;
+        let mut cursor = start;
+        while cursor < end
+        {
+            let next_start = self.ranges.get(index).map_or(end, |(range_start, _, _)| (*range_start).min(end));
+            if next_start > cursor
+            {
+                self.ranges.insert(index, (cursor, next_start, ResourceState::default()));
+            }
+
+            cursor = self.ranges[index].1;
+            index += 1;
+        }
+
+        first..index
+    }
+}
+
+// per encoder view of every image subresource and buffer range recorded on it. image subresources start
+// out in the layout the last submitted command buffer left them in, read when the encoder first touches them, and finish
+// collects the layouts this one leaves them in for Queue::submit to hand back to the images. record encoders in the
+// order they get submitted, an encoder that touches an image before the previous command buffer using it was submitted
+// starts out from a stale layout. work submitted before the encoder has to be synchronized with semaphores or fences as usual
+#[derive(Default)]
+pub(crate) struct ResourceTracker
+{
+    images: HashMap<usize, TrackedImage>,
+    buffers: HashMap<usize, TrackedBuffer>,
+    render_pass: Option<(RenderPass, Framebuffer)>,
+    rendering: bool
+}
+
+impl ResourceTracker
+{
+    fn image(&mut self, image: &Image) -> &mut TrackedImage
+    {
+        let key = Arc::as_ptr(&image.internal) as *const () as usize;
+        self.images.entry(key).or_insert_with(||
+        {
+            let create_info = image.create_info();
+            let subresources = (0..create_info.mip_levels)
+                .flat_map(|mip| (0..create_info.array_layers).map(move |layer| (mip, layer)))
+                .map(|(mip, layer)| ResourceState { layout: image.internal.current_layout(mip, layer), ..Default::default() })
+                .collect();
+            TrackedImage
+            {
+                image: image.clone(),
+                mip_levels: create_info.mip_levels,
+                array_layers: create_info.array_layers,
+                subresources
+            }
+        })
+    }
+
+    fn buffer(&mut self, buffer: &Buffer) -> &mut TrackedBuffer
+    {
+        let key = Arc::as_ptr(&buffer.internal) as *const () as usize;
+        self.buffers.entry(key).or_insert_with(|| TrackedBuffer { _buffer: buffer.clone(), ranges: Vec::new() })
+    }
+
+    pub(crate) fn check_outside_render_pass(&self, what: &str)
+    {
+        if cfg!(debug_assertions) && self.render_pass.is_some()
+        {
+            validation_error(&format!("{} needs a barrier inside a render pass", what));
+        }
+    }
+
+    // the barriers moving the range to the new access, one per run of subresources that need the same
+    // transition and a single one when the whole range does
+    pub(crate) fn use_image(&mut self, image: &Image, range: Option<&ImageSubresourceRange>, next: &Access, discard: bool) -> Vec<(ImageSubresourceRange, Transition)>
+    {
+        let tracked = self.image(image);
+        let range = tracked.resolve(range);
+        let subresources: Vec<(u32, u32, usize)> = tracked.subresources(&range).collect();
+
+        let mut runs: Vec<(ImageSubresourceRange, Transition)> = Vec::new();
+        let mut count = 0;
+        for (mip, layer, index) in subresources.iter().copied()
+        {
+            let Some(transition) = tracked.subresources[index].transition(next, discard) else { continue; };
+            count += 1;
+
+            match runs.last_mut()
+            {
+                Some((run, previous)) if *previous == transition && run.base_mip_level == mip && run.base_array_layer + run.array_layer_count == layer =>
+                {
+                    run.array_layer_count += 1;
+                }
+                _ => runs.push((ImageSubresourceRange { base_mip_level: mip, mip_level_count: 1, base_array_layer: layer, array_layer_count: 1 }, transition))
+            }
+        }
+
+        if count == subresources.len() && !runs.is_empty() && runs.iter().all(|(_, transition)| *transition == runs[0].1)
+        {
+            runs = vec![ (range, runs[0].1) ];
+        }
+
+        if !runs.is_empty()
+        {
+            self.check_outside_render_pass("image access");
+        }
+
+        runs
+    }
+
+    // a single barrier over the range, covering whatever each part of it was last used for
+    pub(crate) fn use_buffer(&mut self, buffer: &Buffer, offset: u64, size: u64, next: &Access) -> Option<Transition>
+    {
+        let tracked = self.buffer(buffer);
+        let mut merged: Option<Transition> = None;
+        for index in tracked.cover(offset, offset.saturating_add(size))
+        {
+            let Some(transition) = tracked.ranges[index].2.transition(next, false) else { continue; };
+            let merged = merged.get_or_insert(transition);
+            merged.src_stage_mask |= transition.src_stage_mask;
+            merged.src_access_mask |= transition.src_access_mask;
+        }
+
+        if merged.is_some()
+        {
+            self.check_outside_render_pass("buffer access");
+        }
+
+        merged
+    }
+
+    pub(crate) fn validate_layout(&mut self, image: &Image, range: Option<&ImageSubresourceRange>, expected: ImageLayout, what: &str)
+    {
+        if !cfg!(debug_assertions)
+        {
+            return;
+        }
+
+        let tracked = self.image(image);
+        let range = tracked.resolve(range);
+        for (mip, layer, index) in tracked.subresources(&range)
+        {
+            match tracked.subresources[index].layout
+            {
+                Some(layout) if layout != expected =>
+                {
+                    validation_error(&format!("{} expects {:?} but mip {} layer {} is in {:?}", what, expected, mip, layer, layout));
+                    return;
+                }
+                _ => ()
+            }
+        }
+    }
+
+    pub(crate) fn record_image_barrier(&mut self, barrier: &ImageBarrier)
+    {
+        self.check_outside_render_pass("image_barrier");
+        if let Some(old_layout) = barrier.old_layout
+        {
+            self.validate_layout(barrier.image, barrier.subresource_range.as_ref(), old_layout, "image_barrier old_layout");
+        }
+
+        let tracked = self.image(barrier.image);
+        let range = tracked.resolve(barrier.subresource_range.as_ref());
+        let subresources: Vec<usize> = tracked.subresources(&range).map(|(_, _, index)| index).collect();
+        for index in subresources
+        {
+            tracked.subresources[index].after_barrier(Some(barrier.new_layout), barrier.dst_stage_mask, barrier.dst_access_mask);
+        }
+    }
+
+    pub(crate) fn record_buffer_barrier(&mut self, barrier: &BufferBarrier)
+    {
+        self.check_outside_render_pass("buffer_barrier");
+
+        let tracked = self.buffer(barrier.buffer);
+        for index in tracked.cover(barrier.offset, barrier.offset.saturating_add(barrier.size))
+        {
+            tracked.ranges[index].2.after_barrier(None, barrier.dst_stage_mask, barrier.dst_access_mask);
+        }
+    }
+
+    // attachments keep their layout until the render pass ends, so only execution and memory hazards
+    // are resolved up front, with one global barrier
+    pub(crate) fn begin_render_pass(&mut self, render_pass: &RenderPass, framebuffer: &Framebuffer) -> Option<MemoryBarrier>
+    {
+        let mut barrier = MemoryBarrier { src_stage_mask: 0, src_access_mask: 0, dst_stage_mask: 0, dst_access_mask: 0 };
+
+        for (view, description) in framebuffer.attachments().iter().zip(render_pass.create_info().attachments.iter())
+        {
+            let image = view.image();
+            let range = view.subresource_range();
+            if let Some(initial_layout) = description.initial_layout
+            {
+                self.validate_layout(&image, Some(&range), initial_layout, "render pass initial_layout");
+            }
+
+            let access = if description.format.has_depth() { ImageAccess::DepthStencilAttachment } else { ImageAccess::ColorAttachment };
+            let access = Access { layout: None, ..access.access() };
+            for (_, transition) in self.use_image(&image, Some(&range), &access, description.initial_layout.is_none())
+            {
+                barrier.src_stage_mask |= transition.src_stage_mask;
+                barrier.src_access_mask |= transition.src_access_mask;
+                barrier.dst_stage_mask |= transition.dst_stage_mask;
+                barrier.dst_access_mask |= transition.dst_access_mask;
+            }
+        }
+
+        if cfg!(debug_assertions) && (self.render_pass.is_some() || self.rendering)
+        {
+            validation_error("begin_render_pass inside a render pass");
+        }
+        self.render_pass = Some((render_pass.clone(), framebuffer.clone()));
+
+        if barrier.src_stage_mask == 0 { None } else { Some(barrier) }
+    }
+
+    pub(crate) fn end_render_pass(&mut self)
+    {
+        let Some((render_pass, framebuffer)) = self.render_pass.take() else
+        {
+            if cfg!(debug_assertions)
+            {
+                validation_error("end_render_pass outside of a render pass");
+            }
+            return;
+        };
+
+        for (view, description) in framebuffer.attachments().iter().zip(render_pass.create_info().attachments.iter())
+        {
+            let range = view.subresource_range();
+            let tracked = self.image(&view.image());
+            let subresources: Vec<usize> = tracked.subresources(&range).map(|(_, _, index)| index).collect();
+            for index in subresources
+            {
+                tracked.subresources[index].layout = Some(description.final_layout);
+            }
+        }
+    }
+
+    pub(crate) fn begin_rendering(&mut self)
+    {
+        if cfg!(debug_assertions) && (self.render_pass.is_some() || self.rendering)
+        {
+            validation_error("begin_rendering inside a render pass");
+        }
+        self.rendering = true;
+    }
+
+    pub(crate) fn end_rendering(&mut self)
+    {
+        if cfg!(debug_assertions) && !self.rendering
+        {
+            validation_error("end_rendering outside of begin_rendering");
+        }
+        self.rendering = false;
+    }
+
+    pub(crate) fn finish(&self) -> Vec<FinalLayout>
+    {
+        if cfg!(debug_assertions) && (self.render_pass.is_some() || self.rendering)
+        {
+            validation_error("finish inside a render pass");
+        }
+
+        let mut layouts = Vec::new();
+        for tracked in self.images.values()
+        {
+            let range = tracked.resolve(None);
+            for (mip_level, array_layer, index) in tracked.subresources(&range)
+            {
+                if let Some(layout) = tracked.subresources[index].layout
+                {
+                    layouts.push(FinalLayout { image: tracked.image.clone(), mip_level, array_layer, layout });
+                }
+            }
+        }
+
+        layouts
+    }
+}
+
+// the layout a command buffer leaves an image subresource in, applied once it has been submitted
+pub(crate) struct FinalLayout
+{
+    image: Image,
+    mip_level: u32,
+    array_layer: u32,
+    layout: ImageLayout
+}
+
+impl FinalLayout
+{
+    pub(crate) fn apply(&self)
+    {
+        self.image.internal.set_current_layout(self.mip_level, self.array_layer, self.layout);
+    }
+}
@@ -4,6 +4,7 @@
 */
 
 use ash::vk;
+use std::{collections::VecDeque, sync::{atomic::{AtomicU32, Ordering}, Mutex}};
 
 use super::*;
 
@@ -21,6 +22,8 @@ pub struct VkInstance
     pub entry: ash::Entry,
     pub debug_utils: ash::extensions::ext::DebugUtils,
     pub utils_messenger: vk::DebugUtilsMessengerEXT,
+    // most device extensions depend on VK_KHR_get_physical_device_properties2 on a 1.0 instance
+    pub properties2: bool
 }
 
 impl VkInstance
@@ -41,13 +44,29 @@ impl VkInstance
             .map(|layer_name| layer_name.as_ptr())
             .collect();
         let surface_extension_name = std::ffi::CString::new(qpl::vk_get_surface_extension()).unwrap();
-        let extension_name_pointers: Vec<*const i8> =
+        let mut extension_name_pointers: Vec<*const i8> =
             vec![
                 ash::extensions::ext::DebugUtils::name().as_ptr(),
                 ash::extensions::khr::Surface::name().as_ptr(),
                 surface_extension_name.as_ptr()
             ];
 
+        // surfaces only report the extended and hdr color spaces with this enabled
+        let available_extensions = entry.enumerate_instance_extension_properties(None).unwrap_or_default();
+        let extension_available = |name: &std::ffi::CStr|
+        {
+            available_extensions.iter().any(|extension| unsafe { std::ffi::CStr::from_ptr(extension.extension_name.as_ptr()) } == name)
+        };
+        if extension_available(vk::ExtSwapchainColorspaceFn::name())
+        {
+            extension_name_pointers.push(vk::ExtSwapchainColorspaceFn::name().as_ptr());
+        }
+        let properties2 = extension_available(vk::KhrGetPhysicalDeviceProperties2Fn::name());
+        if properties2
+        {
+            extension_name_pointers.push(vk::KhrGetPhysicalDeviceProperties2Fn::name().as_ptr());
+        }
+
         let application_info = vk::ApplicationInfo::builder()
             .api_version(vk::make_api_version(0, 1, 0, 0))
             .build();
@@ -84,7 +103,8 @@ impl VkInstance
             handle,
             entry,
             debug_utils,
-            utils_messenger
+            utils_messenger,
+            properties2
         })
     }
 
@@ -96,7 +116,7 @@ impl VkInstance
 
 		for handle in &handles
 		{
-			let physical_device = VkPhysicalDevice::new(*handle, &self.handle);
+			let physical_device = VkPhysicalDevice::new(*handle, &self.entry, &self.handle, self.properties2);
 
 			chosen_device = Some(physical_device);
 			break;
@@ -120,12 +140,12 @@ impl AbstractInstance for VkInstance
         let loader = ash::extensions::khr::Surface::new(&self.entry, &self.handle);
 		let extent = vk::Extent2D { width: window.width, height: window.height };
 
-        Ok(Surface { internal: Rc::new(VkSurface { handle, loader, extent }) })
+        Ok(Surface { internal: Arc::new(VkSurface { handle, loader, extent }) })
     }
 
-    fn create_device(&self, surface: &Surface) -> Result<Device, ()>
+    fn create_device(&self, surface: &Surface, create_info: &DeviceCreateInfo) -> Result<Device, DeviceError>
     {
-        let physical_device = self.select_physical_device().unwrap();
+        let physical_device = self.select_physical_device().map_err(|_| DeviceError::NoSuitableDevice)?;
         let surface = surface.downcast_ref::<VkSurface>().unwrap();
         let queue_family_properties = unsafe { self.handle.get_physical_device_queue_family_properties(physical_device.handle) };
 
@@ -135,7 +155,8 @@ impl AbstractInstance for VkInstance
 
             for (index, queue_family) in queue_family_properties.iter().enumerate()
             {
-                let mut present_support: bool = unsafe { surface.loader.get_physical_device_surface_support(physical_device.handle, index as _, surface.handle) }.unwrap();
+                let mut present_support: bool = unsafe { surface.loader.get_physical_device_surface_support(physical_device.handle, index as _, surface.handle) }
+                    .map_err(|_| DeviceError::CreationFailed)?;
 
                 if queue_family.queue_count > 0 &&
                     queue_family.queue_flags.contains(vk::QueueFlags::GRAPHICS) &&
@@ -146,32 +167,174 @@ impl AbstractInstance for VkInstance
                 }
             }
 
-            found_index.unwrap()
+            found_index.ok_or(DeviceError::NoSuitableDevice)?
         };
 
-        let priortities = [ 1.0f32 ];
-        let queue_infos =
-        [
-            vk::DeviceQueueCreateInfo::builder()
-                .queue_family_index(queue_family_index)
-                .queue_priorities(&priortities)
-                .build()
-        ];
+        // extra queues prefer families without graphics so their work can overlap with rendering,
+        // falling back to spare queues in the graphics family and finally to the graphics queue itself
+        let mut priorities: Vec<Vec<f32>> = vec![ Vec::new(); queue_family_properties.len() ];
+        priorities[queue_family_index as usize].push(1.0);
+        let graphics_queue = VkQueueSlot { kind: QueueKind::Graphics, family_index: queue_family_index, index: 0, lock: Arc::new(Mutex::new(())) };
+        let mut queues: Vec<VkQueueSlot> = vec![ graphics_queue.clone() ];
+
+        for request in create_info.queues.iter()
+        {
+            let preferences: &[(vk::QueueFlags, vk::QueueFlags)] = match request.kind
+            {
+                QueueKind::Graphics => &[ (vk::QueueFlags::GRAPHICS, vk::QueueFlags::empty()) ],
+                QueueKind::Compute => &[
+                    (vk::QueueFlags::COMPUTE, vk::QueueFlags::GRAPHICS),
+                    (vk::QueueFlags::COMPUTE, vk::QueueFlags::empty()) ],
+                QueueKind::Transfer => &[
+                    (vk::QueueFlags::TRANSFER, vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE),
+                    (vk::QueueFlags::TRANSFER, vk::QueueFlags::GRAPHICS),
+                    (vk::QueueFlags::TRANSFER, vk::QueueFlags::empty()) ]
+            };
+
+            let family_index = preferences.iter().find_map(|(required, excluded)|
+            {
+                queue_family_properties.iter().enumerate()
+                    .find(|(index, queue_family)|
+                    {
+                        queue_family.queue_flags.contains(*required) &&
+                            !queue_family.queue_flags.intersects(*excluded) &&
+                            priorities[*index].len() < queue_family.queue_count as usize
+                    })
+                    .map(|(index, _)| index)
+            });
+
+            match family_index
+            {
+                Some(family_index) =>
+                {
+                    queues.push(VkQueueSlot
+                    {
+                        kind: request.kind,
+                        family_index: family_index as u32,
+                        index: priorities[family_index].len() as u32,
+                        lock: Arc::new(Mutex::new(()))
+                    });
+                    priorities[family_index].push(request.priority.clamp(0.0, 1.0));
+                },
+                None => queues.push(VkQueueSlot { kind: request.kind, ..graphics_queue.clone() })
+            }
+        }
+
+        let queue_infos: Vec<vk::DeviceQueueCreateInfo> = priorities.iter().enumerate()
+            .filter(|(_, priorities)| !priorities.is_empty())
+            .map(|(index, priorities)|
+            {
+                vk::DeviceQueueCreateInfo::builder()
+                    .queue_family_index(index as u32)
+                    .queue_priorities(priorities)
+                    .build()
+            })
+            .collect();
         let layer_names: Vec<std::ffi::CString> =
             vec![std::ffi::CString::new("VK_LAYER_KHRONOS_validation").unwrap()];
         let layer_name_pointers: Vec<*const i8> = layer_names
             .iter()
             .map(|layer_name| layer_name.as_ptr())
             .collect();
-        let device_extension_name_pointers: Vec<*const i8> =
-            vec![ash::extensions::khr::Swapchain::name().as_ptr()];
-        let device_create_info = vk::DeviceCreateInfo::builder()
+        let supported_features = physical_device.features();
+        let missing_features = create_info.required_features & !supported_features;
+        if missing_features != 0
+        {
+            return Err(DeviceError::MissingFeatures(missing_features));
+        }
+        let features = (create_info.required_features | create_info.optional_features) & supported_features;
+
+        let extension_supported = |name: &std::ffi::CStr| physical_device.supports_extension(name);
+        let missing_extensions: Vec<DeviceExtension> = create_info.required_extensions.iter()
+            .filter(|extension| !vk_device_extension(extension).is_some_and(|name| extension_supported(&name)))
+            .cloned()
+            .collect();
+        if !missing_extensions.is_empty()
+        {
+            return Err(DeviceError::MissingExtensions(missing_extensions));
+        }
+
+        let mut requested_extensions: Vec<std::ffi::CString> = vec![ ash::extensions::khr::Swapchain::name().to_owned() ];
+        if features & FeatureFlags::DRAW_INDIRECT_COUNT != 0
+        {
+            requested_extensions.push(ash::extensions::khr::DrawIndirectCount::name().to_owned());
+        }
+        if features & FeatureFlags::VERTEX_ATTRIBUTE_DIVISOR != 0
+        {
+            requested_extensions.push(vk::ExtVertexAttributeDivisorFn::name().to_owned());
+        }
+        if features & FeatureFlags::DYNAMIC_RENDERING != 0
+        {
+            requested_extensions.push(vk::KhrDynamicRenderingFn::name().to_owned());
+        }
+        requested_extensions.extend(create_info.required_extensions.iter().filter_map(vk_device_extension));
+        requested_extensions.extend(create_info.optional_extensions.iter().filter_map(vk_device_extension).filter(|name| extension_supported(name)));
+
+        let mut extensions: Vec<std::ffi::CString> = Vec::new();
+        for name in requested_extensions.iter()
+        {
+            add_device_extension(&mut extensions, name);
+        }
+        let has_extension = |name: &std::ffi::CStr| extensions.iter().any(|extension| extension.as_c_str() == name);
+        let supports_draw_indirect_count = has_extension(ash::extensions::khr::DrawIndirectCount::name());
+        let supports_vertex_attribute_divisor = has_extension(vk::ExtVertexAttributeDivisorFn::name()) && physical_device.vertex_attribute_instance_rate_divisor;
+        let device_extension_name_pointers: Vec<*const i8> = extensions.iter().map(|name| name.as_ptr()).collect();
+
+        // the extension is only enabled when properties2 reported non zero divisors, zero divisors are
+        // optional and left off
+        let mut vertex_attribute_divisor_features = vk::PhysicalDeviceVertexAttributeDivisorFeaturesEXT::builder()
+            .vertex_attribute_instance_rate_divisor(true);
+        // these features are required by their extensions, so they can be turned on without a query
+        let mut dynamic_rendering_features = vk::PhysicalDeviceDynamicRenderingFeatures::builder()
+            .dynamic_rendering(true);
+        let mut timeline_semaphore_features = vk::PhysicalDeviceTimelineSemaphoreFeatures::builder()
+            .timeline_semaphore(true);
+        let mut synchronization2_features = vk::PhysicalDeviceSynchronization2Features::builder()
+            .synchronization2(true);
+        let enabled_features = vk_features(features);
+        let mut device_create_info = vk::DeviceCreateInfo::builder()
             .queue_create_infos(&queue_infos)
             .enabled_extension_names(&device_extension_name_pointers)
-            .enabled_layer_names(&layer_name_pointers);
-        let handle = unsafe { self.handle.create_device(physical_device.handle, &device_create_info, None).unwrap() };
+            .enabled_layer_names(&layer_name_pointers)
+            .enabled_features(&enabled_features);
+        if supports_vertex_attribute_divisor
+        {
+            device_create_info = device_create_info.push_next(&mut vertex_attribute_divisor_features);
+        }
+        if has_extension(vk::KhrDynamicRenderingFn::name())
+        {
+            device_create_info = device_create_info.push_next(&mut dynamic_rendering_features);
+        }
+        if has_extension(vk::KhrTimelineSemaphoreFn::name())
+        {
+            device_create_info = device_create_info.push_next(&mut timeline_semaphore_features);
+        }
+        if has_extension(vk::KhrSynchronization2Fn::name())
+        {
+            device_create_info = device_create_info.push_next(&mut synchronization2_features);
+        }
+        let handle = unsafe { self.handle.create_device(physical_device.handle, &device_create_info, None) }.map_err(|_| DeviceError::CreationFailed)?;
+        let draw_indirect_count = supports_draw_indirect_count.then(|| ash::extensions::khr::DrawIndirectCount::new(&self.handle, &handle));
+        let dynamic_rendering = has_extension(vk::KhrDynamicRenderingFn::name()).then(|| ash::extensions::khr::DynamicRendering::new(&self.handle, &handle));
+
+        let device = VkDevice
+        {
+            handle,
+            instance: self.handle.clone(),
+            queue_family_index,
+            queues,
+            physical_device,
+            enabled_features,
+            draw_indirect_count,
+            dynamic_rendering,
+            vertex_attribute_divisor: supports_vertex_attribute_divisor,
+            extensions,
+            debug_utils: self.debug_utils.clone(),
+            staging: Arc::new(Mutex::new(None))
+        };
+        device.set_object_name(device.handle.handle(), create_info.label);
 
-        Ok(Device { internal: Box::new(VkDevice { handle, instance: self.handle.clone(), queue_family_index, physical_device }) })
+        Ok(Device { internal: Box::new(device) })
     }
 
     fn create_swapchain(&self, device: &Device, surface: &Surface, create_info: &SwapchainCreateInfo) -> Result<Swapchain, ()>
@@ -179,23 +342,38 @@ impl AbstractInstance for VkInstance
         let device: &VkDevice = device.downcast_ref::<VkDevice>().unwrap();
         let surface: &VkSurface = surface.downcast_ref::<VkSurface>().unwrap();
 
-		let swapchain_info = device.get_swapchain_support_info(surface);
+		let swapchain_info = device.get_swapchain_support_info(surface).map_err(|_| ())?;
 
-		let surface_format = choose_swap_surface_format(&swapchain_info.formats);
-		let present_mode = choose_swap_present_mode(&swapchain_info.modes, create_info.present_mode);
+		let surface_format = choose_swap_surface_format(&swapchain_info.formats, create_info.preferred_formats, create_info.color_space);
+		let present_mode = choose_swap_present_mode(&swapchain_info.modes, create_info.present_modes);
         let extent = choose_swap_extent(surface, &swapchain_info.capabilities);
 
 		dbg!(&surface_format);
 		dbg!(&present_mode);
 		dbg!(&extent);
 
-		let mut image_count: u32 = swapchain_info.capabilities.min_image_count + 1;
+		let mut image_count: u32 = create_info.image_count.unwrap_or(swapchain_info.capabilities.min_image_count + 1).max(swapchain_info.capabilities.min_image_count);
 		if swapchain_info.capabilities.max_image_count > 0 &&
 			image_count > swapchain_info.capabilities.max_image_count
 		{
 			image_count = swapchain_info.capabilities.max_image_count;
 		}
 
+        // TRANSFER_SRC lets frames be read back for captures
+        let format_properties = unsafe { self.handle.get_physical_device_format_properties(device.physical_device.handle, surface_format.format) };
+        let mut supported_usage = swapchain_info.capabilities.supported_usage_flags;
+        if !format_properties.optimal_tiling_features.contains(vk::FormatFeatureFlags::STORAGE_IMAGE)
+        {
+            supported_usage &= !vk::ImageUsageFlags::STORAGE;
+        }
+        let image_usage = vk::ImageUsageFlags::COLOR_ATTACHMENT |
+            ((vk_image_usage(create_info.usage) | vk::ImageUsageFlags::TRANSFER_SRC) & supported_usage);
+
+        let composite_alpha = [ create_info.composite_alpha, CompositeAlpha::Opaque, CompositeAlpha::Inherit, CompositeAlpha::PreMultiplied, CompositeAlpha::PostMultiplied ].iter()
+            .map(|composite_alpha| vk_composite_alpha(*composite_alpha))
+            .find(|composite_alpha| swapchain_info.capabilities.supported_composite_alpha.contains(*composite_alpha))
+            .unwrap_or(vk::CompositeAlphaFlagsKHR::OPAQUE);
+
         let queue_families = [ device.queue_family_index ];
         let swapchain_create_info = vk::SwapchainCreateInfoKHR::builder()
             .surface(surface.handle)
@@ -204,23 +382,35 @@ impl AbstractInstance for VkInstance
             .image_color_space(surface_format.color_space)
             .image_extent(extent)
             .image_array_layers(1)
-            .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
+            .image_usage(image_usage)
             .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
             .queue_family_indices(&queue_families)
             .pre_transform(swapchain_info.capabilities.current_transform)
-            .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
+            .composite_alpha(composite_alpha)
             .present_mode(present_mode);
         let loader = ash::extensions::khr::Swapchain::new(&self.handle, &device.handle);
         let handle = unsafe { loader.create_swapchain(&swapchain_create_info, None).unwrap() };
+        device.set_object_name(handle, create_info.label);
 
         let swapchain_images = unsafe { loader.get_swapchain_images(handle).unwrap() };
 
         let mut images: Vec<VkImage> = Vec::with_capacity(swapchain_images.len());
-        let mut views: Vec<VkImageView> = Vec::with_capacity(swapchain_images.len());
+        let mut views: Vec<ImageView> = Vec::with_capacity(swapchain_images.len());
 
         for handle in swapchain_images.iter()
         {
-            images.push(VkImage { handle: *handle });
+            let image = VkImage
+            {
+                handle: *handle,
+                memory: VkImageMemory::Swapchain,
+                format: surface_format.format,
+                extent: vk::Extent3D { width: extent.width, height: extent.height, depth: 1 },
+                mip_levels: 1,
+                array_layers: 1,
+                samples: vk::SampleCountFlags::TYPE_1,
+                usage: qrl_image_usage(image_usage),
+                layouts: Arc::new(Mutex::new(vec![ vk::ImageLayout::UNDEFINED ]))
+            };
 
             let subresource_range = vk::ImageSubresourceRange::builder()
                 .aspect_mask(vk::ImageAspectFlags::COLOR)
@@ -234,10 +424,112 @@ impl AbstractInstance for VkInstance
                 .format(surface_format.format)
                 .subresource_range(*subresource_range);
             
-            views.push(VkImageView { handle: unsafe { device.handle.create_image_view(&imageview_create_info, None) }.unwrap() });
+            views.push(ImageView { internal: Arc::new(VkImageView
+            {
+                handle: unsafe { device.handle.create_image_view(&imageview_create_info, None) }.unwrap(),
+                parent: Image { internal: Arc::new(image.clone()) },
+                range: ImageSubresourceRange::default()
+            }) });
+            images.push(image);
+        }
+
+        let mut depth_images: Vec<Image> = Vec::new();
+        let mut depth_views: Vec<ImageView> = Vec::new();
+
+        if let Some(depth_format) = create_info.depth_format
+        {
+            let format_properties = unsafe { self.handle.get_physical_device_format_properties(device.physical_device.handle, vk_format(depth_format)) };
+            if !depth_format.has_depth() ||
+                !format_properties.optimal_tiling_features.contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
+            {
+                return Err(());
+            }
+
+            for _ in 0..images.len()
+            {
+                let image = device.create_image(&ImageCreateInfo
+                {
+                    format: depth_format,
+                    width: extent.width,
+                    height: extent.height,
+                    usage: ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+                    ..Default::default()
+                })?;
+                let view = device.create_image_view(&ImageViewCreateInfo
+                {
+                    image: &image,
+                    base_mip_level: 0,
+                    mip_level_count: 1,
+                    base_array_layer: 0,
+                    array_layer_count: 1,
+                    label: None
+                })?;
+
+                depth_images.push(image);
+                depth_views.push(view);
+            }
+
+            // move the depth buffers into their attachment layout once up front so render
+            // passes can declare DepthStencilAttachmentOptimal as their initial layout
+            device.immediate_submit(|command_buffer|
+            {
+                let barriers: Vec<vk::ImageMemoryBarrier> = depth_images.iter()
+                    .map(|image|
+                    {
+                        let image = image.downcast_ref::<VkImage>().unwrap();
+                        vk::ImageMemoryBarrier::builder()
+                            .dst_access_mask(vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+                            .old_layout(vk::ImageLayout::UNDEFINED)
+                            .new_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+                            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                            .image(image.handle)
+                            .subresource_range(vk::ImageSubresourceRange
+                            {
+                                aspect_mask: vk_aspect_mask(image.format),
+                                base_mip_level: 0,
+                                level_count: 1,
+                                base_array_layer: 0,
+                                layer_count: 1
+                            })
+                            .build()
+                    })
+                    .collect();
+
+                unsafe
+                {
+                    device.handle.cmd_pipeline_barrier(
+                        command_buffer,
+                        vk::PipelineStageFlags::TOP_OF_PIPE,
+                        vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+                        vk::DependencyFlags::empty(),
+                        &[],
+                        &[],
+                        &barriers)
+                };
+            })?;
+
+            for image in depth_images.iter()
+            {
+                let image = image.downcast_ref::<VkImage>().unwrap();
+                image.set_layout(&image.full_range(), vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
+            }
         }
 
-        Ok(Swapchain { internal: Rc::new(VkSwapchain { handle, loader, images, views }) })
+        Ok(Swapchain { internal: Arc::new(VkSwapchain
+        {
+            handle,
+            loader,
+            device: device.clone(),
+            images,
+            views,
+            extent,
+            surface_format,
+            present_mode,
+            image_usage,
+            depth_views,
+            current_image: Mutex::new(None)
+        }) })
     }
 
 
@@ -249,250 +541,3060 @@ pub struct VkDevice
     pub handle: ash::Device,
 	pub instance: ash::Instance,
     pub queue_family_index: u32,
-	pub physical_device: VkPhysicalDevice
+    // the graphics queue comes first
+    pub queues: Vec<VkQueueSlot>,
+	pub physical_device: VkPhysicalDevice,
+    pub enabled_features: vk::PhysicalDeviceFeatures,
+    pub draw_indirect_count: Option<ash::extensions::khr::DrawIndirectCount>,
+    pub dynamic_rendering: Option<ash::extensions::khr::DynamicRendering>,
+    pub vertex_attribute_divisor: bool,
+    pub extensions: Vec<std::ffi::CString>,
+    pub debug_utils: ash::extensions::ext::DebugUtils,
+    pub staging: Arc<Mutex<Option<VkStagingRing>>>
 }
 
 impl VkDevice
 {
-	fn get_swapchain_support_info(&self, surface: &VkSurface) -> SwapchainSupportInfo
+	// fails when the surface is lost or the driver runs out of memory while answering
+	fn get_swapchain_support_info(&self, surface: &VkSurface) -> ash::prelude::VkResult<SwapchainSupportInfo>
 	{
 		let capabilities: vk::SurfaceCapabilitiesKHR = unsafe
 		{
 			surface.loader.get_physical_device_surface_capabilities(self.physical_device.handle, surface.handle)
-		}.unwrap();
+		}?;
 
 		let formats: Vec<vk::SurfaceFormatKHR> = unsafe
 		{
 			surface.loader.get_physical_device_surface_formats(self.physical_device.handle, surface.handle)
-		}.unwrap();
+		}?;
 
 		let modes: Vec<vk::PresentModeKHR> = unsafe
 		{
 			surface.loader.get_physical_device_surface_present_modes(self.physical_device.handle, surface.handle)
-		}.unwrap();
+		}?;
 
-		SwapchainSupportInfo { capabilities, formats, modes }
+		Ok(SwapchainSupportInfo { capabilities, formats, modes })
 	}
-}
-
-impl AbstractDevice for VkDevice
-{
-    fn as_any(&self) -> &dyn Any { self }
 
-    fn get_device_queue(&self) -> Result<Queue, ()>
+    // internal submissions go through the same lock as Queue handles to the graphics queue
+    unsafe fn submit_graphics(&self, submits: &[vk::SubmitInfo], fence: vk::Fence) -> ash::prelude::VkResult<()>
     {
-        let handle = unsafe { self.handle.get_device_queue(self.queue_family_index, 0) };
-        Ok(Queue { internal: Rc::new(VkQueue { handle }) })
+        let slot = &self.queues[0];
+        let _lock = slot.lock.lock().unwrap();
+        self.handle.queue_submit(self.handle.get_device_queue(slot.family_index, slot.index), submits, fence)
     }
 
-	fn get_physical_device_properties(&self) -> Result<PhysicalDeviceProperties, ()>
-	{
-		Ok(PhysicalDeviceProperties
-		{
-			vendor_id: self.physical_device.properties.vendor_id,
-			device_id: self.physical_device.properties.device_id,
-			device_type: match self.physical_device.properties.device_type
-			{
-				vk::PhysicalDeviceType::CPU => DeviceType::CPU,
-				vk::PhysicalDeviceType::DISCRETE_GPU => DeviceType::DiscreteGPU,
-				vk::PhysicalDeviceType::INTEGRATED_GPU => DeviceType::IntegratedGPU,
-				vk::PhysicalDeviceType::VIRTUAL_GPU => DeviceType::VirtualGPU,
-				_ => DeviceType::Other
-			},
-			device_name: unsafe { std::ffi::CStr::from_ptr(self.physical_device.properties.device_name.as_ptr()) }.to_str().unwrap().to_owned()
-		})
-	}
-
-    fn create_shader_module(&self, create_info: &ShaderModuleCreateInfo) -> Result<ShaderModule, ShaderModuleError>
+    // records and submits a one-off command buffer, then blocks until it has executed
+    fn immediate_submit<F>(&self, record: F) -> Result<(), ()> where F: FnOnce(vk::CommandBuffer)
     {
-        let code: Vec<u32> = match &create_info.source
+        let pool_create_info = vk::CommandPoolCreateInfo::builder()
+            .queue_family_index(self.queue_family_index)
+            .flags(vk::CommandPoolCreateFlags::TRANSIENT);
+        let pool = unsafe { self.handle.create_command_pool(&pool_create_info, None) }.map_err(|_| ())?;
+
+        let result = (|| -> Result<(), ()>
         {
-            ShaderModuleSource::Spirv(code) =>
+            let allocate_info = vk::CommandBufferAllocateInfo::builder()
+                .command_pool(pool)
+                .level(vk::CommandBufferLevel::PRIMARY)
+                .command_buffer_count(1);
+            let command_buffer = unsafe { self.handle.allocate_command_buffers(&allocate_info) }.map_err(|_| ())?[0];
+
+            let begin_info = vk::CommandBufferBeginInfo::builder()
+                .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+            unsafe { self.handle.begin_command_buffer(command_buffer, &begin_info) }.map_err(|_| ())?;
+            record(command_buffer);
+            unsafe { self.handle.end_command_buffer(command_buffer) }.map_err(|_| ())?;
+
+            let fence = unsafe { self.handle.create_fence(&vk::FenceCreateInfo::builder(), None) }.map_err(|_| ())?;
+            let command_buffers = [ command_buffer ];
+            let submit = vk::SubmitInfo::builder()
+                .command_buffers(&command_buffers);
+            let result = unsafe
             {
-                code.clone()
-            },
-            _ => { panic!() }
-        };
+                self.submit_graphics(&[ *submit ], fence)
+                    .and_then(|_| self.handle.wait_for_fences(&[ fence ], true, u64::MAX))
+            };
+            unsafe { self.handle.destroy_fence(fence, None) };
 
-        let handle = unsafe { self.handle.create_shader_module(&ash::vk::ShaderModuleCreateInfo::builder()
-            .code(&code)
-            .build(), None) }.unwrap();
+            result.map_err(|_| ())
+        })();
 
-        Ok(ShaderModule { internal: Rc::new(VkShaderModule { handle, stage: create_info.stage }) })
+        unsafe { self.handle.destroy_command_pool(pool, None) };
+        result
     }
-}
 
-#[derive(Clone)]
-pub struct VkPhysicalDevice
-{
-    pub handle: ash::vk::PhysicalDevice,
-    pub supported_features: vk::PhysicalDeviceFeatures,
-    pub properties: vk::PhysicalDeviceProperties,
-    pub memory_properties: vk::PhysicalDeviceMemoryProperties,
-    pub queue_family_properties: Vec<vk::QueueFamilyProperties>,
-}
+    fn memory_type_index(&self, memory_type_bits: u32, location: MemoryLocation) -> Result<u32, ()>
+    {
+        let (required, preferred) = match location
+        {
+            MemoryLocation::GpuOnly => (vk::MemoryPropertyFlags::DEVICE_LOCAL, vk::MemoryPropertyFlags::DEVICE_LOCAL),
+            MemoryLocation::CpuToGpu => (vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT, vk::MemoryPropertyFlags::DEVICE_LOCAL),
+            MemoryLocation::GpuToCpu => (vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT, vk::MemoryPropertyFlags::HOST_CACHED)
+        };
 
-impl VkPhysicalDevice
-{
-    pub fn new(handle: vk::PhysicalDevice, instance: &ash::Instance) -> Self
+        self.physical_device.find_memory_type(memory_type_bits, required | preferred)
+            .or_else(|| self.physical_device.find_memory_type(memory_type_bits, required))
+            .ok_or(())
+    }
+
+    fn allocate_device_memory(&self, requirements: &vk::MemoryRequirements, location: MemoryLocation) -> Result<vk::DeviceMemory, ()>
     {
-        let supported_features = unsafe { instance.get_physical_device_features(handle) };
-        let properties = unsafe { instance.get_physical_device_properties(handle) };
-        let memory_properties = unsafe { instance.get_physical_device_memory_properties(handle) };
-        let queue_family_properties = unsafe { instance.get_physical_device_queue_family_properties(handle) };
+        let allocate_info = vk::MemoryAllocateInfo::builder()
+            .allocation_size(requirements.size)
+            .memory_type_index(self.memory_type_index(requirements.memory_type_bits, location)?);
 
-        Self
+        unsafe { self.handle.allocate_memory(&allocate_info, None) }.map_err(|_| ())
+    }
+
+    fn create_buffer_handle(&self, create_info: &BufferCreateInfo) -> Result<vk::Buffer, ()>
+    {
+        let buffer_create_info = vk::BufferCreateInfo::builder()
+            .size(create_info.size)
+            .usage(vk_buffer_usage(create_info.usage))
+            .sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+        unsafe { self.handle.create_buffer(&buffer_create_info, None) }.map_err(|_| ())
+    }
+
+    fn create_image_handle(&self, create_info: &ImageCreateInfo) -> Result<vk::Image, ()>
+    {
+        if !self.is_sample_count_supported(create_info.samples, create_info.format, create_info.usage)
         {
-            handle, supported_features, properties, memory_properties, queue_family_properties
+            return Err(());
         }
-    }
-}
 
-#[derive(Clone)]
-pub struct VkQueue
-{
-    pub handle: ash::vk::Queue
-}
+        let image_create_info = vk::ImageCreateInfo::builder()
+            .image_type(if create_info.depth > 1 { vk::ImageType::TYPE_3D } else { vk::ImageType::TYPE_2D })
+            .format(vk_format(create_info.format))
+            .extent(vk::Extent3D { width: create_info.width, height: create_info.height, depth: create_info.depth })
+            .mip_levels(create_info.mip_levels)
+            .array_layers(create_info.array_layers)
+            .samples(vk::SampleCountFlags::from_raw(create_info.samples))
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(vk_image_usage(create_info.usage))
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
 
-impl AbstractQueue for VkQueue
-{
-    fn as_any(&self) -> &dyn Any { self }
-}
+        unsafe { self.handle.create_image(&image_create_info, None) }.map_err(|_| ())
+    }
 
-#[derive(Clone)]
-pub struct VkSurface
-{
-    pub handle: vk::SurfaceKHR,
-    pub loader: ash::extensions::khr::Surface,
-	pub extent: vk::Extent2D
-}
+    // names show up in validation messages and captures, a label that isn't a valid C string is skipped
+    fn set_object_name<T>(&self, handle: T, label: Option<&str>) where T: vk::Handle
+    {
+        if let Some(name) = label.and_then(|label| std::ffi::CString::new(label).ok())
+        {
+            let name_info = vk::DebugUtilsObjectNameInfoEXT::builder()
+                .object_type(T::TYPE)
+                .object_handle(handle.as_raw())
+                .object_name(&name);
+            let _ = unsafe { self.debug_utils.set_debug_utils_object_name(self.handle.handle(), &name_info) };
+        }
+    }
 
-impl AbstractSurface for VkSurface
-{
-    fn as_any(&self) -> &dyn Any { self }
-}
+    fn create_pipeline_layout(&self, set_layouts: &[&DescriptorSetLayout]) -> Result<vk::PipelineLayout, ()>
+    {
+        let set_layouts: Vec<vk::DescriptorSetLayout> = set_layouts.iter()
+            .map(|layout| layout.downcast_ref::<VkDescriptorSetLayout>().unwrap().handle)
+            .collect();
+        let layout_create_info = vk::PipelineLayoutCreateInfo::builder()
+            .set_layouts(&set_layouts);
 
-#[derive(Clone)]
-pub struct VkImage
-{
-    pub handle: vk::Image
-}
+        unsafe { self.handle.create_pipeline_layout(&layout_create_info, None) }.map_err(|_| ())
+    }
 
-impl VkImage
-{
-    
-}
+    fn create_staging_buffer(&self, size: u64) -> Result<(vk::Buffer, vk::DeviceMemory), ()>
+    {
+        let buffer_create_info = vk::BufferCreateInfo::builder()
+            .size(size)
+            .usage(vk::BufferUsageFlags::TRANSFER_SRC)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE);
+        let buffer = unsafe { self.handle.create_buffer(&buffer_create_info, None) }.map_err(|_| ())?;
 
-#[derive(Clone)]
-pub struct VkImageView
-{
-    pub handle: vk::ImageView
-}
+        let requirements = unsafe { self.handle.get_buffer_memory_requirements(buffer) };
+        let memory = self.allocate_device_memory(&requirements, MemoryLocation::CpuToGpu)?;
+        unsafe { self.handle.bind_buffer_memory(buffer, memory, 0) }.map_err(|_| ())?;
 
-impl VkImageView
-{
-    
-}
+        Ok((buffer, memory))
+    }
 
-#[derive(Clone)]
-pub struct VkSwapchain
-{
-    pub handle: vk::SwapchainKHR,
-    pub loader: ash::extensions::khr::Swapchain,
-    pub images: Vec<VkImage>,
-    pub views: Vec<VkImageView>
-}
+    fn create_staging_ring(&self) -> Result<VkStagingRing, ()>
+    {
+        let (buffer, memory) = self.create_staging_buffer(STAGING_RING_SIZE)?;
+        let mapped = unsafe { self.handle.map_memory(memory, 0, STAGING_RING_SIZE, vk::MemoryMapFlags::empty()) }.map_err(|_| ())? as *mut u8;
 
-impl AbstractSwapchain for VkSwapchain
-{
-    fn as_any(&self) -> &dyn Any { self }
-}
+        let pool_create_info = vk::CommandPoolCreateInfo::builder()
+            .queue_family_index(self.queue_family_index)
+            .flags(vk::CommandPoolCreateFlags::TRANSIENT);
+        let command_pool = unsafe { self.handle.create_command_pool(&pool_create_info, None) }.map_err(|_| ())?;
 
-#[derive(Clone)]
-pub struct VkShaderModule
-{
-    pub handle: vk::ShaderModule,
-    pub stage: ShaderStage
-}
+        Ok(VkStagingRing
+        {
+            buffer,
+            mapped,
+            size: STAGING_RING_SIZE,
+            alignment: self.physical_device.properties.limits.optimal_buffer_copy_offset_alignment.max(16),
+            head: 0,
+            command_pool,
+            in_flight: VecDeque::new()
+        })
+    }
 
-impl AbstractShaderModule for VkShaderModule
-{
-    fn as_any(&self) -> &dyn Any { self }
-}
+    // copies data into the staging ring (or a dedicated buffer when it does not fit), lets record
+    // issue the transfer commands reading from (buffer, offset) and submits them without waiting
+    fn staged_submit<F>(&self, data: &[u8], record: F) -> Result<Fence, ()> where F: FnOnce(vk::CommandBuffer, vk::Buffer, u64)
+    {
+        let mut staging = self.staging.lock().unwrap();
+        if staging.is_none()
+        {
+            *staging = Some(self.create_staging_ring()?);
+        }
+        let ring = staging.as_mut().unwrap();
+        ring.reclaim(&self.handle, false)?;
 
-#[derive(Clone)]
-pub struct VkRenderPass
-{
-    pub handle: vk::RenderPass
-}
+        let size = data.len() as u64;
+        let (buffer, offset, range, dedicated) = if size > ring.size
+        {
+            let (buffer, memory) = self.create_staging_buffer(size)?;
+            let Ok(ptr) = (unsafe { self.handle.map_memory(memory, 0, size, vk::MemoryMapFlags::empty()) }) else
+            {
+                unsafe { self.destroy_staging_buffer((buffer, memory)) };
+                return Err(());
+            };
+            unsafe
+            {
+                std::ptr::copy_nonoverlapping(data.as_ptr(), ptr as *mut u8, data.len());
+                self.handle.unmap_memory(memory);
+            }
 
-impl VkRenderPass
-{
-    
-}
+            (buffer, 0, None, Some((buffer, memory)))
+        }
+        else
+        {
+            let offset = loop
+            {
+                if let Some(offset) = ring.allocate(size)
+                {
+                    break offset;
+                }
+                ring.reclaim(&self.handle, true)?;
+            };
+            unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), ring.mapped.add(offset as usize), data.len()) };
 
-#[derive(Clone)]
-pub struct VkRenderPipeline
-{
-    pub handle: vk::Pipeline
-}
+            (ring.buffer, offset, Some(offset), None)
+        };
 
-impl VkRenderPipeline
-{
-    
-}
+        let command_pool = ring.command_pool;
+        let submitted = (|| -> Result<(vk::CommandBuffer, Fence), ()>
+        {
+            let allocate_info = vk::CommandBufferAllocateInfo::builder()
+                .command_pool(command_pool)
+                .level(vk::CommandBufferLevel::PRIMARY)
+                .command_buffer_count(1);
+            let command_buffer = unsafe { self.handle.allocate_command_buffers(&allocate_info) }.map_err(|_| ())?[0];
 
-unsafe extern "system" fn vulkan_debug_utils_callback(
-    message_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
-    message_type: vk::DebugUtilsMessageTypeFlagsEXT,
-    p_callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT,
-    _p_user_data: *mut std::ffi::c_void,
-) -> vk::Bool32
-{
-    let message = std::ffi::CStr::from_ptr((*p_callback_data).p_message);
-    let severity = format!("{:?}", message_severity).to_lowercase();
-    let ty = format!("{:?}", message_type).to_lowercase();
-    println!("[Debug][{}][{}] {:?}", severity, ty, message);
-    vk::FALSE
-}
+            let begin_info = vk::CommandBufferBeginInfo::builder()
+                .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+            unsafe { self.handle.begin_command_buffer(command_buffer, &begin_info) }.map_err(|_| ())?;
+            record(command_buffer, buffer, offset);
+            unsafe { self.handle.end_command_buffer(command_buffer) }.map_err(|_| ())?;
 
-fn choose_swap_surface_format(formats: &[vk::SurfaceFormatKHR]) -> vk::SurfaceFormatKHR
-{
-	for format in formats
-	{
-		if format.format == vk::Format::B8G8R8A8_SRGB &&
-			format.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR
-		{
-			return *format;
-		}
-	}
+            let fence = self.create_fence(false)?;
+            let command_buffers = [ command_buffer ];
+            let submit = vk::SubmitInfo::builder()
+                .command_buffers(&command_buffers);
+            unsafe { self.submit_graphics(&[ *submit ], fence.downcast_ref::<VkFence>().unwrap().handle) }.map_err(|_| ())?;
 
-	formats[0]
-}
+            Ok((command_buffer, fence))
+        })();
 
-fn choose_swap_present_mode(modes: &[vk::PresentModeKHR], preferred_mode: PresentMode) -> vk::PresentModeKHR
-{
-	for mode in modes
+        // nothing reads a dedicated buffer when the submission didn't happen
+        let Ok((command_buffer, fence)) = submitted else
+        {
+            if let Some(dedicated) = dedicated
+            {
+                unsafe { self.destroy_staging_buffer(dedicated) };
+            }
+            return Err(());
+        };
+
+        ring.in_flight.push_back(VkStagingUpload { fence: fence.clone(), command_buffer, start: range, dedicated });
+        Ok(fence)
+    }
+
+    unsafe fn destroy_staging_buffer(&self, (buffer, memory): (vk::Buffer, vk::DeviceMemory))
+    {
+        self.handle.destroy_buffer(buffer, None);
+        self.handle.free_memory(memory, None);
+    }
+
+    // lets record copy into a host readable buffer of the given size and blocks until the copy
+    // has executed, then returns the buffer's contents
+    fn read_back<F>(&self, size: u64, record: F) -> Result<Vec<u8>, ()> where F: FnOnce(vk::CommandBuffer, vk::Buffer)
+    {
+        let buffer_create_info = vk::BufferCreateInfo::builder()
+            .size(size)
+            .usage(vk::BufferUsageFlags::TRANSFER_DST)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE);
+        let buffer = unsafe { self.handle.create_buffer(&buffer_create_info, None) }.map_err(|_| ())?;
+
+        let requirements = unsafe { self.handle.get_buffer_memory_requirements(buffer) };
+        let memory = self.allocate_device_memory(&requirements, MemoryLocation::GpuToCpu)?;
+
+        let result = (|| -> Result<Vec<u8>, ()>
+        {
+            unsafe { self.handle.bind_buffer_memory(buffer, memory, 0) }.map_err(|_| ())?;
+
+            self.immediate_submit(|command_buffer|
+            {
+                record(command_buffer, buffer);
+
+                let barrier = vk::MemoryBarrier::builder()
+                    .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                    .dst_access_mask(vk::AccessFlags::HOST_READ);
+                unsafe
+                {
+                    self.handle.cmd_pipeline_barrier(
+                        command_buffer,
+                        vk::PipelineStageFlags::TRANSFER,
+                        vk::PipelineStageFlags::HOST,
+                        vk::DependencyFlags::empty(),
+                        &[ *barrier ],
+                        &[],
+                        &[])
+                };
+            })?;
+
+            let mut data = vec![0; size as usize];
+            unsafe
+            {
+                let ptr = self.handle.map_memory(memory, 0, size, vk::MemoryMapFlags::empty()).map_err(|_| ())?;
+                std::ptr::copy_nonoverlapping(ptr as *const u8, data.as_mut_ptr(), data.len());
+                self.handle.unmap_memory(memory);
+            }
+
+            Ok(data)
+        })();
+
+        unsafe
+        {
+            self.handle.destroy_buffer(buffer, None);
+            self.handle.free_memory(memory, None);
+        }
+
+        result
+    }
+
+    // every subresource comes back to the layout it was in, or stays in TransferSrcOptimal if it was unknown
+    fn read_image_region(&self, image: &VkImage, region: &ImageRegion) -> Result<Vec<u8>, ()>
+    {
+        let size = image.region_size(region)?;
+        let runs = image.layout_runs(&image.region_subresource_range(region));
+        let restored_layout = |layout: vk::ImageLayout| if layout == vk::ImageLayout::UNDEFINED { vk::ImageLayout::TRANSFER_SRC_OPTIMAL } else { layout };
+
+        let data = self.read_back(size, |command_buffer, buffer|
+        {
+            let to_transfer: Vec<vk::ImageMemoryBarrier> = runs.iter()
+                .map(|(range, layout)| vk::ImageMemoryBarrier::builder()
+                    .src_access_mask(vk::AccessFlags::MEMORY_WRITE)
+                    .dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+                    .old_layout(*layout)
+                    .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .image(image.handle)
+                    .subresource_range(*range)
+                    .build())
+                .collect();
+            let copy = image.region_buffer_copy(region, 0);
+            let restore: Vec<vk::ImageMemoryBarrier> = runs.iter()
+                .map(|(range, layout)| vk::ImageMemoryBarrier::builder()
+                    .src_access_mask(vk::AccessFlags::empty())
+                    .dst_access_mask(vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE)
+                    .old_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+                    .new_layout(restored_layout(*layout))
+                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .image(image.handle)
+                    .subresource_range(*range)
+                    .build())
+                .collect();
+
+            unsafe
+            {
+                self.handle.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::ALL_COMMANDS,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &to_transfer);
+                self.handle.cmd_copy_image_to_buffer(command_buffer, image.handle, vk::ImageLayout::TRANSFER_SRC_OPTIMAL, buffer, &[ copy ]);
+                self.handle.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::ALL_COMMANDS,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &restore);
+            }
+        })?;
+
+        for (range, layout) in runs.iter()
+        {
+            image.set_layout(range, restored_layout(*layout));
+        }
+        Ok(data)
+    }
+
+    // every usage of the image has its own limit, the sample count has to be in all of them
+    fn is_sample_count_supported(&self, samples: u32, format: Format, usage: u32) -> bool
+    {
+        let limits = &self.physical_device.properties.limits;
+        let integer = matches!(format, Format::R8Uint | Format::R8Sint | Format::R16Uint | Format::R16Sint | Format::R32Uint | Format::R32Sint
+            | Format::R8G8B8A8Uint | Format::R32G32B32A32Uint);
+
+        let mut supported = vk::SampleCountFlags::from_raw(!0);
+        if usage & ImageUsageFlags::COLOR_ATTACHMENT != 0
+        {
+            supported &= limits.framebuffer_color_sample_counts;
+        }
+        if usage & ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT != 0
+        {
+            supported &= limits.framebuffer_depth_sample_counts;
+            if format.has_stencil()
+            {
+                supported &= limits.framebuffer_stencil_sample_counts;
+            }
+        }
+        if usage & ImageUsageFlags::SAMPLED != 0
+        {
+            supported &= match format
+            {
+                format if format.has_depth() && format.has_stencil() => limits.sampled_image_depth_sample_counts & limits.sampled_image_stencil_sample_counts,
+                format if format.has_depth() => limits.sampled_image_depth_sample_counts,
+                _ if integer => limits.sampled_image_integer_sample_counts,
+                _ => limits.sampled_image_color_sample_counts
+            };
+        }
+        if usage & ImageUsageFlags::STORAGE != 0
+        {
+            supported &= limits.storage_image_sample_counts;
+        }
+
+        samples.is_power_of_two() && supported.contains(vk::SampleCountFlags::from_raw(samples))
+    }
+}
+
+const STAGING_RING_SIZE: u64 = 16 * 1024 * 1024;
+
+struct VkStagingUpload
+{
+    fence: Fence,
+    command_buffer: vk::CommandBuffer,
+    start: Option<u64>,
+    dedicated: Option<(vk::Buffer, vk::DeviceMemory)>
+}
+
+// a persistently mapped host buffer handed out front to back. uploads retire in submission
+// order, so the used region always runs from the oldest in flight upload up to head
+pub struct VkStagingRing
+{
+    buffer: vk::Buffer,
+    mapped: *mut u8,
+    size: u64,
+    alignment: u64,
+    head: u64,
+    command_pool: vk::CommandPool,
+    in_flight: VecDeque<VkStagingUpload>
+}
+
+// the mapped pointer is only touched while the device's staging lock is held
+unsafe impl Send for VkStagingRing {}
+
+impl VkStagingRing
+{
+    fn allocate(&mut self, size: u64) -> Option<u64>
+    {
+        let tail = self.in_flight.iter().find_map(|upload| upload.start);
+        let aligned_head = (self.head + self.alignment - 1) / self.alignment * self.alignment;
+
+        let offset = match tail
+        {
+            None => 0,
+            Some(tail) if self.head > tail =>
+            {
+                if aligned_head + size <= self.size { aligned_head }
+                else if size < tail { 0 }
+                else { return None; }
+            },
+            Some(tail) =>
+            {
+                if aligned_head + size < tail { aligned_head }
+                else { return None; }
+            }
+        };
+
+        self.head = offset + size;
+        Some(offset)
+    }
+
+    fn reclaim(&mut self, device: &ash::Device, wait_oldest: bool) -> Result<(), ()>
+    {
+        if let (true, Some(upload)) = (wait_oldest, self.in_flight.front())
+        {
+            upload.fence.wait(u64::MAX)?;
+        }
+
+        while let Some(upload) = self.in_flight.front()
+        {
+            if !upload.fence.is_signaled()?
+            {
+                break;
+            }
+
+            let upload = self.in_flight.pop_front().unwrap();
+            unsafe { device.free_command_buffers(self.command_pool, &[ upload.command_buffer ]) };
+            if let Some((buffer, memory)) = upload.dedicated
+            {
+                unsafe
+                {
+                    device.destroy_buffer(buffer, None);
+                    device.free_memory(memory, None);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl AbstractDevice for VkDevice
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn get_device_queue(&self) -> Result<Queue, ()>
+    {
+        self.get_queue(QueueKind::Graphics, 0)
+    }
+
+    fn get_queue(&self, kind: QueueKind, index: u32) -> Result<Queue, ()>
+    {
+        let slot = match self.queues.iter().filter(|slot| slot.kind == kind).nth(index as usize)
+        {
+            Some(slot) => slot,
+            None if index == 0 => &self.queues[0],
+            None => { return Err(()); }
+        };
+        let handle = unsafe { self.handle.get_device_queue(slot.family_index, slot.index) };
+
+        Ok(Queue { internal: Arc::new(VkQueue
+        {
+            handle,
+            device: self.handle.clone(),
+            kind,
+            family_index: slot.family_index,
+            lock: slot.lock.clone()
+        }) })
+    }
+
+	fn get_physical_device_properties(&self) -> Result<PhysicalDeviceProperties, ()>
+	{
+		Ok(PhysicalDeviceProperties
+		{
+			vendor_id: self.physical_device.properties.vendor_id,
+			device_id: self.physical_device.properties.device_id,
+			device_type: match self.physical_device.properties.device_type
+			{
+				vk::PhysicalDeviceType::CPU => DeviceType::CPU,
+				vk::PhysicalDeviceType::DISCRETE_GPU => DeviceType::DiscreteGPU,
+				vk::PhysicalDeviceType::INTEGRATED_GPU => DeviceType::IntegratedGPU,
+				vk::PhysicalDeviceType::VIRTUAL_GPU => DeviceType::VirtualGPU,
+				_ => DeviceType::Other
+			},
+			device_name: unsafe { std::ffi::CStr::from_ptr(self.physical_device.properties.device_name.as_ptr()) }.to_str().unwrap().to_owned(),
+			features: self.physical_device.features(),
+			limits: self.physical_device.limits()
+		})
+	}
+
+    fn enabled_features(&self) -> u32
+    {
+        let mut features = qrl_features(&self.enabled_features);
+        if self.draw_indirect_count.is_some()
+        {
+            features |= FeatureFlags::DRAW_INDIRECT_COUNT;
+        }
+        if self.vertex_attribute_divisor
+        {
+            features |= FeatureFlags::VERTEX_ATTRIBUTE_DIVISOR;
+        }
+        if self.dynamic_rendering.is_some()
+        {
+            features |= FeatureFlags::DYNAMIC_RENDERING;
+        }
+        features
+    }
+
+    fn enabled_extensions(&self) -> Vec<DeviceExtension>
+    {
+        self.extensions.iter().map(|name| qrl_device_extension(name)).collect()
+    }
+
+    fn is_extension_enabled(&self, extension: &DeviceExtension) -> bool
+    {
+        vk_device_extension(extension).is_some_and(|name| self.extensions.contains(&name))
+    }
+
+    fn create_shader_module(&self, create_info: &ShaderModuleCreateInfo) -> Result<ShaderModule, ShaderModuleError>
+    {
+        let code = create_info.source.to_spirv(create_info.stage)?;
+
+        if code.first() != Some(&SPIRV_MAGIC)
+        {
+            return Err(ShaderModuleError::CompilationFailed("not a SPIR-V module".to_string()));
+        }
+
+        let handle = unsafe { self.handle.create_shader_module(&ash::vk::ShaderModuleCreateInfo::builder()
+            .code(&code)
+            .build(), None) }.map_err(|err| ShaderModuleError::CompilationFailed(format!("vkCreateShaderModule failed: {:?}", err)))?;
+
+        self.set_object_name(handle, create_info.label);
+        Ok(ShaderModule { internal: Arc::new(VkShaderModule { handle, stage: create_info.stage }) })
+    }
+
+    fn create_buffer(&self, create_info: &BufferCreateInfo) -> Result<Buffer, ()>
+    {
+        let handle = self.create_buffer_handle(create_info)?;
+
+        let requirements = unsafe { self.handle.get_buffer_memory_requirements(handle) };
+        let memory = self.allocate_device_memory(&requirements, create_info.location)?;
+        unsafe { self.handle.bind_buffer_memory(handle, memory, 0) }.map_err(|_| ())?;
+
+        self.set_object_name(handle, create_info.label);
+        Ok(Buffer { internal: Arc::new(VkBuffer
+        {
+            handle,
+            memory,
+            memory_offset: 0,
+            dedicated: true,
+            size: create_info.size,
+            location: create_info.location,
+            map_lock: Arc::new(Mutex::new(()))
+        }) })
+    }
+
+    fn create_image(&self, create_info: &ImageCreateInfo) -> Result<Image, ()>
+    {
+        let handle = self.create_image_handle(create_info)?;
+
+        let requirements = unsafe { self.handle.get_image_memory_requirements(handle) };
+        let memory = self.allocate_device_memory(&requirements, MemoryLocation::GpuOnly)?;
+        unsafe { self.handle.bind_image_memory(handle, memory, 0) }.map_err(|_| ())?;
+
+        self.set_object_name(handle, create_info.label);
+        Ok(Image { internal: Arc::new(VkImage::new(handle, VkImageMemory::Dedicated(memory), create_info)) })
+    }
+
+    fn buffer_memory_requirements(&self, create_info: &BufferCreateInfo) -> Result<MemoryRequirements, ()>
+    {
+        // vulkan 1.0 can only query requirements of an existing object
+        let handle = self.create_buffer_handle(create_info)?;
+        let requirements = unsafe { self.handle.get_buffer_memory_requirements(handle) };
+        unsafe { self.handle.destroy_buffer(handle, None) };
+
+        Ok(MemoryRequirements { size: requirements.size, alignment: requirements.alignment, memory_type_bits: requirements.memory_type_bits })
+    }
+
+    fn image_memory_requirements(&self, create_info: &ImageCreateInfo) -> Result<MemoryRequirements, ()>
+    {
+        let handle = self.create_image_handle(create_info)?;
+        let requirements = unsafe { self.handle.get_image_memory_requirements(handle) };
+        unsafe { self.handle.destroy_image(handle, None) };
+
+        Ok(MemoryRequirements { size: requirements.size, alignment: requirements.alignment, memory_type_bits: requirements.memory_type_bits })
+    }
+
+    fn allocate_memory(&self, allocate_info: &MemoryAllocateInfo) -> Result<Memory, ()>
+    {
+        let memory_type_index = self.memory_type_index(allocate_info.memory_type_bits, allocate_info.location)?;
+        let vk_allocate_info = vk::MemoryAllocateInfo::builder()
+            .allocation_size(allocate_info.size)
+            .memory_type_index(memory_type_index);
+        let handle = unsafe { self.handle.allocate_memory(&vk_allocate_info, None) }.map_err(|_| ())?;
+
+        self.set_object_name(handle, allocate_info.label);
+        Ok(Memory { internal: Arc::new(VkMemory
+        {
+            handle,
+            size: allocate_info.size,
+            memory_type_index,
+            location: allocate_info.location,
+            map_lock: Arc::new(Mutex::new(()))
+        }) })
+    }
+
+    fn create_placed_buffer(&self, create_info: &BufferCreateInfo, memory: &Memory, offset: u64) -> Result<Buffer, ()>
+    {
+        let memory = memory.downcast_ref::<VkMemory>().unwrap();
+        let handle = self.create_buffer_handle(create_info)?;
+
+        let requirements = unsafe { self.handle.get_buffer_memory_requirements(handle) };
+        if !memory.can_place(&requirements, offset) || unsafe { self.handle.bind_buffer_memory(handle, memory.handle, offset) }.is_err()
+        {
+            unsafe { self.handle.destroy_buffer(handle, None) };
+            return Err(());
+        }
+
+        self.set_object_name(handle, create_info.label);
+        Ok(Buffer { internal: Arc::new(VkBuffer
+        {
+            handle,
+            memory: memory.handle,
+            memory_offset: offset,
+            dedicated: false,
+            size: create_info.size,
+            location: memory.location,
+            map_lock: memory.map_lock.clone()
+        }) })
+    }
+
+    fn create_placed_image(&self, create_info: &ImageCreateInfo, memory: &Memory, offset: u64) -> Result<Image, ()>
+    {
+        let memory = memory.downcast_ref::<VkMemory>().unwrap();
+        let handle = self.create_image_handle(create_info)?;
+
+        let requirements = unsafe { self.handle.get_image_memory_requirements(handle) };
+        if !memory.can_place(&requirements, offset) || unsafe { self.handle.bind_image_memory(handle, memory.handle, offset) }.is_err()
+        {
+            unsafe { self.handle.destroy_image(handle, None) };
+            return Err(());
+        }
+
+        self.set_object_name(handle, create_info.label);
+        Ok(Image { internal: Arc::new(VkImage::new(handle, VkImageMemory::Placed, create_info)) })
+    }
+
+    fn create_image_view(&self, create_info: &ImageViewCreateInfo) -> Result<ImageView, ()>
+    {
+        let image = create_info.image.downcast_ref::<VkImage>().unwrap();
+
+        let view_type = if image.extent.depth > 1
+        {
+            vk::ImageViewType::TYPE_3D
+        }
+        else if create_info.array_layer_count > 1
+        {
+            vk::ImageViewType::TYPE_2D_ARRAY
+        }
+        else
+        {
+            vk::ImageViewType::TYPE_2D
+        };
+
+        let subresource_range = vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk_aspect_mask(image.format))
+            .base_mip_level(create_info.base_mip_level)
+            .level_count(create_info.mip_level_count)
+            .base_array_layer(create_info.base_array_layer)
+            .layer_count(create_info.array_layer_count);
+        let imageview_create_info = vk::ImageViewCreateInfo::builder()
+            .image(image.handle)
+            .view_type(view_type)
+            .format(image.format)
+            .subresource_range(*subresource_range);
+        let handle = unsafe { self.handle.create_image_view(&imageview_create_info, None) }.map_err(|_| ())?;
+
+        self.set_object_name(handle, create_info.label);
+        Ok(ImageView { internal: Arc::new(VkImageView
+        {
+            handle,
+            parent: create_info.image.clone(),
+            range: ImageSubresourceRange
+            {
+                base_mip_level: create_info.base_mip_level,
+                mip_level_count: create_info.mip_level_count,
+                base_array_layer: create_info.base_array_layer,
+                array_layer_count: create_info.array_layer_count
+            }
+        }) })
+    }
+
+    fn create_descriptor_set_layout(&self, create_info: &DescriptorSetLayoutCreateInfo) -> Result<DescriptorSetLayout, ()>
+    {
+        let bindings: Vec<vk::DescriptorSetLayoutBinding> = create_info.bindings.iter()
+            .map(|binding| vk::DescriptorSetLayoutBinding::builder()
+                .binding(binding.binding)
+                .descriptor_type(vk_descriptor_type(binding.descriptor_type))
+                .descriptor_count(binding.count)
+                .stage_flags(vk::ShaderStageFlags::from_raw(binding.stages))
+                .build())
+            .collect();
+        let layout_create_info = vk::DescriptorSetLayoutCreateInfo::builder()
+            .bindings(&bindings);
+        let handle = unsafe { self.handle.create_descriptor_set_layout(&layout_create_info, None) }.map_err(|_| ())?;
+
+        self.set_object_name(handle, create_info.label);
+        Ok(DescriptorSetLayout { internal: Arc::new(VkDescriptorSetLayout { handle, bindings: create_info.bindings.clone() }) })
+    }
+
+    fn create_descriptor_set(&self, create_info: &DescriptorSetCreateInfo) -> Result<DescriptorSet, ()>
+    {
+        let layout = create_info.layout.downcast_ref::<VkDescriptorSetLayout>().unwrap();
+
+        // every set gets a pool sized exactly for its layout, the pool is destroyed together with the set
+        let pool_sizes: Vec<vk::DescriptorPoolSize> = layout.bindings.iter()
+            .map(|binding| vk::DescriptorPoolSize
+            {
+                ty: vk_descriptor_type(binding.descriptor_type),
+                descriptor_count: binding.count
+            })
+            .collect();
+        let pool_create_info = vk::DescriptorPoolCreateInfo::builder()
+            .max_sets(1)
+            .pool_sizes(&pool_sizes);
+        let pool = unsafe { self.handle.create_descriptor_pool(&pool_create_info, None) }.map_err(|_| ())?;
+
+        let set_layouts = [ layout.handle ];
+        let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(pool)
+            .set_layouts(&set_layouts);
+        let handle = match unsafe { self.handle.allocate_descriptor_sets(&allocate_info) }
+        {
+            Ok(sets) => sets[0],
+            Err(_) =>
+            {
+                unsafe { self.handle.destroy_descriptor_pool(pool, None) };
+                return Err(());
+            }
+        };
+        let descriptor_set = VkDescriptorSet { handle, pool, device: self.handle.clone() };
+
+        for write in create_info.writes
+        {
+            let descriptor_type = match layout.bindings.iter().find(|binding| binding.binding == write.binding)
+            {
+                Some(binding) => vk_descriptor_type(binding.descriptor_type),
+                None => { return Err(()); }
+            };
+
+            match &write.resource
+            {
+                DescriptorResource::Buffer { buffer, offset, size } =>
+                {
+                    let buffer = buffer.downcast_ref::<VkBuffer>().unwrap();
+                    let buffer_infos = [ vk::DescriptorBufferInfo { buffer: buffer.handle, offset: *offset, range: *size } ];
+                    let descriptor_write = vk::WriteDescriptorSet::builder()
+                        .dst_set(handle)
+                        .dst_binding(write.binding)
+                        .descriptor_type(descriptor_type)
+                        .buffer_info(&buffer_infos);
+                    unsafe { self.handle.update_descriptor_sets(&[ *descriptor_write ], &[]) };
+                },
+                DescriptorResource::Image(view) =>
+                {
+                    let view = view.downcast_ref::<VkImageView>().unwrap();
+                    let image_infos = [ vk::DescriptorImageInfo { sampler: vk::Sampler::null(), image_view: view.handle, image_layout: vk::ImageLayout::GENERAL } ];
+                    let descriptor_write = vk::WriteDescriptorSet::builder()
+                        .dst_set(handle)
+                        .dst_binding(write.binding)
+                        .descriptor_type(descriptor_type)
+                        .image_info(&image_infos);
+                    unsafe { self.handle.update_descriptor_sets(&[ *descriptor_write ], &[]) };
+                }
+            }
+        }
+
+        self.set_object_name(handle, create_info.label);
+        Ok(DescriptorSet { internal: Arc::new(descriptor_set) })
+    }
+
+    fn create_compute_pipeline(&self, create_info: &ComputePipelineCreateInfo) -> Result<ComputePipeline, PipelineError>
+    {
+        let shader = create_info.shader.downcast_ref::<VkShaderModule>().ok_or(PipelineError::UnsupportedShaderSource)?;
+        if shader.stage != ShaderStage::Compute
+        {
+            return Err(PipelineError::InvalidShaderStage);
+        }
+
+        let layout = self.create_pipeline_layout(create_info.set_layouts).map_err(|_| PipelineError::CreationFailed)?;
+
+        let entry_point = std::ffi::CString::new("main").unwrap();
+        let stage = vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::COMPUTE)
+            .module(shader.handle)
+            .name(&entry_point);
+        let pipeline_create_info = vk::ComputePipelineCreateInfo::builder()
+            .stage(*stage)
+            .layout(layout);
+        let pipeline_cache = create_info.pipeline_cache.map_or(vk::PipelineCache::null(), |cache| cache.downcast_ref::<VkPipelineCache>().unwrap().handle);
+        let handle = match unsafe { self.handle.create_compute_pipelines(pipeline_cache, &[ *pipeline_create_info ], None) }
+        {
+            Ok(pipelines) => pipelines[0],
+            Err(_) => { return Err(PipelineError::CreationFailed); }
+        };
+
+        self.set_object_name(handle, create_info.label);
+        Ok(ComputePipeline { internal: Arc::new(VkComputePipeline { handle, layout }) })
+    }
+
+    fn create_render_pass(&self, create_info: &RenderPassCreateInfo) -> Result<RenderPass, ()>
+    {
+        let attachment_usage = |format: Format| if format.has_depth() { ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT } else { ImageUsageFlags::COLOR_ATTACHMENT };
+        if create_info.attachments.iter().any(|attachment| !self.is_sample_count_supported(attachment.samples, attachment.format, attachment_usage(attachment.format)))
+        {
+            return Err(());
+        }
+
+        if create_info.subpasses.iter().any(|subpass| !subpass.resolve_attachments.is_empty() && subpass.resolve_attachments.len() != subpass.color_attachments.len())
+        {
+            return Err(());
+        }
+
+        let attachments: Vec<vk::AttachmentDescription> = create_info.attachments.iter()
+            .map(|attachment| vk::AttachmentDescription::builder()
+                .format(vk_format(attachment.format))
+                .samples(vk::SampleCountFlags::from_raw(attachment.samples))
+                .load_op(vk_load_op(attachment.operations.load_op))
+                .store_op(vk_store_op(attachment.operations.store_op))
+                .stencil_load_op(vk_load_op(attachment.stencil_operations.load_op))
+                .stencil_store_op(vk_store_op(attachment.stencil_operations.store_op))
+                .initial_layout(vk_image_layout(attachment.initial_layout))
+                .final_layout(vk_image_layout(Some(attachment.final_layout)))
+                .build())
+            .collect();
+
+        let attachment_reference = |reference: &AttachmentReference| vk::AttachmentReference
+        {
+            attachment: reference.attachment,
+            layout: vk_image_layout(Some(reference.layout))
+        };
+        let color_references: Vec<Vec<vk::AttachmentReference>> = create_info.subpasses.iter()
+            .map(|subpass| subpass.color_attachments.iter().map(attachment_reference).collect())
+            .collect();
+        let resolve_references: Vec<Vec<vk::AttachmentReference>> = create_info.subpasses.iter()
+            .map(|subpass| subpass.resolve_attachments.iter().map(attachment_reference).collect())
+            .collect();
+        let depth_references: Vec<Option<vk::AttachmentReference>> = create_info.subpasses.iter()
+            .map(|subpass| subpass.depth_stencil_attachment.as_ref().map(attachment_reference))
+            .collect();
+
+        let subpasses: Vec<vk::SubpassDescription> = (0..create_info.subpasses.len())
+            .map(|index|
+            {
+                let mut subpass = vk::SubpassDescription::builder()
+                    .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+                    .color_attachments(&color_references[index]);
+
+                if !resolve_references[index].is_empty()
+                {
+                    subpass = subpass.resolve_attachments(&resolve_references[index]);
+                }
+
+                if let Some(depth_reference) = &depth_references[index]
+                {
+                    subpass = subpass.depth_stencil_attachment(depth_reference);
+                }
+
+                subpass.build()
+            })
+            .collect();
+
+        let dependencies: Vec<vk::SubpassDependency> = create_info.dependencies.iter()
+            .map(|dependency| vk::SubpassDependency
+            {
+                src_subpass: dependency.src_subpass,
+                dst_subpass: dependency.dst_subpass,
+                src_stage_mask: vk::PipelineStageFlags::from_raw(dependency.src_stage_mask),
+                dst_stage_mask: vk::PipelineStageFlags::from_raw(dependency.dst_stage_mask),
+                src_access_mask: vk::AccessFlags::from_raw(dependency.src_access_mask),
+                dst_access_mask: vk::AccessFlags::from_raw(dependency.dst_access_mask),
+                dependency_flags: vk::DependencyFlags::empty()
+            })
+            .collect();
+
+        let render_pass_create_info = vk::RenderPassCreateInfo::builder()
+            .attachments(&attachments)
+            .subpasses(&subpasses)
+            .dependencies(&dependencies);
+        let handle = unsafe { self.handle.create_render_pass(&render_pass_create_info, None) }.map_err(|_| ())?;
+
+        self.set_object_name(handle, create_info.label);
+        Ok(RenderPass { internal: Arc::new(VkRenderPass { handle, create_info: RenderPassCreateInfo
+        {
+            attachments: create_info.attachments.clone(),
+            subpasses: create_info.subpasses.clone(),
+            dependencies: create_info.dependencies.clone(),
+            label: None
+        } }) })
+    }
+
+    fn create_framebuffer(&self, create_info: &FramebufferCreateInfo) -> Result<Framebuffer, ()>
+    {
+        let render_pass = create_info.render_pass.downcast_ref::<VkRenderPass>().unwrap();
+        let attachments: Vec<vk::ImageView> = create_info.attachments.iter()
+            .map(|view| view.downcast_ref::<VkImageView>().unwrap().handle)
+            .collect();
+
+        let framebuffer_create_info = vk::FramebufferCreateInfo::builder()
+            .render_pass(render_pass.handle)
+            .attachments(&attachments)
+            .width(create_info.width)
+            .height(create_info.height)
+            .layers(create_info.layers);
+        let handle = unsafe { self.handle.create_framebuffer(&framebuffer_create_info, None) }.map_err(|_| ())?;
+
+        self.set_object_name(handle, create_info.label);
+        Ok(Framebuffer { internal: Arc::new(VkFramebuffer
+        {
+            handle,
+            attachments: create_info.attachments.iter().map(|view| (*view).clone()).collect(),
+            width: create_info.width,
+            height: create_info.height
+        }) })
+    }
+
+    fn create_render_pipeline(&self, create_info: &RenderPipelineCreateInfo) -> Result<RenderPipeline, PipelineError>
+    {
+        let color_attachment_count = match create_info.rendering
+        {
+            PipelineRendering::RenderPass { render_pass, subpass } =>
+            {
+                let render_pass = render_pass.downcast_ref::<VkRenderPass>().unwrap();
+                render_pass.create_info.subpasses.get(subpass as usize).ok_or(PipelineError::CreationFailed)?.color_attachments.len()
+            },
+            PipelineRendering::Dynamic { color_formats, .. } if self.dynamic_rendering.is_some() => color_formats.len(),
+            PipelineRendering::Dynamic { .. } => return Err(PipelineError::FeatureNotSupported)
+        };
+
+        let mut shaders: Vec<&VkShaderModule> = Vec::with_capacity(create_info.stages.len());
+        for stage in create_info.stages
+        {
+            shaders.push(stage.downcast_ref::<VkShaderModule>().ok_or(PipelineError::UnsupportedShaderSource)?);
+        }
+
+        let stages: Vec<ShaderStage> = shaders.iter().map(|shader| shader.stage).collect();
+        validate_render_pipeline_stages(&stages, create_info.primitive)?;
+        validate_vertex_buffers(create_info.vertex_buffers)?;
+
+        let divisors: Vec<vk::VertexInputBindingDivisorDescriptionEXT> = create_info.vertex_buffers.iter().enumerate()
+            .filter_map(|(binding, layout)| match layout.step_mode
+            {
+                VertexStepMode::Instance(divisor) if divisor != 1 => Some(vk::VertexInputBindingDivisorDescriptionEXT { binding: binding as u32, divisor }),
+                _ => None
+            })
+            .collect();
+
+        if divisors.iter().any(|divisor| divisor.divisor == 0) || (!divisors.is_empty() && !self.vertex_attribute_divisor)
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        if stages.contains(&ShaderStage::TessellationEvaluation) && self.enabled_features.tessellation_shader != vk::TRUE
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        if stages.contains(&ShaderStage::Geometry) && self.enabled_features.geometry_shader != vk::TRUE
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        if !self.is_sample_count_supported(create_info.multisample.samples, Format::Undefined, ImageUsageFlags::COLOR_ATTACHMENT)
+        {
+            return Err(PipelineError::InvalidSampleCount);
+        }
+
+        if create_info.multisample.min_sample_shading.is_some() && self.enabled_features.sample_rate_shading != vk::TRUE
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        let (color_targets, independent_blend) = resolve_color_targets(create_info.color_targets, color_attachment_count)?;
+
+        if independent_blend && self.enabled_features.independent_blend != vk::TRUE
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        if color_targets.iter().any(|target| target.uses_dual_source()) && self.enabled_features.dual_src_blend != vk::TRUE
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        if create_info.depth_clamp && self.enabled_features.depth_clamp != vk::TRUE
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        if create_info.polygon_mode != PolygonMode::Fill && self.enabled_features.fill_mode_non_solid != vk::TRUE
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        if create_info.depth_bias.is_some_and(|depth_bias| depth_bias.clamp != 0.0) && self.enabled_features.depth_bias_clamp != vk::TRUE
+        {
+            return Err(PipelineError::FeatureNotSupported);
+        }
+
+        let entry_point = std::ffi::CString::new("main").unwrap();
+        let shader_stages: Vec<vk::PipelineShaderStageCreateInfo> = shaders.iter()
+            .map(|shader| vk::PipelineShaderStageCreateInfo::builder()
+                .stage(vk_shader_stage(shader.stage))
+                .module(shader.handle)
+                .name(&entry_point)
+                .build())
+            .collect();
+
+        let vertex_bindings: Vec<vk::VertexInputBindingDescription> = create_info.vertex_buffers.iter().enumerate()
+            .map(|(binding, layout)| vk::VertexInputBindingDescription
+            {
+                binding: binding as u32,
+                stride: layout.stride,
+                input_rate: match layout.step_mode
+                {
+                    VertexStepMode::Vertex => vk::VertexInputRate::VERTEX,
+                    VertexStepMode::Instance(_) => vk::VertexInputRate::INSTANCE
+                }
+            })
+            .collect();
+        let vertex_attributes: Vec<vk::VertexInputAttributeDescription> = create_info.vertex_buffers.iter().enumerate()
+            .flat_map(|(binding, layout)| layout.attributes.iter().map(move |attribute| vk::VertexInputAttributeDescription
+            {
+                location: attribute.location,
+                binding: binding as u32,
+                format: vk_vertex_format(attribute.format),
+                offset: attribute.offset
+            }))
+            .collect();
+        let mut divisor_state = vk::PipelineVertexInputDivisorStateCreateInfoEXT::builder()
+            .vertex_binding_divisors(&divisors);
+        let mut vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder()
+            .vertex_binding_descriptions(&vertex_bindings)
+            .vertex_attribute_descriptions(&vertex_attributes);
+        if !divisors.is_empty()
+        {
+            vertex_input_state = vertex_input_state.push_next(&mut divisor_state);
+        }
+        let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
+            .topology(vk_primitive_topology(create_info.primitive));
+        let tessellation_state = vk::PipelineTessellationStateCreateInfo::builder()
+            .patch_control_points(match create_info.primitive { Primitive::PatchList(control_points) => control_points, _ => 0 });
+
+        let viewports =
+        [
+            vk::Viewport
+            {
+                x: create_info.viewport.x,
+                y: create_info.viewport.y,
+                width: create_info.viewport.width,
+                height: create_info.viewport.height,
+                min_depth: create_info.viewport.min_depth,
+                max_depth: create_info.viewport.max_depth
+            }
+        ];
+        let scissors =
+        [
+            vk::Rect2D
+            {
+                offset: vk::Offset2D { x: create_info.viewport.x as i32, y: create_info.viewport.y as i32 },
+                extent: vk::Extent2D { width: create_info.viewport.width as u32, height: create_info.viewport.height as u32 }
+            }
+        ];
+        let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
+            .viewports(&viewports)
+            .scissors(&scissors);
+
+        let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
+            .polygon_mode(match create_info.polygon_mode
+            {
+                PolygonMode::Fill => vk::PolygonMode::FILL,
+                PolygonMode::Line => vk::PolygonMode::LINE,
+                PolygonMode::Point => vk::PolygonMode::POINT
+            })
+            .cull_mode(match create_info.cull_mode
+            {
+                None => vk::CullModeFlags::NONE,
+                Some(CullMode::Front) => vk::CullModeFlags::FRONT,
+                Some(CullMode::Back) => vk::CullModeFlags::BACK
+            })
+            .front_face(match create_info.front_face
+            {
+                FrontFace::Clockwise => vk::FrontFace::CLOCKWISE,
+                FrontFace::CounterClockwise => vk::FrontFace::COUNTER_CLOCKWISE
+            })
+            .depth_clamp_enable(create_info.depth_clamp)
+            .depth_bias_enable(create_info.depth_bias.is_some() || create_info.dynamic_state & DynamicStateFlags::DEPTH_BIAS != 0)
+            .depth_bias_constant_factor(create_info.depth_bias.map_or(0.0, |depth_bias| depth_bias.constant_factor))
+            .depth_bias_slope_factor(create_info.depth_bias.map_or(0.0, |depth_bias| depth_bias.slope_factor))
+            .depth_bias_clamp(create_info.depth_bias.map_or(0.0, |depth_bias| depth_bias.clamp))
+            .line_width(1.0);
+
+        let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+            .rasterization_samples(vk::SampleCountFlags::from_raw(create_info.multisample.samples))
+            .sample_shading_enable(create_info.multisample.min_sample_shading.is_some())
+            .min_sample_shading(create_info.multisample.min_sample_shading.unwrap_or(0.0))
+            .alpha_to_coverage_enable(create_info.multisample.alpha_to_coverage_enable);
+
+        let depth_stencil = &create_info.depth_stencil;
+        let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::builder()
+            .depth_test_enable(depth_stencil.depth_test_enable)
+            .depth_write_enable(depth_stencil.depth_write_enable)
+            .depth_compare_op(vk_compare_op(depth_stencil.depth_compare_op))
+            .stencil_test_enable(depth_stencil.stencil_test_enable)
+            .front(vk_stencil_op_state(&depth_stencil.front))
+            .back(vk_stencil_op_state(&depth_stencil.back));
+
+        let color_blend_attachments: Vec<vk::PipelineColorBlendAttachmentState> = color_targets.iter()
+            .map(|target| vk::PipelineColorBlendAttachmentState::builder()
+                .blend_enable(target.blend_enable)
+                .src_color_blend_factor(vk_blend_factor(target.src_color_factor))
+                .dst_color_blend_factor(vk_blend_factor(target.dst_color_factor))
+                .color_blend_op(vk_blend_op(target.color_op))
+                .src_alpha_blend_factor(vk_blend_factor(target.src_alpha_factor))
+                .dst_alpha_blend_factor(vk_blend_factor(target.dst_alpha_factor))
+                .alpha_blend_op(vk_blend_op(target.alpha_op))
+                .color_write_mask(vk::ColorComponentFlags::from_raw(target.write_mask & ColorWriteFlags::ALL))
+                .build())
+            .collect();
+        let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
+            .attachments(&color_blend_attachments);
+
+        let dynamic_states: Vec<vk::DynamicState> =
+        [
+            (DynamicStateFlags::VIEWPORT, vk::DynamicState::VIEWPORT),
+            (DynamicStateFlags::SCISSOR, vk::DynamicState::SCISSOR),
+            (DynamicStateFlags::LINE_WIDTH, vk::DynamicState::LINE_WIDTH),
+            (DynamicStateFlags::DEPTH_BIAS, vk::DynamicState::DEPTH_BIAS),
+            (DynamicStateFlags::BLEND_CONSTANTS, vk::DynamicState::BLEND_CONSTANTS),
+            (DynamicStateFlags::STENCIL_REFERENCE, vk::DynamicState::STENCIL_REFERENCE)
+        ].iter()
+            .filter(|(flag, _)| create_info.dynamic_state & flag != 0)
+            .map(|(_, state)| *state)
+            .collect();
+        let dynamic_state = vk::PipelineDynamicStateCreateInfo::builder()
+            .dynamic_states(&dynamic_states);
+
+        let layout = self.create_pipeline_layout(create_info.set_layouts).map_err(|_| PipelineError::CreationFailed)?;
+
+        let mut pipeline_create_info = vk::GraphicsPipelineCreateInfo::builder()
+            .stages(&shader_stages)
+            .vertex_input_state(&vertex_input_state)
+            .input_assembly_state(&input_assembly_state)
+            .viewport_state(&viewport_state)
+            .rasterization_state(&rasterization_state)
+            .multisample_state(&multisample_state)
+            .depth_stencil_state(&depth_stencil_state)
+            .color_blend_state(&color_blend_state)
+            .dynamic_state(&dynamic_state)
+            .layout(layout);
+
+        let (color_formats, depth_stencil_format) = match create_info.rendering
+        {
+            PipelineRendering::Dynamic { color_formats, depth_stencil_format } => (color_formats.iter().map(|format| vk_format(*format)).collect(), depth_stencil_format),
+            PipelineRendering::RenderPass { .. } => (Vec::new(), None)
+        };
+        let depth_stencil_format = depth_stencil_format.unwrap_or(Format::Undefined);
+        let mut rendering_create_info = vk::PipelineRenderingCreateInfo::builder()
+            .color_attachment_formats(&color_formats)
+            .depth_attachment_format(if depth_stencil_format.has_depth() { vk_format(depth_stencil_format) } else { vk::Format::UNDEFINED })
+            .stencil_attachment_format(if depth_stencil_format.has_stencil() { vk_format(depth_stencil_format) } else { vk::Format::UNDEFINED });
+        match create_info.rendering
+        {
+            PipelineRendering::RenderPass { render_pass, subpass } =>
+            {
+                pipeline_create_info = pipeline_create_info
+                    .render_pass(render_pass.downcast_ref::<VkRenderPass>().unwrap().handle)
+                    .subpass(subpass);
+            },
+            PipelineRendering::Dynamic { .. } =>
+            {
+                pipeline_create_info = pipeline_create_info.push_next(&mut rendering_create_info);
+            }
+        }
+
+        if let Primitive::PatchList(_) = create_info.primitive
+        {
+            pipeline_create_info = pipeline_create_info.tessellation_state(&tessellation_state);
+        }
+
+        let pipeline_cache = create_info.pipeline_cache.map_or(vk::PipelineCache::null(), |cache| cache.downcast_ref::<VkPipelineCache>().unwrap().handle);
+        let handle = match unsafe { self.handle.create_graphics_pipelines(pipeline_cache, &[ *pipeline_create_info ], None) }
+        {
+            Ok(pipelines) => pipelines[0],
+            Err(_) => { return Err(PipelineError::CreationFailed); }
+        };
+
+        self.set_object_name(handle, create_info.label);
+        Ok(RenderPipeline { internal: Arc::new(VkRenderPipeline { handle, layout }) })
+    }
+
+    fn create_pipeline_cache(&self, data: Option<&[u8]>) -> Result<PipelineCache, ()>
+    {
+        let initial_data = data.and_then(|blob| pipeline_cache_payload(&self.physical_device.properties, blob)).unwrap_or(&[]);
+
+        let pipeline_cache_create_info = vk::PipelineCacheCreateInfo::builder()
+            .initial_data(initial_data);
+        let handle = match unsafe { self.handle.create_pipeline_cache(&pipeline_cache_create_info, None) }
+        {
+            Ok(handle) => handle,
+            // the driver can still reject a blob that passed the header checks, start over empty then
+            Err(_) if !initial_data.is_empty() =>
+            {
+                let pipeline_cache_create_info = vk::PipelineCacheCreateInfo::builder();
+                unsafe { self.handle.create_pipeline_cache(&pipeline_cache_create_info, None) }.map_err(|_| ())?
+            }
+            Err(_) => { return Err(()); }
+        };
+
+        Ok(PipelineCache { internal: Arc::new(VkPipelineCache { handle, device: self.clone() }) })
+    }
+
+    fn create_query_pool(&self, create_info: &QueryPoolCreateInfo) -> Result<QueryPool, ()>
+    {
+        let (query_type, pipeline_statistics, values_per_query) = match create_info.query_type
+        {
+            QueryType::Timestamp =>
+            {
+                if self.physical_device.queue_family_properties.iter().all(|family| family.timestamp_valid_bits == 0)
+                {
+                    return Err(());
+                }
+                (vk::QueryType::TIMESTAMP, 0, 1)
+            },
+            QueryType::Occlusion => (vk::QueryType::OCCLUSION, 0, 1),
+            QueryType::PipelineStatistics(statistics) =>
+            {
+                if self.enabled_features.pipeline_statistics_query != vk::TRUE || statistics == 0
+                {
+                    return Err(());
+                }
+                (vk::QueryType::PIPELINE_STATISTICS, statistics, statistics.count_ones())
+            }
+        };
+
+        let query_pool_create_info = vk::QueryPoolCreateInfo::builder()
+            .query_type(query_type)
+            .query_count(create_info.count)
+            .pipeline_statistics(vk::QueryPipelineStatisticFlags::from_raw(pipeline_statistics));
+        let handle = unsafe { self.handle.create_query_pool(&query_pool_create_info, None) }.map_err(|_| ())?;
+
+        self.set_object_name(handle, create_info.label);
+        Ok(QueryPool { internal: Arc::new(VkQueryPool
+        {
+            handle,
+            device: self.handle.clone(),
+            create_info: QueryPoolCreateInfo { query_type: create_info.query_type, count: create_info.count, label: None },
+            values_per_query,
+            timestamp_period: self.physical_device.properties.limits.timestamp_period,
+            timestamp_valid_bits: Arc::new(AtomicU32::new(64))
+        }) })
+    }
+
+    fn create_command_pool(&self, queue: &Queue) -> Result<CommandPool, ()>
+    {
+        let pool_create_info = vk::CommandPoolCreateInfo::builder()
+            .queue_family_index(queue.family_index())
+            .flags(vk::CommandPoolCreateFlags::TRANSIENT);
+        let handle = unsafe { self.handle.create_command_pool(&pool_create_info, None) }.map_err(|_| ())?;
+
+        Ok(CommandPool { internal: Arc::new(VkCommandPool
+        {
+            handle,
+            device: self.handle.clone(),
+            lock: Arc::new(Mutex::new(())),
+            features: self.enabled_features(),
+            line_width_range: self.physical_device.properties.limits.line_width_range,
+            timestamp_valid_bits: self.physical_device.queue_family_properties[queue.family_index() as usize].timestamp_valid_bits,
+            multi_draw_indirect: self.enabled_features.multi_draw_indirect == vk::TRUE,
+            draw_indirect_count: self.draw_indirect_count.clone(),
+            dynamic_rendering: self.dynamic_rendering.clone(),
+            debug_utils: self.debug_utils.clone()
+        }) })
+    }
+
+    fn create_fence(&self, signaled: bool) -> Result<Fence, ()>
+    {
+        let flags = if signaled { vk::FenceCreateFlags::SIGNALED } else { vk::FenceCreateFlags::empty() };
+        let handle = unsafe { self.handle.create_fence(&vk::FenceCreateInfo::builder().flags(flags), None) }.map_err(|_| ())?;
+
+        Ok(Fence { internal: Arc::new(VkFence { handle, device: self.handle.clone() }) })
+    }
+
+    fn create_semaphore(&self) -> Result<Semaphore, ()>
+    {
+        let handle = unsafe { self.handle.create_semaphore(&vk::SemaphoreCreateInfo::builder(), None) }.map_err(|_| ())?;
+
+        Ok(Semaphore { internal: Arc::new(VkSemaphore { handle }) })
+    }
+
+    fn write_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) -> Result<(), ()>
+    {
+        let buffer = buffer.downcast_ref::<VkBuffer>().unwrap();
+        if buffer.location == MemoryLocation::GpuOnly || offset.checked_add(data.len() as u64).map_or(true, |end| end > buffer.size)
+        {
+            return Err(());
+        }
+
+        let _lock = buffer.map_lock.lock().unwrap();
+        unsafe
+        {
+            let ptr = self.handle.map_memory(buffer.memory, buffer.memory_offset + offset, data.len() as u64, vk::MemoryMapFlags::empty()).map_err(|_| ())?;
+            std::ptr::copy_nonoverlapping(data.as_ptr(), ptr as *mut u8, data.len());
+            self.handle.unmap_memory(buffer.memory);
+        }
+
+        Ok(())
+    }
+
+    fn upload_buffer(&self, buffer: &Buffer, offset: u64, data: &[u8]) -> Result<Fence, ()>
+    {
+        let dst = buffer.downcast_ref::<VkBuffer>().unwrap();
+        if offset.checked_add(data.len() as u64).map_or(true, |end| end > dst.size)
+        {
+            return Err(());
+        }
+
+        // host visible buffers are written in place, which has already happened by the time we return
+        if data.is_empty() || dst.location != MemoryLocation::GpuOnly
+        {
+            self.write_buffer(buffer, offset, data)?;
+            return self.create_fence(true);
+        }
+
+        self.staged_submit(data, |command_buffer, src, src_offset|
+        {
+            let region = vk::BufferCopy { src_offset, dst_offset: offset, size: data.len() as u64 };
+            let barrier = vk::MemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE);
+
+            unsafe
+            {
+                self.handle.cmd_copy_buffer(command_buffer, src, dst.handle, &[ region ]);
+                self.handle.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::ALL_COMMANDS,
+                    vk::DependencyFlags::empty(),
+                    &[ *barrier ],
+                    &[],
+                    &[])
+            };
+        })
+    }
+
+    fn upload_image(&self, image: &Image, region: &ImageRegion, data: &[u8]) -> Result<Fence, ()>
+    {
+        let dst = image.downcast_ref::<VkImage>().unwrap();
+        if data.len() as u64 != dst.region_size(region)?
+        {
+            return Err(());
+        }
+
+        let subresource_range = dst.region_subresource_range(region);
+
+        let fence = self.staged_submit(data, |command_buffer, src, src_offset|
+        {
+            let to_transfer: Vec<vk::ImageMemoryBarrier> = dst.layout_runs(&subresource_range).iter()
+                .map(|(range, layout)| vk::ImageMemoryBarrier::builder()
+                    .src_access_mask(vk::AccessFlags::MEMORY_WRITE)
+                    .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                    .old_layout(*layout)
+                    .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .image(dst.handle)
+                    .subresource_range(*range)
+                    .build())
+                .collect();
+            let copy = dst.region_buffer_copy(region, src_offset);
+            let to_shader_read = vk::ImageMemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(dst.handle)
+                .subresource_range(subresource_range);
+
+            unsafe
+            {
+                self.handle.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::ALL_COMMANDS,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &to_transfer);
+                self.handle.cmd_copy_buffer_to_image(command_buffer, src, dst.handle, vk::ImageLayout::TRANSFER_DST_OPTIMAL, &[ copy ]);
+                self.handle.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::ALL_COMMANDS,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[ *to_shader_read ]);
+            }
+        })?;
+
+        dst.set_layout(&subresource_range, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        Ok(fence)
+    }
+
+    fn read_buffer(&self, buffer: &Buffer, offset: u64, size: u64) -> Result<Vec<u8>, ()>
+    {
+        let src = buffer.downcast_ref::<VkBuffer>().unwrap();
+        if offset.checked_add(size).map_or(true, |end| end > src.size)
+        {
+            return Err(());
+        }
+
+        if size == 0
+        {
+            return Ok(Vec::new());
+        }
+
+        if src.location != MemoryLocation::GpuOnly
+        {
+            let mut data = vec![0; size as usize];
+            let _lock = src.map_lock.lock().unwrap();
+            unsafe
+            {
+                let ptr = self.handle.map_memory(src.memory, src.memory_offset + offset, size, vk::MemoryMapFlags::empty()).map_err(|_| ())?;
+                std::ptr::copy_nonoverlapping(ptr as *const u8, data.as_mut_ptr(), data.len());
+                self.handle.unmap_memory(src.memory);
+            }
+
+            return Ok(data);
+        }
+
+        self.read_back(size, |command_buffer, dst|
+        {
+            let barrier = vk::MemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::MEMORY_WRITE)
+                .dst_access_mask(vk::AccessFlags::TRANSFER_READ);
+            let region = vk::BufferCopy { src_offset: offset, dst_offset: 0, size };
+
+            unsafe
+            {
+                self.handle.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::ALL_COMMANDS,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::DependencyFlags::empty(),
+                    &[ *barrier ],
+                    &[],
+                    &[]);
+                self.handle.cmd_copy_buffer(command_buffer, src.handle, dst, &[ region ]);
+            }
+        })
+    }
+
+    fn read_image(&self, image: &Image, region: &ImageRegion) -> Result<Vec<u8>, ()>
+    {
+        self.read_image_region(image.downcast_ref::<VkImage>().unwrap(), region)
+    }
+
+    fn wait_idle(&self) -> Result<(), ()>
+    {
+        unsafe { self.handle.device_wait_idle() }.map_err(|_| ())
+    }
+
+    fn destroy_buffer(&self, buffer: &Buffer)
+    {
+        let buffer = buffer.downcast_ref::<VkBuffer>().unwrap();
+        unsafe
+        {
+            self.handle.destroy_buffer(buffer.handle, None);
+            if buffer.dedicated
+            {
+                self.handle.free_memory(buffer.memory, None);
+            }
+        }
+    }
+
+    fn destroy_image(&self, image: &Image)
+    {
+        let image = image.downcast_ref::<VkImage>().unwrap();
+        match image.memory
+        {
+            VkImageMemory::Dedicated(memory) => unsafe
+            {
+                self.handle.destroy_image(image.handle, None);
+                self.handle.free_memory(memory, None);
+            },
+            VkImageMemory::Placed => unsafe { self.handle.destroy_image(image.handle, None) },
+            VkImageMemory::Swapchain => state::validation_error("swapchain images are destroyed together with their swapchain")
+        }
+    }
+
+    fn destroy_image_view(&self, image_view: &ImageView)
+    {
+        let image_view = image_view.downcast_ref::<VkImageView>().unwrap();
+        unsafe { self.handle.destroy_image_view(image_view.handle, None) };
+    }
+
+    fn destroy_render_pass(&self, render_pass: &RenderPass)
+    {
+        let render_pass = render_pass.downcast_ref::<VkRenderPass>().unwrap();
+        unsafe { self.handle.destroy_render_pass(render_pass.handle, None) };
+    }
+
+    fn destroy_framebuffer(&self, framebuffer: &Framebuffer)
+    {
+        let framebuffer = framebuffer.downcast_ref::<VkFramebuffer>().unwrap();
+        unsafe { self.handle.destroy_framebuffer(framebuffer.handle, None) };
+    }
+
+    fn free_memory(&self, memory: &Memory)
+    {
+        let memory = memory.downcast_ref::<VkMemory>().unwrap();
+        unsafe { self.handle.free_memory(memory.handle, None) };
+    }
+
+    fn destroy_shader_module(&self, shader_module: &ShaderModule)
+    {
+        let shader_module = shader_module.downcast_ref::<VkShaderModule>().unwrap();
+        unsafe { self.handle.destroy_shader_module(shader_module.handle, None) };
+    }
+
+    // every pipeline gets a layout of its own, it goes together with the pipeline
+    fn destroy_compute_pipeline(&self, pipeline: &ComputePipeline)
+    {
+        let pipeline = pipeline.downcast_ref::<VkComputePipeline>().unwrap();
+        unsafe
+        {
+            self.handle.destroy_pipeline(pipeline.handle, None);
+            self.handle.destroy_pipeline_layout(pipeline.layout, None);
+        }
+    }
+
+    fn destroy_render_pipeline(&self, pipeline: &RenderPipeline)
+    {
+        let pipeline = pipeline.downcast_ref::<VkRenderPipeline>().unwrap();
+        unsafe
+        {
+            self.handle.destroy_pipeline(pipeline.handle, None);
+            self.handle.destroy_pipeline_layout(pipeline.layout, None);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct VkPhysicalDevice
+{
+    pub handle: ash::vk::PhysicalDevice,
+    pub supported_features: vk::PhysicalDeviceFeatures,
+    pub properties: vk::PhysicalDeviceProperties,
+    pub memory_properties: vk::PhysicalDeviceMemoryProperties,
+    pub queue_family_properties: Vec<vk::QueueFamilyProperties>,
+    pub extensions: Vec<std::ffi::CString>,
+    pub properties2: bool,
+    // VK_EXT_vertex_attribute_divisor made non zero divisors optional, so this comes from a features2 query
+    pub vertex_attribute_instance_rate_divisor: bool
+}
+
+impl VkPhysicalDevice
+{
+    pub fn new(handle: vk::PhysicalDevice, entry: &ash::Entry, instance: &ash::Instance, properties2: bool) -> Self
+    {
+        let supported_features = unsafe { instance.get_physical_device_features(handle) };
+        let properties = unsafe { instance.get_physical_device_properties(handle) };
+        let memory_properties = unsafe { instance.get_physical_device_memory_properties(handle) };
+        let queue_family_properties = unsafe { instance.get_physical_device_queue_family_properties(handle) };
+        let extensions = unsafe { instance.enumerate_device_extension_properties(handle) }.unwrap_or_default().iter()
+            .map(|extension| unsafe { std::ffi::CStr::from_ptr(extension.extension_name.as_ptr()) }.to_owned())
+            .collect::<Vec<std::ffi::CString>>();
+
+        // the extension depends on properties2, without it there is no way to ask and the feature stays off
+        let has_divisor_extension = extensions.iter().any(|extension| extension.as_c_str() == vk::ExtVertexAttributeDivisorFn::name());
+        let vertex_attribute_instance_rate_divisor = properties2 && has_divisor_extension &&
+        {
+            let loader = ash::extensions::khr::GetPhysicalDeviceProperties2::new(entry, instance);
+            let mut divisor_features = vk::PhysicalDeviceVertexAttributeDivisorFeaturesEXT::default();
+            let mut features2 = vk::PhysicalDeviceFeatures2::builder().push_next(&mut divisor_features);
+            unsafe { loader.get_physical_device_features2(handle, &mut features2) };
+            divisor_features.vertex_attribute_instance_rate_divisor == vk::TRUE
+        };
+
+        Self
+        {
+            handle, supported_features, properties, memory_properties, queue_family_properties, extensions, properties2,
+            vertex_attribute_instance_rate_divisor
+        }
+    }
+
+    // an extension is only usable when everything it depends on is available as well
+    pub fn supports_extension(&self, name: &std::ffi::CStr) -> bool
+    {
+        self.extensions.iter().any(|extension| extension.as_c_str() == name) &&
+            (self.properties2 || !vk_extension_needs_properties2(name)) &&
+            vk_extension_dependencies(name).iter().all(|dependency| self.supports_extension(dependency))
+    }
+
+    pub fn features(&self) -> u32
+    {
+        let mut features = qrl_features(&self.supported_features);
+        if self.supports_extension(ash::extensions::khr::DrawIndirectCount::name())
+        {
+            features |= FeatureFlags::DRAW_INDIRECT_COUNT;
+        }
+        if self.vertex_attribute_instance_rate_divisor && self.supports_extension(vk::ExtVertexAttributeDivisorFn::name())
+        {
+            features |= FeatureFlags::VERTEX_ATTRIBUTE_DIVISOR;
+        }
+        if self.supports_extension(vk::KhrDynamicRenderingFn::name())
+        {
+            features |= FeatureFlags::DYNAMIC_RENDERING;
+        }
+        features
+    }
+
+    pub fn limits(&self) -> Limits
+    {
+        let limits = &self.properties.limits;
+        Limits
+        {
+            max_image_dimension_2d: limits.max_image_dimension2_d,
+            max_image_dimension_3d: limits.max_image_dimension3_d,
+            max_image_array_layers: limits.max_image_array_layers,
+            max_uniform_buffer_range: limits.max_uniform_buffer_range,
+            max_storage_buffer_range: limits.max_storage_buffer_range,
+            max_bound_descriptor_sets: limits.max_bound_descriptor_sets,
+            max_vertex_input_attributes: limits.max_vertex_input_attributes,
+            max_vertex_input_bindings: limits.max_vertex_input_bindings,
+            max_vertex_input_attribute_offset: limits.max_vertex_input_attribute_offset,
+            max_vertex_input_binding_stride: limits.max_vertex_input_binding_stride,
+            max_color_attachments: limits.max_color_attachments,
+            max_framebuffer_width: limits.max_framebuffer_width,
+            max_framebuffer_height: limits.max_framebuffer_height,
+            max_viewport_dimensions: limits.max_viewport_dimensions,
+            max_compute_workgroup_count: limits.max_compute_work_group_count,
+            max_compute_workgroup_size: limits.max_compute_work_group_size,
+            max_compute_workgroup_invocations: limits.max_compute_work_group_invocations,
+            max_draw_indirect_count: limits.max_draw_indirect_count,
+            min_uniform_buffer_offset_alignment: limits.min_uniform_buffer_offset_alignment,
+            min_storage_buffer_offset_alignment: limits.min_storage_buffer_offset_alignment,
+            timestamp_period: limits.timestamp_period,
+            point_size_range: limits.point_size_range,
+            line_width_range: limits.line_width_range
+        }
+    }
+
+    pub fn find_memory_type(&self, type_bits: u32, flags: vk::MemoryPropertyFlags) -> Option<u32>
+    {
+        (0..self.memory_properties.memory_type_count).find(|index|
+        {
+            (type_bits & (1 << index)) != 0 &&
+                self.memory_properties.memory_types[*index as usize].property_flags.contains(flags)
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct VkQueueSlot
+{
+    pub kind: QueueKind,
+    pub family_index: u32,
+    pub index: u32,
+    // shared by everything that submits to this vk::Queue, including kinds aliasing the graphics queue
+    pub lock: Arc<Mutex<()>>
+}
+
+#[derive(Clone)]
+pub struct VkQueue
+{
+    pub handle: ash::vk::Queue,
+    pub device: ash::Device,
+    pub kind: QueueKind,
+    pub family_index: u32,
+    pub lock: Arc<Mutex<()>>
+}
+
+impl AbstractQueue for VkQueue
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn submit(&self, submit_info: &SubmitInfo) -> Result<(), ()>
+    {
+        let command_buffers: Vec<vk::CommandBuffer> = submit_info.command_buffers.iter()
+            .map(|command_buffer| command_buffer.downcast_ref::<VkCommandBuffer>().unwrap().handle)
+            .collect();
+        let fence = match submit_info.fence
+        {
+            Some(fence) => fence.downcast_ref::<VkFence>().unwrap().handle,
+            None => vk::Fence::null()
+        };
+
+        let wait_semaphores: Vec<vk::Semaphore> = submit_info.wait_semaphores.iter()
+            .map(|semaphore| semaphore.downcast_ref::<VkSemaphore>().unwrap().handle)
+            .collect();
+        let wait_stage_masks: Vec<vk::PipelineStageFlags> = submit_info.wait_stage_masks.iter()
+            .map(|mask| vk::PipelineStageFlags::from_raw(*mask))
+            .collect();
+        let signal_semaphores: Vec<vk::Semaphore> = submit_info.signal_semaphores.iter()
+            .map(|semaphore| semaphore.downcast_ref::<VkSemaphore>().unwrap().handle)
+            .collect();
+
+        if wait_semaphores.len() != wait_stage_masks.len()
+        {
+            return Err(());
+        }
+
+        let submit = vk::SubmitInfo::builder()
+            .wait_semaphores(&wait_semaphores)
+            .wait_dst_stage_mask(&wait_stage_masks)
+            .command_buffers(&command_buffers)
+            .signal_semaphores(&signal_semaphores);
+
+        let _lock = self.lock.lock().unwrap();
+        unsafe { self.device.queue_submit(self.handle, &[ *submit ], fence) }.map_err(|_| ())
+    }
+
+    fn present(&self, swapchain: &Swapchain, image_index: u32, wait_semaphores: &[&Semaphore]) -> Result<(), SwapchainError>
+    {
+        let swapchain = swapchain.downcast_ref::<VkSwapchain>().unwrap();
+        let wait_semaphores: Vec<vk::Semaphore> = wait_semaphores.iter()
+            .map(|semaphore| semaphore.downcast_ref::<VkSemaphore>().unwrap().handle)
+            .collect();
+        let swapchains = [ swapchain.handle ];
+        let image_indices = [ image_index ];
+
+        let present_info = vk::PresentInfoKHR::builder()
+            .wait_semaphores(&wait_semaphores)
+            .swapchains(&swapchains)
+            .image_indices(&image_indices);
+
+        let _queue = self.lock.lock().unwrap();
+        let _swapchain = swapchain.current_image.lock().unwrap();
+        match unsafe { swapchain.loader.queue_present(self.handle, &present_info) }
+        {
+            Ok(false) => Ok(()),
+            Ok(true) | Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => Err(SwapchainError::OutOfDate),
+            Err(_) => Err(SwapchainError::Failed)
+        }
+    }
+
+    fn wait_idle(&self) -> Result<(), ()>
+    {
+        let _lock = self.lock.lock().unwrap();
+        unsafe { self.device.queue_wait_idle(self.handle) }.map_err(|_| ())
+    }
+
+    fn kind(&self) -> QueueKind
+    {
+        self.kind
+    }
+
+    fn family_index(&self) -> u32
+    {
+        self.family_index
+    }
+}
+
+#[derive(Clone)]
+pub struct VkSurface
+{
+    pub handle: vk::SurfaceKHR,
+    pub loader: ash::extensions::khr::Surface,
+	pub extent: vk::Extent2D
+}
+
+impl AbstractSurface for VkSurface
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn capabilities(&self, device: &Device) -> Result<SurfaceCapabilities, ()>
+    {
+        let device = device.downcast_ref::<VkDevice>().ok_or(())?;
+        let swapchain_info = device.get_swapchain_support_info(self).map_err(|_| ())?;
+        let capabilities = &swapchain_info.capabilities;
+
+        Ok(SurfaceCapabilities
+        {
+            present_modes: swapchain_info.modes.iter().filter_map(|mode| qrl_present_mode(*mode)).collect(),
+            formats: swapchain_info.formats.iter()
+                .filter_map(|format| Some((qrl_format(format.format), qrl_color_space(format.color_space)?)))
+                .filter(|(format, _)| *format != Format::Undefined)
+                .collect(),
+            min_image_count: capabilities.min_image_count,
+            max_image_count: (capabilities.max_image_count > 0).then_some(capabilities.max_image_count),
+            current_extent: (capabilities.current_extent.width != u32::MAX).then_some((capabilities.current_extent.width, capabilities.current_extent.height)),
+            min_extent: (capabilities.min_image_extent.width, capabilities.min_image_extent.height),
+            max_extent: (capabilities.max_image_extent.width, capabilities.max_image_extent.height),
+            usage: qrl_image_usage(capabilities.supported_usage_flags),
+            composite_alpha: [ CompositeAlpha::Opaque, CompositeAlpha::PreMultiplied, CompositeAlpha::PostMultiplied, CompositeAlpha::Inherit ].into_iter()
+                .filter(|composite_alpha| capabilities.supported_composite_alpha.contains(vk_composite_alpha(*composite_alpha)))
+                .collect()
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum VkImageMemory
+{
+    Dedicated(vk::DeviceMemory),
+    // bound to a range of a Memory, which is freed on its own
+    Placed,
+    // owned by the swapchain
+    Swapchain
+}
+
+#[derive(Clone)]
+pub struct VkImage
+{
+    pub handle: vk::Image,
+    pub memory: VkImageMemory,
+    pub format: vk::Format,
+    pub extent: vk::Extent3D,
+    pub mip_levels: u32,
+    pub array_layers: u32,
+    pub samples: vk::SampleCountFlags,
+    pub usage: u32,
+    // the last layout qrl moved each subresource into, indexed by mip_level * array_layers + array_layer
+    pub layouts: Arc<Mutex<Vec<vk::ImageLayout>>>
+}
+
+impl VkImage
+{
+    fn new(handle: vk::Image, memory: VkImageMemory, create_info: &ImageCreateInfo) -> Self
+    {
+        Self
+        {
+            handle,
+            memory,
+            format: vk_format(create_info.format),
+            extent: vk::Extent3D { width: create_info.width, height: create_info.height, depth: create_info.depth },
+            mip_levels: create_info.mip_levels,
+            array_layers: create_info.array_layers,
+            samples: vk::SampleCountFlags::from_raw(create_info.samples),
+            usage: create_info.usage,
+            layouts: Arc::new(Mutex::new(vec![ vk::ImageLayout::UNDEFINED; (create_info.mip_levels * create_info.array_layers) as usize ]))
+        }
+    }
+
+    fn layout(&self, mip_level: u32, array_layer: u32) -> vk::ImageLayout
+    {
+        self.layouts.lock().unwrap()[(mip_level * self.array_layers + array_layer) as usize]
+    }
+
+    fn set_layout(&self, range: &vk::ImageSubresourceRange, layout: vk::ImageLayout)
+    {
+        let mut layouts = self.layouts.lock().unwrap();
+        for mip_level in range.base_mip_level..range.base_mip_level + range.level_count
+        {
+            for array_layer in range.base_array_layer..range.base_array_layer + range.layer_count
+            {
+                layouts[(mip_level * self.array_layers + array_layer) as usize] = layout;
+            }
+        }
+    }
+
+    fn full_range(&self) -> vk::ImageSubresourceRange
+    {
+        vk::ImageSubresourceRange
+        {
+            aspect_mask: vk_aspect_mask(self.format),
+            base_mip_level: 0,
+            level_count: self.mip_levels,
+            base_array_layer: 0,
+            layer_count: self.array_layers
+        }
+    }
+
+    // splits the range into one piece per mip level and run of array layers sharing a layout, so every piece can be
+    // transitioned from the layout it is actually in
+    fn layout_runs(&self, range: &vk::ImageSubresourceRange) -> Vec<(vk::ImageSubresourceRange, vk::ImageLayout)>
+    {
+        let mut runs: Vec<(vk::ImageSubresourceRange, vk::ImageLayout)> = Vec::new();
+        for mip_level in range.base_mip_level..range.base_mip_level + range.level_count
+        {
+            for array_layer in range.base_array_layer..range.base_array_layer + range.layer_count
+            {
+                let layout = self.layout(mip_level, array_layer);
+                match runs.last_mut()
+                {
+                    Some((run, run_layout)) if run.base_mip_level == mip_level && *run_layout == layout => run.layer_count += 1,
+                    _ => runs.push((vk::ImageSubresourceRange { base_mip_level: mip_level, level_count: 1, base_array_layer: array_layer, layer_count: 1, ..*range }, layout))
+                }
+            }
+        }
+
+        runs
+    }
+
+    // size in bytes of the region's texels when tightly packed, stencil aspects and multisampled
+    // images can't be copied this way
+    fn region_size(&self, region: &ImageRegion) -> Result<u64, ()>
+    {
+        let texel_size = qrl_format(self.format).texel_size() as u64;
+        if texel_size == 0 || vk_aspect_mask(self.format).contains(vk::ImageAspectFlags::STENCIL) ||
+            self.samples != vk::SampleCountFlags::TYPE_1 || region.mip_level >= self.mip_levels ||
+            region.array_layer_count == 0 || region.base_array_layer + region.array_layer_count > self.array_layers
+        {
+            return Err(());
+        }
+
+        let width = (self.extent.width >> region.mip_level).max(1);
+        let height = (self.extent.height >> region.mip_level).max(1);
+        let depth = (self.extent.depth >> region.mip_level).max(1);
+        if region.width == 0 || region.height == 0 || region.depth == 0 ||
+            region.x + region.width > width || region.y + region.height > height || region.z + region.depth > depth
+        {
+            return Err(());
+        }
+
+        Ok(texel_size * region.width as u64 * region.height as u64 * region.depth as u64 * region.array_layer_count as u64)
+    }
+
+    fn region_subresource_range(&self, region: &ImageRegion) -> vk::ImageSubresourceRange
+    {
+        vk::ImageSubresourceRange
+        {
+            aspect_mask: vk_aspect_mask(self.format),
+            base_mip_level: region.mip_level,
+            level_count: 1,
+            base_array_layer: region.base_array_layer,
+            layer_count: region.array_layer_count
+        }
+    }
+
+    fn region_buffer_copy(&self, region: &ImageRegion, buffer_offset: u64) -> vk::BufferImageCopy
+    {
+        vk::BufferImageCopy
+        {
+            buffer_offset,
+            buffer_row_length: 0,
+            buffer_image_height: 0,
+            image_subresource: vk::ImageSubresourceLayers
+            {
+                aspect_mask: vk_aspect_mask(self.format),
+                mip_level: region.mip_level,
+                base_array_layer: region.base_array_layer,
+                layer_count: region.array_layer_count
+            },
+            image_offset: vk::Offset3D { x: region.x as i32, y: region.y as i32, z: region.z as i32 },
+            image_extent: vk::Extent3D { width: region.width, height: region.height, depth: region.depth }
+        }
+    }
+}
+
+impl AbstractImage for VkImage
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_info(&self) -> ImageCreateInfo<'static>
+    {
+        ImageCreateInfo
+        {
+            format: qrl_format(self.format),
+            width: self.extent.width,
+            height: self.extent.height,
+            depth: self.extent.depth,
+            mip_levels: self.mip_levels,
+            array_layers: self.array_layers,
+            samples: self.samples.as_raw(),
+            usage: self.usage,
+            label: None
+        }
+    }
+
+    fn current_layout(&self, mip_level: u32, array_layer: u32) -> Option<ImageLayout>
+    {
+        qrl_image_layout(self.layout(mip_level, array_layer))
+    }
+
+    fn set_current_layout(&self, mip_level: u32, array_layer: u32, layout: ImageLayout)
+    {
+        self.layouts.lock().unwrap()[(mip_level * self.array_layers + array_layer) as usize] = vk_image_layout(Some(layout));
+    }
+}
+
+#[derive(Clone)]
+pub struct VkImageView
+{
+    pub handle: vk::ImageView,
+    pub parent: Image,
+    pub range: ImageSubresourceRange
+}
+
+impl AbstractImageView for VkImageView
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn image(&self) -> Image
+    {
+        self.parent.clone()
+    }
+
+    fn subresource_range(&self) -> ImageSubresourceRange
+    {
+        self.range
+    }
+}
+
+#[derive(Clone)]
+pub struct VkBuffer
+{
+    pub handle: vk::Buffer,
+    pub memory: vk::DeviceMemory,
+    pub memory_offset: u64,
+    // placed buffers leave their memory to the Memory they were placed in
+    pub dedicated: bool,
+    pub size: u64,
+    pub location: MemoryLocation,
+    // vkMapMemory and vkUnmapMemory need the memory externally synchronized, and a memory object can only be mapped
+    // once at a time
+    pub map_lock: Arc<Mutex<()>>
+}
+
+impl AbstractBuffer for VkBuffer
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn size(&self) -> u64 { self.size }
+}
+
+#[derive(Clone)]
+pub struct VkMemory
+{
+    pub handle: vk::DeviceMemory,
+    pub size: u64,
+    pub memory_type_index: u32,
+    pub location: MemoryLocation,
+    // shared with every buffer placed in the memory, see VkBuffer::map_lock
+    pub map_lock: Arc<Mutex<()>>
+}
+
+impl VkMemory
+{
+    fn can_place(&self, requirements: &vk::MemoryRequirements, offset: u64) -> bool
+    {
+        offset % requirements.alignment == 0
+            && offset.checked_add(requirements.size).is_some_and(|end| end <= self.size)
+            && requirements.memory_type_bits & (1 << self.memory_type_index) != 0
+    }
+}
+
+impl AbstractMemory for VkMemory
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn size(&self) -> u64 { self.size }
+}
+
+#[derive(Clone)]
+pub struct VkDescriptorSetLayout
+{
+    pub handle: vk::DescriptorSetLayout,
+    pub bindings: Vec<DescriptorSetLayoutBinding>
+}
+
+impl AbstractDescriptorSetLayout for VkDescriptorSetLayout
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+pub struct VkDescriptorSet
+{
+    pub handle: vk::DescriptorSet,
+    pub pool: vk::DescriptorPool,
+    pub device: ash::Device
+}
+
+impl Drop for VkDescriptorSet
+{
+    fn drop(&mut self)
+    {
+        // destroying the pool frees the set with it
+        unsafe { self.device.destroy_descriptor_pool(self.pool, None) };
+    }
+}
+
+impl AbstractDescriptorSet for VkDescriptorSet
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct VkComputePipeline
+{
+    pub handle: vk::Pipeline,
+    pub layout: vk::PipelineLayout
+}
+
+impl AbstractComputePipeline for VkComputePipeline
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+// serialized caches start with qrl's own header. vulkan only keys its blobs by vendor, device and cache
+// uuid, the driver version and a checksum are added so blobs from an updated driver or a truncated file get dropped
+// before the driver ever sees them
+const PIPELINE_CACHE_MAGIC: [u8; 4] = *b"QRLC";
+const PIPELINE_CACHE_VERSION: u32 = 1;
+const PIPELINE_CACHE_HEADER_SIZE: usize = 48;
+
+fn pipeline_cache_header(properties: &vk::PhysicalDeviceProperties, data: &[u8]) -> Vec<u8>
+{
+    let mut header = Vec::with_capacity(PIPELINE_CACHE_HEADER_SIZE);
+    header.extend_from_slice(&PIPELINE_CACHE_MAGIC);
+    header.extend_from_slice(&PIPELINE_CACHE_VERSION.to_le_bytes());
+    header.extend_from_slice(&properties.vendor_id.to_le_bytes());
+    header.extend_from_slice(&properties.device_id.to_le_bytes());
+    header.extend_from_slice(&properties.driver_version.to_le_bytes());
+    header.extend_from_slice(&properties.pipeline_cache_uuid);
+    header.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    header.extend_from_slice(&(!crate::png::crc32(!0, data)).to_le_bytes());
+    header
+}
+
+fn pipeline_cache_payload<'a>(properties: &vk::PhysicalDeviceProperties, blob: &'a [u8]) -> Option<&'a [u8]>
+{
+    if blob.len() < PIPELINE_CACHE_HEADER_SIZE
+    {
+        return None;
+    }
+
+    let (header, data) = blob.split_at(PIPELINE_CACHE_HEADER_SIZE);
+    if header != pipeline_cache_header(properties, data).as_slice()
+    {
+        return None;
+    }
+
+    Some(data)
+}
+
+pub struct VkPipelineCache
+{
+    pub handle: vk::PipelineCache,
+    pub device: VkDevice
+}
+
+impl AbstractPipelineCache for VkPipelineCache
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn data(&self) -> Result<Vec<u8>, ()>
+    {
+        let data = unsafe { self.device.handle.get_pipeline_cache_data(self.handle) }.map_err(|_| ())?;
+
+        let mut blob = pipeline_cache_header(&self.device.physical_device.properties, &data);
+        blob.extend_from_slice(&data);
+        Ok(blob)
+    }
+}
+
+#[derive(Clone)]
+pub struct VkCommandPool
+{
+    pub handle: vk::CommandPool,
+    pub device: ash::Device,
+    pub lock: Arc<Mutex<()>>,
+    pub features: u32,
+    pub line_width_range: [f32; 2],
+    pub timestamp_valid_bits: u32,
+    pub multi_draw_indirect: bool,
+    pub draw_indirect_count: Option<ash::extensions::khr::DrawIndirectCount>,
+    pub dynamic_rendering: Option<ash::extensions::khr::DynamicRendering>,
+    pub debug_utils: ash::extensions::ext::DebugUtils
+}
+
+impl AbstractCommandPool for VkCommandPool
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_command_encoder(&self) -> Result<CommandEncoder, ()>
+    {
+        let allocate_info = vk::CommandBufferAllocateInfo::builder()
+            .command_pool(self.handle)
+            .level(vk::CommandBufferLevel::PRIMARY)
+            .command_buffer_count(1);
+        let handle =
+        {
+            let _lock = self.lock.lock().unwrap();
+            unsafe { self.device.allocate_command_buffers(&allocate_info) }.map_err(|_| ())?[0]
+        };
+
+        let begin_info = vk::CommandBufferBeginInfo::builder()
+            .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+        unsafe { self.device.begin_command_buffer(handle, &begin_info) }.map_err(|_| ())?;
+
+        Ok(CommandEncoder { internal: Box::new(VkCommandEncoder
+        {
+            handle,
+            device: self.device.clone(),
+            bound_pipeline: None,
+            timestamp_valid_bits: self.timestamp_valid_bits,
+            multi_draw_indirect: self.multi_draw_indirect,
+            draw_indirect_count: self.draw_indirect_count.clone(),
+            dynamic_rendering: self.dynamic_rendering.clone(),
+            debug_utils: self.debug_utils.clone()
+        }), tracker: Default::default(), features: self.features, line_width_range: self.line_width_range })
+    }
+
+    fn reset(&self) -> Result<(), ()>
+    {
+        let _lock = self.lock.lock().unwrap();
+        unsafe { self.device.reset_command_pool(self.handle, vk::CommandPoolResetFlags::empty()) }.map_err(|_| ())
+    }
+}
+
+pub struct VkQueryPool
+{
+    pub handle: vk::QueryPool,
+    pub device: ash::Device,
+    pub create_info: QueryPoolCreateInfo<'static>,
+    pub values_per_query: u32,
+    pub timestamp_period: f32,
+    // of the queue family that last wrote a timestamp, results are masked to them
+    pub timestamp_valid_bits: Arc<AtomicU32>
+}
+
+impl AbstractQueryPool for VkQueryPool
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_info(&self) -> QueryPoolCreateInfo<'static>
+    {
+        self.create_info
+    }
+
+    fn get_results(&self, first_query: u32, query_count: u32, wait: bool) -> Result<Option<Vec<u64>>, ()>
+    {
+        if first_query.checked_add(query_count).map_or(true, |end| end > self.create_info.count)
+        {
+            return Err(());
+        }
+
+        // every query is followed by its availability value
+        let stride = self.values_per_query as usize + 1;
+        let mut data = vec![ 0u64; stride * query_count as usize ];
+        let mut flags = vk::QueryResultFlags::TYPE_64 | vk::QueryResultFlags::WITH_AVAILABILITY;
+        if wait
+        {
+            flags |= vk::QueryResultFlags::WAIT;
+        }
+
+        let result = unsafe
+        {
+            (self.device.fp_v1_0().get_query_pool_results)(
+                self.device.handle(),
+                self.handle,
+                first_query,
+                query_count,
+                data.len() * std::mem::size_of::<u64>(),
+                data.as_mut_ptr().cast(),
+                (stride * std::mem::size_of::<u64>()) as vk::DeviceSize,
+                flags)
+        };
+        if result != vk::Result::SUCCESS && result != vk::Result::NOT_READY
+        {
+            return Err(());
+        }
+
+        if data.chunks_exact(stride).any(|query| query[stride - 1] == 0)
+        {
+            return Ok(None);
+        }
+
+        let mut results: Vec<u64> = data.chunks_exact(stride).flat_map(|query| query[..stride - 1].iter().copied()).collect();
+        if self.create_info.query_type == QueryType::Timestamp
+        {
+            let valid_bits = self.timestamp_valid_bits.load(Ordering::Acquire);
+            let mask = if valid_bits >= 64 { u64::MAX } else { (1u64 << valid_bits) - 1 };
+            for value in results.iter_mut()
+            {
+                *value = ((*value & mask) as f64 * self.timestamp_period as f64) as u64;
+            }
+        }
+
+        Ok(Some(results))
+    }
+}
+
+pub struct VkCommandEncoder
+{
+    pub handle: vk::CommandBuffer,
+    pub device: ash::Device,
+    pub bound_pipeline: Option<(vk::PipelineBindPoint, vk::PipelineLayout)>,
+    // of the queue family the command pool was created for
+    pub timestamp_valid_bits: u32,
+    pub multi_draw_indirect: bool,
+    pub draw_indirect_count: Option<ash::extensions::khr::DrawIndirectCount>,
+    pub dynamic_rendering: Option<ash::extensions::khr::DynamicRendering>,
+    pub debug_utils: ash::extensions::ext::DebugUtils
+}
+
+impl AbstractCommandEncoder for VkCommandEncoder
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn bind_compute_pipeline(&mut self, pipeline: &ComputePipeline)
+    {
+        let pipeline = pipeline.downcast_ref::<VkComputePipeline>().unwrap();
+        unsafe { self.device.cmd_bind_pipeline(self.handle, vk::PipelineBindPoint::COMPUTE, pipeline.handle) };
+        self.bound_pipeline = Some((vk::PipelineBindPoint::COMPUTE, pipeline.layout));
+    }
+
+    fn bind_descriptor_set(&mut self, index: u32, descriptor_set: &DescriptorSet)
+    {
+        let descriptor_set = descriptor_set.downcast_ref::<VkDescriptorSet>().unwrap();
+        let (bind_point, layout) = self.bound_pipeline.expect("a pipeline must be bound before its descriptor sets");
+        unsafe { self.device.cmd_bind_descriptor_sets(self.handle, bind_point, layout, index, &[ descriptor_set.handle ], &[]) };
+    }
+
+    fn dispatch(&mut self, x: u32, y: u32, z: u32)
+    {
+        unsafe { self.device.cmd_dispatch(self.handle, x, y, z) };
+    }
+
+    fn dispatch_indirect(&mut self, buffer: &Buffer, offset: u64)
+    {
+        let buffer = buffer.downcast_ref::<VkBuffer>().unwrap();
+        unsafe { self.device.cmd_dispatch_indirect(self.handle, buffer.handle, offset) };
+    }
+
+    fn begin_render_pass(&mut self, render_pass: &RenderPass, framebuffer: &Framebuffer)
+    {
+        let render_pass = render_pass.downcast_ref::<VkRenderPass>().unwrap();
+        let framebuffer = framebuffer.downcast_ref::<VkFramebuffer>().unwrap();
+
+        let clear_values: Vec<vk::ClearValue> = render_pass.create_info.attachments.iter()
+            .map(vk_clear_value)
+            .collect();
+        let begin_info = vk::RenderPassBeginInfo::builder()
+            .render_pass(render_pass.handle)
+            .framebuffer(framebuffer.handle)
+            .render_area(vk::Rect2D { offset: vk::Offset2D { x: 0, y: 0 }, extent: vk::Extent2D { width: framebuffer.width, height: framebuffer.height } })
+            .clear_values(&clear_values);
+
+        unsafe { self.device.cmd_begin_render_pass(self.handle, &begin_info, vk::SubpassContents::INLINE) };
+    }
+
+    fn end_render_pass(&mut self)
+    {
+        unsafe { self.device.cmd_end_render_pass(self.handle) };
+    }
+
+    fn begin_rendering(&mut self, rendering_info: &RenderingInfo)
+    {
+        let Some(loader) = self.dynamic_rendering.as_ref() else { return };
+
+        let attachment_info = |attachment: &RenderingAttachment, operations: &Operations, layout: vk::ImageLayout|
+        {
+            let format = attachment.view.image().create_info().format;
+            let description = AttachmentDescription
+            {
+                format,
+                samples: 1,
+                operations: *operations,
+                stencil_operations: rendering_info.stencil_operations,
+                initial_layout: None,
+                final_layout: if format.has_depth() { ImageLayout::DepthStencilAttachmentOptimal } else { ImageLayout::ColorAttachmentOptimal }
+            };
+
+            vk::RenderingAttachmentInfo::builder()
+                .image_view(attachment.view.downcast_ref::<VkImageView>().unwrap().handle)
+                .image_layout(layout)
+                .load_op(vk_load_op(operations.load_op))
+                .store_op(vk_store_op(operations.store_op))
+                .clear_value(vk_clear_value(&description))
+                .build()
+        };
+
+        let color_attachments: Vec<vk::RenderingAttachmentInfo> = rendering_info.color_attachments.iter()
+            .map(|attachment| attachment_info(attachment, &attachment.operations, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL))
+            .collect();
+        let depth_stencil = rendering_info.depth_stencil_attachment.as_ref();
+        let format = depth_stencil.map_or(Format::Undefined, |attachment| attachment.view.image().create_info().format);
+        let depth_attachment = depth_stencil.filter(|_| format.has_depth())
+            .map(|attachment| attachment_info(attachment, &attachment.operations, vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL));
+        let stencil_attachment = depth_stencil.filter(|_| format.has_stencil())
+            .map(|attachment| attachment_info(attachment, &rendering_info.stencil_operations, vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL));
+
+        let (width, height) = rendering_info.extent();
+        let mut begin_info = vk::RenderingInfo::builder()
+            .render_area(vk::Rect2D { offset: vk::Offset2D { x: 0, y: 0 }, extent: vk::Extent2D { width, height } })
+            .layer_count(rendering_info.layers())
+            .color_attachments(&color_attachments);
+        if let Some(depth_attachment) = depth_attachment.as_ref()
+        {
+            begin_info = begin_info.depth_attachment(depth_attachment);
+        }
+        if let Some(stencil_attachment) = stencil_attachment.as_ref()
+        {
+            begin_info = begin_info.stencil_attachment(stencil_attachment);
+        }
+
+        unsafe { loader.cmd_begin_rendering(self.handle, &begin_info) };
+    }
+
+    fn end_rendering(&mut self)
+    {
+        let Some(loader) = self.dynamic_rendering.as_ref() else { return };
+        unsafe { loader.cmd_end_rendering(self.handle) };
+    }
+
+    fn bind_render_pipeline(&mut self, pipeline: &RenderPipeline)
+    {
+        let pipeline = pipeline.downcast_ref::<VkRenderPipeline>().unwrap();
+        unsafe { self.device.cmd_bind_pipeline(self.handle, vk::PipelineBindPoint::GRAPHICS, pipeline.handle) };
+        self.bound_pipeline = Some((vk::PipelineBindPoint::GRAPHICS, pipeline.layout));
+    }
+
+    fn bind_vertex_buffer(&mut self, binding: u32, buffer: &Buffer, offset: u64)
+    {
+        let buffer = buffer.downcast_ref::<VkBuffer>().unwrap();
+        unsafe { self.device.cmd_bind_vertex_buffers(self.handle, binding, &[ buffer.handle ], &[ offset ]) };
+    }
+
+    fn bind_index_buffer(&mut self, buffer: &Buffer, offset: u64, format: IndexFormat)
+    {
+        let buffer = buffer.downcast_ref::<VkBuffer>().unwrap();
+        let index_type = match format
+        {
+            IndexFormat::Uint16 => vk::IndexType::UINT16,
+            IndexFormat::Uint32 => vk::IndexType::UINT32
+        };
+
+        unsafe { self.device.cmd_bind_index_buffer(self.handle, buffer.handle, offset, index_type) };
+    }
+
+    fn set_viewport(&mut self, viewport: &Viewport)
+    {
+        let viewport = vk::Viewport
+        {
+            x: viewport.x,
+            y: viewport.y,
+            width: viewport.width,
+            height: viewport.height,
+            min_depth: viewport.min_depth,
+            max_depth: viewport.max_depth
+        };
+
+        unsafe { self.device.cmd_set_viewport(self.handle, 0, &[ viewport ]) };
+    }
+
+    fn set_scissor(&mut self, scissor: &Scissor)
+    {
+        let scissor = vk::Rect2D
+        {
+            offset: vk::Offset2D { x: scissor.x, y: scissor.y },
+            extent: vk::Extent2D { width: scissor.width, height: scissor.height }
+        };
+
+        unsafe { self.device.cmd_set_scissor(self.handle, 0, &[ scissor ]) };
+    }
+
+    fn set_blend_constants(&mut self, blend_constants: [f32; 4])
+    {
+        unsafe { self.device.cmd_set_blend_constants(self.handle, &blend_constants) };
+    }
+
+    fn set_stencil_reference(&mut self, reference: u32)
+    {
+        unsafe { self.device.cmd_set_stencil_reference(self.handle, vk::StencilFaceFlags::FRONT_AND_BACK, reference) };
+    }
+
+    // widths other than 1.0 need the wideLines feature
+    fn set_line_width(&mut self, line_width: f32)
+    {
+        unsafe { self.device.cmd_set_line_width(self.handle, line_width) };
+    }
+
+    fn set_depth_bias(&mut self, depth_bias: &DepthBias)
+    {
+        unsafe { self.device.cmd_set_depth_bias(self.handle, depth_bias.constant_factor, depth_bias.clamp, depth_bias.slope_factor) };
+    }
+
+    fn draw(&mut self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32)
+    {
+        unsafe { self.device.cmd_draw(self.handle, vertex_count, instance_count, first_vertex, first_instance) };
+    }
+
+    fn draw_indexed(&mut self, index_count: u32, instance_count: u32, first_index: u32, vertex_offset: i32, first_instance: u32)
+    {
+        unsafe { self.device.cmd_draw_indexed(self.handle, index_count, instance_count, first_index, vertex_offset, first_instance) };
+    }
+
+    // without multiDrawIndirect every draw is recorded on its own
+    fn draw_indirect(&mut self, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32)
+    {
+        let buffer = buffer.downcast_ref::<VkBuffer>().unwrap();
+
+        if self.multi_draw_indirect || draw_count <= 1
+        {
+            unsafe { self.device.cmd_draw_indirect(self.handle, buffer.handle, offset, draw_count, stride) };
+        }
+        else
+        {
+            for draw in 0..draw_count as u64
+            {
+                unsafe { self.device.cmd_draw_indirect(self.handle, buffer.handle, offset + draw * stride as u64, 1, 0) };
+            }
+        }
+    }
+
+    fn draw_indexed_indirect(&mut self, buffer: &Buffer, offset: u64, draw_count: u32, stride: u32)
+    {
+        let buffer = buffer.downcast_ref::<VkBuffer>().unwrap();
+
+        if self.multi_draw_indirect || draw_count <= 1
+        {
+            unsafe { self.device.cmd_draw_indexed_indirect(self.handle, buffer.handle, offset, draw_count, stride) };
+        }
+        else
+        {
+            for draw in 0..draw_count as u64
+            {
+                unsafe { self.device.cmd_draw_indexed_indirect(self.handle, buffer.handle, offset + draw * stride as u64, 1, 0) };
+            }
+        }
+    }
+
+    // the count lives on the gpu, so there is no fallback without VK_KHR_draw_indirect_count. CommandEncoder
+    // drops these when the feature is missing
+    fn draw_indirect_count(&mut self, buffer: &Buffer, offset: u64, count_buffer: &Buffer, count_offset: u64, max_draw_count: u32, stride: u32)
+    {
+        let Some(loader) = self.draw_indirect_count.as_ref() else { return };
+        let buffer = buffer.downcast_ref::<VkBuffer>().unwrap();
+        let count_buffer = count_buffer.downcast_ref::<VkBuffer>().unwrap();
+
+        unsafe { loader.cmd_draw_indirect_count(self.handle, buffer.handle, offset, count_buffer.handle, count_offset, max_draw_count, stride) };
+    }
+
+    fn draw_indexed_indirect_count(&mut self, buffer: &Buffer, offset: u64, count_buffer: &Buffer, count_offset: u64, max_draw_count: u32, stride: u32)
+    {
+        let Some(loader) = self.draw_indirect_count.as_ref() else { return };
+        let buffer = buffer.downcast_ref::<VkBuffer>().unwrap();
+        let count_buffer = count_buffer.downcast_ref::<VkBuffer>().unwrap();
+
+        unsafe { loader.cmd_draw_indexed_indirect_count(self.handle, buffer.handle, offset, count_buffer.handle, count_offset, max_draw_count, stride) };
+    }
+
+    fn resolve_image(&mut self, src: &Image, src_layout: ImageLayout, dst: &Image, dst_layout: ImageLayout)
+    {
+        let src = src.downcast_ref::<VkImage>().unwrap();
+        let dst = dst.downcast_ref::<VkImage>().unwrap();
+        let subresource = vk::ImageSubresourceLayers
+        {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: src.array_layers.min(dst.array_layers)
+        };
+        let region = vk::ImageResolve
+        {
+            src_subresource: subresource,
+            src_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+            dst_subresource: subresource,
+            dst_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+            extent: vk::Extent3D
+            {
+                width: src.extent.width.min(dst.extent.width),
+                height: src.extent.height.min(dst.extent.height),
+                depth: src.extent.depth.min(dst.extent.depth)
+            }
+        };
+
+        unsafe { self.device.cmd_resolve_image(self.handle, src.handle, vk_image_layout(Some(src_layout)), dst.handle, vk_image_layout(Some(dst_layout)), &[ region ]) };
+    }
+
+    fn reset_query_pool(&mut self, query_pool: &QueryPool, first_query: u32, query_count: u32)
+    {
+        let query_pool = query_pool.downcast_ref::<VkQueryPool>().unwrap();
+        unsafe { self.device.cmd_reset_query_pool(self.handle, query_pool.handle, first_query, query_count) };
+    }
+
+    fn write_timestamp(&mut self, query_pool: &QueryPool, query: u32, stage: u32)
+    {
+        let query_pool = query_pool.downcast_ref::<VkQueryPool>().unwrap();
+        if self.timestamp_valid_bits == 0
+        {
+            return state::validation_error("write_timestamp on a queue family without timestamp support");
+        }
+
+        query_pool.timestamp_valid_bits.store(self.timestamp_valid_bits, Ordering::Release);
+        unsafe { self.device.cmd_write_timestamp(self.handle, vk::PipelineStageFlags::from_raw(stage), query_pool.handle, query) };
+    }
+
+    fn begin_query(&mut self, query_pool: &QueryPool, query: u32)
+    {
+        let query_pool = query_pool.downcast_ref::<VkQueryPool>().unwrap();
+        unsafe { self.device.cmd_begin_query(self.handle, query_pool.handle, query, vk::QueryControlFlags::empty()) };
+    }
+
+    fn end_query(&mut self, query_pool: &QueryPool, query: u32)
+    {
+        let query_pool = query_pool.downcast_ref::<VkQueryPool>().unwrap();
+        unsafe { self.device.cmd_end_query(self.handle, query_pool.handle, query) };
+    }
+
+    // a label that isn't a valid C string still opens a group so pops stay balanced
+    fn push_debug_group(&mut self, label: &str)
+    {
+        let name = std::ffi::CString::new(label).unwrap_or_default();
+        let label = vk::DebugUtilsLabelEXT::builder().label_name(&name);
+        unsafe { self.debug_utils.cmd_begin_debug_utils_label(self.handle, &label) };
+    }
+
+    fn pop_debug_group(&mut self)
+    {
+        unsafe { self.debug_utils.cmd_end_debug_utils_label(self.handle) };
+    }
+
+    fn insert_debug_marker(&mut self, label: &str)
+    {
+        let name = std::ffi::CString::new(label).unwrap_or_default();
+        let label = vk::DebugUtilsLabelEXT::builder().label_name(&name);
+        unsafe { self.debug_utils.cmd_insert_debug_utils_label(self.handle, &label) };
+    }
+
+    fn memory_barrier(&mut self, barrier: &MemoryBarrier)
+    {
+        let memory_barrier = vk::MemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::from_raw(barrier.src_access_mask))
+            .dst_access_mask(vk::AccessFlags::from_raw(barrier.dst_access_mask));
+
+        unsafe
+        {
+            self.device.cmd_pipeline_barrier(
+                self.handle,
+                vk::PipelineStageFlags::from_raw(barrier.src_stage_mask),
+                vk::PipelineStageFlags::from_raw(barrier.dst_stage_mask),
+                vk::DependencyFlags::empty(),
+                &[ *memory_barrier ],
+                &[],
+                &[])
+        };
+    }
+
+    fn buffer_barrier(&mut self, barrier: &BufferBarrier)
+    {
+        let buffer = barrier.buffer.downcast_ref::<VkBuffer>().unwrap();
+        let (src_queue_family, dst_queue_family) = vk_queue_transfer(barrier.queue_transfer);
+        let buffer_barrier = vk::BufferMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::from_raw(barrier.src_access_mask))
+            .dst_access_mask(vk::AccessFlags::from_raw(barrier.dst_access_mask))
+            .src_queue_family_index(src_queue_family)
+            .dst_queue_family_index(dst_queue_family)
+            .buffer(buffer.handle)
+            .offset(barrier.offset)
+            .size(barrier.size);
+
+        unsafe
+        {
+            self.device.cmd_pipeline_barrier(
+                self.handle,
+                vk::PipelineStageFlags::from_raw(barrier.src_stage_mask),
+                vk::PipelineStageFlags::from_raw(barrier.dst_stage_mask),
+                vk::DependencyFlags::empty(),
+                &[],
+                &[ *buffer_barrier ],
+                &[])
+        };
+    }
+
+    fn image_barrier(&mut self, barrier: &ImageBarrier)
+    {
+        let image = barrier.image.downcast_ref::<VkImage>().unwrap();
+        let (src_queue_family, dst_queue_family) = vk_queue_transfer(barrier.queue_transfer);
+        let range = barrier.subresource_range.unwrap_or(ImageSubresourceRange
+        {
+            base_mip_level: 0,
+            mip_level_count: image.mip_levels,
+            base_array_layer: 0,
+            array_layer_count: image.array_layers
+        });
+        let subresource_range = vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk_aspect_mask(image.format))
+            .base_mip_level(range.base_mip_level)
+            .level_count(range.mip_level_count)
+            .base_array_layer(range.base_array_layer)
+            .layer_count(range.array_layer_count);
+        let image_barrier = vk::ImageMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::from_raw(barrier.src_access_mask))
+            .dst_access_mask(vk::AccessFlags::from_raw(barrier.dst_access_mask))
+            .old_layout(vk_image_layout(barrier.old_layout))
+            .new_layout(vk_image_layout(Some(barrier.new_layout)))
+            .src_queue_family_index(src_queue_family)
+            .dst_queue_family_index(dst_queue_family)
+            .image(image.handle)
+            .subresource_range(*subresource_range);
+
+        unsafe
+        {
+            self.device.cmd_pipeline_barrier(
+                self.handle,
+                vk::PipelineStageFlags::from_raw(barrier.src_stage_mask),
+                vk::PipelineStageFlags::from_raw(barrier.dst_stage_mask),
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[ *image_barrier ])
+        };
+    }
+
+    fn finish(self: Box<Self>) -> Result<CommandBuffer, ()>
+    {
+        unsafe { self.device.end_command_buffer(self.handle) }.map_err(|_| ())?;
+
+        Ok(CommandBuffer { internal: Arc::new(VkCommandBuffer { handle: self.handle }), layouts: Arc::default() })
+    }
+}
+
+#[derive(Clone)]
+pub struct VkCommandBuffer
+{
+    pub handle: vk::CommandBuffer
+}
+
+impl AbstractCommandBuffer for VkCommandBuffer
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct VkFence
+{
+    pub handle: vk::Fence,
+    pub device: ash::Device
+}
+
+impl AbstractFence for VkFence
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn wait(&self, timeout_ns: u64) -> Result<bool, ()>
+    {
+        match unsafe { self.device.wait_for_fences(&[ self.handle ], true, timeout_ns) }
+        {
+            Ok(_) => Ok(true),
+            Err(vk::Result::TIMEOUT) => Ok(false),
+            Err(_) => Err(())
+        }
+    }
+
+    fn reset(&self) -> Result<(), ()>
+    {
+        unsafe { self.device.reset_fences(&[ self.handle ]) }.map_err(|_| ())
+    }
+
+    fn is_signaled(&self) -> Result<bool, ()>
+    {
+        unsafe { self.device.get_fence_status(self.handle) }.map_err(|_| ())
+    }
+}
+
+pub struct VkSwapchain
+{
+    pub handle: vk::SwapchainKHR,
+    pub loader: ash::extensions::khr::Swapchain,
+    pub device: VkDevice,
+    pub images: Vec<VkImage>,
+    pub views: Vec<ImageView>,
+    pub extent: vk::Extent2D,
+    pub surface_format: vk::SurfaceFormatKHR,
+    pub present_mode: vk::PresentModeKHR,
+    pub image_usage: vk::ImageUsageFlags,
+    // each view holds its depth image as its parent, so the images need no field of their own
+    pub depth_views: Vec<ImageView>,
+    pub current_image: Mutex<Option<u32>>
+}
+
+impl AbstractSwapchain for VkSwapchain
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn extent(&self) -> (u32, u32)
+    {
+        (self.extent.width, self.extent.height)
+    }
+
+    fn format(&self) -> Format
+    {
+        qrl_format(self.surface_format.format)
+    }
+
+    fn color_space(&self) -> ColorSpace
+    {
+        qrl_color_space(self.surface_format.color_space).unwrap_or(ColorSpace::SrgbNonlinear)
+    }
+
+    fn present_mode(&self) -> PresentMode
+    {
+        qrl_present_mode(self.present_mode).unwrap_or(PresentMode::Fifo)
+    }
+
+    fn image_views(&self) -> Vec<ImageView>
+    {
+        self.views.clone()
+    }
+
+    fn depth_image_views(&self) -> Vec<ImageView>
+    {
+        self.depth_views.clone()
+    }
+
+    fn acquire_next_image(&self, semaphore: Option<&Semaphore>, fence: Option<&Fence>) -> Result<u32, SwapchainError>
+    {
+        let semaphore = semaphore.map_or(vk::Semaphore::null(), |semaphore| semaphore.downcast_ref::<VkSemaphore>().unwrap().handle);
+        let fence = fence.map_or(vk::Fence::null(), |fence| fence.downcast_ref::<VkFence>().unwrap().handle);
+
+        // the lock also serializes acquires against presents, vulkan requires the swapchain to be
+        // externally synchronized for both
+        let mut current_image = self.current_image.lock().unwrap();
+        match unsafe { self.loader.acquire_next_image(self.handle, u64::MAX, semaphore, fence) }
+        {
+            Ok((image_index, _)) =>
+            {
+                *current_image = Some(image_index);
+                Ok(image_index)
+            },
+            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => Err(SwapchainError::OutOfDate),
+            Err(_) => Err(SwapchainError::Failed)
+        }
+    }
+
+    fn capture_current_image(&self) -> Result<CapturedImage, ()>
+    {
+        let index = self.current_image.lock().unwrap().ok_or(())?;
+        if !self.image_usage.contains(vk::ImageUsageFlags::TRANSFER_SRC)
+        {
+            return Err(());
+        }
+
+        let image = &self.images[index as usize];
+        let region = ImageRegion { width: self.extent.width, height: self.extent.height, ..Default::default() };
+        let data = self.device.read_image_region(image, &region)?;
+
+        CapturedImage::from_texels(qrl_format(image.format), self.extent.width, self.extent.height, &data)
+    }
+}
+
+#[derive(Clone)]
+pub struct VkSemaphore
+{
+    pub handle: vk::Semaphore
+}
+
+impl AbstractSemaphore for VkSemaphore
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct VkShaderModule
+{
+    pub handle: vk::ShaderModule,
+    pub stage: ShaderStage
+}
+
+impl AbstractShaderModule for VkShaderModule
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+#[derive(Clone)]
+pub struct VkRenderPass
+{
+    pub handle: vk::RenderPass,
+    pub create_info: RenderPassCreateInfo<'static>
+}
+
+impl AbstractRenderPass for VkRenderPass
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn create_info(&self) -> &RenderPassCreateInfo<'static>
+    {
+        &self.create_info
+    }
+}
+
+#[derive(Clone)]
+pub struct VkFramebuffer
+{
+    pub handle: vk::Framebuffer,
+    pub attachments: Vec<ImageView>,
+    pub width: u32,
+    pub height: u32
+}
+
+impl AbstractFramebuffer for VkFramebuffer
+{
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn attachments(&self) -> &[ImageView]
+    {
+        &self.attachments
+    }
+}
+
+#[derive(Clone)]
+pub struct VkRenderPipeline
+{
+    pub handle: vk::Pipeline,
+    pub layout: vk::PipelineLayout
+}
+
+impl AbstractRenderPipeline for VkRenderPipeline
+{
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+unsafe extern "system" fn vulkan_debug_utils_callback(
+    message_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+    message_type: vk::DebugUtilsMessageTypeFlagsEXT,
+    p_callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT,
+    _p_user_data: *mut std::ffi::c_void,
+) -> vk::Bool32
+{
+    let message = std::ffi::CStr::from_ptr((*p_callback_data).p_message);
+    let severity = format!("{:?}", message_severity).to_lowercase();
+    let ty = format!("{:?}", message_type).to_lowercase();
+    println!("[Debug][{}][{}] {:?}", severity, ty, message);
+    vk::FALSE
+}
+
+// formats qrl has no Format for are only picked when nothing else is left
+fn choose_swap_surface_format(formats: &[vk::SurfaceFormatKHR], preferred_formats: &[Format], color_space: ColorSpace) -> vk::SurfaceFormatKHR
+{
+	let supported = |color_space: ColorSpace| -> Vec<vk::SurfaceFormatKHR>
 	{
-		if *mode == vk::PresentModeKHR::MAILBOX && preferred_mode == PresentMode::Mailbox
-		{
-			return *mode;
-		}
-		else if *mode == vk::PresentModeKHR::FIFO && preferred_mode == PresentMode::Fifo
-		{
-			return *mode;
-		}
-		else if *mode == vk::PresentModeKHR::FIFO_RELAXED && preferred_mode == PresentMode::FifoRelaxed
-		{
-			return *mode;
-		}
-		else if *mode == vk::PresentModeKHR::IMMEDIATE && preferred_mode == PresentMode::Immediate
-		{
-			return *mode;
-		}
+		formats.iter()
+			.filter(|format| format.color_space == vk_color_space(color_space) && qrl_format(format.format) != Format::Undefined)
+			.copied()
+			.collect()
+	};
+
+	let mut candidates = supported(color_space);
+	if candidates.is_empty()
+	{
+		candidates = supported(ColorSpace::SrgbNonlinear);
 	}
 
-	vk::PresentModeKHR::FIFO
+	preferred_formats.iter()
+		.chain([ Format::B8G8R8A8Srgb, Format::R8G8B8A8Srgb ].iter())
+		.find_map(|preferred| candidates.iter().find(|format| qrl_format(format.format) == *preferred))
+		.or(candidates.first())
+		.copied()
+		.unwrap_or(formats[0])
+}
+
+fn choose_swap_present_mode(modes: &[vk::PresentModeKHR], preferred_modes: &[PresentMode]) -> vk::PresentModeKHR
+{
+	preferred_modes.iter()
+		.map(|mode| vk_present_mode(*mode))
+		.find(|mode| modes.contains(mode))
+		.unwrap_or(vk::PresentModeKHR::FIFO)
 }
 
 fn choose_swap_extent(surface: &VkSurface, capabilities: &vk::SurfaceCapabilitiesKHR) -> vk::Extent2D
@@ -508,4 +3610,552 @@ fn choose_swap_extent(surface: &VkSurface, capabilities: &vk::SurfaceCapabilitie
 	actual_extent.height = actual_extent.height.clamp(capabilities.min_image_extent.height, capabilities.max_image_extent.height);
 
 	actual_extent
-}
\ No newline at end of file
+}
+
+fn vk_format(format: Format) -> vk::Format
+{
+    match format
+    {
+        Format::Undefined => vk::Format::UNDEFINED,
+        Format::R8Unorm => vk::Format::R8_UNORM,
+        Format::R8Snorm => vk::Format::R8_SNORM,
+        Format::R8Uint => vk::Format::R8_UINT,
+        Format::R8Sint => vk::Format::R8_SINT,
+        Format::R16Uint => vk::Format::R16_UINT,
+        Format::R16Sint => vk::Format::R16_SINT,
+        Format::R16Unorm => vk::Format::R16_UNORM,
+        Format::R16Snorm => vk::Format::R16_SNORM,
+        Format::R16Float => vk::Format::R16_SFLOAT,
+        Format::R32Uint => vk::Format::R32_UINT,
+        Format::R32Sint => vk::Format::R32_SINT,
+        Format::R32Float => vk::Format::R32_SFLOAT,
+        Format::R8G8Unorm => vk::Format::R8G8_UNORM,
+        Format::R16G16Float => vk::Format::R16G16_SFLOAT,
+        Format::R32G32Float => vk::Format::R32G32_SFLOAT,
+        Format::R8G8B8A8Unorm => vk::Format::R8G8B8A8_UNORM,
+        Format::R8G8B8A8Srgb => vk::Format::R8G8B8A8_SRGB,
+        Format::R8G8B8A8Uint => vk::Format::R8G8B8A8_UINT,
+        Format::B8G8R8A8Unorm => vk::Format::B8G8R8A8_UNORM,
+        Format::B8G8R8A8Srgb => vk::Format::B8G8R8A8_SRGB,
+        Format::A2B10G10R10Unorm => vk::Format::A2B10G10R10_UNORM_PACK32,
+        Format::R16G16B16A16Float => vk::Format::R16G16B16A16_SFLOAT,
+        Format::R32G32B32A32Uint => vk::Format::R32G32B32A32_UINT,
+        Format::R32G32B32A32Float => vk::Format::R32G32B32A32_SFLOAT,
+        Format::D16Unorm => vk::Format::D16_UNORM,
+        Format::D32Float => vk::Format::D32_SFLOAT,
+        Format::D24UnormS8Uint => vk::Format::D24_UNORM_S8_UINT,
+        Format::D32FloatS8Uint => vk::Format::D32_SFLOAT_S8_UINT
+    }
+}
+
+fn qrl_format(format: vk::Format) -> Format
+{
+    match format
+    {
+        vk::Format::R8_UNORM => Format::R8Unorm,
+        vk::Format::R8_SNORM => Format::R8Snorm,
+        vk::Format::R8_UINT => Format::R8Uint,
+        vk::Format::R8_SINT => Format::R8Sint,
+        vk::Format::R16_UINT => Format::R16Uint,
+        vk::Format::R16_SINT => Format::R16Sint,
+        vk::Format::R16_UNORM => Format::R16Unorm,
+        vk::Format::R16_SNORM => Format::R16Snorm,
+        vk::Format::R16_SFLOAT => Format::R16Float,
+        vk::Format::R32_UINT => Format::R32Uint,
+        vk::Format::R32_SINT => Format::R32Sint,
+        vk::Format::R32_SFLOAT => Format::R32Float,
+        vk::Format::R8G8_UNORM => Format::R8G8Unorm,
+        vk::Format::R16G16_SFLOAT => Format::R16G16Float,
+        vk::Format::R32G32_SFLOAT => Format::R32G32Float,
+        vk::Format::R8G8B8A8_UNORM => Format::R8G8B8A8Unorm,
+        vk::Format::R8G8B8A8_SRGB => Format::R8G8B8A8Srgb,
+        vk::Format::R8G8B8A8_UINT => Format::R8G8B8A8Uint,
+        vk::Format::B8G8R8A8_UNORM => Format::B8G8R8A8Unorm,
+        vk::Format::B8G8R8A8_SRGB => Format::B8G8R8A8Srgb,
+        vk::Format::A2B10G10R10_UNORM_PACK32 => Format::A2B10G10R10Unorm,
+        vk::Format::R16G16B16A16_SFLOAT => Format::R16G16B16A16Float,
+        vk::Format::R32G32B32A32_UINT => Format::R32G32B32A32Uint,
+        vk::Format::R32G32B32A32_SFLOAT => Format::R32G32B32A32Float,
+        vk::Format::D16_UNORM => Format::D16Unorm,
+        vk::Format::D32_SFLOAT => Format::D32Float,
+        vk::Format::D24_UNORM_S8_UINT => Format::D24UnormS8Uint,
+        vk::Format::D32_SFLOAT_S8_UINT => Format::D32FloatS8Uint,
+        _ => Format::Undefined
+    }
+}
+
+// DRAW_INDIRECT_COUNT, VERTEX_ATTRIBUTE_DIVISOR and DYNAMIC_RENDERING come from extensions and have no field here
+fn vk_feature(features: &mut vk::PhysicalDeviceFeatures, flag: u32) -> Option<&mut vk::Bool32>
+{
+    match flag
+    {
+        FeatureFlags::GEOMETRY_SHADER => Some(&mut features.geometry_shader),
+        FeatureFlags::TESSELLATION_SHADER => Some(&mut features.tessellation_shader),
+        FeatureFlags::DEPTH_CLAMP => Some(&mut features.depth_clamp),
+        FeatureFlags::DEPTH_BIAS_CLAMP => Some(&mut features.depth_bias_clamp),
+        FeatureFlags::SAMPLE_RATE_SHADING => Some(&mut features.sample_rate_shading),
+        FeatureFlags::PIPELINE_STATISTICS_QUERY => Some(&mut features.pipeline_statistics_query),
+        FeatureFlags::MULTI_DRAW_INDIRECT => Some(&mut features.multi_draw_indirect),
+        FeatureFlags::DRAW_INDIRECT_FIRST_INSTANCE => Some(&mut features.draw_indirect_first_instance),
+        FeatureFlags::FILL_MODE_NON_SOLID => Some(&mut features.fill_mode_non_solid),
+        FeatureFlags::WIDE_LINES => Some(&mut features.wide_lines),
+        FeatureFlags::LARGE_POINTS => Some(&mut features.large_points),
+        FeatureFlags::INDEPENDENT_BLEND => Some(&mut features.independent_blend),
+        FeatureFlags::DUAL_SOURCE_BLEND => Some(&mut features.dual_src_blend),
+        FeatureFlags::FRAGMENT_STORES_AND_ATOMICS => Some(&mut features.fragment_stores_and_atomics),
+        FeatureFlags::VERTEX_PIPELINE_STORES_AND_ATOMICS => Some(&mut features.vertex_pipeline_stores_and_atomics),
+        FeatureFlags::SHADER_FLOAT64 => Some(&mut features.shader_float64),
+        FeatureFlags::SHADER_INT64 => Some(&mut features.shader_int64),
+        FeatureFlags::SHADER_INT16 => Some(&mut features.shader_int16),
+        _ => None
+    }
+}
+
+fn qrl_features(features: &vk::PhysicalDeviceFeatures) -> u32
+{
+    let mut features = *features;
+    (0..32).map(|bit| 1 << bit)
+        .filter(|flag| vk_feature(&mut features, *flag).is_some_and(|supported| *supported == vk::TRUE))
+        .fold(0, |flags, flag| flags | flag)
+}
+
+fn vk_features(features: u32) -> vk::PhysicalDeviceFeatures
+{
+    let mut vk_features = vk::PhysicalDeviceFeatures::default();
+    for flag in (0..32).map(|bit| 1 << bit).filter(|flag| features & flag != 0)
+    {
+        if let Some(enabled) = vk_feature(&mut vk_features, flag)
+        {
+            *enabled = vk::TRUE;
+        }
+    }
+    vk_features
+}
+
+// names with a nul byte have no vulkan equivalent, Instance::create_device rejects them up front
+fn vk_device_extension(extension: &DeviceExtension) -> Option<std::ffi::CString>
+{
+    Some(match extension
+    {
+        DeviceExtension::DynamicRendering => vk::KhrDynamicRenderingFn::name().to_owned(),
+        DeviceExtension::TimelineSemaphore => vk::KhrTimelineSemaphoreFn::name().to_owned(),
+        DeviceExtension::Synchronization2 => vk::KhrSynchronization2Fn::name().to_owned(),
+        DeviceExtension::DescriptorIndexing => vk::ExtDescriptorIndexingFn::name().to_owned(),
+        DeviceExtension::MemoryBudget => vk::ExtMemoryBudgetFn::name().to_owned(),
+        DeviceExtension::PushDescriptor => vk::KhrPushDescriptorFn::name().to_owned(),
+        DeviceExtension::DrawIndirectCount => ash::extensions::khr::DrawIndirectCount::name().to_owned(),
+        DeviceExtension::VertexAttributeDivisor => vk::ExtVertexAttributeDivisorFn::name().to_owned(),
+        DeviceExtension::Named(name) => std::ffi::CString::new(name.as_str()).ok()?
+    })
+}
+
+fn qrl_device_extension(name: &std::ffi::CStr) -> DeviceExtension
+{
+    [
+        DeviceExtension::DynamicRendering,
+        DeviceExtension::TimelineSemaphore,
+        DeviceExtension::Synchronization2,
+        DeviceExtension::DescriptorIndexing,
+        DeviceExtension::MemoryBudget,
+        DeviceExtension::PushDescriptor,
+        DeviceExtension::DrawIndirectCount,
+        DeviceExtension::VertexAttributeDivisor
+    ].into_iter()
+        .find(|extension| vk_device_extension(extension).is_some_and(|extension| extension.as_c_str() == name))
+        .unwrap_or_else(|| DeviceExtension::Named(name.to_string_lossy().into_owned()))
+}
+
+// extensions that have to be enabled together with an extension on a 1.0 device, extensions passed
+// by name are expected to list their own dependencies
+fn vk_extension_dependencies(name: &std::ffi::CStr) -> Vec<&'static std::ffi::CStr>
+{
+    if name == vk::KhrDynamicRenderingFn::name()
+    {
+        vec![ vk::KhrDepthStencilResolveFn::name() ]
+    }
+    else if name == vk::KhrDepthStencilResolveFn::name()
+    {
+        vec![ vk::KhrCreateRenderpass2Fn::name() ]
+    }
+    else if name == vk::KhrCreateRenderpass2Fn::name()
+    {
+        vec![ vk::KhrMultiviewFn::name(), vk::KhrMaintenance2Fn::name() ]
+    }
+    else if name == vk::ExtDescriptorIndexingFn::name()
+    {
+        vec![ vk::KhrMaintenance3Fn::name() ]
+    }
+    else
+    {
+        Vec::new()
+    }
+}
+
+fn vk_extension_needs_properties2(name: &std::ffi::CStr) -> bool
+{
+    [
+        vk::KhrDynamicRenderingFn::name(),
+        vk::KhrDepthStencilResolveFn::name(),
+        vk::KhrCreateRenderpass2Fn::name(),
+        vk::KhrMultiviewFn::name(),
+        vk::KhrTimelineSemaphoreFn::name(),
+        vk::KhrSynchronization2Fn::name(),
+        vk::ExtDescriptorIndexingFn::name(),
+        vk::ExtMemoryBudgetFn::name(),
+        vk::KhrPushDescriptorFn::name(),
+        vk::ExtVertexAttributeDivisorFn::name()
+    ].contains(&name)
+}
+
+// dependencies go first so the list stays in enable order
+fn add_device_extension(extensions: &mut Vec<std::ffi::CString>, name: &std::ffi::CStr)
+{
+    if extensions.iter().any(|extension| extension.as_c_str() == name)
+    {
+        return;
+    }
+
+    for dependency in vk_extension_dependencies(name)
+    {
+        add_device_extension(extensions, dependency);
+    }
+    extensions.push(name.to_owned());
+}
+
+fn vk_present_mode(present_mode: PresentMode) -> vk::PresentModeKHR
+{
+    match present_mode
+    {
+        PresentMode::Immediate => vk::PresentModeKHR::IMMEDIATE,
+        PresentMode::Fifo => vk::PresentModeKHR::FIFO,
+        PresentMode::FifoRelaxed => vk::PresentModeKHR::FIFO_RELAXED,
+        PresentMode::Mailbox => vk::PresentModeKHR::MAILBOX
+    }
+}
+
+fn qrl_present_mode(present_mode: vk::PresentModeKHR) -> Option<PresentMode>
+{
+    match present_mode
+    {
+        vk::PresentModeKHR::IMMEDIATE => Some(PresentMode::Immediate),
+        vk::PresentModeKHR::FIFO => Some(PresentMode::Fifo),
+        vk::PresentModeKHR::FIFO_RELAXED => Some(PresentMode::FifoRelaxed),
+        vk::PresentModeKHR::MAILBOX => Some(PresentMode::Mailbox),
+        _ => None
+    }
+}
+
+fn vk_composite_alpha(composite_alpha: CompositeAlpha) -> vk::CompositeAlphaFlagsKHR
+{
+    match composite_alpha
+    {
+        CompositeAlpha::Opaque => vk::CompositeAlphaFlagsKHR::OPAQUE,
+        CompositeAlpha::PreMultiplied => vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED,
+        CompositeAlpha::PostMultiplied => vk::CompositeAlphaFlagsKHR::POST_MULTIPLIED,
+        CompositeAlpha::Inherit => vk::CompositeAlphaFlagsKHR::INHERIT
+    }
+}
+
+fn vk_color_space(color_space: ColorSpace) -> vk::ColorSpaceKHR
+{
+    match color_space
+    {
+        ColorSpace::SrgbNonlinear => vk::ColorSpaceKHR::SRGB_NONLINEAR,
+        ColorSpace::Bt709Linear => vk::ColorSpaceKHR::BT709_LINEAR_EXT,
+        ColorSpace::ExtendedSrgbLinear => vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT,
+        ColorSpace::Hdr10St2084 => vk::ColorSpaceKHR::HDR10_ST2084_EXT
+    }
+}
+
+fn qrl_color_space(color_space: vk::ColorSpaceKHR) -> Option<ColorSpace>
+{
+    match color_space
+    {
+        vk::ColorSpaceKHR::SRGB_NONLINEAR => Some(ColorSpace::SrgbNonlinear),
+        vk::ColorSpaceKHR::BT709_LINEAR_EXT => Some(ColorSpace::Bt709Linear),
+        vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT => Some(ColorSpace::ExtendedSrgbLinear),
+        vk::ColorSpaceKHR::HDR10_ST2084_EXT => Some(ColorSpace::Hdr10St2084),
+        _ => None
+    }
+}
+
+fn vk_vertex_format(format: VertexFormat) -> vk::Format
+{
+    match format
+    {
+        VertexFormat::Float32 => vk::Format::R32_SFLOAT,
+        VertexFormat::Float32x2 => vk::Format::R32G32_SFLOAT,
+        VertexFormat::Float32x3 => vk::Format::R32G32B32_SFLOAT,
+        VertexFormat::Float32x4 => vk::Format::R32G32B32A32_SFLOAT,
+        VertexFormat::Uint32 => vk::Format::R32_UINT,
+        VertexFormat::Uint32x2 => vk::Format::R32G32_UINT,
+        VertexFormat::Uint32x3 => vk::Format::R32G32B32_UINT,
+        VertexFormat::Uint32x4 => vk::Format::R32G32B32A32_UINT,
+        VertexFormat::Sint32 => vk::Format::R32_SINT,
+        VertexFormat::Sint32x2 => vk::Format::R32G32_SINT,
+        VertexFormat::Sint32x3 => vk::Format::R32G32B32_SINT,
+        VertexFormat::Sint32x4 => vk::Format::R32G32B32A32_SINT,
+        VertexFormat::Float16x2 => vk::Format::R16G16_SFLOAT,
+        VertexFormat::Float16x4 => vk::Format::R16G16B16A16_SFLOAT,
+        VertexFormat::Unorm16x2 => vk::Format::R16G16_UNORM,
+        VertexFormat::Unorm16x4 => vk::Format::R16G16B16A16_UNORM,
+        VertexFormat::Unorm8x4 => vk::Format::R8G8B8A8_UNORM,
+        VertexFormat::Snorm8x4 => vk::Format::R8G8B8A8_SNORM,
+        VertexFormat::Uint8x4 => vk::Format::R8G8B8A8_UINT
+    }
+}
+
+fn vk_aspect_mask(format: vk::Format) -> vk::ImageAspectFlags
+{
+    match format
+    {
+        vk::Format::D16_UNORM | vk::Format::D32_SFLOAT => vk::ImageAspectFlags::DEPTH,
+        vk::Format::D24_UNORM_S8_UINT | vk::Format::D32_SFLOAT_S8_UINT => vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL,
+        _ => vk::ImageAspectFlags::COLOR
+    }
+}
+
+fn vk_image_layout(layout: Option<ImageLayout>) -> vk::ImageLayout
+{
+    match layout
+    {
+        None => vk::ImageLayout::UNDEFINED,
+        Some(ImageLayout::General) => vk::ImageLayout::GENERAL,
+        Some(ImageLayout::ColorAttachmentOptimal) => vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        Some(ImageLayout::DepthStencilAttachmentOptimal) => vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+        Some(ImageLayout::StencilStencilReadOnlyOptimal) => vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+        Some(ImageLayout::ShaderReadOnlyOptimal) => vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        Some(ImageLayout::TransferSrcOptimal) => vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        Some(ImageLayout::TransferDstOptimal) => vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        Some(ImageLayout::Preinitialized) => vk::ImageLayout::PREINITIALIZED,
+        Some(ImageLayout::PresentSrc) => vk::ImageLayout::PRESENT_SRC_KHR
+    }
+}
+
+fn qrl_image_layout(layout: vk::ImageLayout) -> Option<ImageLayout>
+{
+    match layout
+    {
+        vk::ImageLayout::GENERAL => Some(ImageLayout::General),
+        vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL => Some(ImageLayout::ColorAttachmentOptimal),
+        vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL => Some(ImageLayout::DepthStencilAttachmentOptimal),
+        vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL => Some(ImageLayout::StencilStencilReadOnlyOptimal),
+        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL => Some(ImageLayout::ShaderReadOnlyOptimal),
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL => Some(ImageLayout::TransferSrcOptimal),
+        vk::ImageLayout::TRANSFER_DST_OPTIMAL => Some(ImageLayout::TransferDstOptimal),
+        vk::ImageLayout::PREINITIALIZED => Some(ImageLayout::Preinitialized),
+        vk::ImageLayout::PRESENT_SRC_KHR => Some(ImageLayout::PresentSrc),
+        _ => None
+    }
+}
+
+fn vk_queue_transfer(queue_transfer: Option<QueueTransfer>) -> (u32, u32)
+{
+    match queue_transfer
+    {
+        Some(queue_transfer) if queue_transfer.src_queue_family != queue_transfer.dst_queue_family =>
+        {
+            (queue_transfer.src_queue_family, queue_transfer.dst_queue_family)
+        },
+        _ => (vk::QUEUE_FAMILY_IGNORED, vk::QUEUE_FAMILY_IGNORED)
+    }
+}
+
+fn vk_shader_stage(stage: ShaderStage) -> vk::ShaderStageFlags
+{
+    match stage
+    {
+        ShaderStage::Vertex => vk::ShaderStageFlags::VERTEX,
+        ShaderStage::Fragment => vk::ShaderStageFlags::FRAGMENT,
+        ShaderStage::Geometry => vk::ShaderStageFlags::GEOMETRY,
+        ShaderStage::Compute => vk::ShaderStageFlags::COMPUTE,
+        ShaderStage::TessellationControl => vk::ShaderStageFlags::TESSELLATION_CONTROL,
+        ShaderStage::TessellationEvaluation => vk::ShaderStageFlags::TESSELLATION_EVALUATION
+    }
+}
+
+fn vk_primitive_topology(primitive: Primitive) -> vk::PrimitiveTopology
+{
+    match primitive
+    {
+        Primitive::PointList => vk::PrimitiveTopology::POINT_LIST,
+        Primitive::LineList => vk::PrimitiveTopology::LINE_LIST,
+        Primitive::LineStrip => vk::PrimitiveTopology::LINE_STRIP,
+        Primitive::TriangleList => vk::PrimitiveTopology::TRIANGLE_LIST,
+        Primitive::TriangleStrip => vk::PrimitiveTopology::TRIANGLE_STRIP,
+        Primitive::PatchList(_) => vk::PrimitiveTopology::PATCH_LIST
+    }
+}
+
+fn vk_compare_op(compare_op: CompareOp) -> vk::CompareOp
+{
+    match compare_op
+    {
+        CompareOp::Never => vk::CompareOp::NEVER,
+        CompareOp::Less => vk::CompareOp::LESS,
+        CompareOp::Equal => vk::CompareOp::EQUAL,
+        CompareOp::LessOrEqual => vk::CompareOp::LESS_OR_EQUAL,
+        CompareOp::Greater => vk::CompareOp::GREATER,
+        CompareOp::NotEqual => vk::CompareOp::NOT_EQUAL,
+        CompareOp::GreaterOrEqual => vk::CompareOp::GREATER_OR_EQUAL,
+        CompareOp::Always => vk::CompareOp::ALWAYS
+    }
+}
+
+fn vk_blend_factor(blend_factor: BlendFactor) -> vk::BlendFactor
+{
+    match blend_factor
+    {
+        BlendFactor::Zero => vk::BlendFactor::ZERO,
+        BlendFactor::One => vk::BlendFactor::ONE,
+        BlendFactor::SrcColor => vk::BlendFactor::SRC_COLOR,
+        BlendFactor::OneMinusSrcColor => vk::BlendFactor::ONE_MINUS_SRC_COLOR,
+        BlendFactor::DstColor => vk::BlendFactor::DST_COLOR,
+        BlendFactor::OneMinusDstColor => vk::BlendFactor::ONE_MINUS_DST_COLOR,
+        BlendFactor::SrcAlpha => vk::BlendFactor::SRC_ALPHA,
+        BlendFactor::OneMinusSrcAlpha => vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+        BlendFactor::DstAlpha => vk::BlendFactor::DST_ALPHA,
+        BlendFactor::OneMinusDstAlpha => vk::BlendFactor::ONE_MINUS_DST_ALPHA,
+        BlendFactor::ConstantColor => vk::BlendFactor::CONSTANT_COLOR,
+        BlendFactor::OneMinusConstantColor => vk::BlendFactor::ONE_MINUS_CONSTANT_COLOR,
+        BlendFactor::ConstantAlpha => vk::BlendFactor::CONSTANT_ALPHA,
+        BlendFactor::OneMinusConstantAlpha => vk::BlendFactor::ONE_MINUS_CONSTANT_ALPHA,
+        BlendFactor::SrcAlphaSaturate => vk::BlendFactor::SRC_ALPHA_SATURATE,
+        BlendFactor::Src1Color => vk::BlendFactor::SRC1_COLOR,
+        BlendFactor::OneMinusSrc1Color => vk::BlendFactor::ONE_MINUS_SRC1_COLOR,
+        BlendFactor::Src1Alpha => vk::BlendFactor::SRC1_ALPHA,
+        BlendFactor::OneMinusSrc1Alpha => vk::BlendFactor::ONE_MINUS_SRC1_ALPHA
+    }
+}
+
+fn vk_blend_op(blend_op: BlendOp) -> vk::BlendOp
+{
+    match blend_op
+    {
+        BlendOp::Add => vk::BlendOp::ADD,
+        BlendOp::Subtract => vk::BlendOp::SUBTRACT,
+        BlendOp::ReverseSubtract => vk::BlendOp::REVERSE_SUBTRACT,
+        BlendOp::Min => vk::BlendOp::MIN,
+        BlendOp::Max => vk::BlendOp::MAX
+    }
+}
+
+fn vk_stencil_op(stencil_op: StencilOp) -> vk::StencilOp
+{
+    match stencil_op
+    {
+        StencilOp::Keep => vk::StencilOp::KEEP,
+        StencilOp::Zero => vk::StencilOp::ZERO,
+        StencilOp::Replace => vk::StencilOp::REPLACE,
+        StencilOp::IncrementAndClamp => vk::StencilOp::INCREMENT_AND_CLAMP,
+        StencilOp::DecrementAndClamp => vk::StencilOp::DECREMENT_AND_CLAMP,
+        StencilOp::Invert => vk::StencilOp::INVERT,
+        StencilOp::IncrementAndWrap => vk::StencilOp::INCREMENT_AND_WRAP,
+        StencilOp::DecrementAndWrap => vk::StencilOp::DECREMENT_AND_WRAP
+    }
+}
+
+fn vk_stencil_op_state(state: &StencilFaceState) -> vk::StencilOpState
+{
+    vk::StencilOpState
+    {
+        fail_op: vk_stencil_op(state.fail_op),
+        pass_op: vk_stencil_op(state.pass_op),
+        depth_fail_op: vk_stencil_op(state.depth_fail_op),
+        compare_op: vk_compare_op(state.compare_op),
+        compare_mask: state.compare_mask,
+        write_mask: state.write_mask,
+        reference: state.reference
+    }
+}
+
+fn vk_load_op(load_op: LoadOp) -> vk::AttachmentLoadOp
+{
+    match load_op
+    {
+        LoadOp::Clear(..) => vk::AttachmentLoadOp::CLEAR,
+        LoadOp::Load => vk::AttachmentLoadOp::LOAD,
+        LoadOp::DontCare => vk::AttachmentLoadOp::DONT_CARE
+    }
+}
+
+fn vk_store_op(store_op: StoreOp) -> vk::AttachmentStoreOp
+{
+    match store_op
+    {
+        StoreOp::Store => vk::AttachmentStoreOp::STORE,
+        StoreOp::DontCare => vk::AttachmentStoreOp::DONT_CARE
+    }
+}
+
+// depth attachments take their clear depth from the first component of the load op and
+// the clear stencil from the first component of the stencil load op
+fn vk_clear_value(attachment: &AttachmentDescription) -> vk::ClearValue
+{
+    if attachment.format.has_depth()
+    {
+        let depth = match attachment.operations.load_op { LoadOp::Clear(depth, ..) => depth, _ => 1.0 };
+        let stencil = match attachment.stencil_operations.load_op { LoadOp::Clear(stencil, ..) => stencil as u32, _ => 0 };
+
+        vk::ClearValue { depth_stencil: vk::ClearDepthStencilValue { depth, stencil } }
+    }
+    else
+    {
+        let color = match attachment.operations.load_op { LoadOp::Clear(r, g, b, a) => [ r, g, b, a ], _ => [ 0.0; 4 ] };
+
+        vk::ClearValue { color: vk::ClearColorValue { float32: color } }
+    }
+}
+
+fn vk_descriptor_type(descriptor_type: DescriptorType) -> vk::DescriptorType
+{
+    match descriptor_type
+    {
+        DescriptorType::UniformBuffer => vk::DescriptorType::UNIFORM_BUFFER,
+        DescriptorType::StorageBuffer => vk::DescriptorType::STORAGE_BUFFER,
+        DescriptorType::StorageImage => vk::DescriptorType::STORAGE_IMAGE
+    }
+}
+
+fn vk_buffer_usage(usage: u32) -> vk::BufferUsageFlags
+{
+    let mut flags = vk::BufferUsageFlags::empty();
+
+    if usage & BufferUsageFlags::TRANSFER_SRC != 0 { flags |= vk::BufferUsageFlags::TRANSFER_SRC; }
+    if usage & BufferUsageFlags::TRANSFER_DST != 0 { flags |= vk::BufferUsageFlags::TRANSFER_DST; }
+    if usage & BufferUsageFlags::UNIFORM != 0 { flags |= vk::BufferUsageFlags::UNIFORM_BUFFER; }
+    if usage & BufferUsageFlags::STORAGE != 0 { flags |= vk::BufferUsageFlags::STORAGE_BUFFER; }
+    if usage & BufferUsageFlags::INDEX != 0 { flags |= vk::BufferUsageFlags::INDEX_BUFFER; }
+    if usage & BufferUsageFlags::VERTEX != 0 { flags |= vk::BufferUsageFlags::VERTEX_BUFFER; }
+    if usage & BufferUsageFlags::INDIRECT != 0 { flags |= vk::BufferUsageFlags::INDIRECT_BUFFER; }
+
+    flags
+}
+
+fn qrl_image_usage(usage: vk::ImageUsageFlags) -> u32
+{
+    let mut flags = 0;
+
+    if usage.contains(vk::ImageUsageFlags::TRANSFER_SRC) { flags |= ImageUsageFlags::TRANSFER_SRC; }
+    if usage.contains(vk::ImageUsageFlags::TRANSFER_DST) { flags |= ImageUsageFlags::TRANSFER_DST; }
+    if usage.contains(vk::ImageUsageFlags::SAMPLED) { flags |= ImageUsageFlags::SAMPLED; }
+    if usage.contains(vk::ImageUsageFlags::STORAGE) { flags |= ImageUsageFlags::STORAGE; }
+    if usage.contains(vk::ImageUsageFlags::COLOR_ATTACHMENT) { flags |= ImageUsageFlags::COLOR_ATTACHMENT; }
+    if usage.contains(vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT) { flags |= ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT; }
+
+    flags
+}
+
+fn vk_image_usage(usage: u32) -> vk::ImageUsageFlags
+{
+    let mut flags = vk::ImageUsageFlags::empty();
+
+    if usage & ImageUsageFlags::TRANSFER_SRC != 0 { flags |= vk::ImageUsageFlags::TRANSFER_SRC; }
+    if usage & ImageUsageFlags::TRANSFER_DST != 0 { flags |= vk::ImageUsageFlags::TRANSFER_DST; }
+    if usage & ImageUsageFlags::SAMPLED != 0 { flags |= vk::ImageUsageFlags::SAMPLED; }
+    if usage & ImageUsageFlags::STORAGE != 0 { flags |= vk::ImageUsageFlags::STORAGE; }
+    if usage & ImageUsageFlags::COLOR_ATTACHMENT != 0 { flags |= vk::ImageUsageFlags::COLOR_ATTACHMENT; }
+    if usage & ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT != 0 { flags |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT; }
+
+    flags
+}